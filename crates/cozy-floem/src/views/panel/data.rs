@@ -11,7 +11,7 @@ use floem::{
     text::{Attrs, FamilyOwned, LineHeightValue},
 };
 pub use lines::*;
-use log::{error, info};
+use log::error;
 
 use crate::views::tree_with_panel::data::{StyledText, VisualLine};
 
@@ -29,12 +29,14 @@ impl DocManager {
     #[allow(clippy::too_many_arguments)]
     pub fn new(cx: Scope, id: ViewId, doc_style: DocStyle) -> Self {
         let hover_hyperlink = cx.create_rw_signal(None);
+        let clicked_hyperlink = cx.create_rw_signal(None);
         Self {
             panel_id:   id,
             inner_node: None,
             doc:        cx.create_rw_signal(SimpleDoc::new(
                 id,
                 hover_hyperlink,
+                clicked_hyperlink,
                 doc_style,
             )),
         }
@@ -55,6 +57,11 @@ impl DocManager {
         });
     }
 
+    /// The last hyperlink the user clicked in the panel.
+    pub fn clicked_hyperlink(&self) -> RwSignal<Option<Hyperlink>> {
+        self.doc.with_untracked(|x| x.clicked_hyperlink)
+    }
+
     pub fn try_update<O>(&self, f: impl FnOnce(&mut SimpleDoc) -> O) -> Option<O> {
         // not remove `batch`!
         batch(|| self.doc.try_update(f))
@@ -93,21 +100,23 @@ impl Default for DocStyle {
 
 #[derive(Clone)]
 pub struct SimpleDoc {
-    pub id:              ViewId,
+    pub id:                ViewId,
     // pub visual_line:       Vec<VisualLine>,
-    pub line_ending:     LineEnding,
-    pub viewport:        Rect,
-    pub cursor:          Cursor,
-    pub hover_hyperlink: RwSignal<Option<usize>>,
-    pub style:           DocStyle,
-    pub auto_scroll:     bool,
-    pub lines:           Lines,
+    pub line_ending:       LineEnding,
+    pub viewport:          Rect,
+    pub cursor:            Cursor,
+    pub hover_hyperlink:   RwSignal<Option<usize>>,
+    pub clicked_hyperlink: RwSignal<Option<Hyperlink>>,
+    pub style:             DocStyle,
+    pub auto_scroll:       bool,
+    pub lines:             Lines,
 }
 
 impl SimpleDoc {
     pub fn new(
         id: ViewId,
         hover_hyperlink: RwSignal<Option<usize>>,
+        clicked_hyperlink: RwSignal<Option<Hyperlink>>,
         style: DocStyle,
     ) -> Self {
         Self {
@@ -120,6 +129,7 @@ impl SimpleDoc {
                 position: Position::None,
             },
             hover_hyperlink,
+            clicked_hyperlink,
             style,
             auto_scroll: true,
             lines: Default::default(),
@@ -154,7 +164,7 @@ impl SimpleDoc {
             1 => {
                 if self.hover_hyperlink.get_untracked().is_some() {
                     if let Some(link) = self.lines.hyperlink_by_point(event.pos)? {
-                        info!("todo {:?}", link);
+                        self.clicked_hyperlink.set(Some(link.clone()));
                     }
                 }
                 let offset = self.offset_of_pos(event.pos)?.0;
@@ -526,6 +536,14 @@ impl SimpleDoc {
         Ok(())
     }
 
+    pub fn clear(&mut self) {
+        self.lines = Default::default();
+        self.cursor.position = Position::None;
+        self.hover_hyperlink.set(None);
+        self.id.request_layout();
+        self.id.request_paint();
+    }
+
     fn offset_of_line(&self, line: usize) -> anyhow::Result<usize> {
         self.lines.line_info()?.0.offset_of_line(line)
     }
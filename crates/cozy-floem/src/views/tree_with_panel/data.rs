@@ -65,6 +65,27 @@ impl TreePanelData {
         }
     }
 
+    /// Add a line of output to both the tree and the panel.
+    pub fn append(&self, line: StyledText) {
+        self.node
+            .update(|x| x.add_child(line.id.display_id(), line.level));
+        self.doc.update(|x| {
+            if let Err(err) = x.append_lines(line) {
+                error!("{err:?}");
+            }
+        });
+    }
+
+    /// Drop all output, e.g. before running another command.
+    pub fn clear(&self) {
+        self.node.update(|x| {
+            x.children.clear();
+            x.level.set(Level::None);
+            x.open.set(true);
+        });
+        self.doc.update(|x| x.clear());
+    }
+
     pub fn run_with_async_task<F, Fut>(&self, f: F)
     where
         F: Fn(ExtChannel<crate::views::tree_with_panel::data::StyledText>) -> Fut
@@ -76,13 +97,7 @@ impl TreePanelData {
         let data = self.clone();
         self.cx.create_effect(move |_| {
             if let Some(line) = read_signal.get() {
-                data.node
-                    .update(|x| x.add_child(line.id.display_id(), line.level));
-                data.doc.update(|x| {
-                    if let Err(err) = x.append_lines(line) {
-                        error!("{err:?}");
-                    }
-                });
+                data.append(line);
             }
         });
         thread::spawn(|| {
@@ -104,13 +119,7 @@ impl TreePanelData {
         let data = self.clone();
         self.cx.create_effect(move |_| {
             if let Some(line) = read_signal.get() {
-                data.node
-                    .update(|x| x.add_child(line.id.display_id(), line.level));
-                data.doc.update(|x| {
-                    if let Err(err) = x.append_lines(line) {
-                        error!("{err:?}");
-                    }
-                });
+                data.append(line);
             }
         });
         thread::spawn(move || {
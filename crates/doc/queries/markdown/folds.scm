@@ -0,0 +1,6 @@
+[
+  (section)
+  (fenced_code_block)
+  (list)
+  (block_quote)
+] @fold
@@ -1696,6 +1696,7 @@ const LANGUAGES: &[SyntaxProperties] = &[
 ];
 
 impl LapceLanguage {
+    pub const FOLDS_QUERIES_FILE_NAME: &'static str = "folds.scm";
     pub const HIGHLIGHTS_INJECTIONS_FILE_NAME: &'static str = "injections.scm";
     pub const HIGHLIGHTS_QUERIES_FILE_NAME: &'static str = "highlights.scm";
 
@@ -1972,6 +1973,17 @@ pub(crate) fn walk_tree_bracket_ast(
     }
 }
 
+/// The `folds.scm` used when the queries directory has none.
+pub fn default_folds_query(query_name: &str) -> Option<&'static str> {
+    Some(match query_name {
+        "json" => include_str!("../queries/json/folds.scm"),
+        "markdown" => include_str!("../queries/markdown/folds.scm"),
+        "toml" => include_str!("../queries/toml/folds.scm"),
+        "yaml" => include_str!("../queries/yaml/folds.scm"),
+        _ => return None,
+    })
+}
+
 pub fn read_grammar_query(queries_dir: &Path, name: &str, kind: &str) -> String {
     static INHERITS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
         Regex::new(r";+\s*inherits\s*:?\s*([a-z_,()-]+)\s*").unwrap()
@@ -79,6 +79,38 @@ pub fn expand_diff_lines(
     }
 }
 
+/// The line on the left (old) side that `right_line` is unchanged from, or
+/// `None` if the line was added or modified.
+pub fn left_line_of_right<'a>(
+    changes: impl IntoIterator<Item = &'a DiffLines>,
+    right_line: usize,
+) -> Option<usize> {
+    changes.into_iter().find_map(|change| match change {
+        DiffLines::Both(info) if info.right.contains(&right_line) => {
+            Some(info.left.start + right_line - info.right.start)
+        },
+        _ => None,
+    })
+}
+
+/// The lines on the right (new) side that were added or modified, adjacent
+/// ones merged into one range.
+pub fn right_changed_lines<'a>(
+    changes: impl IntoIterator<Item = &'a DiffLines>,
+) -> Vec<Range<usize>> {
+    let mut lines: Vec<Range<usize>> = Vec::new();
+    for change in changes {
+        let DiffLines::Right(range) = change else {
+            continue;
+        };
+        match lines.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => lines.push(range.clone()),
+        }
+    }
+    lines
+}
+
 pub fn rope_diff(
     left_rope: Rope,
     right_rope: Rope,
@@ -88,7 +120,16 @@ pub fn rope_diff(
 ) -> Option<Vec<DiffLines>> {
     let left_lines = left_rope.lines(..).collect::<Vec<Cow<str>>>();
     let right_lines = right_rope.lines(..).collect::<Vec<Cow<str>>>();
+    lines_diff(&left_lines, &right_lines, rev, atomic_rev, context_lines)
+}
 
+fn lines_diff<T: PartialEq>(
+    left_lines: &[T],
+    right_lines: &[T],
+    rev: u64,
+    atomic_rev: Arc<AtomicU64>,
+    context_lines: Option<usize>,
+) -> Option<Vec<DiffLines>> {
     let left_count = left_lines.len();
     let right_count = right_lines.len();
     let min_count = std::cmp::min(left_count, right_count);
@@ -247,3 +288,220 @@ pub fn rope_diff(
 
     Some(changes)
 }
+
+/// A change of a side of a merge: the lines of the base it replaces, and the
+/// lines of the side it replaces them with.
+#[derive(Clone, Debug)]
+struct MergeHunk {
+    base: Range<usize>,
+    side: Range<usize>,
+}
+
+/// The changes of `side` to `base`, by lines with their line endings, so that
+/// a line getting one or losing it is a change too.
+fn merge_hunks(base: &[Cow<str>], side: &[Cow<str>]) -> Vec<MergeHunk> {
+    let changes = lines_diff(base, side, 0, Arc::new(AtomicU64::new(0)), None)
+        .unwrap_or_default();
+    let mut hunks: Vec<MergeHunk> = Vec::new();
+    let (mut base_line, mut side_line) = (0, 0);
+    for change in changes {
+        let (base_len, side_len) = match change {
+            DiffLines::Both(info) => {
+                base_line += info.left.len();
+                side_line += info.right.len();
+                continue;
+            },
+            DiffLines::Left(range) => (range.len(), 0),
+            DiffLines::Right(range) => (0, range.len()),
+        };
+        match hunks.last_mut() {
+            Some(last)
+                if last.base.end == base_line && last.side.end == side_line =>
+            {
+                last.base.end += base_len;
+                last.side.end += side_len;
+            },
+            _ => hunks.push(MergeHunk {
+                base: base_line..base_line + base_len,
+                side: side_line..side_line + side_len,
+            }),
+        }
+        base_line += base_len;
+        side_line += side_len;
+    }
+    hunks
+}
+
+/// The lines `base[range]` turn into with the `hunks` of a side, which are
+/// all within the range.
+fn apply_merge_hunks<'a>(
+    base: &[Cow<'a, str>],
+    side: &[Cow<'a, str>],
+    range: Range<usize>,
+    hunks: &[MergeHunk],
+) -> Vec<Cow<'a, str>> {
+    let mut lines = Vec::new();
+    let mut line = range.start;
+    for hunk in hunks {
+        lines.extend_from_slice(&base[line..hunk.base.start]);
+        lines.extend_from_slice(&side[hunk.side.clone()]);
+        line = hunk.base.end;
+    }
+    lines.extend_from_slice(&base[line..range.end]);
+    lines
+}
+
+fn push_conflict_side<'a>(
+    merged: &mut Vec<Cow<'a, str>>,
+    marker: &'static str,
+    lines: Vec<Cow<'a, str>>,
+) {
+    merged.push(Cow::Borrowed(marker));
+    // the marker after the lines has to start a line
+    let ends_with_newline = lines.last().is_none_or(|line| line.ends_with('\n'));
+    merged.extend(lines);
+    if !ends_with_newline {
+        merged.push(Cow::Borrowed("\n"));
+    }
+}
+
+/// Merges the changes `mine` and `theirs` made to `base`, line by line. Where
+/// both changed the same lines differently, both versions are kept between
+/// conflict markers. Returns the merged text and the number of conflicts.
+pub fn merge_three_way(base: &Rope, mine: &Rope, theirs: &Rope) -> (String, usize) {
+    let base_lines = base.lines_raw(..).collect::<Vec<Cow<str>>>();
+    let mine_lines = mine.lines_raw(..).collect::<Vec<Cow<str>>>();
+    let theirs_lines = theirs.lines_raw(..).collect::<Vec<Cow<str>>>();
+    let mine_hunks = merge_hunks(&base_lines, &mine_lines);
+    let theirs_hunks = merge_hunks(&base_lines, &theirs_lines);
+
+    let mut merged: Vec<Cow<str>> = Vec::new();
+    let mut conflicts = 0;
+    let (mut line, mut i, mut j) = (0, 0, 0);
+    while i < mine_hunks.len() || j < theirs_hunks.len() {
+        // the hunks of both sides that overlap or touch, taken as one change
+        let start = match (mine_hunks.get(i), theirs_hunks.get(j)) {
+            (Some(m), Some(t)) => m.base.start.min(t.base.start),
+            (Some(m), None) => m.base.start,
+            (None, Some(t)) => t.base.start,
+            (None, None) => break,
+        };
+        let (mine_start, theirs_start) = (i, j);
+        let mut end = start;
+        loop {
+            if let Some(hunk) = mine_hunks.get(i).filter(|h| h.base.start <= end) {
+                end = end.max(hunk.base.end);
+                i += 1;
+            } else if let Some(hunk) =
+                theirs_hunks.get(j).filter(|h| h.base.start <= end)
+            {
+                end = end.max(hunk.base.end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        merged.extend_from_slice(&base_lines[line..start]);
+        let mine_version = apply_merge_hunks(
+            &base_lines,
+            &mine_lines,
+            start..end,
+            &mine_hunks[mine_start..i],
+        );
+        let theirs_version = apply_merge_hunks(
+            &base_lines,
+            &theirs_lines,
+            start..end,
+            &theirs_hunks[theirs_start..j],
+        );
+        if theirs_start == j || mine_version == theirs_version {
+            merged.extend(mine_version);
+        } else if mine_start == i {
+            merged.extend(theirs_version);
+        } else {
+            conflicts += 1;
+            push_conflict_side(&mut merged, "<<<<<<< Mine\n", mine_version);
+            push_conflict_side(&mut merged, "=======\n", theirs_version);
+            merged.push(Cow::Borrowed(">>>>>>> On Disk\n"));
+        }
+        line = end;
+    }
+    merged.extend_from_slice(&base_lines[line..]);
+    (merged.concat(), conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, atomic::AtomicU64};
+
+    use lapce_xi_rope::Rope;
+
+    use super::{
+        left_line_of_right, merge_three_way, right_changed_lines, rope_diff,
+    };
+
+    #[test]
+    fn test_left_line_of_right() {
+        let changes = rope_diff(
+            Rope::from("a\nb\nc\nd\n"),
+            Rope::from("a\nx\nb\nd\n"),
+            0,
+            Arc::new(AtomicU64::new(0)),
+            None,
+        )
+        .unwrap();
+        let lines = (0..4)
+            .map(|line| left_line_of_right(&changes, line))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![Some(0), None, Some(1), Some(3)]);
+    }
+
+    #[test]
+    fn test_right_changed_lines() {
+        let changes = rope_diff(
+            Rope::from("a\nb\nc\nd\ne\n"),
+            Rope::from("a\nB\nc\nx\ny\ne\n"),
+            0,
+            Arc::new(AtomicU64::new(0)),
+            None,
+        )
+        .unwrap();
+        assert_eq!(right_changed_lines(&changes), vec![1..2, 3..5]);
+    }
+
+    #[test]
+    fn test_merge_three_way() {
+        let merge = |base: &str, mine: &str, theirs: &str| {
+            merge_three_way(
+                &Rope::from(base),
+                &Rope::from(mine),
+                &Rope::from(theirs),
+            )
+        };
+        assert_eq!(
+            merge("a\nb\nc\nd\ne\n", "a\nB\nc\nd\ne\n", "a\nb\nc\nd\nE\nf\n"),
+            ("a\nB\nc\nd\nE\nf\n".to_string(), 0)
+        );
+        assert_eq!(
+            merge("a\nb\nc\n", "a\nx\nc\n", "a\nx\nc\n"),
+            ("a\nx\nc\n".to_string(), 0)
+        );
+        assert_eq!(
+            merge("a\nb\nc", "a\nmine\nc", "a\ntheirs\nc"),
+            (
+                "a\n<<<<<<< Mine\nmine\n=======\ntheirs\n>>>>>>> On Disk\nc"
+                    .to_string(),
+                1
+            )
+        );
+        assert_eq!(
+            merge("a\nb", "a\nb\nmine", "a\nb\ntheirs"),
+            (
+                "a\n<<<<<<< Mine\nb\nmine\n=======\nb\ntheirs\n>>>>>>> On Disk\n"
+                    .to_string(),
+                1
+            )
+        );
+    }
+}
@@ -385,6 +385,9 @@ pub enum FocusCommand {
     #[strum(message = "Jump to Previous Snippet Placeholder")]
     #[strum(serialize = "jump_to_prev_snippet_placeholder")]
     JumpToPrevSnippetPlaceholder,
+    #[strum(message = "Insert Snippet")]
+    #[strum(serialize = "insert_snippet")]
+    InsertSnippet,
     #[strum(message = "Show Code Actions")]
     #[strum(serialize = "show_code_actions")]
     ShowCodeActions,
@@ -425,6 +428,9 @@ pub enum FocusCommand {
     #[strum(message = "Format Document")]
     #[strum(serialize = "format_document")]
     FormatDocument,
+    #[strum(message = "Format Selection")]
+    #[strum(serialize = "format_selection")]
+    FormatSelection,
     #[strum(message = "Search")]
     #[strum(serialize = "search")]
     Search,
@@ -198,6 +198,8 @@ pub struct DocLines {
     pub inlay_hints:     Option<Spans<InlayHint>>,
     pub completion_lens: Option<String>,
     pub completion_pos:  (usize, usize),
+    /// Text shown after the end of a line, (line, text)
+    pub line_annotation: Option<(usize, String)>,
     pub folding_ranges:  FoldingRanges,
     // pub buffer: Buffer,
     pub diagnostics:     DiagnosticData,
@@ -222,6 +224,9 @@ pub struct DocLines {
     // pub kind: RwSignal<EditorViewKind>,
     pub(crate) signals:    Signals,
     style_from_lsp:        bool,
+    /// The language server provides the folding ranges, else they come from
+    /// the `folds.scm` query of the syntax
+    folding_from_lsp:      bool,
     // folding_items: Vec<FoldingDisplayItem>,
     path:                  Option<PathBuf>,
     document_highlight:    Option<Vec<DocumentHighlight>>,
@@ -261,6 +266,7 @@ impl DocLines {
             // max_width: 0.0,
             inlay_hints: None,
             completion_pos: (0, 0),
+            line_annotation: None,
             folding_ranges: Default::default(),
             // buffer: Buffer::new(""),
             diagnostics,
@@ -273,6 +279,7 @@ impl DocLines {
             // line_styles: Default::default(),
             // kind,
             style_from_lsp: false,
+            folding_from_lsp: false,
             // folding_items: Default::default(),
             document_highlight: None,
         }
@@ -2330,6 +2337,29 @@ impl DocLines {
             }
         }
 
+        if let Some((_, annotation)) = self
+            .line_annotation
+            .as_ref()
+            .filter(|(annotation_line, _)| *annotation_line == line)
+        {
+            let col = buffer.line_end_col(line, true)?;
+            if !folded_ranges.contain_offset(start_offset + col) {
+                text.push(PhantomText {
+                    kind: PhantomTextKind::LineAnnotation,
+                    col,
+                    text: format!("    {annotation}"),
+                    fg: Some(self.config.completion_lens_foreground),
+                    font_size: Some(self.config.inlay_hint_font_size()),
+                    bg: None,
+                    under_line: None,
+                    final_col: col,
+                    line,
+                    visual_merge_col: col,
+                    origin_merge_col: col,
+                });
+            }
+        }
+
         let fg = self.config.inlay_hint_fg;
         let font_size = self.config.inlay_hint_font_size();
         let bg = self.config.inlay_hint_bg;
@@ -4332,6 +4362,11 @@ impl PubUpdateLines {
 
     pub fn set_syntax(&mut self, syntax: Syntax) -> Result<bool> {
         self.syntax = syntax;
+        if !self.folding_from_lsp && self.syntax.rev == self.buffer().rev() {
+            self.update_folding_ranges(UpdateFolding::New(
+                self.syntax.folding_ranges.clone(),
+            ))?;
+        }
         if self.style_from_lsp {
             return Ok(false);
         }
@@ -4344,6 +4379,25 @@ impl PubUpdateLines {
         Ok(true)
     }
 
+    /// The folding ranges of the language server, falling back to the ones of
+    /// the syntax when it has none.
+    pub fn set_lsp_folding_ranges(
+        &mut self,
+        ranges: Vec<lsp_types::FoldingRange>,
+    ) -> Result<()> {
+        self.folding_from_lsp = !ranges.is_empty();
+        let ranges = if self.folding_from_lsp {
+            ranges
+        } else if self.syntax.rev == self.buffer().rev() {
+            self.syntax.folding_ranges.clone()
+        } else {
+            // the next parse of the syntax brings its ranges
+            return Ok(());
+        };
+        self.update_folding_ranges(UpdateFolding::New(ranges))?;
+        Ok(())
+    }
+
     pub fn set_inlay_hints(&mut self, inlay_hint: Spans<InlayHint>) -> Result<()> {
         self.inlay_hints = Some(inlay_hint);
         self.on_update_lines();
@@ -4381,6 +4435,16 @@ impl PubUpdateLines {
         Ok(())
     }
 
+    pub fn set_line_annotation(&mut self, annotation: Option<(usize, String)>) {
+        if self.line_annotation == annotation {
+            return;
+        }
+        self.line_annotation = annotation;
+        self.on_update_lines();
+        self.signals.update_paint_text();
+        self.trigger_signals();
+    }
+
     pub fn update_semantic_styles_from_lsp(
         &mut self,
         styles: (Option<String>, Spans<String>),
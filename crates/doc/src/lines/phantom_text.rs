@@ -292,6 +292,8 @@ pub enum PhantomTextKind {
     InlayHint,
     /// Error lens
     Diagnostic,
+    /// An annotation at the end of a line, like git blame
+    LineAnnotation,
     // 行内折叠。跨行折叠也都转换成行内折叠。跨行折叠会转成2个PhantomText
     LineFoldedRang {
         next_line:      Option<usize>,
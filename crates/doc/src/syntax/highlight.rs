@@ -27,7 +27,9 @@ use tree_sitter::{
 };
 
 use super::{PARSER, util::RopeProvider};
-use crate::language::{LapceLanguage, load_grammar, read_grammar_query};
+use crate::language::{
+    LapceLanguage, default_folds_query, load_grammar, read_grammar_query,
+};
 
 thread_local! {
     static HIGHLIGHT_CONFIGS: RefCell<HashMap<LapceLanguage, Result<Arc<HighlightConfiguration>, HighlightIssue>>> = Default::default();
@@ -141,7 +143,11 @@ pub fn new_highlight_config(
     );
 
     match HighlightConfiguration::new(grammar, &query, &injection, "") {
-        Ok(x) => Ok(x),
+        Ok(mut x) => {
+            x.folds_query =
+                get_folds_query(&x.language, query_name, queries_directory);
+            Ok(x)
+        },
         Err(x) => {
             let str = format!(
                 "Encountered {x:?} while trying to construct \
@@ -153,6 +159,29 @@ pub fn new_highlight_config(
     }
 }
 
+/// The `folds.scm` of the queries directory, or the one built in for the
+/// languages which usually have no language server to fold them.
+fn get_folds_query(
+    language: &Language,
+    query_name: &str,
+    queries_directory: &Path,
+) -> Option<Query> {
+    let file_name = LapceLanguage::FOLDS_QUERIES_FILE_NAME;
+    // most languages have none, which is not worth a warning
+    let query = if queries_directory.join(query_name).join(file_name).exists() {
+        read_grammar_query(queries_directory, query_name, file_name)
+    } else {
+        default_folds_query(query_name)?.to_string()
+    };
+    match Query::new(language, &query) {
+        Ok(query) => Some(query),
+        Err(err) => {
+            error!("{query_name} {file_name}: {err:?}");
+            None
+        },
+    }
+}
+
 fn get_grammar_query(
     query_name: &str,
     highlights_injections_file_name: &str,
@@ -239,6 +268,8 @@ pub struct HighlightConfiguration {
     pub local_def_capture_index: Option<u32>,
     pub local_def_value_capture_index: Option<u32>,
     pub local_ref_capture_index: Option<u32>,
+    /// The nodes captured as `@fold` by `folds.scm` are the folding ranges
+    pub folds_query: Option<Query>,
 }
 
 impl HighlightConfiguration {
@@ -355,6 +386,7 @@ impl HighlightConfiguration {
             local_def_capture_index,
             local_def_value_capture_index,
             local_ref_capture_index,
+            folds_query: None,
         };
         conf.configure(SCOPES);
         Ok(conf)
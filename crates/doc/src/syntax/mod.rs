@@ -24,9 +24,10 @@ use lapce_xi_rope::{
     spans::{Spans, SpansBuilder},
 };
 use log::{debug, error};
+use lsp_types::FoldingRange;
 use slotmap::{DefaultKey as LayerId, HopSlotMap};
 use thiserror::Error;
-use tree_sitter::{Node, Parser, Point, QueryCursor, Tree};
+use tree_sitter::{Node, Parser, Point, Query, QueryCursor, Tree};
 
 use self::{
     edit::SyntaxEdit,
@@ -884,6 +885,10 @@ impl SyntaxLayers {
         self.layers[self.root].try_tree()
     }
 
+    pub fn root_config(&self) -> &HighlightConfiguration {
+        &self.layers[self.root].config
+    }
+
     /// Iterate over the highlighted regions for a given slice of
     /// source code.
     pub fn highlight_iter<'a>(
@@ -961,16 +966,18 @@ impl SyntaxLayers {
 
 #[derive(Clone)]
 pub struct Syntax {
-    pub rev:          u64,
-    pub language:     LapceLanguage,
-    pub text:         Rope,
-    pub layers:       Option<SyntaxLayers>,
-    pub lens:         Lens,
-    pub normal_lines: Vec<usize>,
-    pub line_height:  usize,
-    pub lens_height:  usize,
-    pub styles:       Option<Spans<String>>,
-    pub cancel_flag:  Arc<AtomicUsize>,
+    pub rev:            u64,
+    pub language:       LapceLanguage,
+    pub text:           Rope,
+    pub layers:         Option<SyntaxLayers>,
+    pub lens:           Lens,
+    pub normal_lines:   Vec<usize>,
+    pub line_height:    usize,
+    pub lens_height:    usize,
+    pub styles:         Option<Spans<String>>,
+    /// From the `folds.scm` query of the language, for the `rev`
+    pub folding_ranges: Vec<FoldingRange>,
+    pub cancel_flag:    Arc<AtomicUsize>,
 }
 
 impl std::fmt::Debug for Syntax {
@@ -1025,6 +1032,7 @@ impl Syntax {
             lens_height: 0,
             normal_lines: Vec::new(),
             styles: None,
+            folding_ranges: Vec::new(),
             cancel_flag: Arc::new(AtomicUsize::new(0)),
         }
     }
@@ -1093,6 +1101,11 @@ impl Syntax {
             None
         };
 
+        let folding_ranges = match (tree, &layers.root_config().folds_query) {
+            (Some(tree), Some(query)) => folding_ranges(query, tree, &new_text),
+            _ => Vec::new(),
+        };
+
         let normal_lines = if let Some(tree) = tree {
             let mut cursor = tree.walk();
             let mut normal_lines = HashSet::new();
@@ -1113,6 +1126,7 @@ impl Syntax {
         self.lens = lens;
         self.normal_lines = normal_lines;
         self.styles = styles;
+        self.folding_ranges = folding_ranges;
         self.text = new_text
     }
 
@@ -1329,6 +1343,66 @@ impl Syntax {
     }
 }
 
+/// The ranges of the nodes captured as `@fold` by the query, with at most one
+/// range starting on a line.
+fn folding_ranges(query: &Query, tree: &Tree, text: &Rope) -> Vec<FoldingRange> {
+    let Some(fold) = query.capture_index_for_name("fold") else {
+        return Vec::new();
+    };
+    let mut cursor = QueryCursor::new();
+    let mut ranges: Vec<FoldingRange> = cursor
+        .matches(query, tree.root_node(), RopeProvider(text))
+        .flat_map(|mat| mat.captures)
+        .filter(|capture| capture.index == fold)
+        .filter_map(|capture| {
+            node_folding_range(
+                text,
+                capture.node.start_byte(),
+                capture.node.end_byte(),
+            )
+        })
+        .collect();
+    // the outermost node of a line is the one kept
+    ranges
+        .sort_by_key(|range| (range.start_line, std::cmp::Reverse(range.end_line)));
+    ranges.dedup_by_key(|range| range.start_line);
+    ranges
+}
+
+/// Folds a node from the end of its first line, keeping a closing bracket on
+/// its last line visible. A node within one line is not folded.
+fn node_folding_range(
+    text: &Rope,
+    start: usize,
+    end: usize,
+) -> Option<FoldingRange> {
+    let mut end = end.min(text.len());
+    while end > start && matches!(text.byte_at(end - 1), b'\n' | b'\r') {
+        end -= 1;
+    }
+    let start_line = text.line_of_offset(start);
+    let end_line = text.line_of_offset(end);
+    if end_line <= start_line {
+        return None;
+    }
+    let start_line_end = text.offset_of_line(start_line + 1);
+    let start_character = text
+        .slice_to_cow(text.offset_of_line(start_line)..start_line_end)
+        .trim_end_matches(['\n', '\r'])
+        .len();
+    if matches!(text.byte_at(end - 1), b'}' | b']' | b')') {
+        end -= 1;
+    }
+    Some(FoldingRange {
+        start_line:      start_line as u32,
+        start_character: Some(start_character as u32),
+        end_line:        end_line as u32,
+        end_character:   Some((end - text.offset_of_line(end_line)) as u32),
+        kind:            None,
+        collapsed_text:  None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1365,4 +1439,19 @@ mod tests {
         assert_eq!(Some((90, 25)), iter.next());
         assert_eq!(None, iter.next());
     }
+
+    #[test]
+    fn test_node_folding_range() {
+        let text = Rope::from("fn a() {\n    b();\n}\nc\n");
+        let range = node_folding_range(&text, 7, 20).unwrap();
+        assert_eq!((range.start_line, range.start_character), (0, Some(8)));
+        assert_eq!((range.end_line, range.end_character), (2, Some(0)));
+
+        let text = Rope::from("- a\n  - b\n- c\n");
+        let range = node_folding_range(&text, 0, 10).unwrap();
+        assert_eq!((range.start_line, range.start_character), (0, Some(3)));
+        assert_eq!((range.end_line, range.end_character), (1, Some(5)));
+
+        assert!(node_folding_range(&text, 0, 4).is_none());
+    }
 }
@@ -1,4 +1,5 @@
 pub mod async_command;
+pub mod libtest;
 pub mod sync_command;
 
 use ansi_to_style::parse_byte;
@@ -9,7 +10,7 @@ use cozy_floem::views::{
 };
 use log::warn;
 
-fn resolve_stderr(line: &str) -> StyledText {
+pub fn resolve_stderr(line: &str) -> StyledText {
     let styled_text = parse_byte(line.as_bytes());
     let (text_src, level) =
         if styled_text.text.as_str().trim_start().starts_with("error") {
@@ -35,7 +36,7 @@ fn resolve_stderr(line: &str) -> StyledText {
     }
 }
 
-fn resolve_stdout(line: &str) -> Option<StyledText> {
+pub fn resolve_stdout(line: &str) -> Option<StyledText> {
     if let Ok(parsed) = serde_json::from_str::<Message>(line) {
         match parsed {
             Message::CompilerMessage(msg) => {
@@ -96,6 +97,52 @@ fn resolve_stdout(line: &str) -> Option<StyledText> {
     None
 }
 
+/// A compiler diagnostic from a line of `--message-format=json` stdout,
+/// located by its primary span. Positions are 1-based, as cargo reports them.
+#[derive(Clone, Debug)]
+pub struct BuildDiagnostic {
+    pub file_name:    String,
+    pub line_start:   usize,
+    pub column_start: usize,
+    pub line_end:     usize,
+    pub column_end:   usize,
+    pub level:        Level,
+    pub message:      String,
+    pub code:         Option<String>,
+}
+
+pub fn resolve_diagnostic(line: &str) -> Option<BuildDiagnostic> {
+    let Ok(Message::CompilerMessage(msg)) = serde_json::from_str::<Message>(line)
+    else {
+        return None;
+    };
+    let span = msg.message.spans.iter().find(|x| x.is_primary)?;
+    let level = match msg.message.level {
+        DiagnosticLevel::Ice | DiagnosticLevel::Error => Level::Error,
+        DiagnosticLevel::Warning => Level::Warn,
+        _ => Level::None,
+    };
+    Some(BuildDiagnostic {
+        file_name: span.file_name.clone(),
+        line_start: span.line_start,
+        column_start: span.column_start,
+        line_end: span.line_end,
+        column_end: span.column_end,
+        level,
+        message: msg.message.message.clone(),
+        code: msg.message.code.as_ref().map(|x| x.code.clone()),
+    })
+}
+
+/// Only `E0123` style codes have a page in the error index, lints don't.
+fn error_code_url(code: &str) -> String {
+    if code.starts_with('E') && code[1..].chars().all(|c| c.is_ascii_digit()) {
+        format!("https://doc.rust-lang.org/error_codes/{code}.html")
+    } else {
+        String::new()
+    }
+}
+
 fn resolve_hyperlink_from_message(
     msg: &CompilerMessage,
     text: &str,
@@ -121,12 +168,9 @@ fn resolve_hyperlink_from_message(
         })
         .collect();
     if let Some(code_hyper) = msg.message.code.as_ref().and_then(|x| {
-        text.find(x.code.as_str()).map(|index| {
-            Hyperlink::Url {
-                range: index..index + x.code.len(),
-                // todo
-                url:   "".to_string(),
-            }
+        text.find(x.code.as_str()).map(|index| Hyperlink::Url {
+            range: index..index + x.code.len(),
+            url:   error_code_url(&x.code),
         })
     }) {
         file_hyper.push(code_hyper)
@@ -0,0 +1,270 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+/// A test binary cargo built, from a `compiler-artifact` line of
+/// `cargo test --no-run --message-format=json`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TestBinary {
+    /// The package the target belongs to
+    pub package:       String,
+    /// The kind of the target, like `lib`, `bin` or `test`
+    pub kind:          String,
+    /// The crate or integration test name
+    pub name:          String,
+    /// The root source file of the target
+    pub src:           String,
+    /// The absolute path of the executable
+    pub executable:    String,
+    /// The `Cargo.toml` of the package, cargo runs tests in its directory
+    pub manifest_path: String,
+}
+
+impl TestBinary {
+    /// The arguments of `cargo test` that select this target alone.
+    pub fn target_arguments(&self) -> Vec<String> {
+        let mut arguments = vec!["--package".to_string(), self.package.clone()];
+        match self.kind.as_str() {
+            "bin" | "test" | "bench" | "example" => {
+                arguments.push(format!("--{}", self.kind));
+                arguments.push(self.name.clone());
+            },
+            // lib, rlib, proc-macro and the other library crate types
+            _ => arguments.push("--lib".to_string()),
+        }
+        arguments
+    }
+
+    /// The file that defines the test `name`, following its modules from
+    /// the root of the target the way rustc looks for their files. A module
+    /// without a file of its own is declared inline in its parent.
+    pub fn test_source_file(
+        &self,
+        name: &str,
+        exists: impl Fn(&Path) -> bool,
+    ) -> PathBuf {
+        let mut file = PathBuf::from(&self.src);
+        let mut dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut segments: Vec<&str> = name.split("::").collect();
+        segments.pop();
+        for module in segments {
+            let candidates = [
+                dir.join(format!("{module}.rs")),
+                dir.join(module).join("mod.rs"),
+            ];
+            if let Some(found) = candidates.into_iter().find(|path| exists(path)) {
+                file = found;
+            }
+            dir = dir.join(module);
+        }
+        file
+    }
+}
+
+pub fn parse_test_artifact(line: &str) -> Option<TestBinary> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    if value.get("reason")?.as_str()? != "compiler-artifact"
+        || !value.pointer("/profile/test")?.as_bool()?
+    {
+        return None;
+    }
+    let target = value.get("target")?;
+    let string = |value: &Value, key: &str| -> Option<String> {
+        Some(value.get(key)?.as_str()?.to_string())
+    };
+    Some(TestBinary {
+        package:       package_name(value.get("package_id")?.as_str()?)?.to_string(),
+        kind:          target.get("kind")?.get(0)?.as_str()?.to_string(),
+        name:          string(target, "name")?,
+        src:           string(target, "src_path")?,
+        executable:    string(&value, "executable")?,
+        manifest_path: string(&value, "manifest_path")?,
+    })
+}
+
+/// The name of a package from its id, either a package id spec like
+/// `path+file:///ws/foo#0.1.0` or `path+file:///ws/dir#foo@0.1.0`, or the
+/// `foo 0.1.0 (path+file:///ws/foo)` of older cargo versions.
+fn package_name(id: &str) -> Option<&str> {
+    match id.split_once('#') {
+        Some((url, fragment)) => match fragment.split_once('@') {
+            Some((name, _)) => Some(name),
+            None => url.trim_end_matches('/').rsplit('/').next(),
+        },
+        None => id.split(' ').next(),
+    }
+}
+
+/// The test of a line of `--list --format terse` output, like
+/// `tests::it_works: test`. Benchmarks are skipped.
+pub fn parse_listed_test(line: &str) -> Option<&str> {
+    line.strip_suffix(": test")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestEvent {
+    Started {
+        name: String,
+    },
+    Passed {
+        name:      String,
+        exec_time: Option<f64>,
+    },
+    Failed {
+        name:      String,
+        exec_time: Option<f64>,
+        /// What the test printed, which libtest only keeps for failures
+        stdout:    String,
+    },
+    Ignored {
+        name: String,
+    },
+}
+
+/// The event of a single test from a line of `--format json` output, the
+/// events of the whole suite are skipped.
+pub fn parse_test_event(line: &str) -> Option<TestEvent> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    if value.get("type")?.as_str()? != "test" {
+        return None;
+    }
+    let name = value.get("name")?.as_str()?.to_string();
+    let exec_time = value.get("exec_time").and_then(Value::as_f64);
+    Some(match value.get("event")?.as_str()? {
+        "started" => TestEvent::Started { name },
+        "ok" => TestEvent::Passed { name, exec_time },
+        "failed" | "timeout" => TestEvent::Failed {
+            name,
+            exec_time,
+            stdout: value
+                .get("stdout")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        },
+        "ignored" => TestEvent::Ignored { name },
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary(kind: &str, name: &str, src: &str) -> TestBinary {
+        TestBinary {
+            package:       "foo".to_string(),
+            kind:          kind.to_string(),
+            name:          name.to_string(),
+            src:           src.to_string(),
+            executable:    String::new(),
+            manifest_path: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_test_artifact() {
+        assert_eq!(
+            parse_test_artifact(
+                r#"{"reason":"compiler-artifact","package_id":"path+file:///ws/dir#foo@0.1.0","manifest_path":"/ws/dir/Cargo.toml","target":{"kind":["test"],"name":"it","src_path":"/ws/dir/tests/it.rs"},"profile":{"test":true},"executable":"/ws/target/debug/deps/it-0123abcd","fresh":true}"#
+            ),
+            Some(TestBinary {
+                package:       "foo".to_string(),
+                kind:          "test".to_string(),
+                name:          "it".to_string(),
+                src:           "/ws/dir/tests/it.rs".to_string(),
+                executable:    "/ws/target/debug/deps/it-0123abcd".to_string(),
+                manifest_path: "/ws/dir/Cargo.toml".to_string(),
+            })
+        );
+        // the library itself, built for the tests of the other targets
+        assert_eq!(
+            parse_test_artifact(
+                r#"{"reason":"compiler-artifact","package_id":"path+file:///ws/foo#0.1.0","manifest_path":"/ws/foo/Cargo.toml","target":{"kind":["lib"],"name":"foo","src_path":"/ws/foo/src/lib.rs"},"profile":{"test":false},"executable":null,"fresh":true}"#
+            ),
+            None
+        );
+        assert_eq!(
+            parse_test_artifact(r#"{"reason":"build-finished","success":true}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("path+file:///ws/foo#0.1.0"), Some("foo"));
+        assert_eq!(package_name("path+file:///ws/dir#foo@0.1.0"), Some("foo"));
+        assert_eq!(package_name("foo 0.1.0 (path+file:///ws/foo)"), Some("foo"));
+    }
+
+    #[test]
+    fn test_target_arguments() {
+        assert_eq!(
+            binary("lib", "foo", "src/lib.rs").target_arguments(),
+            ["--package", "foo", "--lib"]
+        );
+        assert_eq!(
+            binary("test", "it", "tests/it.rs").target_arguments(),
+            ["--package", "foo", "--test", "it"]
+        );
+    }
+
+    #[test]
+    fn test_test_source_file() {
+        let binary = binary("lib", "foo", "src/lib.rs");
+        let files = [Path::new("src/a.rs"), Path::new("src/a/b/mod.rs")];
+        let exists = |path: &Path| files.contains(&path);
+        assert_eq!(
+            binary.test_source_file("tests::it_works", exists),
+            Path::new("src/lib.rs")
+        );
+        assert_eq!(
+            binary.test_source_file("a::tests::it_works", exists),
+            Path::new("src/a.rs")
+        );
+        assert_eq!(
+            binary.test_source_file("a::b::tests::it_works", exists),
+            Path::new("src/a/b/mod.rs")
+        );
+        // `c` is declared inline in `a`
+        assert_eq!(
+            binary.test_source_file("a::c::it_works", exists),
+            Path::new("src/a.rs")
+        );
+    }
+
+    #[test]
+    fn test_parse_listed_test() {
+        assert_eq!(
+            parse_listed_test("tests::it_works: test"),
+            Some("tests::it_works")
+        );
+        assert_eq!(parse_listed_test("benches::fast: bench"), None);
+    }
+
+    #[test]
+    fn test_parse_test_event() {
+        assert_eq!(
+            parse_test_event(
+                r#"{ "type": "test", "event": "started", "name": "a::b" }"#
+            ),
+            Some(TestEvent::Started {
+                name: "a::b".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_test_event(
+                r#"{ "type": "test", "name": "a::b", "event": "failed", "exec_time": 0.5, "stdout": "boom\n" }"#
+            ),
+            Some(TestEvent::Failed {
+                name:      "a::b".to_string(),
+                exec_time: Some(0.5),
+                stdout:    "boom\n".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_test_event(r#"{ "type": "suite", "event": "ok", "passed": 1 }"#),
+            None
+        );
+    }
+}
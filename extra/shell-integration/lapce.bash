@@ -0,0 +1,97 @@
+# Shell integration of the Lapce terminal for bash, loaded with `--init-file`.
+# It marks the prompt, the command and its output with OSC 633 sequences.
+
+if [ -r ~/.bashrc ]; then
+    . ~/.bashrc
+fi
+
+# only once, and only in an interactive shell
+if [[ -n "$__lapce_loaded" || $- != *i* ]]; then
+    return
+fi
+__lapce_loaded=1
+
+__lapce_at_prompt=0
+__lapce_running=0
+__lapce_original_prompt_command="$PROMPT_COMMAND"
+
+# `;`, `\` and control characters would end or garble the sequence
+__lapce_escape() {
+    local text="$1" out="" char i
+    for ((i = 0; i < ${#text}; i++)); do
+        char="${text:i:1}"
+        case "$char" in
+            '\') out+='\\' ;;
+            ';') out+='\x3b' ;;
+            $'\n') out+='\x0a' ;;
+            $'\r') out+='\x0d' ;;
+            *) out+="$char" ;;
+        esac
+    done
+    printf '%s' "$out"
+}
+
+__lapce_preexec() {
+    # the DEBUG trap runs for every simple command, the prompt's included
+    if [[ "$__lapce_at_prompt" != 1 || "$BASH_COMMAND" == __lapce_prompt_command* ]]; then
+        return
+    fi
+    __lapce_at_prompt=0
+    __lapce_running=1
+    # a command kept out of the history leaves the last entry as it was, and
+    # the terminal then reads the command line off the screen instead
+    local entry
+    entry="$(HISTTIMEFORMAT= builtin history 1)"
+    if [[ "$entry" != "$__lapce_last_entry" ]]; then
+        __lapce_last_entry="$entry"
+        # the entry starts with its number
+        if [[ "$entry" =~ ^\ *[0-9]+\ *(.*) ]]; then
+            printf '\e]633;E;%s\a' "$(__lapce_escape "${BASH_REMATCH[1]}")"
+        fi
+    fi
+    printf '\e]633;C\a'
+}
+
+__lapce_prompt_command() {
+    local ret=$?
+    if [[ "$__lapce_running" == 1 ]]; then
+        printf '\e]633;D;%s\a' "$ret"
+        __lapce_running=0
+    fi
+    printf '\e]633;P;Cwd=%s\a' "$(__lapce_escape "$PWD")"
+    if [[ -n "$__lapce_original_prompt_command" ]]; then
+        eval "$__lapce_original_prompt_command"
+    fi
+    if [[ "$PS1" != *'633;A'* ]]; then
+        PS1='\[\e]633;A\a\]'"$PS1"'\[\e]633;B\a\]'
+    fi
+    __lapce_at_prompt=1
+    return $ret
+}
+
+# a DEBUG trap of the user's own keeps running, before ours
+__lapce_original_debug_trap="$(trap -p DEBUG)"
+if [[ -n "$__lapce_original_debug_trap" ]]; then
+    # `trap -p` prints `trap -- 'command' DEBUG`, with the command quoted
+    __lapce_original_debug_trap="${__lapce_original_debug_trap#trap -- }"
+    eval "__lapce_original_debug_trap=${__lapce_original_debug_trap% DEBUG}"
+fi
+
+__lapce_return() {
+    return "$1"
+}
+
+__lapce_debug_trap() {
+    local ret=$?
+    if [[ -n "$__lapce_original_debug_trap" ]]; then
+        # with the status the trap would have seen
+        __lapce_return "$ret"
+        eval "$__lapce_original_debug_trap"
+        ret=$?
+    fi
+    __lapce_preexec
+    return $ret
+}
+
+trap '__lapce_debug_trap' DEBUG
+PROMPT_COMMAND=__lapce_prompt_command
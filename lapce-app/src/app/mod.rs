@@ -37,8 +37,8 @@ use floem::{
         create_effect, create_memo, create_rw_signal, provide_context, use_context,
     },
     style::{
-        AlignItems, CursorStyle, Display, FlexDirection, JustifyContent, Position,
-        Style,
+        AlignItems, CursorStyle, Display, FlexDirection, FlexWrap, JustifyContent,
+        Position, Style,
     },
     taffy::{
         Line,
@@ -67,11 +67,11 @@ use lapce_core::{
 };
 use lapce_rpc::{
     RpcMessage,
-    core::{CoreMessage, CoreNotification},
+    core::{CoreMessage, CoreNotification, CoreRpcHandler},
     file::PathObject,
 };
 use log::{error, trace};
-use lsp_types::{CompletionItemKind, MessageType, ShowMessageParams};
+use lsp_types::{CompletionItemKind, MessageType};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -117,7 +117,7 @@ use crate::{
     title::title,
     update::ReleaseInfo,
     window::{WindowData, WindowInfo},
-    window_workspace::{Focus, WindowWorkspaceData},
+    window_workspace::{Focus, WindowMessage, WindowWorkspaceData},
 };
 
 pub(crate) mod grammars;
@@ -247,7 +247,26 @@ impl AppData {
         } else {
             config
         };
-        let workspace = Arc::new(LapceWorkspace::new_with_path(folder));
+        let workspace = match folder {
+            Some(file) if LapceWorkspace::is_workspace_file(&file) => {
+                match LapceWorkspace::from_file(
+                    LapceWorkspaceType::Local,
+                    file,
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs(),
+                ) {
+                    Ok(workspace) => workspace,
+                    Err(err) => {
+                        error!("failed to open the workspace file: {err:?}");
+                        return;
+                    },
+                }
+            },
+            folder => LapceWorkspace::new_with_path(folder),
+        };
+        let workspace = Arc::new(workspace);
         let app_data = self.clone();
         floem::new_window(
             move |window_id| {
@@ -312,10 +331,11 @@ impl AppData {
 
         let mut inital_windows = 0;
 
-        // Split user input into known existing directors and
-        // file paths that exist or not
-        let (dirs, files): (Vec<&PathObject>, Vec<&PathObject>) =
-            paths.iter().partition(|p| p.is_dir);
+        // Split user input into known existing directors and workspace files,
+        // and file paths that exist or not
+        let (dirs, files): (Vec<&PathObject>, Vec<&PathObject>) = paths
+            .iter()
+            .partition(|p| p.is_dir || LapceWorkspace::is_workspace_file(&p.path));
 
         let files: Vec<PathObject> = files.into_iter().cloned().collect();
         let mut files = if files.is_empty() { None } else { Some(files) };
@@ -345,15 +365,26 @@ impl AppData {
                 #[cfg(not(windows))]
                 let workspace_type = LapceWorkspaceType::Local;
 
+                let workspace = if dir.is_dir {
+                    LapceWorkspace::new(workspace_type, Some(dir.path.to_owned()), 0)
+                } else {
+                    match LapceWorkspace::from_file(
+                        workspace_type,
+                        dir.path.to_owned(),
+                        0,
+                    ) {
+                        Ok(workspace) => workspace,
+                        Err(err) => {
+                            error!("failed to open {:?}: {err:?}", dir.path);
+                            continue;
+                        },
+                    }
+                };
                 let info = WindowInfo {
                     size,
                     pos,
                     maximised: false,
-                    workspace: Arc::new(LapceWorkspace::new(
-                        workspace_type,
-                        Some(dir.path.to_owned()),
-                        0,
-                    )),
+                    workspace: Arc::new(workspace),
                 };
 
                 pos += (50.0, 50.0);
@@ -649,7 +680,9 @@ impl AppData {
         })
         .on_event_stop(EventListener::DroppedFile, move |event: &Event| {
             if let Event::DroppedFile(file) = event {
-                if file.path.is_dir() {
+                if file.path.is_dir()
+                    || LapceWorkspace::is_workspace_file(&file.path)
+                {
                     app_command.send(AppCommand::NewWindow {
                         folder: Some(file.path.clone()),
                     });
@@ -2287,7 +2320,11 @@ fn workbench(window_tab_data: WindowWorkspaceData) -> impl View {
                     .border_top(1.0)
                     .border_color(config.with_color(LapceColor::LAPCE_BORDER))
             }),
-        window_message_view(window_tab_data.messages, window_tab_data.common.config),
+        window_message_view(
+            window_tab_data.messages,
+            window_tab_data.proxy.core_rpc.clone(),
+            window_tab_data.common.config,
+        ),
     ))
     .on_resize(move |rect| {
         let size = rect.size();
@@ -2476,14 +2513,7 @@ fn palette_item(
             let kind = *kind;
 
             let path = location.path.clone();
-            let full_path = location.path.clone();
-            let path = if let Some(workspace_path) = workspace.path() {
-                path.strip_prefix(workspace_path)
-                    .unwrap_or(&full_path)
-                    .to_path_buf()
-            } else {
-                path
-            };
+            let path = workspace.relative_path(&path).unwrap_or(path);
 
             let hint = path.to_string_lossy().to_string();
             let text_indices: Vec<usize> = item
@@ -2672,9 +2702,11 @@ fn palette_item(
         | PaletteItemContent::SshHost { .. }
         | PaletteItemContent::Language { .. }
         | PaletteItemContent::LineEnding { .. }
+        | PaletteItemContent::Encoding { .. }
         | PaletteItemContent::ColorTheme { .. }
         | PaletteItemContent::SCMReference { .. }
         | PaletteItemContent::TerminalProfile { .. }
+        | PaletteItemContent::AttachProcess { .. }
         | PaletteItemContent::IconTheme { .. } => {
             let text = item.filter_text;
             let indices = item.indices;
@@ -2972,99 +3004,164 @@ fn palette(window_tab_data: WindowWorkspaceData) -> impl View {
 }
 
 fn window_message_view(
-    messages: RwSignal<Vec<(String, ShowMessageParams)>>,
+    messages: RwSignal<Vec<WindowMessage>>,
+    core_rpc: CoreRpcHandler,
     config: WithLapceConfig,
 ) -> impl View {
-    let view_fn =
-        move |(i, (title, message)): (usize, (String, ShowMessageParams))| {
-            stack((
-                svg(move || {
-                    if let MessageType::ERROR = message.typ {
-                        config.with_ui_svg(LapceIcons::ERROR)
-                    } else {
-                        config.with_ui_svg(LapceIcons::WARNING)
-                    }
-                })
-                .style(move |s| {
-                    let (size, color) = config.signal(|config| {
-                        (
-                            config.ui.icon_size.signal(),
-                            if let MessageType::ERROR = message.typ {
-                                config.color(LapceColor::LAPCE_ERROR)
-                            } else {
-                                config.color(LapceColor::LAPCE_WARN)
-                            },
-                        )
-                    });
-                    let size = size.get() as f32;
-                    s.min_width(size)
-                        .size(size, size)
-                        .margin_right(10.0)
-                        .margin_top(4.0)
-                        .color(color.get())
-                }),
-                stack((
-                    text(title.clone()).style(|s| {
-                        s.min_width(0.0).line_height(1.8).font_weight(Weight::BOLD)
-                    }),
-                    text(message.message.clone()).style(|s| {
-                        s.min_width(0.0).line_height(1.8).margin_top(5.0)
-                    }),
-                ))
-                .style(move |s| {
-                    s.flex_col().min_width(0.0).flex_basis(0.0).flex_grow(1.0)
-                }),
-                clickable_icon(
-                    || LapceIcons::CLOSE,
-                    move || {
-                        messages.update(|messages| {
-                            messages.remove(i);
-                        });
-                    },
-                    || false,
-                    || false,
-                    || "Close",
-                    config,
-                )
-                .style(|s| s.margin_left(6.0)),
-            ))
-            .on_secondary_click_stop({
-                let message = message.message.clone();
-                move |_| {
-                    let mut clipboard = SystemClipboard::new();
-                    if !message.is_empty() {
-                        clipboard.put_string(&message);
-                    }
-                }
-            })
-            .on_event_stop(EventListener::PointerDown, move |event: &Event| {
-                if let Event::PointerDown(pointer) = event {
-                    if pointer.count == 2 {
+    // a message waiting for an action is answered with none when dismissed
+    let remove = move |core_rpc: &CoreRpcHandler, i: usize| {
+        messages.update(|messages| {
+            if i < messages.len() {
+                messages.remove(i).answer(core_rpc, None);
+            }
+        });
+    };
+    let view_fn = move |(i, window_message): (usize, WindowMessage)| {
+        let WindowMessage {
+            title,
+            message,
+            actions,
+            ..
+        } = window_message;
+        let core_rpc = core_rpc.clone();
+        let action_buttons = {
+            let core_rpc = core_rpc.clone();
+            dyn_stack(
+                move || actions.clone(),
+                |action| action.title.clone(),
+                move |action| {
+                    let core_rpc = core_rpc.clone();
+                    label({
+                        let title = action.title.clone();
+                        move || title.clone()
+                    })
+                    .on_click_stop(move |_| {
                         messages.update(|messages| {
-                            messages.remove(i);
+                            if i < messages.len() {
+                                messages
+                                    .remove(i)
+                                    .answer(&core_rpc, Some(action.clone()));
+                            }
                         });
-                    } else if pointer.count > 3 {
-                        messages.update(|messages| messages.clear());
-                    }
+                    })
+                    .style(move |s| {
+                        let (border_color, br_color, abr_color) =
+                            config.signal(|config| {
+                                (
+                                    config.color(LapceColor::LAPCE_BORDER),
+                                    config
+                                        .color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                                    config.color(
+                                        LapceColor::PANEL_HOVERED_ACTIVE_BACKGROUND,
+                                    ),
+                                )
+                            });
+                        s.margin_top(8.0)
+                            .margin_right(8.0)
+                            .padding_horiz(10.0)
+                            .line_height(1.6)
+                            .border(1.0)
+                            .border_radius(6.0)
+                            .border_color(border_color.get())
+                            .hover(|s| {
+                                s.cursor(CursorStyle::Pointer)
+                                    .background(br_color.get())
+                            })
+                            .active(|s| s.background(abr_color.get()))
+                    })
+                },
+            )
+            .style(|s| s.flex_row().flex_wrap(FlexWrap::Wrap))
+        };
+        stack((
+            svg(move || {
+                if let MessageType::ERROR = message.typ {
+                    config.with_ui_svg(LapceIcons::ERROR)
+                } else {
+                    config.with_ui_svg(LapceIcons::WARNING)
                 }
             })
             .style(move |s| {
-                let (caret_color, bg) = config.signal(|config| {
+                let (size, color) = config.signal(|config| {
                     (
-                        config.color(LapceColor::LAPCE_BORDER),
-                        config.color(LapceColor::PANEL_BACKGROUND),
+                        config.ui.icon_size.signal(),
+                        if let MessageType::ERROR = message.typ {
+                            config.color(LapceColor::LAPCE_ERROR)
+                        } else {
+                            config.color(LapceColor::LAPCE_WARN)
+                        },
                     )
                 });
-                s.width_full()
-                    .items_start()
-                    .padding(10.0)
-                    .border(1.0)
-                    .border_radius(6.0)
-                    .border_color(caret_color.get())
-                    .background(bg.get())
-                    .apply_if(i > 0, |s| s.margin_top(10.0))
-            })
-        };
+                let size = size.get() as f32;
+                s.min_width(size)
+                    .size(size, size)
+                    .margin_right(10.0)
+                    .margin_top(4.0)
+                    .color(color.get())
+            }),
+            stack((
+                text(title.clone()).style(|s| {
+                    s.min_width(0.0).line_height(1.8).font_weight(Weight::BOLD)
+                }),
+                text(message.message.clone())
+                    .style(|s| s.min_width(0.0).line_height(1.8).margin_top(5.0)),
+                action_buttons,
+            ))
+            .style(move |s| {
+                s.flex_col().min_width(0.0).flex_basis(0.0).flex_grow(1.0)
+            }),
+            clickable_icon(
+                || LapceIcons::CLOSE,
+                {
+                    let core_rpc = core_rpc.clone();
+                    move || remove(&core_rpc, i)
+                },
+                || false,
+                || false,
+                || "Close",
+                config,
+            )
+            .style(|s| s.margin_left(6.0)),
+        ))
+        .on_secondary_click_stop({
+            let message = message.message.clone();
+            move |_| {
+                let mut clipboard = SystemClipboard::new();
+                if !message.is_empty() {
+                    clipboard.put_string(&message);
+                }
+            }
+        })
+        .on_event_stop(EventListener::PointerDown, move |event: &Event| {
+            if let Event::PointerDown(pointer) = event {
+                if pointer.count == 2 {
+                    remove(&core_rpc, i);
+                } else if pointer.count > 3 {
+                    messages.update(|messages| {
+                        for message in messages.drain(..) {
+                            message.answer(&core_rpc, None);
+                        }
+                    });
+                }
+            }
+        })
+        .style(move |s| {
+            let (caret_color, bg) = config.signal(|config| {
+                (
+                    config.color(LapceColor::LAPCE_BORDER),
+                    config.color(LapceColor::PANEL_BACKGROUND),
+                )
+            });
+            s.width_full()
+                .items_start()
+                .padding(10.0)
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(caret_color.get())
+                .background(bg.get())
+                .apply_if(i > 0, |s| s.margin_top(10.0))
+        })
+    };
 
     let id = AtomicU64::new(0);
     container(
@@ -4424,6 +4521,9 @@ pub fn window_menu(
                 .entry(MenuItem::new("Open Folder").action(move || {
                     workbench_command.send(LapceWorkbenchCommand::OpenFolder);
                 }))
+                .entry(MenuItem::new("Open Workspace File").action(move || {
+                    workbench_command.send(LapceWorkbenchCommand::OpenWorkspaceFile);
+                }))
                 .separator()
                 .entry(MenuItem::new("Save").action(move || {
                     lapce_command.send(LapceCommand {
@@ -1,7 +1,9 @@
 use std::rc::Rc;
 
 use lapce_core::debug::RunDebugMode;
-use lapce_rpc::dap_types::{ConfigSource, RunDebugConfig, RunDebugProgram};
+use lapce_rpc::dap_types::{
+    ConfigSource, DebugRequest, RunDebugConfig, RunDebugProgram,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -34,7 +36,9 @@ impl CodeLensData {
         Self { common }
     }
 
-    pub fn run(&self, command: &str, args: Vec<Value>) {
+    /// Runs the commands known to need the editor, returning false for the
+    /// others, which are left for the language server to execute.
+    pub fn run(&self, command: &str, args: &[Value]) -> bool {
         match command {
             "rust-analyzer.runSingle" | "rust-analyzer.debugSingle" => {
                 let mode = if command == "rust-analyzer.runSingle" {
@@ -42,15 +46,14 @@ impl CodeLensData {
                 } else {
                     RunDebugMode::Debug
                 };
-                if let Some(config) = self.get_rust_command_config(&args, mode) {
+                if let Some(config) = self.get_rust_command_config(args, mode) {
                     self.common
                         .internal_command
                         .send(InternalCommand::RunAndDebug { mode, config });
                 }
+                true
             },
-            _ => {
-                log::debug!("todo {:}", command);
-            },
+            _ => false,
         }
     }
 
@@ -128,6 +131,7 @@ impl CodeLensData {
             Some(RunDebugConfig {
                 ty,
                 name: cargo_args.label,
+                request: DebugRequest::Launch,
                 program,
                 args: Some(cargo_args.args.cargo_args),
                 cwd: None,
@@ -137,6 +141,9 @@ impl CodeLensData {
                 dap_id: Default::default(),
                 tracing_output,
                 config_source: ConfigSource::RustCodeLens,
+                exception_filters: None,
+                break_on_panic: false,
+                pid: None,
             })
         } else {
             log::error!("no args");
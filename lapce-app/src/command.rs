@@ -13,7 +13,8 @@ use lapce_rpc::{
     dap_types::{DapId, RunDebugConfig},
     plugin::{PluginId, VoltID},
     proxy::ProxyStatus,
-    terminal::TerminalProfile,
+    source_control::FileDiff,
+    terminal::{TermId, TerminalProfile},
 };
 use log::LevelFilter;
 use lsp_types::{CodeActionOrCommand, Position, WorkspaceEdit};
@@ -23,6 +24,7 @@ use strum_macros::{Display, EnumIter, EnumMessage, EnumString, IntoStaticStr};
 
 use crate::{
     alert::AlertButton,
+    debug::BreakpointEditField,
     doc::Doc,
     editor::{DocSignal, location::EditorLocation},
     editor_tab::EditorTabChildId,
@@ -171,6 +173,10 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Open Folder")]
     OpenFolder,
 
+    #[strum(serialize = "open_workspace_file")]
+    #[strum(message = "Open Workspace File")]
+    OpenWorkspaceFile,
+
     #[strum(serialize = "close_folder")]
     #[strum(message = "Close Folder")]
     CloseFolder,
@@ -183,6 +189,10 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Show Call Hierarchy")]
     ShowCallHierarchy,
 
+    #[strum(serialize = "show_type_hierarchy")]
+    #[strum(message = "Show Type Hierarchy")]
+    ShowTypeHierarchy,
+
     #[strum(serialize = "find_references")]
     #[strum(message = "Find References")]
     FindReferences,
@@ -325,6 +335,22 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Previous Terminal Tab")]
     PreviousTerminalTab,
 
+    #[strum(serialize = "terminal_previous_prompt")]
+    #[strum(message = "Go To Previous Terminal Prompt")]
+    TerminalPreviousPrompt,
+
+    #[strum(serialize = "terminal_next_prompt")]
+    #[strum(message = "Go To Next Terminal Prompt")]
+    TerminalNextPrompt,
+
+    #[strum(serialize = "terminal_copy_last_output")]
+    #[strum(message = "Copy Last Terminal Command Output")]
+    TerminalCopyLastOutput,
+
+    #[strum(serialize = "terminal_rerun_last_command")]
+    #[strum(message = "Rerun Last Terminal Command")]
+    TerminalRerunLastCommand,
+
     #[strum(serialize = "next_window_tab")]
     #[strum(message = "Go To Next Window Tab")]
     NextWindowTab,
@@ -494,6 +520,38 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "focus_terminal")]
     FocusTerminal,
 
+    #[strum(message = "Cargo: Check")]
+    #[strum(serialize = "cargo_check")]
+    CargoCheck,
+
+    #[strum(message = "Cargo: Build")]
+    #[strum(serialize = "cargo_build")]
+    CargoBuild,
+
+    #[strum(message = "Cargo: Clippy")]
+    #[strum(serialize = "cargo_clippy")]
+    CargoClippy,
+
+    #[strum(message = "Cargo: Test")]
+    #[strum(serialize = "cargo_test")]
+    CargoTest,
+
+    #[strum(message = "Cargo: Stop")]
+    #[strum(serialize = "cargo_stop")]
+    CargoStop,
+
+    #[strum(message = "Test: Refresh Tests")]
+    #[strum(serialize = "test_refresh")]
+    TestRefresh,
+
+    #[strum(message = "Test: Run All Tests")]
+    #[strum(serialize = "test_run_all")]
+    TestRunAll,
+
+    #[strum(message = "Test: Stop")]
+    #[strum(serialize = "test_stop")]
+    TestStop,
+
     #[strum(message = "Source Control: Init")]
     #[strum(serialize = "source_control_init")]
     SourceControlInit,
@@ -516,6 +574,30 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "source_control_discard_workspace_changes")]
     SourceControlDiscardWorkspaceChanges,
 
+    #[strum(message = "Source Control: Stage Selected Lines")]
+    #[strum(serialize = "source_control_stage_selected_lines")]
+    SourceControlStageSelectedLines,
+
+    #[strum(message = "Source Control: Stage Hunk")]
+    #[strum(serialize = "source_control_stage_hunk")]
+    SourceControlStageHunk,
+
+    #[strum(message = "Source Control: Unstage Selected Lines")]
+    #[strum(serialize = "source_control_unstage_selected_lines")]
+    SourceControlUnstageSelectedLines,
+
+    #[strum(message = "Source Control: Unstage Hunk")]
+    #[strum(serialize = "source_control_unstage_hunk")]
+    SourceControlUnstageHunk,
+
+    #[strum(message = "Source Control: Show File History")]
+    #[strum(serialize = "source_control_show_file_history")]
+    SourceControlShowFileHistory,
+
+    #[strum(message = "Source Control: Show History")]
+    #[strum(serialize = "source_control_show_history")]
+    SourceControlShowHistory,
+
     #[strum(serialize = "export_current_theme_settings")]
     #[strum(message = "Export current settings to a theme file")]
     ExportCurrentThemeSettings,
@@ -532,6 +614,18 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Change current file line ending")]
     ChangeFileLineEnding,
 
+    #[strum(serialize = "change_file_encoding")]
+    #[strum(message = "Reopen or save current file with encoding")]
+    ChangeFileEncoding,
+
+    #[strum(serialize = "merge_changes_on_disk")]
+    #[strum(message = "Merge Changes on Disk")]
+    MergeChangesOnDisk,
+
+    #[strum(serialize = "show_local_history")]
+    #[strum(message = "Local History: Show File History")]
+    ShowLocalHistory,
+
     #[strum(serialize = "next_editor_tab")]
     #[strum(message = "Next Editor Tab")]
     NextEditorTab,
@@ -642,7 +736,14 @@ pub enum InternalCommand {
     },
     MakeConfirmed,
     OpenFileChanges {
-        path: PathBuf,
+        path:   PathBuf,
+        /// Show the changes in the index rather than the working copy
+        staged: bool,
+    },
+    /// Show the changes a commit made to a file against its first parent
+    OpenCommitFileChanges {
+        commit: String,
+        diff:   FileDiff,
     },
     ReloadFileExplorer,
     /// Test whether a file/directory can be created at that path
@@ -689,18 +790,22 @@ pub enum InternalCommand {
     NewTerminal {
         profile: Option<TerminalProfile>,
     },
-    // SplitTerminal {
-    //     term_id: TermId,
-    // },
-    // SplitTerminalPrevious {
-    //     term_id: TermId,
-    // },
-    // SplitTerminalNext {
-    //     term_id: TermId,
-    // },
-    // SplitTerminalExchange {
-    //     term_id: TermId,
-    // },
+    SplitTerminal {
+        term_id:   TermId,
+        direction: SplitDirection,
+    },
+    SplitTerminalPrevious {
+        term_id: TermId,
+    },
+    SplitTerminalNext {
+        term_id: TermId,
+    },
+    SplitTerminalExchange {
+        term_id: TermId,
+    },
+    CloseTerminal {
+        term_id: TermId,
+    },
     EditorTabClose {
         editor_tab_id: EditorTabManageId,
     },
@@ -797,6 +902,18 @@ pub enum InternalCommand {
         dap_id:   DapId,
         frame_id: usize,
     },
+    DapDisassemble {
+        dap_id:           DapId,
+        memory_reference: String,
+        /// Open the disassembly if it isn't already shown.
+        reveal:           bool,
+    },
+    OpenVirtualDoc {
+        name:    String,
+        content: String,
+        line:    Option<usize>,
+        reveal:  bool,
+    },
     OpenVoltView {
         volt_id: VoltID,
     },
@@ -810,7 +927,8 @@ pub enum InternalCommand {
         program:   String,
         arguments: Vec<String>,
     },
-    CallHierarchyIncoming {
+    /// Fetches the children of an item of the call or the type hierarchy
+    HierarchyExpand {
         root_id: ViewId,
         item_id: ViewId,
     },
@@ -829,9 +947,17 @@ pub enum InternalCommand {
         doc:      DocSignal,
         line_num: usize,
     },
+    EditBreakPoint {
+        doc:      DocSignal,
+        line_num: usize,
+        field:    BreakpointEditField,
+    },
     ShowStatusMessage {
         message: String,
     },
+    /// The index or HEAD changed, so the git versions in diff editors are
+    /// stale
+    ReloadHistoryDocs,
 }
 
 #[derive(Clone)]
@@ -34,6 +34,18 @@ pub struct ScoredCompletionItem {
     pub indices:     Vec<usize>,
 }
 
+impl ScoredCompletionItem {
+    pub fn new(item: CompletionItem, plugin_id: PluginId) -> Self {
+        Self {
+            item,
+            plugin_id,
+            score: 0,
+            label_score: 0,
+            indices: Vec::new(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct CompletionData {
     pub status:           CompletionStatus,
@@ -58,6 +70,9 @@ pub struct CompletionData {
     pub input_items:      im::HashMap<String, im::Vector<ScoredCompletionItem>>,
     /// The filtered items that are being displayed to the user
     pub filtered_items:   im::Vector<ScoredCompletionItem>,
+    /// The user snippets of the language of the document, listed along with
+    /// the items of the language servers
+    pub snippets:         im::Vector<ScoredCompletionItem>,
     /// The size of the completion element.  
     /// This is used for positioning the element.  
     /// As well, it is needed for some movement commands like page up/down that
@@ -83,6 +98,7 @@ impl CompletionData {
             input: "".to_string(),
             input_items: im::HashMap::new(),
             filtered_items: im::Vector::new(),
+            snippets: im::Vector::new(),
             layout_rect: Rect::ZERO,
             matcher: cx
                 .create_rw_signal(nucleo::Matcher::new(nucleo::Config::DEFAULT)),
@@ -112,13 +128,7 @@ impl CompletionData {
         };
         let items: im::Vector<ScoredCompletionItem> = items
             .iter()
-            .map(|i| ScoredCompletionItem {
-                item: i.to_owned(),
-                plugin_id,
-                score: 0,
-                label_score: 0,
-                indices: Vec::new(),
-            })
+            .map(|i| ScoredCompletionItem::new(i.to_owned(), plugin_id))
             .collect();
         self.input_items.insert(input.to_string(), items);
         self.filter_items();
@@ -150,6 +160,7 @@ impl CompletionData {
         self.input.clear();
         self.input_items.clear();
         self.filtered_items.clear();
+        self.snippets.clear();
     }
 
     pub fn update_input(&mut self, input: String) {
@@ -167,13 +178,16 @@ impl CompletionData {
     }
 
     fn all_items(&self) -> im::Vector<ScoredCompletionItem> {
-        self.input_items
+        let mut items = self
+            .input_items
             .get(&self.input)
             .cloned()
             .filter(|items| !items.is_empty())
             .unwrap_or_else(move || {
                 self.input_items.get("").cloned().unwrap_or_default()
-            })
+            });
+        items.append(self.snippets.clone());
+        items
     }
 
     pub fn filter_items(&mut self) {
@@ -1,3 +1,4 @@
+use lapce_rpc::buffer::SaveMode;
 use serde::{Deserialize, Serialize};
 use structdesc::FieldNames;
 
@@ -21,4 +22,16 @@ pub struct CoreConfig {
         desc = "Enable auto-reload for the plugin when its configuration changes."
     )]
     pub auto_reload_plugin: bool,
+    #[field_names(desc = "How many snapshots of a file to keep in the local \
+                          history. Set it to 0 to stop taking snapshots.")]
+    pub local_history_max_entries: usize,
+    #[field_names(desc = "How many days to keep the snapshots of the local \
+                          history. Set it to 0 to keep them until the file has \
+                          too many.")]
+    pub local_history_max_days: usize,
+    #[field_names(desc = "How files are written on save. \"atomic\" writes a \
+                          temporary file and renames it over the file, falling \
+                          back to writing in place for hard-linked files and \
+                          directories that can't be written to.")]
+    pub save_mode: SaveMode,
 }
@@ -15,6 +15,28 @@ pub enum ClickMode {
     DoubleClickAll,
 }
 
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::VariantNames,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum FormatOnSaveMode {
+    /// Format the whole document
+    #[default]
+    File,
+    /// Format only the lines changed since the git HEAD, or the whole document
+    /// if it isn't tracked
+    ModifiedLines,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum WrapStyle {
@@ -123,6 +145,13 @@ pub struct EditorConfig {
     #[field_names(desc = "Whether it should format the document on save (if \
                           there is an available formatter)")]
     pub format_on_save: bool,
+    #[field_names(desc = "What is formatted on save: the whole document (file) \
+                          or only the lines changed since the git HEAD \
+                          (modified-lines)")]
+    pub format_on_save_mode: FormatOnSaveMode,
+    #[field_names(desc = "Whether the language server should format the line \
+                          after typing one of its trigger characters")]
+    pub format_on_type: bool,
 
     #[field_names(desc = "Whether newlines should be automatically converted to \
                           the current line ending")]
@@ -190,6 +219,10 @@ pub struct EditorConfig {
     pub render_whitespace:           RenderWhitespace,
     #[field_names(desc = "Whether the editor show indent guide.")]
     pub show_indent_guide:           bool,
+    #[field_names(desc = "Show who last changed each line in a gutter column.")]
+    pub show_blame_gutter:           bool,
+    #[field_names(desc = "Show who last changed the cursor line at its end.")]
+    pub show_blame_current_line:     bool,
     #[field_names(desc = "Set the auto save delay (in milliseconds), Set to 0 to \
                           completely disable")]
     pub autosave_interval:           u64,
@@ -16,7 +16,7 @@ use lapce_core::{
     workspace::{LapceWorkspace, LapceWorkspaceType},
 };
 use lapce_proxy::plugin::wasi::sync_find_all_volts;
-use lapce_rpc::plugin::VoltID;
+use lapce_rpc::{buffer::SaveMode, plugin::VoltID};
 use log::error;
 use lsp_types::CompletionItemKind;
 use once_cell::sync::Lazy;
@@ -319,6 +319,7 @@ impl LapceConfig {
         ];
 
         lapce_config.terminal.get_indexed_colors();
+        lapce_config.terminal.compile_link_matchers();
 
         lapce_config
     }
@@ -441,6 +442,7 @@ impl LapceConfig {
             self.editor = new.editor;
             self.terminal = new.terminal;
             self.terminal.get_indexed_colors();
+            self.terminal.compile_link_matchers();
 
             self.color_theme = new.color_theme;
             self.icon_theme = new.icon_theme;
@@ -1078,6 +1080,13 @@ impl LapceConfig {
                     .unwrap_or(0),
                 items:        self.icon_theme_list.clone(),
             }),
+            ("core", "save-mode") => Some(DropdownInfo {
+                active_index: self.core.save_mode as usize,
+                items:        SaveMode::VARIANTS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            }),
             ("editor", "wrap-style") => Some(DropdownInfo {
                 // TODO: it would be better to have the text not be the default
                 // kebab-case when displayed in settings, but we
@@ -1091,6 +1100,13 @@ impl LapceConfig {
                     .unwrap_or(0),
                 items:        self.wrap_style_list.clone(),
             }),
+            ("editor", "format-on-save-mode") => Some(DropdownInfo {
+                active_index: self.editor.format_on_save_mode as usize,
+                items:        editor::FormatOnSaveMode::VARIANTS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            }),
             ("ui", "tab-close-button") => Some(DropdownInfo {
                 active_index: self.ui.tab_close_button as usize,
                 items:        ui::TabCloseButton::VARIANTS
@@ -110,6 +110,8 @@ pub struct EditorConfigSignal {
     pub multicursor_whole_words: SignalManager<bool>,
     pub render_whitespace: SignalManager<RenderWhitespace>,
     pub show_indent_guide: SignalManager<bool>,
+    pub show_blame_gutter: SignalManager<bool>,
+    pub show_blame_current_line: SignalManager<bool>,
     pub autosave_interval: SignalManager<u64>,
     pub format_on_autosave: SignalManager<bool>,
     pub atomic_soft_tabs: SignalManager<bool>,
@@ -197,6 +199,9 @@ impl EditorConfigSignal {
             SignalManager::new(cx, config.multicursor_whole_words);
         let render_whitespace = SignalManager::new(cx, config.render_whitespace);
         let show_indent_guide = SignalManager::new(cx, config.show_indent_guide);
+        let show_blame_gutter = SignalManager::new(cx, config.show_blame_gutter);
+        let show_blame_current_line =
+            SignalManager::new(cx, config.show_blame_current_line);
         let autosave_interval = SignalManager::new(cx, config.autosave_interval);
         let format_on_autosave = SignalManager::new(cx, config.format_on_autosave);
         let atomic_soft_tabs = SignalManager::new(cx, config.atomic_soft_tabs);
@@ -255,6 +260,8 @@ impl EditorConfigSignal {
             multicursor_whole_words,
             render_whitespace,
             show_indent_guide,
+            show_blame_gutter,
+            show_blame_current_line,
             autosave_interval,
             format_on_autosave,
             atomic_soft_tabs,
@@ -362,6 +369,10 @@ impl EditorConfigSignal {
             .update_and_trigger_if_not_equal(config.render_whitespace);
         self.show_indent_guide
             .update_and_trigger_if_not_equal(config.show_indent_guide);
+        self.show_blame_gutter
+            .update_and_trigger_if_not_equal(config.show_blame_gutter);
+        self.show_blame_current_line
+            .update_and_trigger_if_not_equal(config.show_blame_current_line);
         self.autosave_interval
             .update_and_trigger_if_not_equal(config.autosave_interval);
         self.format_on_autosave
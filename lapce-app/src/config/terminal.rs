@@ -1,6 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use floem::peniko::Color;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use structdesc::FieldNames;
 
@@ -24,23 +25,54 @@ pub struct TerminalConfig {
     pub profiles:        HashMap<String, TerminalProfile>,
     #[field_names(skip)]
     pub default_profile: HashMap<String, String>,
+    /// What ctrl+click opens in the terminal, tried in order
+    #[field_names(skip)]
+    #[serde(default)]
+    pub link_matchers:   Vec<TerminalLinkMatcher>,
 
     #[serde(skip)]
     #[field_names(skip)]
-    pub indexed_colors: Arc<HashMap<u8, Color>>,
+    pub indexed_colors:         Arc<HashMap<u8, Color>>,
+    #[serde(skip)]
+    #[field_names(skip)]
+    pub compiled_link_matchers: Arc<Vec<(Regex, TerminalLinkAction)>>,
+}
+
+/// A regex finding links in the output of the terminal. For a file, the named
+/// groups `path`, `line` and `column` locate it; a url is the group `url`, or
+/// else the whole match.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TerminalLinkMatcher {
+    pub regex:  String,
+    #[serde(default)]
+    pub action: TerminalLinkAction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TerminalLinkAction {
+    /// Opens the file at its line and column, a relative path being looked up
+    /// in the directory of the shell and then in the workspace
+    #[default]
+    OpenFile,
+    OpenUrl,
 }
 
 #[derive(FieldNames, Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct TerminalProfile {
     #[field_names(desc = "Command to execute when launching terminal")]
-    pub command:     Option<String>,
+    pub command:           Option<String>,
     #[field_names(desc = "Arguments passed to command")]
-    pub arguments:   Option<Vec<String>>,
+    pub arguments:         Option<Vec<String>>,
     #[field_names(desc = "Command to execute when launching terminal")]
-    pub workdir:     Option<std::path::PathBuf>,
+    pub workdir:           Option<std::path::PathBuf>,
     #[field_names(desc = "Arguments passed to command")]
-    pub environment: Option<HashMap<String, String>>,
+    pub environment:       Option<HashMap<String, String>>,
+    #[field_names(desc = "Load the shell integration of bash and zsh, which \
+                          marks the prompts and commands. Defaults to true")]
+    pub shell_integration: Option<bool>,
 }
 
 impl TerminalConfig {
@@ -74,6 +106,21 @@ impl TerminalConfig {
         self.indexed_colors = Arc::new(indexed_colors);
     }
 
+    pub fn compile_link_matchers(&mut self) {
+        let matchers = self
+            .link_matchers
+            .iter()
+            .filter_map(|matcher| match Regex::new(&matcher.regex) {
+                Ok(regex) => Some((regex, matcher.action)),
+                Err(err) => {
+                    log::error!("invalid terminal link matcher: {err}");
+                    None
+                },
+            })
+            .collect();
+        self.compiled_link_matchers = Arc::new(matchers);
+    }
+
     pub fn get_default_profile(
         &self,
     ) -> Option<lapce_rpc::terminal::TerminalProfile> {
@@ -96,6 +143,7 @@ impl TerminalConfig {
             arguments: profile.arguments,
             workdir,
             environment: profile.environment,
+            shell_integration: profile.shell_integration.unwrap_or(true),
         })
     }
 }
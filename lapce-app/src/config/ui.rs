@@ -53,6 +53,15 @@ pub struct UIConfig {
     #[field_names(desc = "Trim whitespace from search results")]
     pub trim_search_results_whitespace: bool,
 
+    #[field_names(desc = "Skip files larger than this many kilobytes in the \
+                          global search. 0 means no limit")]
+    pub search_max_file_size: u64,
+
+    #[field_names(
+        desc = "Stop the global search after this many matches. 0 means no limit"
+    )]
+    pub search_max_matches: usize,
+
     #[field_names(desc = "Set the line height for list items")]
     list_line_height: usize,
 
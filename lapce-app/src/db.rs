@@ -156,7 +156,10 @@ impl LapceDb {
 
         let mut exits = false;
         for w in workspaces.iter_mut() {
-            if w.path() == workspace.path() && w.kind() == workspace.kind() {
+            if w.path() == workspace.path()
+                && w.file() == workspace.file()
+                && w.kind() == workspace.kind()
+            {
                 w.update_last_open(
                     std::time::SystemTime::now()
                         .duration_since(std::time::UNIX_EPOCH)
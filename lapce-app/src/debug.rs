@@ -4,6 +4,7 @@ use std::{
     rc::Rc,
 };
 
+use base64::{Engine as _, engine::general_purpose};
 use doc::lines::{DocLinesManager, buffer::rope_text::RopeText};
 use floem::{
     ext_event::create_ext_action,
@@ -11,11 +12,13 @@ use floem::{
     views::VirtualVector,
 };
 use lapce_core::debug::{
-    BreakpointAction, DapVariableViewdata, LapceBreakpoint, ScopeOrVar,
+    BreakpointAction, DapVariableViewdata, LapceBreakpoint,
+    LapceExceptionBreakpoints, ScopeOrVar,
 };
 use lapce_rpc::{
     dap_types::{
-        self, DapId, SourceBreakpoint, StackFrame, Stopped, ThreadId, Variable,
+        self, DapId, DebuggerCapabilities, DisassembledInstruction, RunDebugConfig,
+        SourceBreakpoint, StackFrame, Stopped, ThreadId, Variable,
     },
     proxy::{ProxyResponse, ProxyRpcHandler},
     terminal::TermId,
@@ -29,9 +32,25 @@ use crate::{
     window_workspace::CommonData,
 };
 
+/// The input of the breakpoint editor that gets the focus when it opens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakpointEditField {
+    Condition,
+    LogMessage,
+}
+
 #[derive(Clone, Copy)]
 pub struct BreakPoints {
-    pub breakpoints: RwSignal<BTreeMap<PathBuf, BTreeMap<usize, LapceBreakpoint>>>,
+    pub breakpoints:   RwSignal<BTreeMap<PathBuf, BTreeMap<usize, LapceBreakpoint>>>,
+    /// The breakpoint whose condition, hit count and log message are being
+    /// edited in the Breakpoints section.
+    pub editing:       RwSignal<Option<(PathBuf, usize)>>,
+    /// Whether the breakpoint being edited was added by "Add Conditional
+    /// Breakpoint..." or "Add Logpoint...", and so goes away when the edit
+    /// is cancelled.
+    pub editing_new:   RwSignal<bool>,
+    /// The input focused in the breakpoint being edited
+    pub editing_field: RwSignal<BreakpointEditField>,
 }
 
 impl BreakPoints {
@@ -64,15 +83,7 @@ impl BreakPoints {
                 if let std::collections::btree_map::Entry::Vacant(e) =
                     breakpoints.entry(line)
                 {
-                    e.insert(LapceBreakpoint {
-                        id: None,
-                        verified: false,
-                        message: None,
-                        line,
-                        offset,
-                        dap_line: None,
-                        active: true,
-                    });
+                    e.insert(LapceBreakpoint::new(line, offset));
                 } else {
                     breakpoints.remove(&line);
                 }
@@ -93,15 +104,7 @@ impl BreakPoints {
                 if let std::collections::btree_map::Entry::Vacant(e) =
                     breakpoints.entry(line)
                 {
-                    e.insert(LapceBreakpoint {
-                        id: None,
-                        verified: false,
-                        message: None,
-                        line,
-                        offset,
-                        dap_line: None,
-                        active: true,
-                    });
+                    e.insert(LapceBreakpoint::new(line, offset));
                 } else {
                     let mut toggle_active = false;
                     if let Some(breakpint) = breakpoints.get_mut(&line) {
@@ -136,6 +139,40 @@ impl BreakPoints {
             .unwrap()
     }
 
+    pub fn edit_by_path_line(
+        &self,
+        path: &Path,
+        line: usize,
+        offset: usize,
+        condition: Option<String>,
+        hit_condition: Option<String>,
+        log_message: Option<String>,
+    ) -> BTreeMap<usize, LapceBreakpoint> {
+        let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+        self.breakpoints
+            .try_update(|breakpoints| {
+                let breakpoints = breakpoints.entry(path.to_path_buf()).or_default();
+                let breakpoint = breakpoints
+                    .entry(line)
+                    .or_insert_with(|| LapceBreakpoint::new(line, offset));
+                breakpoint.condition = non_empty(condition);
+                breakpoint.hit_condition = non_empty(hit_condition);
+                breakpoint.log_message = non_empty(log_message);
+                breakpoint.verified = false;
+                breakpoints.clone()
+            })
+            .unwrap()
+    }
+
+    pub fn get_by_path_line_untracked(
+        &self,
+        path: &Path,
+        line: usize,
+    ) -> Option<LapceBreakpoint> {
+        self.breakpoints
+            .with_untracked(|x| x.get(path).and_then(|x| x.get(&line)).cloned())
+    }
+
     pub fn remove_by_path_line(
         &self,
         path: &Path,
@@ -258,13 +295,7 @@ impl BreakPoints {
                         .iter()
                         .filter_map(|(_, b)| {
                             if b.active {
-                                Some(SourceBreakpoint {
-                                    line:          b.line + 1,
-                                    column:        None,
-                                    condition:     None,
-                                    hit_condition: None,
-                                    log_message:   None,
-                                })
+                                Some(b.source_breakpoint())
                             } else {
                                 None
                             }
@@ -291,9 +322,11 @@ impl BreakPoints {
 
 #[derive(Clone)]
 pub struct RunDebugData {
-    pub active_term: RwSignal<Option<TermId>>,
-    pub daps:        RwSignal<im::HashMap<DapId, DapData>>,
-    pub breakpoints: BreakPoints,
+    pub active_term:           RwSignal<Option<TermId>>,
+    pub daps:                  RwSignal<im::HashMap<DapId, DapData>>,
+    pub breakpoints:           BreakPoints,
+    /// Exception breakpoint choices keyed by run configuration name
+    pub exception_breakpoints: RwSignal<HashMap<String, LapceExceptionBreakpoints>>,
 }
 
 impl RunDebugData {
@@ -306,8 +339,62 @@ impl RunDebugData {
             active_term,
             daps,
             breakpoints,
+            exception_breakpoints: cx.create_rw_signal(HashMap::new()),
+        }
+    }
+
+    /// Carry the saved exception breakpoint choices into a config that is
+    /// about to be launched.
+    pub fn apply_exception_breakpoints(&self, config: &mut RunDebugConfig) {
+        if let Some(saved) = self
+            .exception_breakpoints
+            .with_untracked(|x| x.get(&config.name).cloned())
+        {
+            config.exception_filters = Some(saved.filters);
+            config.break_on_panic = saved.break_on_panic;
         }
     }
+
+    /// The choices in effect for the session, falling back to the adapter's
+    /// default filters when nothing was saved for its configuration.
+    pub fn exception_breakpoints_of(
+        &self,
+        dap: &DapData,
+    ) -> LapceExceptionBreakpoints {
+        self.exception_breakpoints
+            .with(|x| x.get(&dap.config_name).cloned())
+            .unwrap_or_else(|| LapceExceptionBreakpoints {
+                filters:        dap.capabilities.with(|c| {
+                    c.as_ref()
+                        .map(|c| c.default_exception_filters())
+                        .unwrap_or_default()
+                }),
+                break_on_panic: false,
+            })
+    }
+
+    pub fn update_exception_breakpoints(
+        &self,
+        dap: &DapData,
+        proxy: &ProxyRpcHandler,
+        f: impl FnOnce(&mut LapceExceptionBreakpoints),
+    ) {
+        let mut choices = self.exception_breakpoints_of(dap);
+        f(&mut choices);
+        self.exception_breakpoints.update(|x| {
+            x.insert(dap.config_name.clone(), choices.clone());
+        });
+        let break_on_panic = if dap.supports_break_on_panic() {
+            Some(choices.break_on_panic)
+        } else {
+            None
+        };
+        proxy.dap_set_exception_breakpoints(
+            dap.dap_id,
+            choices.filters,
+            break_on_panic,
+        );
+    }
 }
 
 #[derive(Clone, PartialEq)]
@@ -336,6 +423,15 @@ pub struct DapData {
     pub stack_traces: RwSignal<BTreeMap<ThreadId, StackTraceData>>,
     pub variables_id: RwSignal<usize>,
     pub variables:    RwSignal<DapVariable>,
+    /// The stack frame that expressions are evaluated against
+    pub frame_id:     RwSignal<Option<usize>>,
+    pub console_id:   RwSignal<usize>,
+    /// The results of the debug console, one child per evaluated expression
+    pub console:      RwSignal<DapVariable>,
+    /// The name of the run configuration the session was started from
+    pub config_name:  String,
+    pub is_lldb:      bool,
+    pub capabilities: RwSignal<Option<DebuggerCapabilities>>,
     pub breakline:    Memo<Option<(usize, PathBuf)>>,
     pub common:       Rc<CommonData>,
 }
@@ -345,6 +441,7 @@ impl DapData {
         cx: Scope,
         dap_id: DapId,
         term_id: Option<TermId>,
+        config: &RunDebugConfig,
         common: Rc<CommonData>,
     ) -> Self {
         let stopped = cx.create_rw_signal(false);
@@ -391,11 +488,52 @@ impl DapData {
                 children:                Vec::new(),
                 children_expanded_count: 0,
             }),
+            frame_id: cx.create_rw_signal(None),
+            console_id: cx.create_rw_signal(0),
+            console: cx.create_rw_signal(DapVariable {
+                item:                    ScopeOrVar::Scope(
+                    dap_types::Scope::default(),
+                ),
+                parent:                  Vec::new(),
+                expanded:                true,
+                read:                    true,
+                children:                Vec::new(),
+                children_expanded_count: 0,
+            }),
+            config_name: config.name.clone(),
+            is_lldb: config.is_lldb(),
+            capabilities: cx.create_rw_signal(None),
             breakline,
             common,
         }
     }
 
+    pub fn supports_disassemble(&self) -> bool {
+        self.capabilities.with_untracked(|c| {
+            c.as_ref()
+                .and_then(|c| c.supports_disassemble_request)
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn supports_instruction_stepping(&self) -> bool {
+        self.capabilities.with(|c| {
+            c.as_ref()
+                .and_then(|c| c.supports_stepping_granularity)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Whether "break on `rust_panic`" can be offered for this session.
+    pub fn supports_break_on_panic(&self) -> bool {
+        self.is_lldb
+            && self.capabilities.with_untracked(|c| {
+                c.as_ref()
+                    .and_then(|c| c.supports_function_breakpoints)
+                    .unwrap_or_default()
+            })
+    }
+
     pub fn stopped(
         &self,
         cx: Scope,
@@ -415,9 +553,22 @@ impl DapData {
             let is_main_thread = main_thread_id.as_ref() == Some(thread_id);
             if is_main_thread {
                 if let Some(frame) = frames.first() {
-                    if let Some(path) =
-                        frame.source.as_ref().and_then(|source| source.path.clone())
+                    let path =
+                        frame.source.as_ref().and_then(|source| source.path.clone());
+                    if let Some(memory_reference) =
+                        frame.instruction_pointer_reference.clone()
                     {
+                        // only reveal the disassembly when there is no source to
+                        // jump to
+                        self.common.internal_command.send(
+                            InternalCommand::DapDisassemble {
+                                dap_id: self.dap_id,
+                                memory_reference,
+                                reveal: path.is_none(),
+                            },
+                        );
+                    }
+                    if let Some(path) = path {
                         self.common.internal_command.send(
                             InternalCommand::JumpToLocation {
                                 location: EditorLocation {
@@ -451,6 +602,24 @@ impl DapData {
             }
         }
         self.stack_traces.set(current_stack_traces);
+        let frame_id = main_thread_id
+            .and_then(|thread_id| stack_traces.get(&thread_id))
+            .and_then(|frames| frames.first())
+            .map(|frame| frame.id);
+        self.select_frame(frame_id);
+        // the variable references of earlier results are only valid while
+        // the debuggee stays stopped at the same place
+        self.console_id.update(|id| {
+            *id += 1;
+        });
+        self.console.update(|console| {
+            console.children.iter_mut().for_each(DapVariable::collapse);
+            console.children_expanded_count = console
+                .children
+                .iter()
+                .map(|v| v.children_expanded_count + 1)
+                .sum::<usize>();
+        });
         self.variables.update(|dap_var| {
             dap_var.children = variables
                 .iter()
@@ -482,11 +651,45 @@ impl DapData {
         });
     }
 
+    pub fn select_frame(&self, frame_id: Option<usize>) {
+        self.frame_id.set(frame_id);
+        self.common
+            .stopped_frame
+            .set(frame_id.map(|frame_id| (self.dap_id, frame_id)));
+    }
+
+    pub fn continued(&self) {
+        self.thread_id.set(None);
+        self.stopped.set(false);
+        self.frame_id.set(None);
+        if self
+            .common
+            .stopped_frame
+            .with_untracked(|x| x.map(|(dap_id, _)| dap_id) == Some(self.dap_id))
+        {
+            self.common.stopped_frame.set(None);
+        }
+    }
+
     pub fn toggle_expand(&self, parent: Vec<usize>, reference: usize) {
-        self.variables_id.update(|id| {
+        self.toggle_expand_in(self.variables, self.variables_id, parent, reference);
+    }
+
+    pub fn toggle_console_expand(&self, parent: Vec<usize>, reference: usize) {
+        self.toggle_expand_in(self.console, self.console_id, parent, reference);
+    }
+
+    fn toggle_expand_in(
+        &self,
+        root: RwSignal<DapVariable>,
+        root_id: RwSignal<usize>,
+        parent: Vec<usize>,
+        reference: usize,
+    ) {
+        root_id.update(|id| {
             *id += 1;
         });
-        self.variables.update(|variables| {
+        root.update(|variables| {
             if let Some(var) = variables.get_var_mut(&parent, reference) {
                 if var.expanded {
                     var.expanded = false;
@@ -495,7 +698,7 @@ impl DapData {
                     var.expanded = true;
                     if !var.read {
                         var.read = true;
-                        self.read_var_children(&parent, reference);
+                        self.read_var_children(root, root_id, &parent, reference);
                     } else {
                         variables.update_count_recursive(&parent, reference);
                     }
@@ -504,10 +707,155 @@ impl DapData {
         });
     }
 
-    fn read_var_children(&self, parent: &[usize], reference: usize) {
-        let root = self.variables;
+    /// Evaluate `expression` in the selected stack frame and append the
+    /// result to the debug console.
+    pub fn evaluate(&self, expression: String) {
+        let expression = expression.trim().to_string();
+        if expression.is_empty() {
+            return;
+        }
+        let console = self.console;
+        let console_id = self.console_id;
+        let send = {
+            let expression = expression.clone();
+            create_ext_action(self.common.scope, move |result| {
+                let var = match result {
+                    Ok(ProxyResponse::DapEvaluateResponse { result }) => {
+                        result.into_variable(expression)
+                    },
+                    Ok(_) => return,
+                    Err(err) => Variable {
+                        name: expression,
+                        value: err.message,
+                        ..Default::default()
+                    },
+                };
+                console_id.update(|id| {
+                    *id += 1;
+                });
+                console.update(|console| {
+                    console.children.push(DapVariable {
+                        item:                    ScopeOrVar::Var(var),
+                        parent:                  Vec::new(),
+                        expanded:                false,
+                        read:                    false,
+                        children:                Vec::new(),
+                        children_expanded_count: 0,
+                    });
+                    console.children_expanded_count = console
+                        .children
+                        .iter()
+                        .map(|v| v.children_expanded_count + 1)
+                        .sum::<usize>();
+                });
+            })
+        };
+        self.common.proxy.proxy_rpc.dap_evaluate(
+            self.dap_id,
+            expression,
+            self.frame_id.get_untracked(),
+            Some("repl".to_string()),
+            move |(_, result)| {
+                send(result);
+            },
+        );
+    }
+
+    /// Disassemble the code around `memory_reference` into the disassembly
+    /// document, marking the instruction at `memory_reference`.
+    pub fn disassemble(&self, memory_reference: String, reveal: bool) {
+        if !self.supports_disassemble() {
+            return;
+        }
+        let internal_command = self.common.internal_command;
+        let send = {
+            let memory_reference = memory_reference.clone();
+            create_ext_action(self.common.scope, move |result| {
+                if let Ok(ProxyResponse::DapDisassembleResponse { instructions }) =
+                    result
+                {
+                    let (content, line) =
+                        disassembly_text(&instructions, &memory_reference);
+                    internal_command.send(InternalCommand::OpenVirtualDoc {
+                        name: DISASSEMBLY_DOC.to_string(),
+                        content,
+                        line,
+                        reveal,
+                    });
+                }
+            })
+        };
+        self.common.proxy.proxy_rpc.dap_disassemble(
+            self.dap_id,
+            memory_reference,
+            -(DISASSEMBLY_CONTEXT as i64),
+            DISASSEMBLY_CONTEXT * 2,
+            move |(_, result)| {
+                send(result);
+            },
+        );
+    }
+
+    /// Show a hex dump of the memory at `memory_reference`.
+    pub fn read_memory(&self, memory_reference: String) {
+        let internal_command = self.common.internal_command;
+        let send = {
+            let name = format!("Memory {memory_reference}");
+            create_ext_action(self.common.scope, move |result| {
+                let content = match result {
+                    Ok(ProxyResponse::DapReadMemoryResponse { result }) => {
+                        let bytes = result
+                            .data
+                            .and_then(|data| {
+                                general_purpose::STANDARD.decode(data).ok()
+                            })
+                            .unwrap_or_default();
+                        memory_text(
+                            parse_address(&result.address).unwrap_or_default(),
+                            &bytes,
+                            result.unreadable_bytes.unwrap_or_default(),
+                        )
+                    },
+                    Ok(_) => return,
+                    Err(err) => err.message,
+                };
+                internal_command.send(InternalCommand::OpenVirtualDoc {
+                    name,
+                    content,
+                    line: Some(0),
+                    reveal: true,
+                });
+            })
+        };
+        self.common.proxy.proxy_rpc.dap_read_memory(
+            self.dap_id,
+            memory_reference,
+            0,
+            MEMORY_VIEW_BYTES,
+            move |(_, result)| {
+                send(result);
+            },
+        );
+    }
+
+    pub fn clear_console(&self) {
+        self.console_id.update(|id| {
+            *id += 1;
+        });
+        self.console.update(|console| {
+            console.children.clear();
+            console.children_expanded_count = 0;
+        });
+    }
+
+    fn read_var_children(
+        &self,
+        root: RwSignal<DapVariable>,
+        variables_id: RwSignal<usize>,
+        parent: &[usize],
+        reference: usize,
+    ) {
         let parent = parent.to_vec();
-        let variables_id = self.variables_id;
 
         let send = create_ext_action(self.common.scope, move |result| {
             if let Ok(ProxyResponse::DapVariableResponse { varialbes }) = result {
@@ -569,7 +917,37 @@ impl VirtualVector<DapVariableViewdata> for DapVariable {
     }
 }
 
+/// The rows of the debug console. Rows are keyed by their position as the same
+/// expression can be evaluated more than once.
+#[derive(Clone, Default)]
+pub struct DebugConsoleRows(pub DapVariable);
+
+impl VirtualVector<(usize, DapVariableViewdata)> for DebugConsoleRows {
+    fn total_len(&self) -> usize {
+        self.0.children_expanded_count
+    }
+
+    fn slice(
+        &mut self,
+        range: std::ops::Range<usize>,
+    ) -> impl Iterator<Item = (usize, DapVariableViewdata)> {
+        let start = range.start;
+        VirtualVector::<DapVariableViewdata>::slice(&mut self.0, range)
+            .enumerate()
+            .map(move |(i, item)| (start + i, item))
+    }
+}
+
 impl DapVariable {
+    /// Collapse the node and drop the children that were read, so they are
+    /// fetched again on the next expansion.
+    pub fn collapse(&mut self) {
+        self.expanded = false;
+        self.read = false;
+        self.children.clear();
+        self.children_expanded_count = 0;
+    }
+
     pub fn append_view_slice(
         &self,
         view_items: &mut Vec<DapVariableViewdata>,
@@ -678,19 +1056,31 @@ pub fn update_breakpoints(
             breakpoints.add_or_remove_by_path_line_offset(path, line, offset),
             path,
         ),
+        BreakpointAction::Edit {
+            path,
+            line,
+            offset,
+            condition,
+            hit_condition,
+            log_message,
+        } => (
+            breakpoints.edit_by_path_line(
+                path,
+                line,
+                offset,
+                condition,
+                hit_condition,
+                log_message,
+            ),
+            path,
+        ),
     };
 
     let source_breakpoints: Vec<SourceBreakpoint> = path_breakpoints
         .iter()
         .filter_map(|(_, b)| {
             if b.active {
-                Some(SourceBreakpoint {
-                    line:          b.line + 1,
-                    column:        None,
-                    condition:     None,
-                    hit_condition: None,
-                    log_message:   None,
-                })
+                Some(b.source_breakpoint())
             } else {
                 None
             }
@@ -707,11 +1097,139 @@ pub fn update_breakpoints(
     }
 }
 
+pub const DISASSEMBLY_DOC: &str = "Disassembly";
+/// Instructions shown on either side of the current one.
+const DISASSEMBLY_CONTEXT: usize = 50;
+const MEMORY_VIEW_BYTES: usize = 256;
+const MEMORY_ROW_BYTES: usize = 16;
+
+fn parse_address(address: &str) -> Option<u64> {
+    let address = address.trim();
+    let hex = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))?;
+    u64::from_str_radix(hex, 16).ok()
+}
+
+/// Lay out the instructions one per line, with a label whenever the symbol
+/// changes. Returns the text and the line of the instruction at `current`.
+pub fn disassembly_text(
+    instructions: &[DisassembledInstruction],
+    current: &str,
+) -> (String, Option<usize>) {
+    let current_address = parse_address(current);
+    let mut lines = Vec::with_capacity(instructions.len());
+    let mut current_line = None;
+    let mut symbol = None;
+    for instruction in instructions {
+        if instruction.symbol.is_some() && instruction.symbol != symbol {
+            symbol = instruction.symbol.clone();
+            lines.push(format!("{}:", symbol.as_deref().unwrap_or_default()));
+        }
+        let is_current = match (current_address, parse_address(&instruction.address))
+        {
+            (Some(current), Some(address)) => current == address,
+            _ => instruction.address == current,
+        };
+        if is_current {
+            current_line = Some(lines.len());
+        }
+        lines.push(format!(
+            "{} {:<18} {:<24} {}",
+            if is_current { "=>" } else { "  " },
+            instruction.address,
+            instruction.instruction_bytes.as_deref().unwrap_or_default(),
+            instruction.instruction
+        ));
+    }
+    (lines.join("\n"), current_line)
+}
+
+/// A classic hex dump of `bytes` read from `address`, 16 bytes per row.
+pub fn memory_text(address: u64, bytes: &[u8], unreadable_bytes: usize) -> String {
+    let mut lines = bytes
+        .chunks(MEMORY_ROW_BYTES)
+        .enumerate()
+        .map(|(i, row)| {
+            let hex = (0..MEMORY_ROW_BYTES)
+                .map(|j| {
+                    let byte = row
+                        .get(j)
+                        .map(|b| format!("{b:02x}"))
+                        .unwrap_or_else(|| "  ".to_string());
+                    if j == MEMORY_ROW_BYTES / 2 {
+                        format!(" {byte}")
+                    } else {
+                        byte
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii = row
+                .iter()
+                .map(|b| {
+                    if b.is_ascii_graphic() || *b == b' ' {
+                        *b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            let row_address = address.wrapping_add((i * MEMORY_ROW_BYTES) as u64);
+            format!("{row_address:016x}  {hex}  |{ascii}|")
+        })
+        .collect::<Vec<_>>();
+    if unreadable_bytes > 0 {
+        lines.push(format!("{unreadable_bytes} bytes could not be read"));
+    }
+    lines.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
-    use lapce_rpc::dap_types::{Scope, Variable};
+    use lapce_rpc::dap_types::{DisassembledInstruction, Scope, Variable};
+
+    use super::{DapVariable, ScopeOrVar, disassembly_text, memory_text};
 
-    use super::{DapVariable, ScopeOrVar};
+    #[test]
+    fn test_disassembly_text() {
+        let instruction =
+            |address: &str, symbol: Option<&str>| DisassembledInstruction {
+                address: address.to_string(),
+                instruction: "nop".to_string(),
+                symbol: symbol.map(|s| s.to_string()),
+                ..Default::default()
+            };
+        let instructions = [
+            instruction("0x1000", Some("main")),
+            instruction("0x1001", Some("main")),
+            instruction("0x1002", Some("foo")),
+        ];
+        let (text, line) = disassembly_text(&instructions, "0x0000000000001002");
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "main:");
+        assert_eq!(lines[3], "foo:");
+        assert_eq!(line, Some(4));
+        assert!(lines[4].starts_with("=> 0x1002"));
+        assert!(lines[1].starts_with("   0x1000"));
+    }
+
+    #[test]
+    fn test_memory_text() {
+        let bytes = b"Hello, world!\n\0\x7fab";
+        let text = memory_text(0x10, bytes, 4);
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "0000000000000010  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 7f  \
+             |Hello, world!...|"
+        );
+        assert!(lines[1].starts_with("0000000000000020  61 62    "));
+        assert!(lines[1].ends_with("|ab|"));
+        assert_eq!(lines[2], "4 bytes could not be read");
+    }
 
     #[test]
     fn test_update_count() {
@@ -18,7 +18,10 @@ use doc::{
         DocLinesManager, EditBuffer, RopeTextPosition,
         buffer::{
             Buffer, InvalLines,
-            diff::DiffLines,
+            diff::{
+                DiffLines, left_line_of_right, merge_three_way, right_changed_lines,
+                rope_diff,
+            },
             rope_text::{RopeText, RopeTextVal},
         },
         char_buffer::CharBuffer,
@@ -46,7 +49,12 @@ use itertools::Itertools;
 use lapce_core::{
     debug::RunDebugConfigs, doc::DocContent, workspace::LapceWorkspace,
 };
-use lapce_rpc::{buffer::BufferId, plugin::PluginId, proxy::ProxyResponse};
+use lapce_rpc::{
+    buffer::{BufferId, FileEncoding},
+    plugin::PluginId,
+    proxy::ProxyResponse,
+    source_control::{BlameCommit, GitBlame},
+};
 use lapce_xi_rope::{Interval, Rope, RopeDelta, spans::SpansBuilder};
 use log::{debug, error};
 use lsp_types::{CodeLens, Diagnostic, DocumentSymbolResponse};
@@ -54,6 +62,7 @@ use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
 use crate::{
+    alert::AlertButton,
     command::InternalCommand,
     editor::{
         floem_editor::CommonAction,
@@ -61,10 +70,12 @@ use crate::{
     },
     find::{Find, FindProgress, FindResult},
     history::DocumentHistory,
+    local_history::{SnapshotReason, take_snapshot},
     local_task::{LocalRequest, LocalResponse},
     panel::document_symbol::{
         DocumentSymbolViewData, SymbolData, SymbolInformationItemData,
     },
+    source_control::commit_date,
     window_workspace::CommonData,
 };
 // #[derive(Clone, Debug)]
@@ -88,25 +99,68 @@ pub struct DocInfo {
     pub cursor_offset: usize,
 }
 
+/// Who last changed a line of a document
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LineBlame {
+    Commit(BlameCommit),
+    NotCommitted,
+}
+
+impl LineBlame {
+    /// `author, date` for the gutter
+    pub fn short_label(&self) -> String {
+        match self {
+            LineBlame::Commit(commit) => {
+                format!("{}, {}", commit.author, commit_date(commit.time))
+            },
+            LineBlame::NotCommitted => "Not Committed Yet".to_string(),
+        }
+    }
+
+    /// `author, date • summary` for the cursor line annotation
+    pub fn label(&self) -> String {
+        match self {
+            LineBlame::Commit(commit) => format!(
+                "{}, {} • {}",
+                commit.author,
+                commit_date(commit.time),
+                commit.summary
+            ),
+            LineBlame::NotCommitted => "Not Committed Yet".to_string(),
+        }
+    }
+}
+
 pub type AllCodeLens = im::HashMap<usize, (PluginId, usize, im::Vector<CodeLens>)>;
 
 #[derive(Clone)]
 pub struct Doc {
-    pub name:      Option<String>,
-    pub scope:     Scope,
-    pub buffer_id: BufferId,
-    pub content:   RwSignal<DocContent>,
-    pub cache_rev: RwSignal<u64>,
+    pub name:            Option<String>,
+    pub scope:           Scope,
+    pub buffer_id:       BufferId,
+    pub content:         RwSignal<DocContent>,
+    pub cache_rev:       RwSignal<u64>,
     /// Whether the buffer's content has been loaded/initialized into the
     /// buffer.
-    pub loaded:    RwSignal<bool>,
+    pub loaded:          RwSignal<bool>,
+    /// The encoding the file was read with, and is saved with
+    pub encoding:        RwSignal<FileEncoding>,
+    /// The text last read from or written to the file, the base of a merge
+    /// with the changes made on disk
+    pub saved_content:   RwSignal<Rope>,
+    /// What the file changed into on disk while the buffer had unsaved edits,
+    /// until the user picks which to keep
+    pub changed_on_disk: RwSignal<Option<Rope>>,
     // pub kind: RwSignal<EditorViewKind>,
-    pub code_lens: RwSignal<AllCodeLens>,
+    pub code_lens:       RwSignal<AllCodeLens>,
 
     /// Stores information about different versions of the document from source
     /// control.
     histories:        RwSignal<im::HashMap<String, DocumentHistory>>,
     pub head_changes: RwSignal<im::Vector<DiffLines>>,
+    /// The blame of the `head` version, mapped onto the buffer through
+    /// `head_changes`
+    pub blame:        RwSignal<Option<Rc<GitBlame>>>,
 
     /// A cache for the sticky headers which maps a line to the lines it should
     /// show in the header.
@@ -181,8 +235,12 @@ impl Doc {
             cache_rev: cx.create_rw_signal(0),
             content: cx.create_rw_signal(doc_content),
             loaded: cx.create_rw_signal(false),
+            encoding: cx.create_rw_signal(FileEncoding::default()),
+            saved_content: cx.create_rw_signal(Rope::from("")),
+            changed_on_disk: cx.create_rw_signal(None),
             histories: cx.create_rw_signal(im::HashMap::new()),
             head_changes: cx.create_rw_signal(im::Vector::new()),
+            blame: cx.create_rw_signal(None),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             find_result: FindResult::new(cx),
             // preedit: PreeditData::new(cx),
@@ -260,8 +318,12 @@ impl Doc {
             content: cx.create_rw_signal(content),
             histories: cx.create_rw_signal(im::HashMap::new()),
             head_changes: cx.create_rw_signal(im::Vector::new()),
+            blame: cx.create_rw_signal(None),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             loaded: cx.create_rw_signal(true),
+            encoding: cx.create_rw_signal(FileEncoding::default()),
+            saved_content: cx.create_rw_signal(Rope::from("")),
+            changed_on_disk: cx.create_rw_signal(None),
             find_result: FindResult::new(cx),
             // preedit: PreeditData::new(cx),
             common,
@@ -346,8 +408,12 @@ impl Doc {
             content: cx.create_rw_signal(content),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             loaded: cx.create_rw_signal(false),
+            encoding: cx.create_rw_signal(FileEncoding::default()),
+            saved_content: cx.create_rw_signal(Rope::from("")),
+            changed_on_disk: cx.create_rw_signal(None),
             histories: cx.create_rw_signal(im::HashMap::new()),
             head_changes: cx.create_rw_signal(im::Vector::new()),
+            blame: cx.create_rw_signal(None),
             find_result: FindResult::new(cx),
             // preedit: PreeditData::new(cx),
             common,
@@ -449,6 +515,7 @@ impl Doc {
     //// Initialize the content with some text, this marks the document as loaded.
     pub fn init_content(&self, content: Rope) {
         batch(|| {
+            self.saved_content.set(content.clone());
             self.buffer_edit_with_config(EditBuffer::Init(content), false);
             self.loaded.set(true);
             self.on_update(None);
@@ -461,6 +528,10 @@ impl Doc {
     pub fn reload(&self, content: Rope, set_pristine: bool) {
         // self.code_actions.clear();
         // self.inlay_hints = None;
+        if set_pristine {
+            self.saved_content.set(content.clone());
+            self.changed_on_disk.set(None);
+        }
         let delta = self
             .buffer_edit(EditBuffer::Reload {
                 content,
@@ -470,10 +541,60 @@ impl Doc {
         self.apply_deltas(&[delta]);
     }
 
-    pub fn handle_file_changed(&self, content: Rope) {
+    /// Takes the new content of the file if the buffer has no unsaved edits,
+    /// else keeps it in `changed_on_disk` and returns true for the user to
+    /// decide.
+    pub fn handle_file_changed(&self, content: Rope) -> bool {
+        let same = |a: &Rope, b: &Rope| {
+            a.len() == b.len() && a.slice_to_cow(..) == b.slice_to_cow(..)
+        };
+        // keep what the file had before in the local history
+        let saved_content = self.saved_content.get_untracked();
+        if let DocContent::File { path, .. } = self.content.get_untracked() {
+            if self.loaded.get_untracked() && !same(&saved_content, &content) {
+                take_snapshot(
+                    &self.common,
+                    path,
+                    saved_content,
+                    SnapshotReason::ExternalChange,
+                );
+            }
+        }
         if self.is_pristine() {
             self.reload(content, true);
+            return false;
+        }
+        if same(&self.text(), &content) {
+            // the file already has the unsaved edits
+            self.saved_content.set(content);
+            self.buffer_edit(EditBuffer::SetPristine(self.rev()));
+            return false;
+        }
+        // the same change reported again
+        if self.changed_on_disk.with_untracked(|changed| {
+            changed.as_ref().is_some_and(|x| same(x, &content))
+        }) {
+            return false;
         }
+        self.changed_on_disk.set(Some(content));
+        true
+    }
+
+    /// Merges the changes made on disk into the unsaved edits, against the
+    /// content they both started from. Returns the number of conflicts, left
+    /// between markers in the buffer.
+    pub fn merge_changes_on_disk(&self) -> Option<usize> {
+        let theirs = self.changed_on_disk.get_untracked()?;
+        let (merged, conflicts) = merge_three_way(
+            &self.saved_content.get_untracked(),
+            &self.text(),
+            &theirs,
+        );
+        self.reload(Rope::from(merged), false);
+        // what is on disk now is what the next merge starts from
+        self.saved_content.set(theirs);
+        self.changed_on_disk.set(None);
+        Some(conflicts)
     }
 
     pub fn do_insert(
@@ -1008,24 +1129,23 @@ impl Doc {
                     if rev != doc.rev() {
                         return;
                     }
-                    if let Ok(ProxyResponse::LspFoldingRangeResponse {
-                        resp, ..
-                    }) = result
-                    {
-                        let folding: Vec<lsp_types::FoldingRange> = resp
+                    // without a language server, the syntax provides the ranges
+                    let folding: Vec<lsp_types::FoldingRange> = match result {
+                        Ok(ProxyResponse::LspFoldingRangeResponse {
+                            resp, ..
+                        }) => resp
                             .unwrap_or_default()
                             .into_iter()
                             .sorted_by(|x, y| x.start_line.cmp(&y.start_line))
-                            .collect();
-                        doc.lines.update(|symbol| {
-                            if let Err(err) =
-                                symbol.update_folding_ranges(folding.into())
-                            {
-                                error!("{err:?}");
-                            }
-                        });
-                        doc.clear_text_cache();
-                    }
+                            .collect(),
+                        _ => Vec::new(),
+                    };
+                    doc.lines.update(|symbol| {
+                        if let Err(err) = symbol.set_lsp_folding_ranges(folding) {
+                            error!("{err:?}");
+                        }
+                    });
+                    doc.clear_text_cache();
                 }
             });
 
@@ -1060,6 +1180,10 @@ impl Doc {
         self.lines.update(|x| x.clear_completion_lens());
     }
 
+    pub fn set_line_annotation(&self, annotation: Option<(usize, String)>) {
+        self.lines.update(|x| x.set_line_annotation(annotation));
+    }
+
     fn update_breakpoints(&self, delta: &RopeDelta, path: &Path, old_text: &Rope) {
         if self.common.breakpoints.contains_path(path) {
             self.common
@@ -1253,9 +1377,73 @@ impl Doc {
             proxy.proxy_rpc.get_buffer_head(path, move |(_, result)| {
                 send(result);
             });
+            self.retrieve_blame();
         }
     }
 
+    /// Retrieve the blame of the `head` version of the buffer, when it is
+    /// shown somewhere
+    pub fn retrieve_blame(&self) {
+        let DocContent::File { path, .. } = self.content.get_untracked() else {
+            return;
+        };
+        let enabled = self.common.config.with_untracked(|config| {
+            config.editor.show_blame_gutter || config.editor.show_blame_current_line
+        });
+        if !enabled {
+            return;
+        }
+
+        let blame = self.blame;
+        let send = create_ext_action(self.scope, move |result| {
+            if let Ok(ProxyResponse::GitBlameResponse { blame: result }) = result {
+                blame.set(Some(Rc::new(result)));
+            } else {
+                blame.set(None);
+            }
+        });
+        self.common
+            .proxy
+            .proxy_rpc
+            .git_blame(path, move |(_, result)| {
+                send(result);
+            });
+    }
+
+    /// Who last changed `line` of the buffer. Lines edited since HEAD haven't
+    /// been committed yet.
+    pub fn line_blame(&self, line: usize) -> Option<LineBlame> {
+        let blame = self.blame.get_untracked()?;
+        let head_line = self.head_changes.with_untracked(|changes| {
+            if changes.is_empty() {
+                Some(line)
+            } else {
+                left_line_of_right(changes, line)
+            }
+        });
+        Some(match head_line {
+            Some(head_line) => {
+                LineBlame::Commit(blame.commit_of_line(head_line)?.clone())
+            },
+            None => LineBlame::NotCommitted,
+        })
+    }
+
+    /// The lines added or modified since the `head` version, which the file
+    /// doesn't have if it isn't tracked by git.
+    pub fn modified_lines(&self) -> Option<Vec<Range<usize>>> {
+        let head = self.histories.with_untracked(|histories| {
+            histories
+                .get("head")
+                .map(|history| history.buffer.text().clone())
+        })?;
+        let (atomic_rev, text) = self.lines.with_untracked(|b| {
+            (b.buffer().atomic_rev(), b.buffer().text().clone())
+        });
+        let changes = rope_diff(head, text, self.rev(), atomic_rev, None)?;
+        Some(right_changed_lines(&changes))
+    }
+
     pub fn trigger_head_change(&self) {
         let history = if let Some(text) =
             self.histories.with_untracked(|histories| {
@@ -1320,29 +1508,146 @@ impl Doc {
     }
 
     pub fn save(&self, after_action: impl FnOnce() + 'static) {
+        self.save_encoded(None, after_action);
+    }
+
+    /// Saves the file with another encoding, which it keeps afterwards.
+    pub fn save_with_encoding(&self, encoding: FileEncoding) {
+        self.save_encoded(Some(encoding), || {});
+    }
+
+    fn save_encoded(
+        &self,
+        encoding: Option<FileEncoding>,
+        after_action: impl FnOnce() + 'static,
+    ) {
         let content = self.content.get_untracked();
         if let DocContent::File { path, .. } = content {
             let rev = self.rev();
+            let text = self.text();
             // let buffer = self.lines.with_untracked(|x| x.signal_buffer());
             let doc = self.clone();
+            let new_encoding = encoding.clone();
+            let saved_path = path.clone();
 
             let send = create_ext_action(self.scope, move |result| match result {
                 Ok(_) => {
                     // lines.try_update(|x| x.set_pristine(rev));
                     doc.buffer_edit(EditBuffer::SetPristine(rev));
+                    take_snapshot(
+                        &doc.common,
+                        saved_path,
+                        text.clone(),
+                        SnapshotReason::Save,
+                    );
+                    doc.saved_content.set(text);
+                    doc.changed_on_disk.set(None);
+                    if let Some(encoding) = new_encoding {
+                        doc.encoding.set(encoding);
+                    }
                     after_action();
                     doc.update_run_debug_config_after_save();
                 },
-                Err(err) => error!("{err}"),
+                Err(err) => {
+                    error!("{err}");
+                    doc.common.internal_command.send(
+                        InternalCommand::ShowStatusMessage {
+                            message: format!("Failed to save: {}", err.message),
+                        },
+                    );
+                },
             });
 
-            self.common
-                .proxy
-                .proxy_rpc
-                .save(rev, path, true, move |(_, result)| {
+            let save_mode = self
+                .common
+                .config
+                .with_untracked(|config| config.core.save_mode);
+            self.common.proxy.proxy_rpc.save(
+                rev,
+                path,
+                true,
+                encoding,
+                save_mode,
+                move |(_, result)| {
                     send(result);
-                })
+                },
+            )
+        }
+    }
+
+    /// Reads the file again with another encoding, asking first when the
+    /// buffer has unsaved edits, which are lost. The change can be undone
+    /// like an edit.
+    pub fn reopen_with_encoding(&self, encoding: FileEncoding) {
+        let DocContent::File { path, .. } = self.content.get_untracked() else {
+            return;
+        };
+        if self.is_pristine() {
+            self.reopen_file_with_encoding(path, encoding);
+            return;
         }
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let doc = self.clone();
+        let internal_command = self.common.internal_command;
+        internal_command.send(InternalCommand::ShowAlert {
+            title:   format!(
+                "Do you want to discard the changes you made to {name}?"
+            ),
+            msg:     format!(
+                "The file is read again with {encoding}, and your changes will be \
+                 lost."
+            ),
+            buttons: vec![AlertButton {
+                text:   "Discard and Reopen".to_string(),
+                action: Rc::new(move || {
+                    internal_command.send(InternalCommand::HideAlert);
+                    doc.reopen_file_with_encoding(path.clone(), encoding.clone());
+                }),
+            }],
+        });
+    }
+
+    /// Takes the content of the file read with `encoding`, read-only when the
+    /// file isn't valid in it, as saving would lose the bytes replaced.
+    fn reopen_file_with_encoding(&self, path: PathBuf, encoding: FileEncoding) {
+        let doc = self.clone();
+        let send = create_ext_action(self.scope, move |result| match result {
+            Ok(ProxyResponse::NewBufferResponse {
+                content,
+                read_only: lossy,
+                encoding,
+            }) => {
+                doc.content.update(|content| {
+                    if let DocContent::File { read_only, .. } = content {
+                        *read_only = lossy;
+                    }
+                });
+                if lossy {
+                    doc.common.internal_command.send(
+                        InternalCommand::ShowStatusMessage {
+                            message: format!(
+                                "The file isn't valid {encoding}, it is read-only \
+                                 until reopened with another encoding"
+                            ),
+                        },
+                    );
+                }
+                doc.encoding.set(encoding);
+                doc.reload(Rope::from(content), true);
+            },
+            Ok(_) => {},
+            Err(err) => error!("{err:?}"),
+        });
+        self.common.proxy.proxy_rpc.reopen_with_encoding(
+            path,
+            encoding,
+            move |(_, result)| {
+                send(result);
+            },
+        );
     }
 
     fn update_run_debug_config_after_save(&self) {
@@ -7,12 +7,26 @@ use floem::{
     peniko::Color,
     prelude::{SignalGet, SignalWith},
 };
+use lapce_core::debug::BreakpointKind;
 
 use crate::{
-    config::color::LapceColor, editor::EditorData,
-    window_workspace::WindowWorkspaceData,
+    config::color::LapceColor, doc::LineBlame, editor::EditorData,
+    test_explorer::test_function_name, window_workspace::WindowWorkspaceData,
 };
 
+/// How many characters of `author, date` the blame gutter column fits
+const BLAME_GUTTER_CHARS: usize = 24;
+
+/// The width of the blame gutter column, zero when it's hidden
+pub fn blame_gutter_width(show: bool, font_size: usize) -> f64 {
+    if show {
+        // An estimate of the average character width
+        (BLAME_GUTTER_CHARS * font_size) as f64 * 0.6
+    } else {
+        0.0
+    }
+}
+
 pub fn gutter_data(
     window_tab_data: WindowWorkspaceData,
     e_data: &EditorData,
@@ -31,6 +45,16 @@ pub fn gutter_data(
         (Default::default(), usize::MAX)
     };
     let code_lens = doc.code_lens.get();
+    let failed_tests = content
+        .path()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .and_then(|path| {
+            window_tab_data
+                .test_explorer
+                .failed_functions()
+                .remove(path)
+        })
+        .unwrap_or_default();
     let offset = e_data.cursor.get().offset();
     let (current_line, signal_last_line) = doc.lines.with_untracked(|x| {
         (x.buffer().line_of_offset(offset), x.signal_last_line())
@@ -38,21 +62,30 @@ pub fn gutter_data(
     let width = signal_last_line.get().1 + 8.0;
     let screen_lines = e_data.screen_lines.read_only();
 
-    let (fg, dim, style_font_size, font_family) =
+    let (fg, dim, style_font_size, font_family, show_blame) =
         window_tab_data.common.config.signal(|config| {
             (
                 config.color(LapceColor::EDITOR_FOREGROUND),
                 config.color(LapceColor::EDITOR_DIM),
                 config.editor.font_size.signal(),
                 config.editor.font_family.signal(),
+                config.editor.show_blame_gutter.signal(),
             )
         });
-    let (fg, dim, style_font_size, font_family) = (
+    let (fg, dim, style_font_size, font_family, show_blame) = (
         fg.get(),
         dim.get(),
         style_font_size.get(),
         font_family.get(),
+        show_blame.get(),
     );
+    if show_blame {
+        doc.blame.track();
+        doc.head_changes.track();
+    }
+    let blame_width = blame_gutter_width(show_blame, style_font_size);
+    // Only the first line of a run from the same commit is labelled
+    let mut last_blame: Option<LineBlame> = None;
 
     screen_lines.with(|screen_lines| {
         screen_lines
@@ -61,6 +94,15 @@ pub fn gutter_data(
             .map(|vl_info| {
                 match vl_info {
                     VisualLineInfo::OriginText { text } => {
+                        let line = text.folded_line.origin_line_start;
+                        let line_blame =
+                            show_blame.then(|| doc.line_blame(line)).flatten();
+                        let blame = if line_blame != last_blame {
+                            line_blame.as_ref().map(LineBlame::short_label)
+                        } else {
+                            None
+                        };
+                        last_blame = line_blame;
                         let style_color =
                             if text.folded_line.origin_line_start == current_line {
                                 fg
@@ -78,6 +120,28 @@ pub fn gutter_data(
                                 style_width: width,
                                 style_font_size,
                                 style_font_family: font_family.1.clone(),
+                                blame: blame.clone(),
+                                blame_width,
+                            }
+                        } else if !failed_tests.is_empty()
+                            && doc.lines.with_untracked(|x| {
+                                x.buffer().line_content(line).is_ok_and(|content| {
+                                    test_function_name(&content).is_some_and(
+                                        |name| failed_tests.contains(name),
+                                    )
+                                })
+                            })
+                        {
+                            GutterData {
+                                origin_line_start: Some(line),
+                                paint_point_y: text.folded_line_y,
+                                marker: GutterMarker::TestFailed,
+                                style_color,
+                                style_width: width,
+                                style_font_size,
+                                style_font_family: font_family.1.clone(),
+                                blame: blame.clone(),
+                                blame_width,
                             }
                         } else if code_lens
                             .contains_key(&text.folded_line.origin_line_start)
@@ -92,46 +156,45 @@ pub fn gutter_data(
                                 style_width: width,
                                 style_font_size,
                                 style_font_family: font_family.1.clone(),
+                                blame: blame.clone(),
+                                blame_width,
                             }
                         } else if let Some(breakpoint) =
                             breakpoints.get(&text.folded_line.origin_line_start)
                         {
-                            if breakpoint.verified {
-                                GutterData {
-                                    origin_line_start: Some(
-                                        text.folded_line.origin_line_start,
-                                    ),
-                                    paint_point_y: text.folded_line_y,
-                                    marker: GutterMarker::BreakpointVerified,
-                                    style_color,
-                                    style_width: width,
-                                    style_font_size,
-                                    style_font_family: font_family.1.clone(),
-                                }
-                            } else if breakpoint.active {
-                                GutterData {
-                                    origin_line_start: Some(
-                                        text.folded_line.origin_line_start,
-                                    ),
-                                    paint_point_y: text.folded_line_y,
-                                    marker: GutterMarker::Breakpoint,
-                                    style_color,
-                                    style_width: width,
-                                    style_font_size,
-                                    style_font_family: font_family.1.clone(),
-                                }
+                            let marker = if !breakpoint.active {
+                                GutterMarker::BreakpointInactive
                             } else {
-                                GutterData {
-                                    origin_line_start: Some(
-                                        text.folded_line.origin_line_start,
-                                    ),
-                                    paint_point_y: text.folded_line_y,
-                                    marker: GutterMarker::BreakpointInactive,
-                                    style_color,
-                                    style_width: width,
-                                    style_font_size,
-                                    style_font_family: font_family.1.clone(),
+                                match breakpoint.kind() {
+                                    BreakpointKind::Conditional => {
+                                        GutterMarker::BreakpointConditional
+                                    },
+                                    BreakpointKind::HitCount => {
+                                        GutterMarker::BreakpointHitCount
+                                    },
+                                    BreakpointKind::Log => {
+                                        GutterMarker::BreakpointLog
+                                    },
+                                    BreakpointKind::Plain if breakpoint.verified => {
+                                        GutterMarker::BreakpointVerified
+                                    },
+                                    BreakpointKind::Plain => {
+                                        GutterMarker::Breakpoint
+                                    },
                                 }
+                            };
+                            GutterData {
+                                origin_line_start: Some(
+                                    text.folded_line.origin_line_start,
+                                ),
+                                paint_point_y: text.folded_line_y,
+                                marker,
+                                style_color,
+                                style_width: width,
+                                style_font_size,
+                                style_font_family: font_family.1.clone(),
+                                blame: blame.clone(),
+                                blame_width,
                             }
                         } else {
                             GutterData {
@@ -144,6 +207,8 @@ pub fn gutter_data(
                                 style_width: width,
                                 style_font_size,
                                 style_font_family: font_family.1.clone(),
+                                blame: blame.clone(),
+                                blame_width,
                             }
                         }
                     },
@@ -157,6 +222,8 @@ pub fn gutter_data(
                             style_width: width,
                             style_font_size,
                             style_font_family: font_family.1.clone(),
+                            blame: None,
+                            blame_width,
                         }
                     },
                 }
@@ -174,6 +241,9 @@ pub struct GutterData {
     style_color:       Color,
     style_font_size:   usize,
     style_font_family: String,
+    /// The blame label, on the first line of each commit's run
+    blame:             Option<String>,
+    blame_width:       f64,
 }
 
 impl GutterData {
@@ -189,6 +259,8 @@ impl PartialEq for GutterData {
         self.paint_point_y.to_bits() == other.paint_point_y.to_bits()
             && self.origin_line_start == other.origin_line_start
             && self.marker == other.marker
+            && self.blame == other.blame
+            && self.blame_width.to_bits() == other.blame_width.to_bits()
     }
 }
 
@@ -199,6 +271,8 @@ impl Hash for GutterData {
         self.origin_line_start.hash(state);
         self.marker.hash(state);
         self.paint_point_y.to_bits().hash(state);
+        self.blame.hash(state);
+        self.blame_width.to_bits().hash(state);
     }
 }
 #[derive(Debug, Clone, Hash, Copy, Eq, PartialEq)]
@@ -206,9 +280,26 @@ pub enum GutterMarker {
     None,
     CodeLen,
     CurrentDebugLine,
+    /// A test function that failed in the last run
+    TestFailed,
     Breakpoint,
     BreakpointInactive,
-    BreakpointVerified, // CodeLenAndBreakPoint,
+    BreakpointVerified,
+    BreakpointConditional,
+    BreakpointHitCount,
+    BreakpointLog, // CodeLenAndBreakPoint,
+}
+
+impl GutterMarker {
+    pub fn is_breakpoint(&self) -> bool {
+        !matches!(
+            self,
+            GutterMarker::None
+                | GutterMarker::CodeLen
+                | GutterMarker::CurrentDebugLine
+                | GutterMarker::TestFailed
+        )
+    }
 }
 
 #[derive(Debug, Clone, Hash, Copy, Eq, PartialEq)]
@@ -1,18 +1,23 @@
 use floem::{
     View,
+    action::show_context_menu,
+    menu::{Menu, MenuItem},
     prelude::{Decorators, SignalGet, Svg, clip, container, palette, static_label},
     style::{CursorStyle, StyleValue},
     taffy::{AlignItems, JustifyContent},
     views::{dyn_stack, svg},
 };
-use lapce_core::icon::LapceIcons;
+use lapce_core::{icon::LapceIcons, panel::PanelKind};
 
 use crate::{
+    command::InternalCommand,
     config::{WithLapceConfig, color::LapceColor},
+    debug::BreakpointEditField,
     editor::{
         DocSignal, EditorData,
-        gutter_new::{GutterData, GutterMarker, gutter_data},
+        gutter_new::{GutterData, GutterMarker, blame_gutter_width, gutter_data},
     },
+    listener::Listener,
     window_workspace::WindowWorkspaceData,
 };
 
@@ -66,6 +71,22 @@ fn gutter_marker_breakpoint_inactive_svg_view(config: WithLapceConfig) -> Svg {
     )
 }
 
+fn gutter_marker_breakpoint_kind_svg_view(
+    config: WithLapceConfig,
+    icon: &'static str,
+) -> Svg {
+    svg(move || config.with_ui_svg(icon)).style(move |s| {
+        let (icon_size, color) = config.signal(|config| {
+            (
+                config.ui.icon_size.signal(),
+                config.color(LapceColor::DEBUG_BREAKPOINT),
+            )
+        });
+        let size = icon_size.get() as f64;
+        s.size(size, size).color(color.get())
+    })
+}
+
 fn gutter_marker_code_len_svg_view(
     window_tab_data: WindowWorkspaceData,
     line: Option<usize>,
@@ -105,6 +126,20 @@ fn gutter_marker_debug_line_svg_view(window_tab_data: WindowWorkspaceData) -> Sv
     })
 }
 
+fn gutter_marker_test_failed_svg_view(window_tab_data: WindowWorkspaceData) -> Svg {
+    let config = window_tab_data.common.config;
+    svg(move || config.with_ui_svg(LapceIcons::TEST_FAILED))
+        .style(move |s| {
+            let size = config.with_icon_size() as f64;
+            s.size(size, size)
+                .color(config.with_color(LapceColor::LAPCE_ERROR))
+                .hover(|s| s.cursor(CursorStyle::Pointer))
+        })
+        .on_click_stop(move |_| {
+            window_tab_data.show_panel(PanelKind::Test);
+        })
+}
+
 pub fn editor_gutter_new(
     window_tab_data: WindowWorkspaceData,
     e_data: EditorData,
@@ -133,7 +168,14 @@ pub fn editor_gutter_new(
         let size = config.with_icon_size() as f64;
         let last_line_width =
             doc.lines.with_untracked(|x| x.signal_last_line()).get().1;
-        let width = last_line_width + size * 2.0 + 8.0;
+        let (show_blame, font_size) = config.signal(|config| {
+            (
+                config.editor.show_blame_gutter.signal(),
+                config.editor.font_size.signal(),
+            )
+        });
+        let blame_width = blame_gutter_width(show_blame.get(), font_size.get());
+        let width = blame_width + last_line_width + size * 2.0 + 8.0;
         log::debug!("signal_last_line ={last_line_width} size={size}");
         style
             .width(width) // 父组件宽度
@@ -151,7 +193,21 @@ fn gutter_data_view(
     let data_clone = data.clone();
     let line_height = window_tab_data.common.ui_line_height;
     let paint_point_y = data.paint_point_y;
+    let blame_data = data.clone();
     container((
+        static_label(data.blame.clone().unwrap_or_default())
+            .style(move |style| {
+                style
+                    .height_full()
+                    .width(blame_data.blame_width)
+                    .font_size(blame_data.style_font_size as f32 - 1.0)
+                    .color(blame_data.style_color)
+                    .padding_horiz(4.0)
+                    .text_ellipsis()
+                    .align_items(AlignItems::Center)
+                    .apply_if(blame_data.blame_width == 0.0, |s| s.hide())
+            })
+            .debug_name("blame"),
         static_label(data_clone.display_line_num())
             .style(move |style| {
                 style
@@ -199,11 +255,30 @@ fn marker_view(
         GutterMarker::BreakpointInactive => {
             gutter_marker_breakpoint_inactive_svg_view(config)
         },
+        GutterMarker::BreakpointConditional => {
+            gutter_marker_breakpoint_kind_svg_view(
+                config,
+                LapceIcons::DEBUG_BREAKPOINT_CONDITIONAL,
+            )
+        },
+        GutterMarker::BreakpointHitCount => gutter_marker_breakpoint_kind_svg_view(
+            config,
+            LapceIcons::DEBUG_BREAKPOINT_HIT,
+        ),
+        GutterMarker::BreakpointLog => gutter_marker_breakpoint_kind_svg_view(
+            config,
+            LapceIcons::DEBUG_BREAKPOINT_LOG,
+        ),
         GutterMarker::CurrentDebugLine => {
             gutter_marker_debug_line_svg_view(window_tab_data)
         },
+        GutterMarker::TestFailed => {
+            gutter_marker_test_failed_svg_view(window_tab_data)
+        },
     };
     let origin_line_start = data.origin_line_start;
+    let is_breakpoint = data.marker.is_breakpoint();
+    let internal_command = window_tab_data_click.common.internal_command;
     container(svg)
         .style(move |s| {
             let size = config.with_icon_size() as f64;
@@ -227,4 +302,60 @@ fn marker_view(
                 );
             }
         })
+        .on_secondary_click_stop(move |_| {
+            if let Some(line) = origin_line_start {
+                breakpoint_secondary_click(
+                    internal_command,
+                    doc_signal,
+                    line,
+                    is_breakpoint,
+                );
+            }
+        })
+}
+
+fn breakpoint_secondary_click(
+    internal_command: Listener<InternalCommand>,
+    doc: DocSignal,
+    line_num: usize,
+    is_breakpoint: bool,
+) {
+    let mut menu = Menu::new("");
+    if is_breakpoint {
+        menu = menu
+            .entry(MenuItem::new("Edit Breakpoint...").action(move || {
+                internal_command.send(InternalCommand::EditBreakPoint {
+                    doc,
+                    line_num,
+                    field: BreakpointEditField::Condition,
+                });
+            }))
+            .entry(MenuItem::new("Remove Breakpoint").action(move || {
+                internal_command
+                    .send(InternalCommand::AddOrRemoveBreakPoint { doc, line_num });
+            }));
+    } else {
+        menu = menu
+            .entry(MenuItem::new("Add Breakpoint").action(move || {
+                internal_command
+                    .send(InternalCommand::AddOrRemoveBreakPoint { doc, line_num });
+            }))
+            .entry(MenuItem::new("Add Conditional Breakpoint...").action(
+                move || {
+                    internal_command.send(InternalCommand::EditBreakPoint {
+                        doc,
+                        line_num,
+                        field: BreakpointEditField::Condition,
+                    });
+                },
+            ))
+            .entry(MenuItem::new("Add Logpoint...").action(move || {
+                internal_command.send(InternalCommand::EditBreakPoint {
+                    doc,
+                    line_num,
+                    field: BreakpointEditField::LogMessage,
+                });
+            }));
+    }
+    show_context_menu(menu, None);
 }
@@ -1,4 +1,5 @@
 use std::{
+    cell::RefCell,
     cmp::Ordering,
     collections::{HashMap, HashSet},
     path::PathBuf,
@@ -30,9 +31,10 @@ use doc::{
         mode::{Mode, MotionMode},
         movement::Movement,
         phantom_text::Text,
+        register::Clipboard,
         screen_lines::{ScreenLines, VisualLineInfo},
         selection::{InsertDrift, SelRegion, Selection},
-        text::Preedit,
+        text::{Preedit, SystemClipboard},
     },
 };
 use floem::{
@@ -58,7 +60,7 @@ use lapce_core::{
     main_split::{SplitDirection, SplitMoveDirection},
     panel::PanelKind,
 };
-use lapce_rpc::{plugin::PluginId, proxy::ProxyResponse};
+use lapce_rpc::{dap_types::DapId, plugin::PluginId, proxy::ProxyResponse};
 use lapce_xi_rope::{Rope, RopeDelta, Transformer};
 use log::{error, info};
 use lsp_types::{
@@ -74,8 +76,8 @@ use view::StickyHeaderInfo;
 use self::location::{EditorLocation, EditorPosition};
 use crate::{
     command::{CommandKind, InternalCommand, LapceCommand, LapceWorkbenchCommand},
-    completion::CompletionStatus,
-    config::color::LapceColor,
+    completion::{CompletionStatus, ScoredCompletionItem},
+    config::{color::LapceColor, editor::FormatOnSaveMode},
     db::LapceDb,
     doc::Doc,
     editor::{
@@ -90,11 +92,16 @@ use crate::{
         MarkdownContent, from_marked_string, from_plaintext, parse_markdown,
     },
     panel::{
-        call_hierarchy_view::{CallHierarchyData, CallHierarchyItemData},
+        call_hierarchy_view::{
+            HierarchyData, HierarchyDirection, HierarchyItem, HierarchyItemData,
+        },
         document_symbol::MatchDocumentSymbol,
         implementation_view::{init_implementation_root, map_to_location},
     },
-    snippet::Snippet,
+    snippet::{
+        SNIPPET_CHOICE_PLUGIN_ID, Snippet, USER_SNIPPETS_PLUGIN_ID,
+        choice_completion_items,
+    },
     window_workspace::{CommonData, Focus, WindowWorkspaceData},
 };
 
@@ -144,6 +151,8 @@ pub struct EditorData {
     pub diff_editor_id:   RwSignal<Option<(EditorTabManageId, DiffEditorId)>>,
     // pub confirmed:            RwSignal<bool>,
     pub snippet:          RwSignal<Option<SnippetIndex>>,
+    /// The options of the choices of the snippet, by tab number
+    pub snippet_choices:  RwSignal<HashMap<usize, Vec<String>>>,
     pub inline_find:      RwSignal<Option<InlineFindDirection>>,
     pub on_screen_find:   RwSignal<OnScreenFind>,
     pub last_inline_find: RwSignal<Option<(InlineFindDirection, String)>>,
@@ -246,6 +255,7 @@ impl EditorData {
             diff_editor_id: cx.create_rw_signal(diff_editor_id),
             // confirmed,
             snippet: cx.create_rw_signal(None),
+            snippet_choices: cx.create_rw_signal(HashMap::new()),
             inline_find: cx.create_rw_signal(None),
             on_screen_find: cx.create_rw_signal(OnScreenFind {
                 active:  false,
@@ -484,6 +494,9 @@ impl EditorData {
         }
 
         if *cmd == EditCommand::InsertNewLine {
+            if !deltas.is_empty() {
+                self.format_on_type("\n");
+            }
             // Cancel so that there's no flickering
             self.cancel_inline_completion();
             self.update_inline_completion(InlineCompletionTriggerKind::Automatic)?;
@@ -1057,6 +1070,7 @@ impl EditorData {
                 self.cancel_inline_completion();
             },
             FocusCommand::JumpToNextSnippetPlaceholder => {
+                let mut tab = None;
                 self.snippet.update(|snippet| {
                     if let Some(snippet_mut) = snippet.as_mut() {
                         let mut current = 0;
@@ -1071,8 +1085,10 @@ impl EditorData {
 
                         let last_placeholder = current + 1 >= snippet_mut.len() - 1;
 
-                        if let Some((_, (start, end))) = snippet_mut.get(current + 1)
+                        if let Some((next, (start, end))) =
+                            snippet_mut.get(current + 1)
                         {
+                            tab = Some(*next);
                             let mut selection = Selection::new();
                             let region = SelRegion::new(*start, *end, None);
                             selection.add_region(region);
@@ -1089,8 +1105,12 @@ impl EditorData {
                         self.cancel_inline_completion();
                     }
                 });
+                if let Some(tab) = tab {
+                    self.show_snippet_choice(tab);
+                }
             },
             FocusCommand::JumpToPrevSnippetPlaceholder => {
+                let mut tab = None;
                 self.snippet.update(|snippet| {
                     if let Some(snippet_mut) = snippet.as_mut() {
                         let mut current = 0;
@@ -1104,9 +1124,10 @@ impl EditorData {
                         }
 
                         if current > 0 {
-                            if let Some((_, (start, end))) =
+                            if let Some((previous, (start, end))) =
                                 snippet_mut.get(current - 1)
                             {
+                                tab = Some(*previous);
                                 let mut selection = Selection::new();
                                 let region = SelRegion::new(*start, *end, None);
                                 selection.add_region(region);
@@ -1120,6 +1141,9 @@ impl EditorData {
                         }
                     }
                 });
+                if let Some(tab) = tab {
+                    self.show_snippet_choice(tab);
+                }
             },
             FocusCommand::GotoDefinition => {
                 if let Err(err) = self.go_to_definition() {
@@ -1147,6 +1171,9 @@ impl EditorData {
             FocusCommand::FormatDocument => {
                 self.format();
             },
+            FocusCommand::FormatSelection => {
+                self.format_selection();
+            },
             FocusCommand::InlineFindLeft => {
                 self.inline_find.set(Some(InlineFindDirection::Left));
             },
@@ -1498,6 +1525,62 @@ impl EditorData {
         &self,
         window_tab_data: WindowWorkspaceData,
     ) -> Result<()> {
+        let Some((path, position, range)) = self.hierarchy_position()? else {
+            return Ok(());
+        };
+        self.common.proxy.proxy_rpc.show_call_hierarchy(
+            path,
+            position,
+            create_ext_action(self.scope, move |(_, result)| {
+                if let Ok(ProxyResponse::ShowCallHierarchyResponse {
+                    items, ..
+                }) = result
+                {
+                    if let Some(item) = items.and_then(|x| x.into_iter().next()) {
+                        show_hierarchy(
+                            &window_tab_data,
+                            HierarchyItem::Call(item),
+                            range,
+                            HierarchyDirection::Incoming,
+                        );
+                    }
+                }
+            }),
+        );
+        Ok(())
+    }
+
+    pub fn type_hierarchy(
+        &self,
+        window_tab_data: WindowWorkspaceData,
+    ) -> Result<()> {
+        let Some((path, position, range)) = self.hierarchy_position()? else {
+            return Ok(());
+        };
+        self.common.proxy.proxy_rpc.show_type_hierarchy(
+            path,
+            position,
+            create_ext_action(self.scope, move |(_, result)| {
+                if let Ok(ProxyResponse::ShowTypeHierarchyResponse { items }) =
+                    result
+                {
+                    if let Some(item) = items.and_then(|x| x.into_iter().next()) {
+                        show_hierarchy(
+                            &window_tab_data,
+                            HierarchyItem::Type(item),
+                            range,
+                            HierarchyDirection::Subtypes,
+                        );
+                    }
+                }
+            }),
+        );
+        Ok(())
+    }
+
+    /// The path of the document, the position of the cursor and the range of
+    /// the word before it, to prepare a call or type hierarchy at.
+    fn hierarchy_position(&self) -> Result<Option<(PathBuf, Position, Range)>> {
         let doc = self.doc();
         let path = match if doc.loaded() {
             doc.content.with_untracked(|c| c.path().cloned())
@@ -1505,7 +1588,7 @@ impl EditorData {
             None
         } {
             Some(path) => path,
-            None => return Ok(()),
+            None => return Ok(None),
         };
 
         let offset = self.cursor().with_untracked(|c| c.offset());
@@ -1516,60 +1599,11 @@ impl EditorData {
             (start_position, position)
         });
         let (_start_position, position) = (_start_position?, position?);
-
-        let scope = window_tab_data.scope;
         let range = Range {
             start: _start_position,
             end:   position,
         };
-        self.common.proxy.proxy_rpc.show_call_hierarchy(
-            path,
-            position,
-            create_ext_action(self.scope, move |(_, result)| {
-                if let Ok(ProxyResponse::ShowCallHierarchyResponse {
-                    items, ..
-                }) = result
-                {
-                    if let Some(item) = items.and_then(|x| x.into_iter().next()) {
-                        let root_id = ViewId::new();
-                        let name = item.name.clone();
-                        let root = CallHierarchyItemData {
-                            root_id,
-                            view_id: root_id,
-                            item: Rc::new(item),
-                            from_range: range,
-                            init: false,
-                            open: scope.create_rw_signal(true),
-                            children: scope.create_rw_signal(Vec::with_capacity(0)),
-                        };
-                        let root = window_tab_data
-                            .main_split
-                            .hierarchy
-                            .cx
-                            .create_rw_signal(root);
-                        window_tab_data.main_split.hierarchy.push_tab(
-                            name,
-                            CallHierarchyData {
-                                root,
-                                root_id,
-                                scroll_to_line: None,
-                            },
-                        );
-                        // call_hierarchy_data.root.update(|x| {
-                        //     *x = Some(root);
-                        // });
-                        window_tab_data.show_panel(PanelKind::CallHierarchy);
-                        window_tab_data.common.internal_command.send(
-                            InternalCommand::CallHierarchyIncoming {
-                                item_id: root_id,
-                                root_id,
-                            },
-                        );
-                    }
-                }
-            }),
-        );
-        Ok(())
+        Ok(Some((path, position, range)))
     }
 
     pub fn document_highlight(
@@ -2030,7 +2064,9 @@ impl EditorData {
         let doc = self.doc();
         if let Some(item) = item {
             log::warn!("select_completion {item:?}");
-            if item.item.data.is_some() {
+            if item.plugin_id == SNIPPET_CHOICE_PLUGIN_ID {
+                self.apply_snippet_choice(&item.item.label);
+            } else if item.item.data.is_some() {
                 let editor = self.clone();
                 let rev = doc.lines.with_untracked(|b| b.buffer().rev());
                 let path = doc.content.with_untracked(|c| c.path().cloned());
@@ -2185,6 +2221,7 @@ impl EditorData {
         }
 
         let doc = self.doc();
+        let snippets = self.user_snippet_items();
         self.common.completion.update(|completion| {
             completion.path.clone_from(&path);
             completion.offset = start_offset;
@@ -2192,6 +2229,7 @@ impl EditorData {
             completion.status = CompletionStatus::Started;
             completion.input_items.clear();
             completion.request_id += 1;
+            completion.snippets = snippets;
             let start_pos = match doc
                 .lines
                 .with_untracked(|x| x.buffer().offset_to_position(start_offset))
@@ -2229,6 +2267,8 @@ impl EditorData {
                     position,
                 );
             }
+            // the snippets are listed before any server answers
+            completion.filter_items();
         });
     }
 
@@ -2309,7 +2349,8 @@ impl EditorData {
                         },
 
                         lsp_types::InsertTextFormat::SNIPPET => {
-                            let snippet = Snippet::from_str(&edit.new_text)?;
+                            let snippet =
+                                self.completion_snippet(item, &edit.new_text)?;
                             let text = snippet.text();
                             additional_edit.push((selection.clone(), text.as_str()));
                             self.completion_apply_snippet(
@@ -2334,6 +2375,23 @@ impl EditorData {
         let end_offset = buffer.next_code_boundary(offset);
         let selection = Selection::region(start_offset, end_offset);
 
+        if text_format == lsp_types::InsertTextFormat::SNIPPET {
+            let snippet = self.completion_snippet(
+                item,
+                item.insert_text.as_deref().unwrap_or(item.label.as_str()),
+            )?;
+            let text = snippet.text();
+            additional_edit.push((selection.clone(), text.as_str()));
+            self.completion_apply_snippet(
+                snippet,
+                &selection,
+                additional_edit,
+                start_offset,
+            )?;
+            self.check_auto_save();
+            return Ok(());
+        }
+
         self.do_edit(
             &selection,
             &[
@@ -2391,8 +2449,8 @@ impl EditorData {
         }
 
         let mut selection = Selection::new();
-        let (_tab, (start, end)) = &snippet_tabs[0];
-        let region = SelRegion::new(*start, *end, None);
+        let (tab, (start, end)) = snippet_tabs[0];
+        let region = SelRegion::new(start, end, None);
         selection.add_region(region);
         cursor.set_insert(selection);
 
@@ -2406,10 +2464,264 @@ impl EditorData {
 
         self.cursor().set(cursor);
         self.apply_deltas(&[(b_text, delta, inval_lines)]);
+        let choices = snippet.choices();
+        if self.snippet.with_untracked(|snippet| snippet.is_none()) {
+            self.snippet_choices.set(choices);
+        } else {
+            self.snippet_choices.update(|c| c.extend(choices));
+        }
         self.add_snippet_placeholders(snippet_tabs);
+        self.show_snippet_choice(tab);
         Ok(())
     }
 
+    /// The snippet of a completion item, indented like the line of the cursor
+    /// when the item asks for it.
+    fn completion_snippet(
+        &self,
+        item: &CompletionItem,
+        text: &str,
+    ) -> anyhow::Result<Snippet> {
+        let mut snippet = self.parse_snippet(text)?;
+        if item.insert_text_mode
+            == Some(lsp_types::InsertTextMode::ADJUST_INDENTATION)
+        {
+            self.adjust_snippet_indentation(&mut snippet)?;
+        }
+        Ok(snippet)
+    }
+
+    /// Parses a snippet, with its variables resolved for the cursor.
+    fn parse_snippet(&self, text: &str) -> anyhow::Result<Snippet> {
+        let mut snippet = Snippet::from_str(text)?;
+        snippet.resolve_variables(&|name| self.snippet_variable(name));
+        Ok(snippet)
+    }
+
+    fn adjust_snippet_indentation(&self, snippet: &mut Snippet) -> Result<()> {
+        let offset = self.cursor().with_untracked(|c| c.offset());
+        let (indent, indent_unit) = self.doc().lines.with_untracked(|x| {
+            let buffer = x.buffer();
+            let indent = buffer.indent_on_line(buffer.line_of_offset(offset))?;
+            anyhow::Ok((indent, buffer.indent_unit()))
+        })?;
+        snippet.adjust_indentation(&indent, indent_unit);
+        Ok(())
+    }
+
+    /// The value of a snippet variable, `None` for an unknown one.
+    fn snippet_variable(&self, name: &str) -> Option<String> {
+        let doc = self.doc();
+        let path = doc.content.with_untracked(|c| c.path().cloned());
+        let cursor = self.cursor().get_untracked();
+        let offset = cursor.offset();
+        let now = chrono::Local::now();
+        let date = |format: &str| Some(now.format(format).to_string());
+        match name {
+            "TM_SELECTED_TEXT" | "TM_CURRENT_LINE" | "TM_CURRENT_WORD"
+            | "TM_LINE_INDEX" | "TM_LINE_NUMBER" => doc.lines.with_untracked(|x| {
+                let buffer = x.buffer();
+                let line = buffer.line_of_offset(offset);
+                Some(match name {
+                    "TM_SELECTED_TEXT" => {
+                        let (start, end) = cursor.get_selection()?;
+                        buffer
+                            .slice_to_cow(start.min(end)..start.max(end))
+                            .to_string()
+                    },
+                    "TM_CURRENT_LINE" => buffer
+                        .line_content(line)
+                        .ok()?
+                        .trim_end_matches(['\n', '\r'])
+                        .to_string(),
+                    "TM_CURRENT_WORD" => {
+                        let (start, end) = buffer.select_word(offset);
+                        buffer.slice_to_cow(start..end).to_string()
+                    },
+                    "TM_LINE_INDEX" => line.to_string(),
+                    _ => (line + 1).to_string(),
+                })
+            }),
+            "TM_FILENAME" => {
+                path?.file_name().map(|x| x.to_string_lossy().into_owned())
+            },
+            "TM_FILENAME_BASE" => {
+                path?.file_stem().map(|x| x.to_string_lossy().into_owned())
+            },
+            "TM_DIRECTORY" => {
+                path?.parent().map(|x| x.to_string_lossy().into_owned())
+            },
+            "TM_FILEPATH" => path.map(|x| x.to_string_lossy().into_owned()),
+            "RELATIVE_FILEPATH" => {
+                let path = path?;
+                let relative = self
+                    .common
+                    .workspace
+                    .path()
+                    .and_then(|workspace| path.strip_prefix(workspace).ok())
+                    .unwrap_or(&path);
+                Some(relative.to_string_lossy().into_owned())
+            },
+            "WORKSPACE_NAME" => self
+                .common
+                .workspace
+                .path()?
+                .file_name()
+                .map(|x| x.to_string_lossy().into_owned()),
+            "WORKSPACE_FOLDER" => self
+                .common
+                .workspace
+                .path()
+                .map(|x| x.to_string_lossy().into_owned()),
+            "CLIPBOARD" => SystemClipboard::new().get_string(),
+            "LINE_COMMENT" => {
+                let token = doc
+                    .lines
+                    .with_untracked(|x| x.syntax.language.comment_token());
+                (!token.is_empty()).then(|| token.to_string())
+            },
+            "CURRENT_YEAR" => date("%Y"),
+            "CURRENT_YEAR_SHORT" => date("%y"),
+            "CURRENT_MONTH" => date("%m"),
+            "CURRENT_MONTH_NAME" => date("%B"),
+            "CURRENT_MONTH_NAME_SHORT" => date("%b"),
+            "CURRENT_DATE" => date("%d"),
+            "CURRENT_DAY_NAME" => date("%A"),
+            "CURRENT_DAY_NAME_SHORT" => date("%a"),
+            "CURRENT_HOUR" => date("%H"),
+            "CURRENT_MINUTE" => date("%M"),
+            "CURRENT_SECOND" => date("%S"),
+            "CURRENT_SECONDS_UNIX" => Some(now.timestamp().to_string()),
+            _ => None,
+        }
+    }
+
+    /// Inserts a user snippet over the selection, given its name or prefix,
+    /// or a `{ "snippet": "..." }` body. Without one, the snippets of the
+    /// language are listed in the completion.
+    fn insert_snippet(&self, snippet: Option<&Value>) -> Result<()> {
+        let language = self.doc().lines.with_untracked(|x| x.syntax.language);
+        let body = match snippet {
+            None => {
+                self.show_snippet_completion();
+                return Ok(());
+            },
+            Some(Value::Object(args)) if args.contains_key("snippet") => args
+                .get("snippet")
+                .and_then(|x| x.as_str())
+                .map(|x| x.to_string()),
+            Some(snippet) => {
+                let name = snippet
+                    .as_str()
+                    .or_else(|| snippet.get("name").and_then(|x| x.as_str()))
+                    .unwrap_or_default();
+                self.common.snippets.with_untracked(|snippets| {
+                    snippets.find(language, name).map(|x| x.body.clone())
+                })
+            },
+        }
+        .ok_or_else(|| anyhow::anyhow!("no snippet {snippet:?}"))?;
+
+        let (start, end) = self.cursor().with_untracked(|c| {
+            c.get_selection().unwrap_or((c.offset(), c.offset()))
+        });
+        let selection = Selection::region(start.min(end), start.max(end));
+        let mut snippet = self.parse_snippet(&body)?;
+        self.adjust_snippet_indentation(&mut snippet)?;
+        let text = snippet.text();
+        self.completion_apply_snippet(
+            snippet,
+            &selection,
+            vec![(selection.clone(), text.as_str())],
+            start.min(end),
+        )
+    }
+
+    /// Lists the user snippets of the language in the completion.
+    fn show_snippet_completion(&self) {
+        let doc = self.doc();
+        let Some(path) = doc.content.with_untracked(|c| c.path().cloned()) else {
+            return;
+        };
+        let offset = self.cursor().with_untracked(|c| c.offset());
+        let (start_offset, input) = doc.lines.with_untracked(|x| {
+            let buffer = x.buffer();
+            let start_offset = buffer.prev_code_boundary(offset);
+            (
+                start_offset,
+                buffer.slice_to_cow(start_offset..offset).to_string(),
+            )
+        });
+        let snippets = self.user_snippet_items();
+        self.common.completion.update(|completion| {
+            completion.path = path;
+            completion.offset = start_offset;
+            completion.input = input;
+            completion.status = CompletionStatus::Started;
+            completion.input_items.clear();
+            completion.request_id += 1;
+            completion.latest_editor_id = Some(self.id());
+            completion.snippets = snippets;
+            completion.filter_items();
+        });
+    }
+
+    /// Lists the options in the completion when the placeholder `tab` the
+    /// cursor moved to is a choice.
+    fn show_snippet_choice(&self, tab: usize) {
+        let Some(options) = self
+            .snippet_choices
+            .with_untracked(|choices| choices.get(&tab).cloned())
+        else {
+            return;
+        };
+        let Some(path) = self.doc().content.with_untracked(|c| c.path().cloned())
+        else {
+            return;
+        };
+        let offset = self.cursor().with_untracked(|c| {
+            c.get_selection()
+                .map(|(start, _)| start)
+                .unwrap_or(c.offset())
+        });
+        self.common.completion.update(|completion| {
+            completion.path = path;
+            completion.offset = offset;
+            completion.input = String::new();
+            completion.status = CompletionStatus::Started;
+            completion.input_items.clear();
+            completion.request_id += 1;
+            completion.latest_editor_id = Some(self.id());
+            completion.snippets = choice_completion_items(&options)
+                .map(|item| {
+                    ScoredCompletionItem::new(item, SNIPPET_CHOICE_PLUGIN_ID)
+                })
+                .collect();
+            completion.filter_items();
+        });
+    }
+
+    /// Replaces the choice the cursor selects with the option picked in the
+    /// completion.
+    fn apply_snippet_choice(&self, option: &str) {
+        let (start, end) = self.cursor().with_untracked(|c| {
+            c.get_selection().unwrap_or((c.offset(), c.offset()))
+        });
+        let selection = Selection::region(start, end);
+        self.do_edit(&selection, &[(selection.clone(), option)], false);
+    }
+
+    fn user_snippet_items(&self) -> im::Vector<ScoredCompletionItem> {
+        let language = self.doc().lines.with_untracked(|x| x.syntax.language);
+        self.common.snippets.with_untracked(|snippets| {
+            snippets
+                .for_language(language)
+                .flat_map(|snippet| snippet.completion_items())
+                .map(|item| ScoredCompletionItem::new(item, USER_SNIPPETS_PLUGIN_ID))
+                .collect()
+        })
+    }
+
     fn add_snippet_placeholders(
         &self,
         new_placeholders: Vec<(usize, (usize, usize))>,
@@ -2847,11 +3159,12 @@ impl EditorData {
             return;
         }
 
-        let (normalize_line_endings, format_on_save) =
+        let (normalize_line_endings, format_on_save, format_on_save_mode) =
             self.common.config.with_untracked(|config| {
                 (
                     config.editor.normalize_line_endings,
                     config.editor.format_on_save,
+                    config.editor.format_on_save_mode,
                 )
             });
 
@@ -2873,7 +3186,18 @@ impl EditorData {
 
         let rev = doc.rev();
         let format_on_save = allow_formatting && format_on_save;
-        if format_on_save {
+        // an untracked file has no head to diff against, and is formatted whole
+        let modified_lines = if format_on_save
+            && format_on_save_mode == FormatOnSaveMode::ModifiedLines
+        {
+            doc.modified_lines()
+        } else {
+            None
+        };
+        if let Some(lines) = modified_lines {
+            let editor = self.clone();
+            self.format_lines(path, lines, move || editor.do_save(after_action));
+        } else if format_on_save {
             let editor = self.clone();
             let send = create_ext_action(self.scope, move |result| {
                 if let Ok(ProxyResponse::GetDocumentFormatting { edits }) = result {
@@ -2922,6 +3246,164 @@ impl EditorData {
         }
     }
 
+    /// Formats the lines of the selections, or the line of the cursor.
+    pub fn format_selection(&self) {
+        let doc = self.doc();
+        let DocContent::File { path, .. } = doc.content.get_untracked() else {
+            return;
+        };
+        let lines = doc.lines.with_untracked(|x| {
+            let buffer = x.buffer();
+            let selection = self.cursor().get_untracked().edit_selection(buffer)?;
+            Ok::<_, anyhow::Error>(
+                selection
+                    .regions()
+                    .iter()
+                    .map(|region| {
+                        // a selection up to the start of a line leaves it out
+                        let end = if region.is_caret() {
+                            region.max()
+                        } else {
+                            region.max() - 1
+                        };
+                        buffer.line_of_offset(region.min())
+                            ..buffer.line_of_offset(end) + 1
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        });
+        match lines {
+            Ok(lines) => self.format_lines(path, lines, || {}),
+            Err(err) => error!("{err:?}"),
+        }
+    }
+
+    /// Formats each range of whole lines with a range formatting request, and
+    /// applies the edits together once all are answered, unless the document
+    /// changed meanwhile.
+    fn format_lines(
+        &self,
+        path: PathBuf,
+        lines: Vec<std::ops::Range<usize>>,
+        after_action: impl FnOnce() + 'static,
+    ) {
+        // the edits of overlapping ranges would conflict
+        let lines = merge_line_ranges(lines);
+        let doc = self.doc();
+        let rev = doc.rev();
+        let ranges = doc.lines.with_untracked(|x| {
+            let buffer = x.buffer();
+            lines
+                .iter()
+                .map(|lines| {
+                    Ok(Range {
+                        start: buffer.offset_to_position(
+                            buffer.offset_of_line(lines.start)?,
+                        )?,
+                        end:   buffer
+                            .offset_to_position(buffer.offset_of_line(lines.end)?)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        });
+        let ranges = match ranges {
+            Ok(ranges) if !ranges.is_empty() => ranges,
+            Ok(_) => {
+                after_action();
+                return;
+            },
+            Err(err) => {
+                error!("{err:?}");
+                after_action();
+                return;
+            },
+        };
+
+        let pending = Rc::new(RefCell::new((
+            ranges.len(),
+            Vec::new(),
+            Some(Box::new(after_action) as Box<dyn FnOnce()>),
+        )));
+        for range in ranges {
+            let editor = self.clone();
+            let pending = pending.clone();
+            let send = create_ext_action(self.scope, move |result| {
+                let (edits, after_action) = {
+                    let mut pending = pending.borrow_mut();
+                    let (remaining, edits, after_action) = &mut *pending;
+                    if let Ok(ProxyResponse::GetDocumentFormatting {
+                        edits: range_edits,
+                    }) = result
+                    {
+                        edits.extend(range_edits);
+                    }
+                    *remaining -= 1;
+                    if *remaining > 0 {
+                        return;
+                    }
+                    (std::mem::take(edits), after_action.take())
+                };
+                if !edits.is_empty() && editor.doc().rev() == rev {
+                    editor.do_text_edit(&edits, true);
+                }
+                if let Some(after_action) = after_action {
+                    after_action();
+                }
+            });
+            self.common.proxy.proxy_rpc.get_document_range_formatting(
+                path.clone(),
+                range,
+                move |(_, result)| {
+                    send(result);
+                },
+            );
+        }
+    }
+
+    /// Asks the server for the edits after typing `ch`, which it only gets for
+    /// its trigger characters.
+    fn format_on_type(&self, ch: &str) {
+        let format_on_type = self
+            .common
+            .config
+            .with_untracked(|config| config.editor.format_on_type);
+        if !format_on_type {
+            return;
+        }
+        let doc = self.doc();
+        let DocContent::File { path, .. } = doc.content.get_untracked() else {
+            return;
+        };
+        let rev = doc.rev();
+        let offset = self.cursor().with_untracked(|c| c.offset());
+        let position = match doc
+            .lines
+            .with_untracked(|x| x.buffer().offset_to_position(offset))
+        {
+            Ok(position) => position,
+            Err(err) => {
+                error!("{err:?}");
+                return;
+            },
+        };
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, move |result| {
+            if let Ok(ProxyResponse::GetDocumentFormatting { edits }) = result {
+                if !edits.is_empty() && editor.doc().rev() == rev {
+                    editor.do_text_edit(&edits, false);
+                }
+            }
+        });
+        self.common.proxy.proxy_rpc.get_on_type_formatting(
+            path,
+            position,
+            ch.to_string(),
+            move |(_, result)| {
+                send(result);
+            },
+        );
+    }
+
     fn search_whole_word_forward(&self, mods: Modifiers) {
         let offset = self.cursor().with_untracked(|c| c.offset());
         let (word, buffer) = self.doc().lines.with_untracked(|buffer| {
@@ -3703,7 +4185,8 @@ impl EditorData {
             },
             DocContent::Local
             | DocContent::History(_)
-            | DocContent::Scratch { .. } => (None, false),
+            | DocContent::Scratch { .. }
+            | DocContent::Virtual { .. } => (None, false),
         });
         let mut menu = Menu::new("");
         let mut cmds = if let Some(path) = path {
@@ -3728,6 +4211,29 @@ impl EditorData {
                 LapceWorkbenchCommand::GoToLocation,
             )));
         }
+        if self.diff_editor_id.get_untracked().is_some() {
+            let is_index = doc.content.with_untracked(|content| match content {
+                DocContent::History(history) => history.version == "index",
+                _ => false,
+            });
+            if is_file {
+                cmds.push(None);
+                cmds.push(Some(CommandKind::Workbench(
+                    LapceWorkbenchCommand::SourceControlStageSelectedLines,
+                )));
+                cmds.push(Some(CommandKind::Workbench(
+                    LapceWorkbenchCommand::SourceControlStageHunk,
+                )));
+            } else if is_index {
+                cmds.push(None);
+                cmds.push(Some(CommandKind::Workbench(
+                    LapceWorkbenchCommand::SourceControlUnstageSelectedLines,
+                )));
+                cmds.push(Some(CommandKind::Workbench(
+                    LapceWorkbenchCommand::SourceControlUnstageHunk,
+                )));
+            }
+        }
         let lapce_command = self.common.lapce_command;
         for cmd in cmds {
             if let Some(cmd) = cmd {
@@ -3840,9 +4346,85 @@ impl EditorData {
                 return;
             },
         };
+        if let Some((dap_id, frame_id)) = self.common.stopped_frame.get_untracked() {
+            let expression = doc.lines.with_untracked(|x| {
+                let buffer = x.buffer();
+                let (start, end) = buffer.select_word(offset);
+                buffer.slice_to_cow(start..end).to_string()
+            });
+            if is_identifier(&expression) {
+                self.get_hover_from_dap(
+                    offset, path, position, dap_id, frame_id, expression,
+                );
+                return;
+            }
+        }
         self.get_hover_from_lsp(offset, path, position);
     }
 
+    /// Evaluate the identifier under the mouse in the stopped debuggee, falling
+    /// back to the language server hover when the debugger can't evaluate it.
+    fn get_hover_from_dap(
+        &self,
+        offset: usize,
+        path: PathBuf,
+        position: Position,
+        dap_id: DapId,
+        frame_id: usize,
+        expression: String,
+    ) {
+        let config = self.common.config;
+        let hover_data = self.common.hover.clone();
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, move |resp| {
+            let result = match resp {
+                Ok(ProxyResponse::DapEvaluateResponse { result }) => result,
+                _ => {
+                    editor.get_hover_from_lsp(offset, path, position);
+                    return;
+                },
+            };
+            let msg = match result.ty.as_deref() {
+                Some(ty) if !ty.is_empty() => {
+                    format!("{expression}: {ty} = {}", result.result)
+                },
+                _ => format!("{expression} = {}", result.result),
+            };
+            let (font_family, editor_fg, font_size) = config.signal(|config| {
+                (
+                    config.editor.font_family.val().clone(),
+                    config.color_val(LapceColor::EDITOR_FOREGROUND),
+                    *config.ui.font_size.val() as f32,
+                )
+            });
+            let default_attrs = Attrs::new()
+                .color(editor_fg)
+                .family(&font_family.0)
+                .font_size(font_size)
+                .line_height(LineHeightValue::Normal(1.8));
+            let attrs_list = AttrsList::new(default_attrs);
+            let content = vec![MarkdownContent::Text(TextLayout::new_with_text(
+                &msg, attrs_list,
+            ))];
+            let editor_id = editor.id();
+            batch(|| {
+                hover_data.content.set(content);
+                hover_data.offset.set(offset);
+                hover_data.editor_id.set(editor_id);
+                hover_data.active.set(true);
+            });
+        });
+        self.common.proxy.proxy_rpc.dap_evaluate(
+            dap_id,
+            expression.clone(),
+            Some(frame_id),
+            Some("hover".to_string()),
+            move |(_, resp)| {
+                send(resp);
+            },
+        );
+    }
+
     fn get_hover_from_lsp(&self, offset: usize, path: PathBuf, position: Position) {
         let config = self.common.config;
         let hover_data = self.common.hover.clone();
@@ -4221,6 +4803,12 @@ impl KeyPressFocus for EditorData {
                 {
                     return CommandExecuted::No;
                 }
+                if *cmd == FocusCommand::InsertSnippet {
+                    if let Err(err) = self.insert_snippet(command.data.as_ref()) {
+                        error!("{err:?}");
+                    }
+                    return CommandExecuted::Yes;
+                }
                 self.run_focus_command(cmd, count, mods)
             },
             crate::command::CommandKind::MotionMode(cmd) => {
@@ -4291,6 +4879,9 @@ impl KeyPressFocus for EditorData {
                 }
 
                 self.apply_deltas(&deltas);
+                if !deltas.is_empty() {
+                    self.format_on_type(c);
+                }
                 self.check_auto_save();
             } else if let Some(direction) = self.inline_find.get_untracked() {
                 if let Err(err) = self.inline_find(direction.clone(), c) {
@@ -4697,6 +5288,56 @@ fn should_trigger_code_action_after_completion(
     None
 }
 
+/// Opens a tab with `item` as the root in the call or the type hierarchy
+/// panel, and fetches its children.
+fn show_hierarchy(
+    window_tab_data: &WindowWorkspaceData,
+    item: HierarchyItem,
+    from_range: Range,
+    direction: HierarchyDirection,
+) {
+    let (tabs, panel) = match item {
+        HierarchyItem::Call(_) => (
+            window_tab_data.main_split.hierarchy,
+            PanelKind::CallHierarchy,
+        ),
+        HierarchyItem::Type(_) => (
+            window_tab_data.main_split.type_hierarchy,
+            PanelKind::TypeHierarchy,
+        ),
+    };
+    let scope = window_tab_data.scope;
+    let root_id = ViewId::new();
+    let name = item.name().to_string();
+    let root = HierarchyItemData {
+        root_id,
+        view_id: root_id,
+        item: Rc::new(item),
+        from_range,
+        init: false,
+        open: scope.create_rw_signal(true),
+        children: scope.create_rw_signal(Vec::with_capacity(0)),
+    };
+    let root = tabs.cx.create_rw_signal(root);
+    tabs.push_tab(
+        name,
+        HierarchyData {
+            root,
+            root_id,
+            direction: tabs.cx.create_rw_signal(direction),
+            scroll_to_line: None,
+        },
+    );
+    window_tab_data.show_panel(panel);
+    window_tab_data
+        .common
+        .internal_command
+        .send(InternalCommand::HierarchyExpand {
+            item_id: root_id,
+            root_id,
+        });
+}
+
 #[allow(clippy::too_many_arguments)]
 fn parse_hover_resp(
     hover: lsp_types::Hover,
@@ -4778,6 +5419,9 @@ fn file_menu(_path: PathBuf) -> Vec<Option<CommandKind>> {
         Some(CommandKind::Workbench(
             LapceWorkbenchCommand::ShowCallHierarchy,
         )),
+        Some(CommandKind::Workbench(
+            LapceWorkbenchCommand::ShowTypeHierarchy,
+        )),
         Some(CommandKind::Workbench(
             LapceWorkbenchCommand::FindReferences,
         )),
@@ -4839,3 +5483,44 @@ fn run_toml_menu() -> Vec<Option<CommandKind>> {
         )),
     ]
 }
+
+/// Sorts the ranges of lines and merges those that overlap or touch.
+fn merge_line_ranges(
+    mut lines: Vec<std::ops::Range<usize>>,
+) -> Vec<std::ops::Range<usize>> {
+    lines.sort_by_key(|lines| lines.start);
+    let mut merged: Vec<std::ops::Range<usize>> = Vec::with_capacity(lines.len());
+    for lines in lines {
+        match merged.last_mut() {
+            Some(last) if lines.start <= last.end => {
+                last.end = last.end.max(lines.end);
+            },
+            _ => merged.push(lines),
+        }
+    }
+    merged
+}
+
+/// Whether `word` looks like an identifier that a debugger can evaluate.
+fn is_identifier(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|c| c.is_alphabetic() || c == '_')
+        .unwrap_or(false)
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge_line_ranges;
+
+    #[test]
+    fn test_merge_line_ranges() {
+        assert_eq!(merge_line_ranges(vec![8..9, 0..2, 1..4]), vec![0..4, 8..9]);
+        // a caret on the line after a selection
+        assert_eq!(merge_line_ranges(vec![3..5, 5..6]), vec![3..6]);
+        assert_eq!(merge_line_ranges(vec![2..7, 3..4]), vec![2..7]);
+        assert_eq!(merge_line_ranges(vec![]), vec![]);
+    }
+}
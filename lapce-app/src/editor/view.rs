@@ -1892,6 +1892,52 @@ fn editor_content(
 
     let current_scroll = scope.create_rw_signal(Rect::ZERO);
 
+    {
+        let editor = editor.clone();
+        // Blame the cursor line at its end
+        scope.create_effect(move |_| {
+            let show = editor
+                .common
+                .config
+                .signal(|x| x.editor.show_blame_current_line.signal())
+                .get();
+            let doc = editor.doc_signal().get();
+            let offset = cursor.get().offset();
+            doc.blame.track();
+            doc.head_changes.track();
+            if !is_active(true) {
+                return;
+            }
+            let annotation = if show {
+                let line = doc
+                    .lines
+                    .with_untracked(|x| x.buffer().line_of_offset(offset));
+                doc.line_blame(line).map(|blame| (line, blame.label()))
+            } else {
+                None
+            };
+            doc.set_line_annotation(annotation);
+        });
+    }
+
+    {
+        let editor = editor.clone();
+        // The blame is only retrieved while it's shown, fetch it once turned on
+        scope.create_effect(move |_| {
+            let (gutter, current_line) = editor.common.config.signal(|x| {
+                (
+                    x.editor.show_blame_gutter.signal(),
+                    x.editor.show_blame_current_line.signal(),
+                )
+            });
+            let show = gutter.get() || current_line.get();
+            let doc = editor.doc_signal().get();
+            if show && doc.blame.get_untracked().is_none() {
+                doc.retrieve_blame();
+            }
+        });
+    }
+
     {
         let editor = editor.clone();
         scope.create_effect(move |_| {
@@ -131,7 +131,8 @@ impl EditorTabChildId {
                         DocContent::File { path, .. } => Some((path, is_pristine)),
                         DocContent::Local => None,
                         DocContent::History(_) => None,
-                        DocContent::Scratch { name, .. } => {
+                        DocContent::Scratch { name, .. }
+                        | DocContent::Virtual { name } => {
                             Some((PathBuf::from(name), is_pristine))
                         },
                     }
@@ -200,7 +201,8 @@ impl EditorTabChildId {
                                 },
                                 DocContent::Local => None,
                                 DocContent::History(_) => None,
-                                DocContent::Scratch { name, .. } => {
+                                DocContent::Scratch { name, .. }
+                                | DocContent::Virtual { name } => {
                                     Some((PathBuf::from(name), is_pristine))
                                 },
                             }
@@ -460,13 +462,16 @@ impl EditorTabManageData {
         for (i, child) in self.children.iter().enumerate() {
             if let EditorTabChildId::Editor(editor_id) = child.id() {
                 if let Some(editor) = editors.editor_untracked(*editor_id) {
-                    let is_path = editor.doc().content.with_untracked(|content| {
-                        if let DocContent::File { path: p, .. } = content {
-                            p == path
-                        } else {
-                            false
-                        }
-                    });
+                    let is_path =
+                        editor.doc().content.with_untracked(
+                            |content| match content {
+                                DocContent::File { path: p, .. } => p == path,
+                                DocContent::Virtual { name } => {
+                                    Path::new(name) == path
+                                },
+                                _ => false,
+                            },
+                        );
                     if is_path {
                         return Some((i, editor));
                     }
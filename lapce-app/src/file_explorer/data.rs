@@ -115,15 +115,43 @@ impl KeyPressFocus for FileExplorerData {
 
 impl FileExplorerData {
     pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
-        let path = common.workspace.path().cloned().unwrap_or_default();
-        let root = cx.create_rw_signal(FileNodeItem {
-            path:                path.clone(),
-            is_dir:              true,
-            read:                false,
-            open:                false,
-            children:            HashMap::new(),
-            children_open_count: 0,
-        });
+        let folders = common.workspace.folders();
+        let root = if common.workspace.is_multi_root() {
+            // a root with an empty path, whose children are the folders
+            FileNodeItem {
+                path:                PathBuf::new(),
+                is_dir:              true,
+                read:                true,
+                open:                true,
+                children:            folders
+                    .iter()
+                    .map(|folder| {
+                        (
+                            folder.clone(),
+                            FileNodeItem {
+                                path:                folder.clone(),
+                                is_dir:              true,
+                                read:                false,
+                                open:                false,
+                                children:            HashMap::new(),
+                                children_open_count: 0,
+                            },
+                        )
+                    })
+                    .collect(),
+                children_open_count: folders.len(),
+            }
+        } else {
+            FileNodeItem {
+                path:                folders.first().cloned().unwrap_or_default(),
+                is_dir:              true,
+                read:                false,
+                open:                false,
+                children:            HashMap::new(),
+                children_open_count: 0,
+            }
+        };
+        let root = cx.create_rw_signal(root);
         let naming = cx.create_rw_signal(Naming::None);
         let naming_str = cx.create_rw_signal(String::new());
 
@@ -136,18 +164,27 @@ impl FileExplorerData {
             select: cx.create_rw_signal(None),
             naming_str,
         };
-        if data.common.workspace.path().is_some() {
-            // only fill in the child files if there is open folder
-            data.toggle_expand(&path);
+        // only fill in the child files if there is open folder
+        for folder in &folders {
+            data.toggle_expand(folder);
         }
         data
     }
 
-    /// Reload the file explorer data via reading the root directory.  
+    /// Reload the file explorer data via reading the root directory, or each
+    /// folder of a multi-root workspace.  
     /// Note that this will not update immediately.
     pub fn reload(&self) {
-        let path = self.root.with_untracked(|root| root.path.clone());
-        self.read_dir(&path);
+        let paths = self.root.with_untracked(|root| {
+            if root.is_virtual_root() {
+                root.children.keys().cloned().collect()
+            } else {
+                vec![root.path.clone()]
+            }
+        });
+        for path in paths {
+            self.read_dir(&path);
+        }
     }
 
     /// Toggle whether the directory is expanded or not.  
@@ -466,8 +503,12 @@ impl FileExplorerData {
             })
             .unwrap_or(false);
         if done {
-            let (found, line) =
+            let (found, mut line) =
                 self.root.with_untracked(|x| x.find_file_at_line(&path));
+            // the virtual root of a multi-root workspace has no row
+            if self.root.with_untracked(FileNodeItem::is_virtual_root) {
+                line -= 1.0;
+            }
             if found {
                 self.scroll_to_line.set(Some(line));
                 self.select.set(Some(FileNodeViewKind::Path(path)));
@@ -509,7 +550,12 @@ impl FileExplorerData {
             return;
         };
 
-        let is_workspace = path == workspace_path;
+        let is_workspace = self
+            .common
+            .workspace
+            .folders()
+            .iter()
+            .any(|folder| folder == path);
 
         let base_path_a = if is_dir {
             Some(path_a.clone())
@@ -641,10 +687,10 @@ impl FileExplorerData {
         let path = path_a.clone();
         let workspace = common.workspace.clone();
         menu = menu.entry(MenuItem::new("Copy Relative Path").action(move || {
-            let relative_path = if let Some(workspace_path) = workspace.path() {
-                path.strip_prefix(workspace_path).unwrap_or(&path)
+            let relative_path = if let Some(folder) = workspace.folder_of(&path) {
+                path.strip_prefix(folder).unwrap_or(&path).to_path_buf()
             } else {
-                path.as_ref()
+                path.clone()
             };
 
             let mut clipboard = SystemClipboard::new();
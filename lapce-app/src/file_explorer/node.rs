@@ -17,6 +17,10 @@ impl FileNodeVirtualList {
 
 impl VirtualVector<FileNodeViewData> for FileNodeVirtualList {
     fn total_len(&self) -> usize {
+        // the folders of a multi-root workspace are the top rows
+        if self.file_node_item.is_virtual_root() {
+            return self.file_node_item.children_open_count;
+        }
         if !self.file_node_item.path.exists() {
             return 0;
         }
@@ -30,13 +34,32 @@ impl VirtualVector<FileNodeViewData> for FileNodeVirtualList {
         let naming = &self.naming;
         let root = &self.file_node_item;
 
-        if !root.path.exists() {
-            return Vec::new().into_iter();
-        }
         let min = range.start;
         let max = range.end;
         let mut view_items = Vec::new();
 
+        if root.is_virtual_root() {
+            let mut next = 0;
+            for folder in root.sorted_children() {
+                let last = folder.append_view_slice(
+                    &mut view_items,
+                    naming,
+                    min,
+                    max,
+                    next,
+                    1,
+                );
+                if last > max {
+                    break;
+                }
+                next = last + 1;
+            }
+            return view_items.into_iter();
+        }
+
+        if !root.path.exists() {
+            return Vec::new().into_iter();
+        }
         root.append_view_slice(&mut view_items, naming, min, max, 0, 1);
 
         view_items.into_iter()
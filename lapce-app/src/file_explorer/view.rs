@@ -153,7 +153,7 @@ fn file_node_text_color(
                 .find(|p| p.as_path().starts_with(path))
                 .map(|_| FileDiffKind::Modified)
         } else {
-            file_diffs.get(path).map(|(diff, _)| diff.kind())
+            file_diffs.get(path).map(|diff| diff.kind())
         }
     });
 
@@ -1,11 +1,15 @@
 use std::{
+    collections::HashSet,
     hash::{Hash, Hasher},
     ops::{AddAssign, Range},
     path::PathBuf,
     rc::Rc,
 };
 
-use doc::lines::{editor_command::CommandExecuted, mode::Mode};
+use doc::lines::{
+    edit::EditType, editor_command::CommandExecuted, mode::Mode,
+    selection::Selection,
+};
 use floem::{
     ViewId,
     ext_event::create_ext_action,
@@ -14,12 +18,18 @@ use floem::{
     views::VirtualVector,
 };
 use indexmap::IndexMap;
-use lapce_core::workspace::LapceWorkspace;
-use lapce_rpc::proxy::{ProxyResponse, SearchMatch};
+use lapce_core::{doc::DocContent, search::Replacer, workspace::LapceWorkspace};
+use lapce_rpc::{
+    RpcError,
+    proxy::{FileReplace, ProxyResponse, SearchMatch, SearchOptions},
+};
+use lapce_xi_rope::Rope;
+use log::error;
 
 use crate::{
     command::{CommandKind, LapceWorkbenchCommand},
     keypress::{KeyPressFocus, condition::Condition},
+    local_history::{SnapshotReason, take_snapshot, take_snapshots},
     main_split::MainSplitData,
     window_workspace::CommonData,
 };
@@ -28,6 +38,10 @@ use crate::{
 pub struct SearchMatchData {
     pub expanded:    RwSignal<bool>,
     pub matches:     RwSignal<im::Vector<SearchMatch>>,
+    /// Whether the file takes part in a replace
+    pub included:    RwSignal<bool>,
+    /// The matches left alone by a replace, by line and start in the line
+    pub excluded:    RwSignal<im::HashSet<(usize, usize)>>,
     pub line_height: Memo<f64>,
 }
 
@@ -63,8 +77,10 @@ impl SearchMatchData {
                 for child in x {
                     if *next >= min && *next < max {
                         children.push(SearchItem::Item {
-                            path: path.clone(),
-                            m:    child.clone(),
+                            path:     path.clone(),
+                            m:        child.clone(),
+                            included: self.included,
+                            excluded: self.excluded,
                         });
                         next.add_assign(1);
                     } else if *next > max {
@@ -79,13 +95,7 @@ impl SearchMatchData {
     }
 
     pub fn folder(&self, path: PathBuf, workspace: &LapceWorkspace) -> SearchItem {
-        let path = if let Some(workspace_path) = workspace.path() {
-            path.strip_prefix(workspace_path)
-                .unwrap_or(&path)
-                .to_path_buf()
-        } else {
-            path
-        };
+        let path = workspace.relative_path(&path).unwrap_or(path);
         let file_name = path
             .file_name()
             .and_then(|s| s.to_str())
@@ -99,24 +109,43 @@ impl SearchMatchData {
 
         SearchItem::Folder {
             expanded: self.expanded,
+            included: self.included,
             file_name,
             folder,
             path,
         }
     }
+
+    /// The matches a replace should change, by line and start in the line.
+    fn replace_matches(&self) -> Vec<(usize, usize)> {
+        if !self.included.get_untracked() {
+            return Vec::new();
+        }
+        let excluded = self.excluded.get_untracked();
+        self.matches.with_untracked(|matches| {
+            matches
+                .iter()
+                .map(|m| (m.line, m.start))
+                .filter(|m| !excluded.contains(m))
+                .collect()
+        })
+    }
 }
 
 #[derive(Clone, Debug, Eq)]
 pub enum SearchItem {
     Folder {
         expanded:  RwSignal<bool>,
+        included:  RwSignal<bool>,
         file_name: String,
         folder:    String,
         path:      PathBuf,
     },
     Item {
-        path: PathBuf,
-        m:    SearchMatch,
+        path:     PathBuf,
+        m:        SearchMatch,
+        included: RwSignal<bool>,
+        excluded: RwSignal<im::HashSet<(usize, usize)>>,
     },
 }
 
@@ -135,10 +164,11 @@ impl PartialEq for SearchItem {
                     && path == other_path
             },
             (
-                SearchItem::Item { path, m },
+                SearchItem::Item { path, m, .. },
                 SearchItem::Item {
                     path: other_path,
                     m: other_m,
+                    ..
                 },
             ) => path == other_path && m == other_m,
             _ => false,
@@ -153,7 +183,7 @@ impl Hash for SearchItem {
                 expanded.get_untracked().hash(state);
                 path.hash(state);
             },
-            SearchItem::Item { path, m } => {
+            SearchItem::Item { path, m, .. } => {
                 m.hash(state);
                 path.hash(state);
             },
@@ -161,10 +191,26 @@ impl Hash for SearchItem {
     }
 }
 
+/// The running totals of the current global search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchProgress {
+    pub files_searched: usize,
+    pub match_count:    usize,
+    pub searching:      bool,
+    pub limit_reached:  bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct GlobalSearchData {
     pub search_result: RwSignal<IndexMap<PathBuf, SearchMatchData>>,
     pub search_str:    RwSignal<String>,
+    pub replace_str:   RwSignal<String>,
+    /// Comma separated globs of the files to search
+    pub include:       RwSignal<String>,
+    /// Comma separated globs of the files to skip
+    pub exclude:       RwSignal<String>,
+    pub search_id:     RwSignal<u64>,
+    pub progress:      RwSignal<SearchProgress>,
     pub view_id:       RwSignal<ViewId>,
     pub main_split:    MainSplitData,
     pub common:        Rc<CommonData>,
@@ -255,6 +301,11 @@ impl GlobalSearchData {
             main_split,
             common,
             search_str,
+            replace_str: cx.create_rw_signal(String::new()),
+            include: cx.create_rw_signal(String::new()),
+            exclude: cx.create_rw_signal(String::new()),
+            search_id: cx.create_rw_signal(0),
+            progress: cx.create_rw_signal(SearchProgress::default()),
             view_id: cx.create_rw_signal(ViewId::default()),
         };
 
@@ -263,28 +314,74 @@ impl GlobalSearchData {
             let global_search = global_search.clone();
             cx.create_effect(move |_| {
                 let pattern = buffer.get();
-                if pattern.is_empty() {
-                    global_search.search_result.update(|r| r.clear());
-                    return;
-                }
+                let include = split_globs(&global_search.include.get());
+                let exclude = split_globs(&global_search.exclude.get());
                 let case_sensitive = global_search.common.find.case_sensitive(true);
                 let whole_word = global_search.common.find.whole_words.get();
                 let is_regex = global_search.common.find.is_regex.get();
+
+                global_search.cancel();
+                global_search.search_result.update(|r| r.clear());
+                if pattern.is_empty() {
+                    global_search.progress.set(SearchProgress::default());
+                    return;
+                }
+
+                let search_id = global_search.search_id.get_untracked() + 1;
+                global_search.search_id.set(search_id);
+                global_search.progress.set(SearchProgress {
+                    searching: true,
+                    ..Default::default()
+                });
+                let (max_file_size, max_matches) =
+                    global_search.common.config.with_untracked(|config| {
+                        (
+                            config.ui.search_max_file_size * 1024,
+                            config.ui.search_max_matches,
+                        )
+                    });
+                let options = SearchOptions {
+                    include,
+                    exclude,
+                    max_file_size,
+                    max_matches,
+                };
                 let send = {
                     let global_search = global_search.clone();
                     create_ext_action(cx, move |result| {
-                        if let Ok(ProxyResponse::GlobalSearchResponse { matches }) =
-                            result
-                        {
-                            global_search.update_matches(matches);
+                        if global_search.search_id.get_untracked() != search_id {
+                            return;
+                        }
+                        match result {
+                            Ok(ProxyResponse::GlobalSearchResponse {
+                                files_searched,
+                                match_count,
+                                limit_reached,
+                            }) => {
+                                global_search.progress.set(SearchProgress {
+                                    files_searched,
+                                    match_count,
+                                    searching: false,
+                                    limit_reached,
+                                });
+                            },
+                            Ok(_) => {},
+                            Err(err) => {
+                                log::warn!("global search failed: {}", err.message);
+                                global_search.progress.update(|progress| {
+                                    progress.searching = false;
+                                });
+                            },
                         }
                     })
                 };
                 global_search.common.proxy.proxy_rpc.global_search(
+                    search_id,
                     pattern,
                     case_sensitive,
                     whole_word,
                     is_regex,
+                    options,
                     move |(_, result)| {
                         send(result);
                     },
@@ -305,37 +402,244 @@ impl GlobalSearchData {
         global_search
     }
 
-    fn update_matches(&self, matches: IndexMap<PathBuf, Vec<SearchMatch>>) {
-        let current = self.search_result.get_untracked();
+    /// Append a batch of streamed matches if they belong to the current
+    /// search.
+    pub fn receive_matches(
+        &self,
+        search_id: u64,
+        matches: IndexMap<PathBuf, Vec<SearchMatch>>,
+        files_searched: usize,
+        match_count: usize,
+    ) {
+        if self.search_id.get_untracked() != search_id {
+            return;
+        }
+        self.progress.update(|progress| {
+            progress.files_searched = progress.files_searched.max(files_searched);
+            progress.match_count = progress.match_count.max(match_count);
+        });
+        if matches.is_empty() {
+            return;
+        }
+        self.search_result.update(|result| {
+            for (path, matches) in matches {
+                let match_data =
+                    result.entry(path).or_insert_with(|| SearchMatchData {
+                        expanded:    self.common.scope.create_rw_signal(true),
+                        matches:     self
+                            .common
+                            .scope
+                            .create_rw_signal(im::Vector::new()),
+                        included:    self.common.scope.create_rw_signal(true),
+                        excluded:    self
+                            .common
+                            .scope
+                            .create_rw_signal(im::HashSet::new()),
+                        line_height: self.common.ui_line_height,
+                    });
+                match_data.matches.update(|m| m.extend(matches));
+            }
+        });
+    }
 
-        self.search_result.set(
-            matches
-                .into_iter()
-                .map(|(path, matches)| {
-                    let match_data =
-                        current.get(&path).cloned().unwrap_or_else(|| {
-                            SearchMatchData {
-                                expanded:    self
-                                    .common
-                                    .scope
-                                    .create_rw_signal(true),
-                                matches:     self
-                                    .common
-                                    .scope
-                                    .create_rw_signal(im::Vector::new()),
-                                line_height: self.common.ui_line_height,
-                            }
-                        });
+    /// Stop the running search, keeping the matches found so far.
+    pub fn cancel(&self) {
+        let progress = self.progress.get_untracked();
+        if progress.searching {
+            self.common
+                .proxy
+                .proxy_rpc
+                .cancel_global_search(self.search_id.get_untracked());
+            self.progress.set(SearchProgress {
+                searching: false,
+                ..progress
+            });
+        }
+    }
 
-                    match_data.matches.set(matches.into());
+    fn replacer(&self) -> Option<Replacer> {
+        let pattern = self.search_str.get_untracked();
+        if pattern.is_empty() {
+            return None;
+        }
+        match Replacer::new(
+            &pattern,
+            self.common.find.case_sensitive(false),
+            self.common.find.whole_words.get_untracked(),
+            self.common.find.is_regex.get_untracked(),
+            self.replace_str.get_untracked(),
+        ) {
+            Ok(replacer) => Some(replacer),
+            Err(err) => {
+                error!("{err}");
+                None
+            },
+        }
+    }
+
+    /// Open a diff of the file at `path` against the result of replacing its
+    /// included matches.
+    pub fn preview_replace(&self, path: PathBuf) {
+        let Some(replacer) = self.replacer() else {
+            return;
+        };
+        let Some(matches) = self
+            .search_result
+            .with_untracked(|result| result.get(&path).map(|m| m.replace_matches()))
+        else {
+            return;
+        };
+        let matches = matches.into_iter().collect::<HashSet<_>>();
+        // by path, as files of the same name are previewed apart
+        let name = format!(
+            "{} (Replace Preview)",
+            self.common
+                .workspace
+                .relative_path(&path)
+                .unwrap_or_else(|| path.clone())
+                .display()
+        );
+        self.main_split.open_preview_diff(path, name, move |text| {
+            replacer.replace(text, &matches)
+        });
+    }
 
-                    (path, match_data)
+    /// Replace the included matches in every file. Open documents get an
+    /// undoable edit, the rest are rewritten by the proxy.
+    pub fn replace_all(&self) {
+        let Some(replacer) = self.replacer() else {
+            return;
+        };
+        let mut files = Vec::new();
+        for (path, data) in self.search_result.get_untracked() {
+            let matches = data.replace_matches();
+            if matches.is_empty() {
+                continue;
+            }
+            let doc = self.main_split.docs.with_untracked(|docs| {
+                docs.get(&DocContent::File {
+                    path:      path.clone(),
+                    read_only: false,
                 })
-                .collect(),
+                .cloned()
+            });
+            match doc.filter(|doc| doc.loaded.get_untracked()) {
+                Some(doc) => {
+                    let matches = matches.into_iter().collect::<HashSet<_>>();
+                    let edits = replacer.edits(&doc.text().to_string(), &matches);
+                    let edits = edits
+                        .iter()
+                        .map(|(range, new)| {
+                            (Selection::region(range.start, range.end), new.as_str())
+                        })
+                        .collect::<Vec<_>>();
+                    if !edits.is_empty() {
+                        take_snapshot(
+                            &self.common,
+                            path,
+                            doc.text(),
+                            SnapshotReason::Replace,
+                        );
+                        doc.do_raw_edit(&edits, EditType::Other);
+                    }
+                },
+                None => files.push(FileReplace { path, matches }),
+            }
+        }
+        self.remove_replaced();
+
+        if files.is_empty() {
+            return;
+        }
+        let (pattern, case_sensitive, whole_word, is_regex, replacement) = (
+            self.search_str.get_untracked(),
+            self.common.find.case_sensitive(false),
+            self.common.find.whole_words.get_untracked(),
+            self.common.find.is_regex.get_untracked(),
+            self.replace_str.get_untracked(),
+        );
+        let save_mode = self
+            .common
+            .config
+            .with_untracked(|config| config.core.save_mode);
+        let paths = files.iter().map(|file| file.path.clone()).collect();
+        // the files are rewritten once their snapshots are stored
+        let common = self.common.clone();
+        let send = create_ext_action(
+            self.common.scope,
+            move |contents: Vec<(PathBuf, String)>| {
+                let snapshots = contents
+                    .into_iter()
+                    .map(|(path, content)| (path, Rope::from(content)))
+                    .collect();
+                let proxy_rpc = common.proxy.proxy_rpc.clone();
+                take_snapshots(
+                    &common,
+                    snapshots,
+                    SnapshotReason::Replace,
+                    move || {
+                        proxy_rpc.replace_in_files(
+                            pattern,
+                            case_sensitive,
+                            whole_word,
+                            is_regex,
+                            replacement,
+                            files,
+                            save_mode,
+                            log_replace_errors,
+                        );
+                    },
+                );
+            },
+        );
+        self.common.proxy.proxy_rpc.read_files(
+            paths,
+            move |(_, result)| match result {
+                Ok(ProxyResponse::ReadFilesResponse { files }) => send(files),
+                Ok(_) => {},
+                Err(err) => error!("replace in files failed: {}", err.message),
+            },
         );
     }
 
+    /// Drop the matches a replace has changed from the result, keeping the
+    /// ones that were left out.
+    fn remove_replaced(&self) {
+        self.search_result.update(|result| {
+            result.retain(|_, data| {
+                if data.included.get_untracked() {
+                    let excluded = data.excluded.get_untracked();
+                    data.matches.update(|m| {
+                        m.retain(|m| excluded.contains(&(m.line, m.start)))
+                    });
+                }
+                data.matches.with_untracked(|m| !m.is_empty())
+            });
+        });
+    }
+
     pub fn set_pattern(&self, pattern: String) {
         self.search_str.set(pattern);
     }
 }
+
+fn log_replace_errors((_, result): (u64, Result<ProxyResponse, RpcError>)) {
+    match result {
+        Ok(ProxyResponse::ReplaceInFilesResponse { errors, .. }) => {
+            if !errors.is_empty() {
+                error!("replace in files failed: {}", errors.join("\n"));
+            }
+        },
+        Ok(_) => {},
+        Err(err) => error!("replace in files failed: {}", err.message),
+    }
+}
+
+fn split_globs(globs: &str) -> Vec<String> {
+    globs
+        .split(',')
+        .map(|glob| glob.trim())
+        .filter(|glob| !glob.is_empty())
+        .map(|glob| glob.to_string())
+        .collect()
+}
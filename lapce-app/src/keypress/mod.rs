@@ -264,6 +264,18 @@ impl KeyPressData {
     ) -> CommandExecuted {
         if let Some(cmd) = self.commands.get(command) {
             focus.run_command(cmd, count, mods)
+        } else if let Some((cmd, argument)) = command
+            .split_once(':')
+            .and_then(|(name, argument)| Some((self.commands.get(name)?, argument)))
+        {
+            // a keymap passes an argument to its command after a colon, so
+            // `insert_snippet:log` runs `insert_snippet` with the argument
+            // `log`, as documented in docs/USAGE.md
+            let cmd = LapceCommand {
+                kind: cmd.kind.clone(),
+                data: Some(serde_json::Value::String(argument.to_string())),
+            };
+            focus.run_command(&cmd, count, mods)
         } else {
             CommandExecuted::No
         }
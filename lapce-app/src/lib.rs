@@ -24,6 +24,7 @@ pub mod inline_completion;
 pub mod keymap;
 pub mod keypress;
 pub mod listener;
+pub mod local_history;
 mod local_task;
 mod log;
 pub mod lsp;
@@ -34,11 +35,13 @@ pub mod panel;
 pub mod plugin;
 pub mod proxy;
 pub mod rename;
+pub mod rust_build;
 pub mod settings;
 pub mod snippet;
 pub mod source_control;
 pub mod status;
 pub mod terminal;
+pub mod test_explorer;
 pub mod text_area;
 pub mod title;
 pub mod update;
@@ -0,0 +1,579 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use anyhow::{Result, anyhow};
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{RwSignal, Scope, SignalGet, SignalUpdate, SignalWith},
+};
+use lapce_core::doc::DocContent;
+use lapce_xi_rope::Rope;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    command::InternalCommand,
+    local_task::{LocalNotification, LocalRequest, LocalResponse},
+    main_split::MainSplitData,
+    window_workspace::CommonData,
+};
+
+/// The directory of the local data directory the snapshots are kept in
+const LOCAL_HISTORY_DIRECTORY: &str = "local-history";
+/// The file next to the snapshots of a file with the path of the file
+const PATH_FILE: &str = "path";
+const SNAPSHOT_EXTENSION: &str = "snapshot";
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+/// What a snapshot of the local history was taken for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SnapshotReason {
+    /// The content as it was saved
+    Save,
+    /// The content before the file changed on disk
+    ExternalChange,
+    /// The content before a replace in the workspace
+    Replace,
+}
+
+impl SnapshotReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotReason::Save => "save",
+            SnapshotReason::ExternalChange => "external-change",
+            SnapshotReason::Replace => "replace",
+        }
+    }
+
+    fn from_str(reason: &str) -> Option<Self> {
+        Some(match reason {
+            "save" => SnapshotReason::Save,
+            "external-change" => SnapshotReason::ExternalChange,
+            "replace" => SnapshotReason::Replace,
+            _ => return None,
+        })
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SnapshotReason::Save => "Saved",
+            SnapshotReason::ExternalChange => "Before Change on Disk",
+            SnapshotReason::Replace => "Before Replace",
+        }
+    }
+}
+
+/// A snapshot of a file in the local history.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LocalHistoryEntry {
+    /// Where the snapshot is kept, named `<time>-<reason>.snapshot`
+    pub snapshot: PathBuf,
+    /// Milliseconds since the Unix epoch
+    pub time:     i64,
+    pub reason:   SnapshotReason,
+}
+
+impl LocalHistoryEntry {
+    fn from_snapshot(snapshot: PathBuf) -> Option<Self> {
+        if snapshot.extension()? != SNAPSHOT_EXTENSION {
+            return None;
+        }
+        let (time, reason) = snapshot.file_stem()?.to_str()?.split_once('-')?;
+        Some(Self {
+            time: time.parse().ok()?,
+            reason: SnapshotReason::from_str(reason)?,
+            snapshot,
+        })
+    }
+
+    /// The local date and time the snapshot was taken
+    pub fn date(&self) -> String {
+        chrono::DateTime::from_timestamp_millis(self.time)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// How many snapshots of a file are kept, from `local-history-max-entries`
+/// and `local-history-max-days` of the core settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalHistoryRetention {
+    /// No snapshots are taken at 0
+    pub max_entries: usize,
+    /// Snapshots are kept forever at 0
+    pub max_days:    usize,
+}
+
+impl LocalHistoryRetention {
+    fn from_common(common: &CommonData) -> Self {
+        common.config.with_untracked(|config| Self {
+            max_entries: config.core.local_history_max_entries,
+            max_days:    config.core.local_history_max_days,
+        })
+    }
+
+    /// Whether the snapshot at `index` of the snapshots of a file, newest
+    /// first, is kept.
+    fn keeps(&self, index: usize, entry: &LocalHistoryEntry, now: i64) -> bool {
+        index < self.max_entries
+            && (self.max_days == 0
+                || now - entry.time <= self.max_days as i64 * DAY_MILLIS)
+    }
+}
+
+/// The snapshots of the files of the user, kept in the local data directory
+/// rather than in git so that any workspace has them. The snapshots of a file
+/// are in a directory named by the hash of its path.
+#[derive(Clone, Debug)]
+pub struct LocalHistoryStore {
+    directory: PathBuf,
+    /// Held while the snapshots in a directory are added or pruned, so that
+    /// the snapshots of a file are taken one after the other
+    locks:     Arc<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>>,
+}
+
+impl LocalHistoryStore {
+    pub fn new(data_local_directory: &Path) -> Self {
+        Self {
+            directory: data_local_directory.join(LOCAL_HISTORY_DIRECTORY),
+            locks:     Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn lock(&self, directory: &Path) -> Arc<Mutex<()>> {
+        self.locks
+            .lock()
+            .entry(directory.to_path_buf())
+            .or_default()
+            .clone()
+    }
+
+    fn file_directory(&self, path: &Path) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(path.to_string_lossy().as_bytes());
+        self.directory.join(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Adds a snapshot of the content of the file at `path`, unless the
+    /// latest one has the same content, and drops the snapshots that are no
+    /// longer kept. Returns whether a snapshot was added.
+    pub fn snapshot(
+        &self,
+        path: &Path,
+        content: &str,
+        reason: SnapshotReason,
+        now: i64,
+        retention: LocalHistoryRetention,
+    ) -> Result<bool> {
+        if retention.max_entries == 0 {
+            return Ok(false);
+        }
+        let directory = self.file_directory(path);
+        let lock = self.lock(&directory);
+        let _guard = lock.lock();
+        let entries = entries_in(&directory)?;
+        let unchanged = match entries.first() {
+            Some(latest) => fs::read(&latest.snapshot)? == content.as_bytes(),
+            None => false,
+        };
+        if !unchanged {
+            fs::create_dir_all(&directory)?;
+            fs::write(directory.join(PATH_FILE), path.to_string_lossy().as_bytes())?;
+            let name = format!("{now}-{}.{SNAPSHOT_EXTENSION}", reason.as_str());
+            fs::write(directory.join(name), content)?;
+        }
+        prune_directory(&directory, retention, now)?;
+        Ok(!unchanged)
+    }
+
+    /// The snapshots of the file at `path`, newest first.
+    pub fn entries(&self, path: &Path) -> Result<Vec<LocalHistoryEntry>> {
+        entries_in(&self.file_directory(path))
+    }
+
+    /// Drops the snapshots of every file that are no longer kept, for the
+    /// files that haven't been saved since the retention got shorter.
+    pub fn prune(&self, retention: LocalHistoryRetention, now: i64) -> Result<()> {
+        // turning the local history off keeps what it has
+        if retention.max_entries == 0 || !self.directory.exists() {
+            return Ok(());
+        }
+        for directory in fs::read_dir(&self.directory)? {
+            let directory = directory?.path();
+            let lock = self.lock(&directory);
+            let _guard = lock.lock();
+            // a snapshot may have pruned the directory away meanwhile
+            if directory.is_dir() {
+                prune_directory(&directory, retention, now)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn entries_in(directory: &Path) -> Result<Vec<LocalHistoryEntry>> {
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for file in fs::read_dir(directory)? {
+        if let Some(entry) = LocalHistoryEntry::from_snapshot(file?.path()) {
+            entries.push(entry);
+        }
+    }
+    entries.sort_by(|a, b| b.time.cmp(&a.time));
+    Ok(entries)
+}
+
+/// Removes the snapshots the retention doesn't keep, and the directory once
+/// it has none left.
+fn prune_directory(
+    directory: &Path,
+    retention: LocalHistoryRetention,
+    now: i64,
+) -> Result<()> {
+    let entries = entries_in(directory)?;
+    let mut kept = 0;
+    for (index, entry) in entries.iter().enumerate() {
+        if retention.keeps(index, entry, now) {
+            kept += 1;
+        } else {
+            fs::remove_file(&entry.snapshot)?;
+        }
+    }
+    if kept == 0 {
+        fs::remove_dir_all(directory)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn local_history_store(
+    store: Option<LocalHistoryStore>,
+) -> Result<LocalHistoryStore> {
+    store.ok_or_else(|| anyhow!("there is no local data directory"))
+}
+
+/// Takes a snapshot of the content of the file at `path` on the thread of the
+/// local tasks.
+pub fn take_snapshot(
+    common: &CommonData,
+    path: PathBuf,
+    content: Rope,
+    reason: SnapshotReason,
+) {
+    take_snapshots(common, vec![(path, content)], reason, || {});
+}
+
+/// Takes a snapshot of each file on the thread of the local tasks, and calls
+/// `then` once all of them are stored, whether they could be or not.
+pub fn take_snapshots(
+    common: &CommonData,
+    snapshots: Vec<(PathBuf, Rope)>,
+    reason: SnapshotReason,
+    then: impl FnOnce() + 'static,
+) {
+    if snapshots.is_empty() {
+        then();
+        return;
+    }
+    let remaining = Rc::new(Cell::new(snapshots.len()));
+    let then = Rc::new(RefCell::new(Some(then)));
+    let updated = common.local_history_updated;
+    let retention = LocalHistoryRetention::from_common(common);
+    for (path, content) in snapshots {
+        let remaining = remaining.clone();
+        let then = then.clone();
+        let send = create_ext_action(common.scope, move |added: Option<PathBuf>| {
+            if added.is_some() {
+                updated.set(added);
+            }
+            remaining.set(remaining.get() - 1);
+            if remaining.get() > 0 {
+                return;
+            }
+            if let Some(then) = then.borrow_mut().take() {
+                then();
+            }
+        });
+        common.local_task.request_async(
+            LocalRequest::LocalHistorySnapshot {
+                path: path.clone(),
+                content,
+                reason,
+                retention,
+            },
+            move |(_, result)| match result {
+                Ok(LocalResponse::LocalHistorySnapshot { added: true }) => {
+                    send(Some(path))
+                },
+                Ok(_) => send(None),
+                Err(err) => {
+                    log::error!("failed to take a snapshot: {err:?}");
+                    send(None)
+                },
+            },
+        );
+    }
+}
+
+/// The snapshots of the file of the active editor, for the Local History
+/// panel.
+#[derive(Clone)]
+pub struct LocalHistoryData {
+    /// The file whose snapshots are listed, the last one an editor was active
+    /// for
+    pub path:       RwSignal<Option<PathBuf>>,
+    /// Newest first
+    pub entries:    RwSignal<im::Vector<LocalHistoryEntry>>,
+    pub main_split: MainSplitData,
+    pub common:     Rc<CommonData>,
+}
+
+impl LocalHistoryData {
+    pub fn new(cx: Scope, main_split: MainSplitData) -> Self {
+        let common = main_split.common.clone();
+        common
+            .local_task
+            .notification(LocalNotification::PruneLocalHistory {
+                retention: LocalHistoryRetention::from_common(&common),
+            });
+        let data = Self {
+            path: cx.create_rw_signal(None),
+            entries: cx.create_rw_signal(im::Vector::new()),
+            main_split,
+            common,
+        };
+
+        {
+            let data = data.clone();
+            let active_editor = data.main_split.active_editor;
+            cx.create_effect(move |_| {
+                let path = active_editor.get().and_then(|editor| {
+                    match editor.doc().content.get() {
+                        DocContent::File { path, .. } => Some(path),
+                        _ => None,
+                    }
+                });
+                // a diff of a snapshot keeps the file it was opened for
+                if path.is_some() && path != data.path.get_untracked() {
+                    data.path.set(path);
+                    data.load();
+                }
+            });
+        }
+        {
+            let data = data.clone();
+            let updated = data.common.local_history_updated;
+            cx.create_effect(move |_| {
+                let updated = updated.get();
+                if updated.is_some() && updated == data.path.get_untracked() {
+                    data.load();
+                }
+            });
+        }
+
+        data
+    }
+
+    /// Lists the snapshots of the file again.
+    pub fn load(&self) {
+        let Some(path) = self.path.get_untracked() else {
+            return;
+        };
+        let path_signal = self.path;
+        let entries = self.entries;
+        let send = create_ext_action(
+            self.common.scope,
+            move |(path, result): (PathBuf, Vec<LocalHistoryEntry>)| {
+                if path_signal.with_untracked(|x| x.as_ref() == Some(&path)) {
+                    entries.set(result.into());
+                }
+            },
+        );
+        self.common.local_task.request_async(
+            LocalRequest::LocalHistoryEntries { path: path.clone() },
+            move |(_, result)| match result {
+                Ok(LocalResponse::LocalHistoryEntries { entries }) => {
+                    send((path, entries))
+                },
+                Ok(_) => {},
+                Err(err) => log::error!("failed to list the snapshots: {err:?}"),
+            },
+        );
+    }
+
+    fn read(&self, entry: &LocalHistoryEntry, f: impl FnOnce(String) + 'static) {
+        let internal_command = self.common.internal_command;
+        let send = create_ext_action(
+            self.common.scope,
+            move |result: Result<String, String>| match result {
+                Ok(content) => f(content),
+                Err(message) => internal_command
+                    .send(InternalCommand::ShowStatusMessage { message }),
+            },
+        );
+        self.common.local_task.request_async(
+            LocalRequest::LocalHistoryRead {
+                snapshot: entry.snapshot.clone(),
+            },
+            move |(_, result)| match result {
+                Ok(LocalResponse::LocalHistoryRead { content }) => send(Ok(content)),
+                Ok(_) => {},
+                Err(err) => send(Err(format!("Failed to read the snapshot: {err}"))),
+            },
+        );
+    }
+
+    /// Opens the snapshot in a diff editor against the file.
+    pub fn compare(&self, entry: &LocalHistoryEntry) {
+        let Some(path) = self.path.get_untracked() else {
+            return;
+        };
+        let version = entry.date();
+        let main_split = self.main_split.clone();
+        self.read(entry, move |content| {
+            main_split.open_local_history_diff(path, version, content);
+        });
+    }
+
+    /// Replaces the content of the file with the snapshot, as an edit that
+    /// can be undone.
+    pub fn restore(&self, entry: &LocalHistoryEntry) {
+        let Some(path) = self.path.get_untracked() else {
+            return;
+        };
+        let main_split = self.main_split.clone();
+        self.read(entry, move |content| {
+            main_split.restore_local_history(path, content);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retention(max_entries: usize, max_days: usize) -> LocalHistoryRetention {
+        LocalHistoryRetention {
+            max_entries,
+            max_days,
+        }
+    }
+
+    fn reasons(store: &LocalHistoryStore, path: &Path) -> Vec<SnapshotReason> {
+        store
+            .entries(path)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.reason)
+            .collect()
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalHistoryStore::new(dir.path());
+        let path = Path::new("/workspace/src/main.rs");
+        let keep = retention(3, 0);
+
+        assert!(
+            store
+                .snapshot(path, "a", SnapshotReason::Save, 1, keep)
+                .unwrap()
+        );
+        // the same content again is not worth a snapshot
+        assert!(
+            !store
+                .snapshot(path, "a", SnapshotReason::Save, 2, keep)
+                .unwrap()
+        );
+        for (time, content) in [(3, "b"), (4, "c"), (5, "d")] {
+            store
+                .snapshot(path, content, SnapshotReason::Replace, time, keep)
+                .unwrap();
+        }
+        let entries = store.entries(path).unwrap();
+        assert_eq!(
+            entries.iter().map(|entry| entry.time).collect::<Vec<_>>(),
+            vec![5, 4, 3]
+        );
+        assert_eq!(fs::read_to_string(&entries[0].snapshot).unwrap(), "d");
+        assert!(
+            store
+                .entries(Path::new("/workspace/other.rs"))
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(
+            !store
+                .snapshot(path, "e", SnapshotReason::Save, 6, retention(0, 0))
+                .unwrap()
+        );
+        assert_eq!(reasons(&store, path), vec![SnapshotReason::Replace; 3]);
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalHistoryStore::new(dir.path());
+        let old = Path::new("/workspace/old.rs");
+        let new = Path::new("/workspace/new.rs");
+        let keep = retention(10, 1);
+
+        store
+            .snapshot(old, "a", SnapshotReason::ExternalChange, 0, keep)
+            .unwrap();
+        store
+            .snapshot(new, "a", SnapshotReason::Save, DAY_MILLIS, keep)
+            .unwrap();
+        store
+            .snapshot(new, "b", SnapshotReason::Save, 2 * DAY_MILLIS, keep)
+            .unwrap();
+        // a snapshot only prunes the snapshots of its own file
+        assert_eq!(store.entries(old).unwrap().len(), 1);
+        assert_eq!(store.entries(new).unwrap().len(), 2);
+
+        store.prune(keep, 3 * DAY_MILLIS).unwrap();
+        assert!(store.entries(old).unwrap().is_empty());
+        assert_eq!(reasons(&store, new), vec![SnapshotReason::Save]);
+        let directories = fs::read_dir(dir.path().join(LOCAL_HISTORY_DIRECTORY));
+        assert_eq!(directories.unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalHistoryStore::new(dir.path());
+        let path = Path::new("/workspace/src/main.rs");
+        let keep = retention(2, 0);
+
+        let threads = (0..16)
+            .map(|time| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    let content = time.to_string();
+                    store.snapshot(path, &content, SnapshotReason::Save, time, keep)
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            assert!(thread.join().unwrap().is_ok());
+        }
+        let entries = store.entries(path).unwrap();
+        assert_eq!(
+            entries.iter().map(|entry| entry.time).collect::<Vec<_>>(),
+            vec![15, 14]
+        );
+    }
+}
@@ -21,6 +21,7 @@ use crate::{
     config::{LapceConfig, color::LapceColor},
     db::{LapceDb, SaveEvent},
     find::{Find, FindSearchString},
+    local_history::{LocalHistoryStore, local_history_store},
     local_task::{
         LocalNotification, LocalRequest, LocalResponse, LocalResponseHandler,
         LocalRpc,
@@ -35,6 +36,8 @@ pub struct LocalTaskHandler {
     pub config:         LapceConfig,
     pub(crate) pending: Arc<Mutex<HashMap<u64, LocalResponseHandler>>>,
     pub db:             Arc<LapceDb>,
+    /// Shared by the tasks so that the snapshots of a file don't race
+    pub local_history:  Option<LocalHistoryStore>,
 }
 
 impl LocalTaskHandler {
@@ -54,6 +57,17 @@ impl LocalTaskHandler {
                             handle_notification_db_save_event(db, event).await;
                         });
                     },
+                    LocalNotification::PruneLocalHistory { retention } => {
+                        let store = self.local_history.clone();
+                        tokio::spawn(async move {
+                            let now = chrono::Utc::now().timestamp_millis();
+                            if let Err(err) = local_history_store(store)
+                                .and_then(|store| store.prune(retention, now))
+                            {
+                                log::error!("{:?}", err);
+                            }
+                        });
+                    },
                 }, /* Shutdown => {
                     *     return;
                     * }, */
@@ -236,6 +250,52 @@ impl LocalTaskHandler {
                     );
                 });
             },
+            LocalRequest::LocalHistorySnapshot {
+                path,
+                content,
+                reason,
+                retention,
+            } => {
+                let pending = self.pending.clone();
+                let store = self.local_history.clone();
+                tokio::spawn(async move {
+                    let now = chrono::Utc::now().timestamp_millis();
+                    let rs = local_history_store(store)
+                        .and_then(|store| {
+                            store.snapshot(
+                                &path,
+                                &content.to_string(),
+                                reason,
+                                now,
+                                retention,
+                            )
+                        })
+                        .map(|added| LocalResponse::LocalHistorySnapshot { added });
+                    handle_response(id, rs, pending);
+                });
+            },
+            LocalRequest::LocalHistoryEntries { path } => {
+                let pending = self.pending.clone();
+                let store = self.local_history.clone();
+                tokio::spawn(async move {
+                    let rs = local_history_store(store)
+                        .and_then(|store| store.entries(&path))
+                        .map(|entries| LocalResponse::LocalHistoryEntries {
+                            entries,
+                        });
+                    handle_response(id, rs, pending);
+                });
+            },
+            LocalRequest::LocalHistoryRead { snapshot } => {
+                let pending = self.pending.clone();
+                tokio::spawn(async move {
+                    let rs = tokio::fs::read_to_string(snapshot)
+                        .await
+                        .map(|content| LocalResponse::LocalHistoryRead { content })
+                        .map_err(anyhow::Error::from);
+                    handle_response(id, rs, pending);
+                });
+            },
         }
     }
 }
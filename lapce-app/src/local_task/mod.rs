@@ -29,6 +29,9 @@ use crate::{
     config::LapceConfig,
     db::{LapceDb, SaveEvent},
     find::FindSearchString,
+    local_history::{
+        LocalHistoryEntry, LocalHistoryRetention, LocalHistoryStore, SnapshotReason,
+    },
     local_task::handler::LocalTaskHandler,
     markdown::MarkdownContent,
     plugin::{VoltIcon, VoltsInfo},
@@ -115,6 +118,18 @@ pub enum LocalRequest {
         edits:  Option<SmallVec<[SyntaxEdit; 3]>>,
         syntax: Syntax,
     },
+    LocalHistorySnapshot {
+        path:      PathBuf,
+        content:   Rope,
+        reason:    SnapshotReason,
+        retention: LocalHistoryRetention,
+    },
+    LocalHistoryEntries {
+        path: PathBuf,
+    },
+    LocalHistoryRead {
+        snapshot: PathBuf,
+    },
 }
 #[allow(clippy::large_enum_variant)]
 pub enum LocalResponse {
@@ -155,10 +170,20 @@ pub enum LocalResponse {
     FindText {
         selection: Selection,
     },
+    LocalHistorySnapshot {
+        added: bool,
+    },
+    LocalHistoryEntries {
+        entries: Vec<LocalHistoryEntry>,
+    },
+    LocalHistoryRead {
+        content: String,
+    },
 }
 
 pub enum LocalNotification {
     DbSaveEvent(SaveEvent),
+    PruneLocalHistory { retention: LocalHistoryRetention },
 }
 
 pub fn new_local_handler(
@@ -169,11 +194,16 @@ pub fn new_local_handler(
     let (tx, rx) = crossbeam_channel::unbounded();
     let pending = Arc::new(Mutex::new(HashMap::new()));
     let requester = LocalTaskRequester::new(tx, pending.clone());
+    let local_history = directory
+        .data_local_directory
+        .as_deref()
+        .map(LocalHistoryStore::new);
     let _handler = LocalTaskHandler {
         config,
         pending,
         directory,
         db,
+        local_history,
     };
     start_proxy(_handler, rx)?;
     Ok(requester)
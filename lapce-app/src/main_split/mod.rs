@@ -4,17 +4,22 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     rc::Rc,
+    time::Duration,
 };
 
 use anyhow::Result;
 use doc::{
     EditorViewKind,
     diagnostic::DiagnosticData,
-    lines::{EditBuffer, RopeTextPosition, command::FocusCommand, cursor::Cursor},
+    lines::{
+        EditBuffer, RopeTextPosition, command::FocusCommand, cursor::Cursor,
+        edit::EditType, selection::Selection,
+    },
     syntax::Syntax,
 };
 pub use editors::*;
 use floem::{
+    action::exec_after,
     ext_event::create_ext_action,
     file::{FileDialogOptions, FileInfo},
     file_action::save_as,
@@ -34,19 +39,21 @@ use lapce_core::{
     },
 };
 use lapce_rpc::{
+    RpcError,
     buffer::BufferId,
     core::FileChanged,
     dap_types::RunDebugConfig,
     plugin::{PluginId, VoltID},
     proxy::ProxyResponse,
+    source_control::FileDiff,
 };
 use lapce_xi_rope::Rope;
 use log::{error, warn};
 use lsp_types::{
-    CodeAction, CodeActionOrCommand, DiagnosticSeverity, DocumentChangeOperation,
-    DocumentChanges, OneOf, Position, TextEdit, Url, WorkspaceEdit,
+    CodeAction, CodeActionOrCommand, Command, Diagnostic, DiagnosticSeverity,
+    DocumentChangeOperation, DocumentChanges, OneOf, Position, TextEdit, Url,
+    WorkspaceEdit,
 };
-use serde_json::Value;
 
 use crate::{
     alert::AlertButton,
@@ -65,8 +72,9 @@ use crate::{
     },
     keypress::{EventRef, KeyPressData, KeyPressHandle},
     panel::{
-        call_hierarchy_view::CallHierarchyData, implementation_view::ReferencesRoot,
+        call_hierarchy_view::HierarchyData, implementation_view::ReferencesRoot,
     },
+    rust_build::is_build_diagnostic,
     window_workspace::{CommonData, Focus, WindowWorkspaceData},
 };
 
@@ -156,6 +164,7 @@ pub struct MainSplitData {
     pub diff_editors:      RwSignal<im::HashMap<DiffEditorId, DiffEditorData>>,
     pub docs:              RwSignal<im::HashMap<DocContent, Rc<Doc>>>,
     pub scratch_docs:      RwSignal<im::HashMap<String, Rc<Doc>>>,
+    pub virtual_docs:      RwSignal<im::HashMap<String, Rc<Doc>>>,
     pub diagnostics:       RwSignal<im::HashMap<PathBuf, DiagnosticData>>,
     pub references:        Tabs<ReferencesRoot>,
     pub implementations:   Tabs<ReferencesRoot>,
@@ -168,7 +177,8 @@ pub struct MainSplitData {
     pub width:             RwSignal<f64>,
     pub code_lens:         RwSignal<CodeLensData>,
     pub common:            Rc<CommonData>,
-    pub hierarchy:         Tabs<CallHierarchyData>,
+    pub hierarchy:         Tabs<HierarchyData>,
+    pub type_hierarchy:    Tabs<HierarchyData>,
 }
 
 impl std::fmt::Debug for MainSplitData {
@@ -196,6 +206,7 @@ impl MainSplitData {
         let references = Tabs::new(common.config, cx);
         let implementations = Tabs::new(common.config, cx);
         let hierarchy = Tabs::new(common.config, cx);
+        let type_hierarchy = Tabs::new(common.config, cx);
         let current_location = cx.create_rw_signal(0);
         let diagnostics = cx.create_rw_signal(im::HashMap::new());
         let find_str = cx.create_rw_signal(String::new());
@@ -249,6 +260,7 @@ impl MainSplitData {
             diff_editors,
             docs,
             scratch_docs,
+            virtual_docs: cx.create_rw_signal(im::HashMap::new()),
             active_editor,
             diagnostics,
             locations,
@@ -259,6 +271,7 @@ impl MainSplitData {
             references,
             implementations,
             hierarchy,
+            type_hierarchy,
         }
     }
 
@@ -430,9 +443,13 @@ impl MainSplitData {
             let doc = doc.clone();
             let local_doc = doc.clone();
             let send = create_ext_action(cx, move |result| {
-                if let Ok(ProxyResponse::NewBufferResponse { content, read_only }) =
-                    result
+                if let Ok(ProxyResponse::NewBufferResponse {
+                    content,
+                    read_only,
+                    encoding,
+                }) = result
                 {
+                    local_doc.encoding.set(encoding);
                     local_doc.init_content(Rope::from(content));
                     if read_only {
                         local_doc.content.update(|content| {
@@ -509,42 +526,62 @@ impl MainSplitData {
         Ok(())
     }
 
-    pub fn open_file_changes(&self, path: PathBuf) {
-        let (right, _) = self.get_doc(
-            path.clone(),
-            None,
+    /// Opens the staged changes of `path` (HEAD against the index) or its
+    /// unstaged ones (the index against the working copy).
+    pub fn open_file_changes(&self, path: PathBuf, staged: bool) {
+        let history = |version: &str| {
+            let doc = Rc::new(Doc::new_history(
+                self.scope,
+                DocContent::History(DocHistory {
+                    path:    path.clone(),
+                    version: version.to_string(),
+                }),
+                self.common.clone(),
+            ));
+            load_history_doc(self.scope, &self.common, doc.clone());
+            doc
+        };
+        let (left, right) = if staged {
+            (history("head"), history("index"))
+        } else {
+            let (right, _) = self.get_doc(
+                path.clone(),
+                None,
+                false,
+                DocContent::File {
+                    path:      path.clone(),
+                    read_only: false,
+                },
+            );
+            (history("index"), right)
+        };
+
+        self.get_editor_tab_child(
+            EditorTabChildSource::DiffEditor { left, right },
+            false,
             false,
-            DocContent::File {
-                path:      path.clone(),
-                read_only: false,
-            },
-        );
-        let left = Doc::new_history(
-            self.scope,
-            DocContent::History(DocHistory {
-                path:    path.clone(),
-                version: "head".to_string(),
-            }),
-            self.common.clone(),
         );
-        let left = Rc::new(left);
+    }
 
-        let send = {
-            let left = left.clone();
-            create_ext_action(self.scope, move |result| {
-                if let Ok(ProxyResponse::BufferHeadResponse { content, .. }) = result
-                {
-                    left.init_content(Rope::from(content));
-                }
-            })
+    /// Opens the changes a commit made to a file, against its first parent.
+    pub fn open_commit_file_changes(&self, commit: String, diff: FileDiff) {
+        let (path, old_path) = match diff {
+            FileDiff::Renamed(path, old_path) => (path, old_path),
+            FileDiff::Modified(path)
+            | FileDiff::Added(path)
+            | FileDiff::Deleted(path) => (path.clone(), path),
         };
-        self.common
-            .proxy
-            .proxy_rpc
-            .get_buffer_head(path, move |(_, result)| {
-                send(result);
-            });
-
+        let history = |path: PathBuf, version: String| {
+            let doc = Rc::new(Doc::new_history(
+                self.scope,
+                DocContent::History(DocHistory { path, version }),
+                self.common.clone(),
+            ));
+            load_history_doc(self.scope, &self.common, doc.clone());
+            doc
+        };
+        let left = history(old_path, format!("{commit}^"));
+        let right = history(path, commit);
         self.get_editor_tab_child(
             EditorTabChildSource::DiffEditor { left, right },
             false,
@@ -552,6 +589,27 @@ impl MainSplitData {
         );
     }
 
+    /// Reloads the git versions shown in diff editors, after the index or
+    /// HEAD changed.
+    pub fn reload_history_docs(&self) {
+        let diff_editors = self.diff_editors.get_untracked();
+        for diff_editor in diff_editors.values() {
+            for editor in [&diff_editor.left, &diff_editor.right] {
+                let doc = editor.doc();
+                // Commits don't change, only the index and HEAD do
+                let reload = match doc.content.get_untracked() {
+                    DocContent::History(history) => {
+                        history.version == "index" || history.version == "head"
+                    },
+                    _ => false,
+                };
+                if reload {
+                    load_history_doc(self.scope, &self.common, doc);
+                }
+            }
+        }
+    }
+
     pub fn open_diff_files(&self, left_path: PathBuf, right_path: PathBuf) {
         let [left, right] = [left_path, right_path].map(|path| {
             self.get_doc(
@@ -573,6 +631,292 @@ impl MainSplitData {
         );
     }
 
+    /// Show `content` in the read-only document `name`, reusing its editor if
+    /// it is already open. Without `reveal` the document is only updated if
+    /// it is open.
+    pub fn open_virtual_doc(
+        &self,
+        name: String,
+        content: String,
+        line: Option<usize>,
+        reveal: bool,
+    ) {
+        let exists = self
+            .virtual_docs
+            .with_untracked(|virtual_docs| virtual_docs.contains_key(&name));
+        if !reveal && !exists {
+            return;
+        }
+        let doc = self.get_virtual_doc(&name);
+        doc.reload(Rope::from(content), true);
+        if !reveal {
+            return;
+        }
+
+        let child = self.get_editor_tab_child(
+            EditorTabChildSource::Editor {
+                path: PathBuf::from(name),
+                doc,
+            },
+            false,
+            false,
+        );
+        if let (EditorTabChildId::Editor(editor_id), Some(line)) = (child, line) {
+            if let Some(editor) = self.editors.editor_untracked(editor_id) {
+                editor.go_to_position(EditorPosition::Line(line), None, None);
+            }
+        }
+    }
+
+    /// Show the file at `path` side by side with what `preview` turns its
+    /// content into.
+    pub fn open_preview_diff(
+        &self,
+        path: PathBuf,
+        name: String,
+        preview: impl Fn(&str) -> String + 'static,
+    ) {
+        let (left, _) = self.get_doc(
+            path.clone(),
+            None,
+            false,
+            DocContent::File {
+                path,
+                read_only: false,
+            },
+        );
+        let right = self.get_virtual_doc(&name);
+        {
+            let left = left.clone();
+            let right = right.clone();
+            let cx = self.scope.create_child();
+            cx.create_effect(move |_| {
+                if left.loaded.get() {
+                    let content = preview(&left.text().to_string());
+                    right.reload(Rope::from(content), true);
+                    // the preview is made once, then the effect is let go
+                    exec_after(Duration::ZERO, move |_| cx.dispose());
+                }
+            });
+        }
+
+        self.get_editor_tab_child(
+            EditorTabChildSource::DiffEditor { left, right },
+            false,
+            false,
+        );
+    }
+
+    /// Asks what to do with the unsaved edits of a file changed on disk.
+    fn show_file_changed_alert(&self, path: &Path, doc: Rc<Doc>) {
+        let internal_command = self.common.internal_command;
+        let name = path
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let take_theirs = {
+            let doc = doc.clone();
+            Rc::new(move || {
+                internal_command.send(InternalCommand::HideAlert);
+                if let Some(content) = doc.changed_on_disk.get_untracked() {
+                    doc.reload(content, true);
+                }
+            })
+        };
+        let compare = {
+            let main_split = self.clone();
+            Rc::new(move || {
+                internal_command.send(InternalCommand::HideAlert);
+                main_split.compare_with_disk(doc.clone());
+            })
+        };
+        internal_command.send(InternalCommand::ShowAlert {
+            title:   format!("{name} has changed on disk"),
+            msg:     "It also has unsaved changes in the editor.".to_string(),
+            buttons: vec![
+                AlertButton {
+                    text:   "Keep Mine".to_string(),
+                    action: Rc::new(move || {
+                        internal_command.send(InternalCommand::HideAlert);
+                    }),
+                },
+                AlertButton {
+                    text:   "Take Theirs".to_string(),
+                    action: take_theirs,
+                },
+                AlertButton {
+                    text:   "Compare".to_string(),
+                    action: compare,
+                },
+            ],
+        });
+    }
+
+    /// Shows what the file is on disk side by side with the unsaved edits.
+    /// The disk side is a read-only history doc of the file's own path.
+    pub fn compare_with_disk(&self, doc: Rc<Doc>) {
+        let (DocContent::File { path, .. }, Some(content)) = (
+            doc.content.get_untracked(),
+            doc.changed_on_disk.get_untracked(),
+        ) else {
+            return;
+        };
+        let left = Rc::new(Doc::new_history(
+            self.scope,
+            DocContent::History(DocHistory {
+                path,
+                version: "on disk".to_string(),
+            }),
+            self.common.clone(),
+        ));
+        left.init_content(content.clone());
+        let child = self.get_editor_tab_child(
+            EditorTabChildSource::DiffEditor {
+                left:  left.clone(),
+                right: doc,
+            },
+            false,
+            false,
+        );
+        // a diff editor already comparing the file keeps its own disk side
+        if let EditorTabChildId::DiffEditor(diff_editor_id) = child {
+            let shown = self.diff_editors.with_untracked(|diff_editors| {
+                diff_editors
+                    .get(&diff_editor_id)
+                    .map(|diff_editor| diff_editor.left.doc())
+            });
+            if let Some(shown) = shown.filter(|shown| !Rc::ptr_eq(shown, &left)) {
+                shown.reload(content, true);
+            }
+        }
+        self.common
+            .internal_command
+            .send(InternalCommand::ShowStatusMessage {
+                message: "Run \"Merge Changes on Disk\" to merge both into the \
+                          editor"
+                    .to_string(),
+            });
+    }
+
+    /// Opens a snapshot of the local history in a diff editor against the
+    /// file, as a read-only history doc labelled with `version`.
+    pub fn open_local_history_diff(
+        &self,
+        path: PathBuf,
+        version: String,
+        content: String,
+    ) {
+        let (right, _) = self.get_doc(
+            path.clone(),
+            None,
+            true,
+            DocContent::File {
+                path:      path.clone(),
+                read_only: false,
+            },
+        );
+        let left = Rc::new(Doc::new_history(
+            self.scope,
+            DocContent::History(DocHistory { path, version }),
+            self.common.clone(),
+        ));
+        left.init_content(Rope::from(content));
+        self.get_editor_tab_child(
+            EditorTabChildSource::DiffEditor { left, right },
+            false,
+            false,
+        );
+    }
+
+    /// Replaces the content of the file with a snapshot of the local history
+    /// and shows it. The replace can be undone like an edit.
+    pub fn restore_local_history(&self, path: PathBuf, content: String) {
+        let (doc, _) = self.get_doc(
+            path.clone(),
+            None,
+            true,
+            DocContent::File {
+                path:      path.clone(),
+                read_only: false,
+            },
+        );
+        // a doc that was not open takes the snapshot once the file is loaded
+        let loaded = doc.loaded;
+        self.scope.create_child().create_effect(move |prev_loaded| {
+            if prev_loaded == Some(true) {
+                return true;
+            }
+            let loaded = loaded.get();
+            if loaded {
+                doc.reload(Rope::from(content.as_str()), false);
+            }
+            loaded
+        });
+        if let Err(err) = self.go_to_location(
+            EditorLocation {
+                path,
+                position: None,
+                scroll_offset: None,
+                ignore_unconfirmed: false,
+                same_editor_tab: false,
+            },
+            None,
+        ) {
+            error!("{err:?}");
+        }
+    }
+
+    /// Merges the changes on disk into the file of the active editor, or of
+    /// the right side of the active diff editor.
+    pub fn merge_changes_on_disk(&self) {
+        let Some(editor) = self.active_editor.get_untracked() else {
+            return;
+        };
+        let doc = match editor.diff_editor_id.get_untracked() {
+            Some((_, diff_editor_id)) => {
+                match self.diff_editors.with_untracked(|diff_editors| {
+                    diff_editors.get(&diff_editor_id).map(|x| x.right.doc())
+                }) {
+                    Some(doc) => doc,
+                    None => return,
+                }
+            },
+            None => editor.doc(),
+        };
+        let message = match doc.merge_changes_on_disk() {
+            None => "The file has no changes on disk to merge".to_string(),
+            Some(0) => "Merged the changes on disk".to_string(),
+            Some(conflicts) => {
+                format!("Merged the changes on disk with {conflicts} conflict(s)")
+            },
+        };
+        self.common
+            .internal_command
+            .send(InternalCommand::ShowStatusMessage { message });
+    }
+
+    fn get_virtual_doc(&self, name: &str) -> Rc<Doc> {
+        if let Some(doc) = self
+            .virtual_docs
+            .with_untracked(|virtual_docs| virtual_docs.get(name).cloned())
+        {
+            return doc;
+        }
+        let doc = Rc::new(Doc::new_content(
+            self.scope,
+            DocContent::Virtual {
+                name: name.to_string(),
+            },
+            self.common.clone(),
+            None,
+        ));
+        doc.init_content(Rope::from(""));
+        self.virtual_docs.update(|virtual_docs| {
+            virtual_docs.insert(name.to_string(), doc.clone());
+        });
+        doc
+    }
+
     fn new_editor_tab(
         &self,
         editor_tab_id: EditorTabManageId,
@@ -1176,6 +1520,23 @@ impl MainSplitData {
                         scratch_docs.remove(&name);
                     });
                 }
+            } else if let DocContent::Virtual { name } = content {
+                let doc_exists = self.editors.with_editors_untracked(|editors| {
+                    editors.iter().any(|(_, editor_data)| {
+                        editor_data.doc().content.with_untracked(|content| {
+                            matches!(
+                                content,
+                                DocContent::Virtual { name: current_name }
+                                    if current_name == &name
+                            )
+                        })
+                    })
+                });
+                if !doc_exists {
+                    self.virtual_docs.update(|virtual_docs| {
+                        virtual_docs.remove(&name);
+                    });
+                }
             }
         }
     }
@@ -1758,6 +2119,7 @@ impl MainSplitData {
                             )),
                             DocContent::Local => None,
                             DocContent::History(_) => None,
+                            DocContent::Virtual { .. } => None,
                             DocContent::Scratch { name, .. } => {
                                 Some((name, doc, editor))
                             },
@@ -1976,6 +2338,7 @@ impl MainSplitData {
                     },
                     DocContent::Local => None,
                     DocContent::History(_) => None,
+                    DocContent::Virtual { .. } => None,
                 };
                 if let Some(save_button) = save_button {
                     let main_split = self.clone();
@@ -2079,14 +2442,16 @@ impl MainSplitData {
         log::info!("run_code_action {action:?}");
         match action {
             CodeActionOrCommand::Command(command) => {
-                self.run_code_lens(
-                    &command.command,
-                    command.arguments.unwrap_or_default(),
-                );
+                self.run_code_lens(plugin_id, command);
             },
             CodeActionOrCommand::CodeAction(action) => {
                 if let Some(edit) = action.edit.as_ref() {
-                    self.apply_workspace_edit(edit);
+                    self.apply_code_action_edit(edit, plugin_id, action.command);
+                } else if action.data.is_none() && action.command.is_some() {
+                    // nothing to resolve, the command does all the work
+                    if let Some(command) = action.command {
+                        self.run_code_lens(plugin_id, command);
+                    }
                 } else {
                     self.resolve_code_action(plugin_id, action);
                 }
@@ -2094,15 +2459,39 @@ impl MainSplitData {
         }
     }
 
-    pub fn run_code_lens(&self, command: &str, args: Vec<Value>) {
-        self.code_lens.get_untracked().run(command, args);
+    /// Runs a command of a code action or code lens, in the editor if it is
+    /// one it knows, else by the language server which sends the resulting
+    /// edits back with `workspace/applyEdit`.
+    pub fn run_code_lens(&self, plugin_id: PluginId, command: Command) {
+        let arguments = command.arguments.unwrap_or_default();
+        if self
+            .code_lens
+            .get_untracked()
+            .run(&command.command, &arguments)
+        {
+            return;
+        }
+        self.common.proxy.proxy_rpc.execute_command(
+            plugin_id,
+            command.command,
+            arguments,
+            |(_, result)| {
+                if let Err(err) = result {
+                    log::error!("{err:?}");
+                }
+            },
+        );
     }
 
-    /// Resolve a code action and apply its held workspace edit
+    /// Resolve a code action, apply its held workspace edit and run its command
     fn resolve_code_action(&self, plugin_id: PluginId, action: CodeAction) {
         let main_split = self.clone();
-        let send = create_ext_action(self.scope, move |edit| {
-            main_split.apply_workspace_edit(&edit);
+        let send = create_ext_action(self.scope, move |item: CodeAction| {
+            if let Some(edit) = item.edit.as_ref() {
+                main_split.apply_code_action_edit(edit, plugin_id, item.command);
+            } else if let Some(command) = item.command {
+                main_split.run_code_lens(plugin_id, command);
+            }
         });
         self.common.proxy.proxy_rpc.code_action_resolve(
             action,
@@ -2110,50 +2499,100 @@ impl MainSplitData {
             move |(_, result)| {
                 if let Ok(ProxyResponse::CodeActionResolveResponse { item }) = result
                 {
-                    if let Some(edit) = item.edit {
-                        send(edit);
-                    }
+                    send(*item);
                 }
             },
         );
     }
 
     /// Perform a workspace edit, which are from the LSP (such as code actions,
-    /// or symbol renaming)
-    pub fn apply_workspace_edit(&self, edit: &WorkspaceEdit) {
-        if let Some(DocumentChanges::Operations(_op)) =
+    /// or symbol renaming), on the documents of its files without showing
+    /// them. `then` gets the reason it failed once every file has its edits,
+    /// or none has. An edit that creates, renames or deletes files isn't
+    /// supported, and is rejected as a whole.
+    pub fn apply_workspace_edit(
+        &self,
+        edit: &WorkspaceEdit,
+        then: impl FnOnce(Result<(), String>) + 'static,
+    ) {
+        if let Some(DocumentChanges::Operations(ops)) =
             edit.document_changes.as_ref()
         {
-            // TODO
-        }
-
-        if let Some(edits) = workspace_edits(edit) {
-            for (url, edits) in edits {
-                if let Ok(path) = url.to_file_path() {
-                    let active_path = self
-                        .active_editor
-                        .get_untracked()
-                        .map(|editor| editor.doc())
-                        .map(|doc| doc.content.get_untracked())
-                        .and_then(|content| content.path().cloned());
-                    let position = if active_path.as_ref() == Some(&path) {
-                        None
-                    } else {
-                        edits
-                            .first()
-                            .map(|edit| EditorPosition::Position(edit.range.start))
-                    };
-                    let location = EditorLocation {
+            if ops
+                .iter()
+                .any(|op| matches!(op, DocumentChangeOperation::Op(_)))
+            {
+                then(Err("creating, renaming or deleting files isn't supported"
+                    .to_string()));
+                return;
+            }
+        }
+
+        let Some(edits) = workspace_edits(edit) else {
+            then(Ok(()));
+            return;
+        };
+        if let Some(url) = edits.keys().find(|url| url.to_file_path().is_err()) {
+            then(Err(format!("{url} is not a file")));
+            return;
+        }
+        let versions = workspace_edit_versions(edit);
+        let files = edits
+            .into_iter()
+            .filter_map(|(url, edits)| {
+                let path = url.to_file_path().ok()?;
+                let (doc, _) = self.get_doc(
+                    path.clone(),
+                    None,
+                    false,
+                    DocContent::File {
                         path,
-                        position,
-                        scroll_offset: None,
-                        ignore_unconfirmed: true,
-                        same_editor_tab: false,
-                    };
-                    self.jump_to_location(location, Some(edits));
+                        read_only: false,
+                    },
+                );
+                Some((doc, edits, versions.get(&url).copied()))
+            })
+            .collect::<Vec<_>>();
+
+        // the edits are applied together, once every document is loaded
+        let loaded = files.iter().map(|(doc, ..)| doc.loaded).collect::<Vec<_>>();
+        let mut pending = Some((files, then));
+        self.scope.create_effect(move |prev_loaded: Option<bool>| {
+            if prev_loaded.unwrap_or_default() {
+                return true;
+            }
+            let loaded = loaded.iter().all(|loaded| loaded.get());
+            if loaded {
+                if let Some((files, then)) = pending.take() {
+                    then(apply_text_edits(&files));
                 }
             }
-        }
+            loaded
+        });
+    }
+
+    /// Applies the workspace edit of a code action, telling the user when
+    /// it can't be, and then runs the command of the action.
+    fn apply_code_action_edit(
+        &self,
+        edit: &WorkspaceEdit,
+        plugin_id: PluginId,
+        command: Option<Command>,
+    ) {
+        let main_split = self.clone();
+        self.apply_workspace_edit(edit, move |result| match result {
+            Ok(()) => {
+                if let Some(command) = command {
+                    main_split.run_code_lens(plugin_id, command);
+                }
+            },
+            Err(message) => {
+                main_split
+                    .common
+                    .internal_command
+                    .send(InternalCommand::ShowStatusMessage { message });
+            },
+        });
     }
 
     pub fn prev_error(&self, severity: DiagnosticSeverity) {
@@ -2301,6 +2740,43 @@ impl MainSplitData {
         }
     }
 
+    /// Replaces the diagnostics a cargo command reported for `path`, keeping
+    /// the language server's.
+    pub fn set_build_diagnostics(&self, path: &Path, build: Vec<Diagnostic>) {
+        let diag = self.get_diagnostic_data(path);
+        diag.diagnostics.update(|diagnostics| {
+            diagnostics.retain(|d| !is_build_diagnostic(d));
+            diagnostics.extend(build);
+            diagnostics.sort_by(|a, b| a.range.start.cmp(&b.range.start));
+        });
+        let doc_content = DocContent::File {
+            path:      path.to_path_buf(),
+            read_only: false,
+        };
+        if let Some(doc) = self
+            .docs
+            .with_untracked(|docs| docs.get(&doc_content).cloned())
+        {
+            doc.init_diagnostics();
+        }
+    }
+
+    pub fn clear_build_diagnostics(&self) {
+        let paths: Vec<PathBuf> = self.diagnostics.with_untracked(|diagnostics| {
+            diagnostics
+                .iter()
+                .filter(|(_, diag)| {
+                    diag.diagnostics
+                        .with_untracked(|d| d.iter().any(is_build_diagnostic))
+                })
+                .map(|(path, _)| path.clone())
+                .collect()
+        });
+        for path in paths {
+            self.set_build_diagnostics(&path, Vec::new());
+        }
+    }
+
     pub fn open_file_changed(&self, path: &Path, content: &FileChanged) {
         log::debug!("open_file_changed {:?}", path);
         let doc_content = DocContent::File {
@@ -2316,7 +2792,9 @@ impl MainSplitData {
                     Some(doc) => doc,
                     None => return,
                 };
-                doc.handle_file_changed(Rope::from(content));
+                if doc.handle_file_changed(Rope::from(content)) {
+                    self.show_file_changed_alert(path, doc);
+                }
             },
             FileChanged::Delete => {
                 if self.docs.with_untracked(|x| x.get(&doc_content).is_none()) {
@@ -2384,11 +2862,12 @@ impl MainSplitData {
     }
 
     pub fn save_as(&self, doc: Rc<Doc>, path: PathBuf, action: impl Fn() + 'static) {
-        let (buffer_id, doc_content, rev, content) = (
+        let (buffer_id, doc_content, rev, content, encoding) = (
             doc.buffer_id,
             doc.content.get_untracked(),
             doc.rev(),
             doc.lines.with_untracked(|x| x.buffer().to_string()),
+            doc.encoding.get_untracked(),
         );
         match doc_content {
             DocContent::Scratch { .. } => {
@@ -2421,12 +2900,18 @@ impl MainSplitData {
                         }
                     })
                 };
+                let save_mode = self
+                    .common
+                    .config
+                    .with_untracked(|config| config.core.save_mode);
                 self.common.proxy.proxy_rpc.save_buffer_as(
                     buffer_id,
                     path,
                     rev,
                     content,
                     true,
+                    encoding,
+                    save_mode,
                     Box::new(move |(_, result)| {
                         send(result);
                     }),
@@ -2435,6 +2920,7 @@ impl MainSplitData {
             DocContent::Local => {},
             DocContent::File { .. } => {},
             DocContent::History(_) => {},
+            DocContent::Virtual { .. } => {},
         }
     }
 
@@ -2444,11 +2930,12 @@ impl MainSplitData {
         path: PathBuf,
         action: impl Fn() + 'static,
     ) {
-        let (buffer_id, doc_content, rev, content) = (
+        let (buffer_id, doc_content, rev, content, encoding) = (
             doc.buffer_id,
             doc.content.get_untracked(),
             doc.rev(),
             doc.lines.with_untracked(|x| x.buffer().to_string()),
+            doc.encoding.get_untracked(),
         );
         match doc_content {
             DocContent::Scratch { .. } => {
@@ -2473,12 +2960,18 @@ impl MainSplitData {
                         }
                     })
                 };
+                let save_mode = self
+                    .common
+                    .config
+                    .with_untracked(|config| config.core.save_mode);
                 self.common.proxy.proxy_rpc.save_buffer_as(
                     buffer_id,
                     path,
                     rev,
                     content,
                     true,
+                    encoding,
+                    save_mode,
                     Box::new(move |(_, result)| {
                         send(result);
                     }),
@@ -2487,6 +2980,7 @@ impl MainSplitData {
             DocContent::Local => {},
             DocContent::File { .. } => {},
             DocContent::History(_) => {},
+            DocContent::Virtual { .. } => {},
         }
     }
 
@@ -3110,6 +3604,9 @@ impl MainSplitData {
             },
             DocContent::Local => editors.new_local(self.scope, common, None),
             DocContent::History(_) => editors.new_local(self.scope, common, None),
+            DocContent::Virtual { .. } => {
+                editors.new_local(self.scope, common, None)
+            },
             DocContent::Scratch { name, .. } => {
                 let doc = self
                     .scratch_docs
@@ -3166,32 +3663,13 @@ impl MainSplitData {
                         data.get_doc(path.clone(), None, false, content.clone());
                     doc
                 },
-                DocContent::Local => {
+                DocContent::Local | DocContent::Virtual { .. } => {
                     Rc::new(Doc::new_local(cx, common.clone(), None))
                 },
-                DocContent::History(history) => {
+                DocContent::History(_) => {
                     let doc = Doc::new_history(cx, content.clone(), common.clone());
                     let doc = Rc::new(doc);
-
-                    {
-                        let doc = doc.clone();
-                        let send = create_ext_action(cx, move |result| {
-                            if let Ok(ProxyResponse::BufferHeadResponse {
-                                content,
-                                ..
-                            }) = result
-                            {
-                                doc.init_content(Rope::from(content));
-                            }
-                        });
-                        common.proxy.proxy_rpc.get_buffer_head(
-                            history.path.clone(),
-                            move |(_, result)| {
-                                send(result);
-                            },
-                        );
-                    }
-
+                    load_history_doc(cx, &common, doc.clone());
                     doc
                 },
                 DocContent::Scratch { name, .. } => {
@@ -3280,6 +3758,94 @@ impl MainSplitData {
     }
 }
 
+/// Loads the content of a `DocContent::History` doc. The `index` version
+/// comes from the git index, `head` from HEAD and any other is a revision.
+fn load_history_doc(cx: Scope, common: &CommonData, doc: Rc<Doc>) {
+    let DocContent::History(history) = doc.content.get_untracked() else {
+        return;
+    };
+    let send = create_ext_action(cx, move |result| {
+        if let Ok(ProxyResponse::BufferHeadResponse { content, .. }) = result {
+            doc.init_content(Rope::from(content));
+        }
+    });
+    let f = move |(_, result): (u64, Result<ProxyResponse, RpcError>)| {
+        send(result);
+    };
+    match history.version.as_str() {
+        "index" => common.proxy.proxy_rpc.git_index_content(history.path, f),
+        "head" => common.proxy.proxy_rpc.get_buffer_head(history.path, f),
+        rev => {
+            common
+                .proxy
+                .proxy_rpc
+                .git_file_content(rev.to_string(), history.path, f)
+        },
+    }
+}
+
+/// The versions of the documents a workspace edit was made for, where it
+/// tells them.
+fn workspace_edit_versions(edit: &WorkspaceEdit) -> HashMap<Url, i32> {
+    let text_documents: Vec<_> = match edit.document_changes.as_ref() {
+        Some(DocumentChanges::Edits(edits)) => {
+            edits.iter().map(|e| &e.text_document).collect()
+        },
+        Some(DocumentChanges::Operations(ops)) => ops
+            .iter()
+            .filter_map(|o| match o {
+                DocumentChangeOperation::Op(_op) => None,
+                DocumentChangeOperation::Edit(e) => Some(&e.text_document),
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    text_documents
+        .into_iter()
+        .filter_map(|doc| Some((doc.uri.clone(), doc.version?)))
+        .collect()
+}
+
+/// Applies the edits of each document, unless one of them can't take its
+/// edits, as when it is read-only, or has changed since the version the
+/// edits were made for.
+fn apply_text_edits(
+    files: &[(Rc<Doc>, Vec<TextEdit>, Option<i32>)],
+) -> Result<(), String> {
+    let mut doc_edits = Vec::with_capacity(files.len());
+    for (doc, edits, version) in files {
+        let content = doc.content.get_untracked();
+        let path = content.path().map(|path| path.display().to_string());
+        let path = path.unwrap_or_default();
+        if content.read_only() {
+            return Err(format!("{path} can't be edited"));
+        }
+        if version.is_some_and(|version| u64::try_from(version) != Ok(doc.rev())) {
+            return Err(format!("{path} has changed"));
+        }
+        let edits = doc
+            .lines
+            .with_untracked(|x| {
+                edits
+                    .iter()
+                    .map(|edit| {
+                        let selection = Selection::region(
+                            x.buffer().offset_of_position(&edit.range.start)?,
+                            x.buffer().offset_of_position(&edit.range.end)?,
+                        );
+                        Ok((selection, edit.new_text.as_str()))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .map_err(|err| format!("{path}: {err}"))?;
+        doc_edits.push((doc, edits));
+    }
+    for (doc, edits) in doc_edits {
+        doc.do_raw_edit(&edits, EditType::Completion);
+    }
+    Ok(())
+}
+
 fn workspace_edits(edit: &WorkspaceEdit) -> Option<HashMap<Url, Vec<TextEdit>>> {
     if let Some(changes) = edit.changes.as_ref() {
         return Some(changes.clone());
@@ -5,7 +5,7 @@ use lapce_core::{
     debug::RunDebugMode,
     workspace::{LapceWorkspace, SshHost},
 };
-use lapce_rpc::dap_types::RunDebugConfig;
+use lapce_rpc::{buffer::FileEncoding, dap_types::RunDebugConfig};
 use lsp_types::{Range, SymbolKind};
 
 use crate::{
@@ -68,6 +68,10 @@ pub enum PaletteItemContent {
         mode:   RunDebugMode,
         config: RunDebugConfig,
     },
+    AttachProcess {
+        pid:     u32,
+        command: String,
+    },
     ColorTheme {
         name: String,
     },
@@ -80,6 +84,12 @@ pub enum PaletteItemContent {
     LineEnding {
         kind: LineEnding,
     },
+    Encoding {
+        encoding: FileEncoding,
+        /// Whether to read the file again with the encoding, rather than save
+        /// it with it
+        reopen:   bool,
+    },
     SCMReference {
         name: String,
     },
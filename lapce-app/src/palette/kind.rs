@@ -15,10 +15,12 @@ pub enum PaletteKind {
     #[cfg(windows)]
     WslHost,
     RunAndDebug,
+    AttachProcess,
     ColorTheme,
     IconTheme,
     Language,
     LineEnding,
+    Encoding,
     SCMReferences,
     TerminalProfile,
     DiffFiles,
@@ -40,10 +42,12 @@ impl PaletteKind {
             PaletteKind::Reference
             | PaletteKind::SshHost
             | PaletteKind::RunAndDebug
+            | PaletteKind::AttachProcess
             | PaletteKind::ColorTheme
             | PaletteKind::IconTheme
             | PaletteKind::Language
             | PaletteKind::LineEnding
+            | PaletteKind::Encoding
             | PaletteKind::SCMReferences
             | PaletteKind::HelpAndFile
             | PaletteKind::DiffFiles => "",
@@ -92,12 +96,14 @@ impl PaletteKind {
             PaletteKind::RunAndDebug => {
                 Some(LapceWorkbenchCommand::PaletteRunAndDebug)
             },
+            PaletteKind::AttachProcess => None, // RunAndDebug without a pid
             PaletteKind::ColorTheme => Some(LapceWorkbenchCommand::ChangeColorTheme),
             PaletteKind::IconTheme => Some(LapceWorkbenchCommand::ChangeIconTheme),
             PaletteKind::Language => Some(LapceWorkbenchCommand::ChangeFileLanguage),
             PaletteKind::LineEnding => {
                 Some(LapceWorkbenchCommand::ChangeFileLineEnding)
             },
+            PaletteKind::Encoding => Some(LapceWorkbenchCommand::ChangeFileEncoding),
             PaletteKind::SCMReferences => {
                 Some(LapceWorkbenchCommand::PaletteSCMReferences)
             },
@@ -124,10 +130,12 @@ impl PaletteKind {
             PaletteKind::Reference
             | PaletteKind::SshHost
             | PaletteKind::RunAndDebug
+            | PaletteKind::AttachProcess
             | PaletteKind::ColorTheme
             | PaletteKind::IconTheme
             | PaletteKind::Language
             | PaletteKind::LineEnding
+            | PaletteKind::Encoding
             | PaletteKind::SCMReferences | PaletteKind::HelpAndFile
             | PaletteKind::DiffFiles => input,
             PaletteKind::PaletteHelp
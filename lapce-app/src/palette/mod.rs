@@ -35,7 +35,9 @@ use lapce_core::{
     doc::DocContent,
     workspace::{LapceWorkspace, LapceWorkspaceType, SshHost},
 };
-use lapce_rpc::proxy::ProxyResponse;
+use lapce_rpc::{
+    buffer::FileEncoding, dap_types::RunDebugConfig, proxy::ProxyResponse,
+};
 use log::{error, info};
 use lsp_types::{DocumentSymbol, DocumentSymbolResponse};
 use nucleo::Utf32Str;
@@ -148,6 +150,8 @@ pub struct PaletteData {
     pub executed_run_configs:  Rc<RefCell<HashMap<(RunDebugMode, String), Instant>>>,
     pub main_split:            MainSplitData,
     pub references:            RwSignal<Vec<EditorLocation>>,
+    /// The attach config waiting for a process to be picked
+    pub attach_config:         RwSignal<Option<RunDebugConfig>>,
     pub source_control:        SourceControlData,
     pub common:                Rc<CommonData>,
     left_diff_path:            RwSignal<Option<PathBuf>>,
@@ -210,6 +214,7 @@ impl PaletteData {
             executed_commands: Rc::new(RefCell::new(HashMap::new())),
             executed_run_configs: Rc::new(RefCell::new(HashMap::new())),
             references,
+            attach_config: cx.create_rw_signal(None),
             source_control,
             common,
             left_diff_path,
@@ -392,6 +397,9 @@ impl PaletteData {
             PaletteKind::RunAndDebug => {
                 self.get_run_configs(run_id, kind_input.to_string())?;
             },
+            PaletteKind::AttachProcess => {
+                self.get_processes(run_id, kind_input.to_string());
+            },
             PaletteKind::ColorTheme => {
                 self.get_color_themes(run_id);
             },
@@ -404,6 +412,9 @@ impl PaletteData {
             PaletteKind::LineEnding => {
                 self.get_line_endings(run_id);
             },
+            PaletteKind::Encoding => {
+                self.get_encodings(run_id);
+            },
             PaletteKind::SCMReferences => {
                 self.get_scm_references(run_id);
             },
@@ -532,14 +543,9 @@ impl PaletteData {
                     .into_iter()
                     .map(|full_path| {
                         // Strip the workspace prefix off the path, to avoid clutter
-                        let path = if let Some(workspace_path) = workspace.path() {
-                            full_path
-                                .strip_prefix(workspace_path)
-                                .unwrap_or(&full_path)
-                                .to_path_buf()
-                        } else {
-                            full_path.clone()
-                        };
+                        let path = workspace
+                            .relative_path(&full_path)
+                            .unwrap_or_else(|| full_path.clone());
                         let filter_text = path.to_string_lossy().into_owned();
                         PaletteItem {
                             content: PaletteItemContent::File { path, full_path },
@@ -718,13 +724,10 @@ impl PaletteData {
             .into_iter()
             .map(|l| {
                 let full_path = l.path.clone();
-                let mut path = l.path.clone();
-                if let Some(workspace_path) = self.workspace.path() {
-                    path = path
-                        .strip_prefix(workspace_path)
-                        .unwrap_or(&full_path)
-                        .to_path_buf();
-                }
+                let path = self
+                    .workspace
+                    .relative_path(&l.path)
+                    .unwrap_or_else(|| full_path.clone());
                 let filter_text = path.to_str().unwrap_or("").to_string();
                 PaletteItem {
                     content: PaletteItemContent::Reference { path, location: l },
@@ -1049,25 +1052,28 @@ impl PaletteData {
             let executed_run_configs = self.executed_run_configs.borrow();
             let mut items = Vec::new();
             for config in &configs.configs {
-                items.push((
-                    executed_run_configs
-                        .get(&(RunDebugMode::Run, config.name.clone())),
-                    PaletteItem {
-                        content: PaletteItemContent::RunAndDebug {
-                            mode:   RunDebugMode::Run,
-                            config: config.clone(),
+                // an attach config can only be debugged
+                if !config.is_attach() {
+                    items.push((
+                        executed_run_configs
+                            .get(&(RunDebugMode::Run, config.name.clone())),
+                        PaletteItem {
+                            content: PaletteItemContent::RunAndDebug {
+                                mode:   RunDebugMode::Run,
+                                config: config.clone(),
+                            },
+                            filter_text: format!(
+                                "Run {} {} {}",
+                                config.name,
+                                config.program,
+                                config.args.clone().unwrap_or_default().join(" ")
+                            ),
+                            score: 0,
+                            indices: vec![],
+                            run_id,
                         },
-                        filter_text: format!(
-                            "Run {} {} {}",
-                            config.name,
-                            config.program,
-                            config.args.clone().unwrap_or_default().join(" ")
-                        ),
-                        score: 0,
-                        indices: vec![],
-                        run_id,
-                    },
-                ));
+                    ));
+                }
                 if config.ty.is_some() {
                     items.push((
                         executed_run_configs
@@ -1103,6 +1109,36 @@ impl PaletteData {
         }
     }
 
+    fn get_processes(&self, run_id: u64, input: String) {
+        let palette = self.clone();
+        let send = create_ext_action(self.common.scope, move |result| {
+            if let Ok(ProxyResponse::ListProcessesResponse { processes }) = result {
+                let items = processes
+                    .into_iter()
+                    .map(|p| PaletteItem {
+                        filter_text: format!("{} {}", p.pid, p.command),
+                        content: PaletteItemContent::AttachProcess {
+                            pid:     p.pid,
+                            command: p.command,
+                        },
+                        score: 0,
+                        indices: Vec::new(),
+                        run_id,
+                    })
+                    .collect();
+                palette.filter_items(run_id, &input, items);
+            } else {
+                palette.update_rs(run_id, Vector::new());
+            }
+        });
+        self.common
+            .proxy
+            .proxy_rpc
+            .list_processes(move |(_, result)| {
+                send(result);
+            });
+    }
+
     fn get_color_themes(&self, run_id: u64) {
         let (items, name) = self.common.config.with_untracked(|config| {
             (
@@ -1191,6 +1227,32 @@ impl PaletteData {
         self.items.set(items);
     }
 
+    fn get_encodings(&self, run_id: u64) {
+        let items = [true, false]
+            .into_iter()
+            .flat_map(|reopen| {
+                FileEncoding::all().into_iter().map(move |encoding| {
+                    let action = if reopen { "Reopen" } else { "Save" };
+                    PaletteItem {
+                        filter_text: format!("{action} with Encoding: {encoding}"),
+                        content: PaletteItemContent::Encoding { encoding, reopen },
+                        score: 0,
+                        indices: Vec::new(),
+                        run_id,
+                    }
+                })
+            })
+            .collect();
+        if let Some(editor) = self.main_split.active_editor.get_untracked() {
+            let encoding = editor.doc().encoding.get_untracked();
+            self.preselect_matching(
+                &items,
+                &format!("Reopen with Encoding: {encoding}"),
+            );
+        }
+        self.items.set(items);
+    }
+
     fn get_scm_references(&self, run_id: u64) {
         let branches = self.source_control.branches.get_untracked();
         let tags = self.source_control.tags.get_untracked();
@@ -1243,11 +1305,12 @@ impl PaletteData {
                 content: PaletteItemContent::TerminalProfile {
                     name:    name.to_owned(),
                     profile: lapce_rpc::terminal::TerminalProfile {
-                        name:        name.to_owned(),
-                        command:     profile.command,
-                        arguments:   profile.arguments,
-                        workdir:     uri,
-                        environment: profile.environment,
+                        name:              name.to_owned(),
+                        command:           profile.command,
+                        arguments:         profile.arguments,
+                        workdir:           uri,
+                        environment:       profile.environment,
+                        shell_integration: profile.shell_integration.unwrap_or(true),
                     },
                 },
                 filter_text: name.to_owned(),
@@ -1415,6 +1478,17 @@ impl PaletteData {
                         },
                     );
                 },
+                PaletteItemContent::AttachProcess { pid, .. } => {
+                    if let Some(mut config) = self.attach_config.get_untracked() {
+                        config.pid = Some(*pid);
+                        self.common.internal_command.send(
+                            InternalCommand::RunAndDebug {
+                                mode: RunDebugMode::Debug,
+                                config,
+                            },
+                        );
+                    }
+                },
                 PaletteItemContent::ColorTheme { name } => self
                     .common
                     .internal_command
@@ -1467,6 +1541,18 @@ impl PaletteData {
                     //     lines.set_line_ending(*kind);
                     // });
                 },
+                PaletteItemContent::Encoding { encoding, reopen } => {
+                    let Some(editor) = self.main_split.active_editor.get_untracked()
+                    else {
+                        return;
+                    };
+                    let doc = editor.doc();
+                    if *reopen {
+                        doc.reopen_with_encoding(encoding.clone());
+                    } else {
+                        doc.save_with_encoding(encoding.clone());
+                    }
+                },
                 PaletteItemContent::SCMReference { name } => {
                     self.common
                         .lapce_command
@@ -1542,11 +1628,13 @@ impl PaletteData {
                 PaletteItemContent::Command { .. } => {},
                 PaletteItemContent::Workspace { .. } => {},
                 PaletteItemContent::RunAndDebug { .. } => {},
+                PaletteItemContent::AttachProcess { .. } => {},
                 PaletteItemContent::SshHost { .. } => {},
                 #[cfg(windows)]
                 PaletteItemContent::WslHost { .. } => {},
                 PaletteItemContent::Language { .. } => {},
                 PaletteItemContent::LineEnding { .. } => {},
+                PaletteItemContent::Encoding { .. } => {},
                 PaletteItemContent::Reference { location, .. } => {
                     has_preview = true;
                     let (doc, new_doc) = self.main_split.get_doc(
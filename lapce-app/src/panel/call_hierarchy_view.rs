@@ -10,38 +10,115 @@ use floem::{
     },
 };
 use lapce_core::{icon::LapceIcons, panel::PanelContainerPosition};
-use lsp_types::{CallHierarchyItem, Range};
+use lsp_types::{CallHierarchyItem, Range, SymbolKind, TypeHierarchyItem, Url};
 
 use crate::{
+    app::clickable_icon,
     command::InternalCommand,
-    common::{TabHead, common_tab_header},
+    common::{TabHead, Tabs, common_tab_header},
     config::color::LapceColor,
     editor::location::EditorLocation,
     window_workspace::WindowWorkspaceData,
 };
 
+/// Which way a hierarchy is expanded from its root.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HierarchyDirection {
+    /// The callers of a function
+    Incoming,
+    /// What a function calls
+    Outgoing,
+    Supertypes,
+    Subtypes,
+}
+
+impl HierarchyDirection {
+    pub fn reversed(self) -> Self {
+        match self {
+            HierarchyDirection::Incoming => HierarchyDirection::Outgoing,
+            HierarchyDirection::Outgoing => HierarchyDirection::Incoming,
+            HierarchyDirection::Supertypes => HierarchyDirection::Subtypes,
+            HierarchyDirection::Subtypes => HierarchyDirection::Supertypes,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HierarchyDirection::Incoming => "Incoming Calls",
+            HierarchyDirection::Outgoing => "Outgoing Calls",
+            HierarchyDirection::Supertypes => "Supertypes",
+            HierarchyDirection::Subtypes => "Subtypes",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum HierarchyItem {
+    Call(CallHierarchyItem),
+    Type(TypeHierarchyItem),
+}
+
+impl HierarchyItem {
+    pub fn name(&self) -> &str {
+        match self {
+            HierarchyItem::Call(item) => &item.name,
+            HierarchyItem::Type(item) => &item.name,
+        }
+    }
+
+    pub fn kind(&self) -> SymbolKind {
+        match self {
+            HierarchyItem::Call(item) => item.kind,
+            HierarchyItem::Type(item) => item.kind,
+        }
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        match self {
+            HierarchyItem::Call(item) => item.detail.as_deref(),
+            HierarchyItem::Type(item) => item.detail.as_deref(),
+        }
+    }
+
+    pub fn uri(&self) -> &Url {
+        match self {
+            HierarchyItem::Call(item) => &item.uri,
+            HierarchyItem::Type(item) => &item.uri,
+        }
+    }
+
+    pub fn selection_range(&self) -> Range {
+        match self {
+            HierarchyItem::Call(item) => item.selection_range,
+            HierarchyItem::Type(item) => item.selection_range,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
-pub struct CallHierarchyData {
-    pub root:           RwSignal<CallHierarchyItemData>,
+pub struct HierarchyData {
+    pub root:           RwSignal<HierarchyItemData>,
     pub root_id:        ViewId,
+    pub direction:      RwSignal<HierarchyDirection>,
     // pub common: Rc<CommonData>,
     pub scroll_to_line: Option<f64>,
 }
 
-impl TabHead for CallHierarchyData {}
+impl TabHead for HierarchyData {}
 
 #[derive(Debug, Clone)]
-pub struct CallHierarchyItemData {
+pub struct HierarchyItemData {
     pub root_id:    ViewId,
     pub view_id:    ViewId,
-    pub item:       Rc<CallHierarchyItem>,
+    pub item:       Rc<HierarchyItem>,
+    /// Where a click on the item jumps to
     pub from_range: Range,
     pub init:       bool,
     pub open:       RwSignal<bool>,
-    pub children:   RwSignal<Vec<RwSignal<CallHierarchyItemData>>>,
+    pub children:   RwSignal<Vec<RwSignal<HierarchyItemData>>>,
 }
 
-impl CallHierarchyItemData {
+impl HierarchyItemData {
     pub fn child_count(&self) -> usize {
         let mut count = 1;
         if self.open.get() {
@@ -53,9 +130,9 @@ impl CallHierarchyItemData {
     }
 
     pub fn find_by_id(
-        root: RwSignal<CallHierarchyItemData>,
+        root: RwSignal<HierarchyItemData>,
         view_id: ViewId,
-    ) -> Option<RwSignal<CallHierarchyItemData>> {
+    ) -> Option<RwSignal<HierarchyItemData>> {
         if root.get_untracked().view_id == view_id {
             Some(root)
         } else {
@@ -69,12 +146,12 @@ impl CallHierarchyItemData {
 }
 
 fn get_children(
-    data: RwSignal<CallHierarchyItemData>,
+    data: RwSignal<HierarchyItemData>,
     next: &mut usize,
     min: usize,
     max: usize,
     level: usize,
-) -> Vec<(usize, usize, RwSignal<CallHierarchyItemData>)> {
+) -> Vec<(usize, usize, RwSignal<HierarchyItemData>)> {
     let mut children = Vec::new();
     if *next >= min && *next < max {
         children.push((*next, level, data));
@@ -95,16 +172,16 @@ fn get_children(
 }
 
 pub struct VirtualList {
-    root: Option<RwSignal<CallHierarchyItemData>>,
+    root: Option<RwSignal<HierarchyItemData>>,
 }
 
 impl VirtualList {
-    pub fn new(root: Option<RwSignal<CallHierarchyItemData>>) -> Self {
+    pub fn new(root: Option<RwSignal<HierarchyItemData>>) -> Self {
         Self { root }
     }
 }
 
-impl VirtualVector<(usize, usize, RwSignal<CallHierarchyItemData>)> for VirtualList {
+impl VirtualVector<(usize, usize, RwSignal<HierarchyItemData>)> for VirtualList {
     fn total_len(&self) -> usize {
         if let Some(root) = &self.root {
             root.with(|x| x.child_count())
@@ -116,7 +193,7 @@ impl VirtualVector<(usize, usize, RwSignal<CallHierarchyItemData>)> for VirtualL
     fn slice(
         &mut self,
         range: std::ops::Range<usize>,
-    ) -> impl Iterator<Item = (usize, usize, RwSignal<CallHierarchyItemData>)> {
+    ) -> impl Iterator<Item = (usize, usize, RwSignal<HierarchyItemData>)> {
         if let Some(root) = &self.root {
             let min = range.start;
             let max = range.end;
@@ -132,20 +209,70 @@ pub fn show_hierarchy_panel(
     window_tab_data: WindowWorkspaceData,
     _position: PanelContainerPosition,
 ) -> impl View {
+    let hierarchy = window_tab_data.main_split.hierarchy;
+    hierarchy_panel(window_tab_data, _position, hierarchy)
+}
+
+pub fn show_type_hierarchy_panel(
+    window_tab_data: WindowWorkspaceData,
+    _position: PanelContainerPosition,
+) -> impl View {
+    let hierarchy = window_tab_data.main_split.type_hierarchy;
+    hierarchy_panel(window_tab_data, _position, hierarchy)
+}
+
+fn hierarchy_panel(
+    window_tab_data: WindowWorkspaceData,
+    _position: PanelContainerPosition,
+    hierarchy: Tabs<HierarchyData>,
+) -> impl View {
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
     stack((
-        common_tab_header(
-            window_tab_data.clone(),
-            window_tab_data.main_split.hierarchy.clone(),
-        )
-        .debug_name("hierarchy head"),
-        _show_hierarchy_panel(window_tab_data.clone(), _position, move || {
-            VirtualList::new(
-                window_tab_data
-                    .main_split
-                    .hierarchy
-                    .get_active_content()
-                    .map(|x| x.root),
+        stack((
+            common_tab_header(window_tab_data.clone(), hierarchy)
+                .style(|s| s.flex_grow(1.0).min_width(0.0))
+                .debug_name("hierarchy head"),
+            clickable_icon(
+                || LapceIcons::HIERARCHY_DIRECTION,
+                move || {
+                    let Some(data) = hierarchy.get_active_content() else {
+                        return;
+                    };
+                    // the children of the other direction are fetched anew
+                    data.direction.update(|direction| {
+                        *direction = direction.reversed();
+                    });
+                    data.root.update(|root| {
+                        root.init = false;
+                        root.open.set(true);
+                        root.children.set(Vec::new());
+                    });
+                    internal_command.send(InternalCommand::HierarchyExpand {
+                        root_id: data.root_id,
+                        item_id: data.root_id,
+                    });
+                },
+                || false,
+                move || hierarchy.get_active_content().is_none(),
+                move || {
+                    hierarchy
+                        .get_active_content()
+                        .map(|data| {
+                            format!(
+                                "Show {}",
+                                data.direction.get().reversed().label()
+                            )
+                        })
+                        .unwrap_or_default()
+                },
+                config,
             )
+            .style(|s| s.padding_horiz(6.0)),
+        ))
+        .style(|s| s.items_center().width_full()),
+        _show_hierarchy_panel(window_tab_data.clone(), _position, move || {
+            VirtualList::new(hierarchy.get_active_content().map(|x| x.root))
         })
         .debug_name("hierarchy panel"),
     ))
@@ -167,7 +294,7 @@ pub fn _show_hierarchy_panel(
             move |(_, level, rw_data)| {
                 let data = rw_data.get_untracked();
                 let open = data.open;
-                let kind = data.item.kind;
+                let kind = data.item.kind();
                 stack((
                     container(
                         svg(move || {
@@ -198,7 +325,7 @@ pub fn _show_hierarchy_panel(
                             if !rw_data.get_untracked().init {
                                 let data = rw_data.get_untracked();
                                 window_tab_data.common.internal_command.send(
-                                    InternalCommand::CallHierarchyIncoming {
+                                    InternalCommand::HierarchyExpand {
                                         root_id: data.root_id,
                                         item_id: data.view_id,
                                     },
@@ -229,10 +356,10 @@ pub fn _show_hierarchy_panel(
                                     caret_color
                                 ).get())
                         }),
-                    data.item.name.clone().into_view(),
-                    if data.item.detail.is_some() {
+                    data.item.name().to_string().into_view(),
+                    if data.item.detail().is_some() {
                         label(move || {
-                            data.item.detail.clone().unwrap_or_default().replace('\n', "↵")
+                            data.item.detail().unwrap_or_default().replace('\n', "↵")
                         }).style(move |s| s.margin_left(6.0)
                                                 .color(config.with_color(LapceColor::EDITOR_DIM))
                         ).into_any()
@@ -259,11 +386,11 @@ pub fn _show_hierarchy_panel(
                         if !rw_data.get_untracked().init {
                             let data = rw_data.get_untracked();
                             window_tab_data.common.internal_command.send(
-                                InternalCommand::CallHierarchyIncoming { item_id: data.view_id, root_id: data.root_id },
+                                InternalCommand::HierarchyExpand { item_id: data.view_id, root_id: data.root_id },
                             );
                         }
                         let data = data.get_untracked();
-                        if let Ok(path) = data.item.uri.to_file_path() {
+                        if let Ok(path) = data.item.uri().to_file_path() {
                             window_tab_data
                                 .common
                                 .internal_command
@@ -1,18 +1,26 @@
+use std::{path::PathBuf, rc::Rc};
+
 use floem::{
     View,
-    event::EventListener,
+    action::show_context_menu,
+    event::{Event, EventListener},
+    keyboard::{Key, NamedKey},
+    menu::{Menu, MenuItem},
     peniko::Color,
     prelude::palette,
-    reactive::{RwSignal, SignalGet, SignalUpdate, SignalWith, create_rw_signal},
+    reactive::{
+        RwSignal, SignalGet, SignalUpdate, SignalWith, create_effect,
+        create_rw_signal,
+    },
     style::CursorStyle,
     text::Style as FontStyle,
     views::{
         Decorators, container, dyn_stack, label, scroll, stack, svg, text,
-        virtual_stack,
+        text_input, virtual_stack,
     },
 };
 use lapce_core::{
-    debug::{BreakpointAction, RunDebugMode},
+    debug::{BreakpointAction, DapVariableViewdata, LapceBreakpoint, RunDebugMode},
     icon::LapceIcons,
     panel::{PanelContainerPosition, PanelSection},
 };
@@ -26,7 +34,10 @@ use crate::{
     app::clickable_icon,
     command::InternalCommand,
     config::{WithLapceConfig, color::LapceColor},
-    debug::{DapVariable, StackTraceData, update_breakpoints},
+    debug::{
+        BreakpointEditField, DapVariable, DebugConsoleRows, StackTraceData,
+        update_breakpoints,
+    },
     editor::location::{EditorLocation, EditorPosition},
     listener::Listener,
     settings::checkbox,
@@ -54,6 +65,13 @@ pub fn debug_panel(
             variables_view(window_tab_data.clone()),
             window_tab_data.panel.section_open(PanelSection::Variable),
         )
+        .add(
+            "Debug Console",
+            debug_console_view(window_tab_data.clone()),
+            window_tab_data
+                .panel
+                .section_open(PanelSection::DebugConsole),
+        )
         .add(
             "Stack Frames",
             debug_stack_traces(terminal.clone(), internal_command, config),
@@ -65,6 +83,14 @@ pub fn debug_panel(
             breakpoints_view(window_tab_data.clone()),
             window_tab_data.panel.section_open(PanelSection::Breakpoint),
         )
+        .add_height(
+            "Exception Breakpoints",
+            100.0,
+            exception_breakpoints_view(window_tab_data.clone()),
+            window_tab_data
+                .panel
+                .section_open(PanelSection::ExceptionBreakpoint),
+        )
         .build()
         .debug_name("Debug Panel")
 }
@@ -202,6 +228,32 @@ fn debug_process_icons(
                 )
                 .style(|s| s.margin_right(4.0))
             },
+            {
+                let terminal = terminal.clone();
+                let supported = {
+                    let terminal = terminal.clone();
+                    move || {
+                        terminal.debug.daps.with_untracked(|daps| {
+                            daps.get(&dap_id)
+                                .map(|dap| dap.supports_instruction_stepping())
+                                .unwrap_or(false)
+                        })
+                    }
+                };
+                clickable_icon(
+                    || LapceIcons::DEBUG_STEP_INSTRUCTION,
+                    move || {
+                        terminal.dap_step_instruction(term_id);
+                    },
+                    || false,
+                    move || !paused() || stopped,
+                    || "Step Instruction",
+                    config,
+                )
+                .style(move |s| {
+                    s.margin_right(4.0).apply_if(!supported(), |s| s.hide())
+                })
+            },
             {
                 let terminal = terminal.clone();
 
@@ -380,49 +432,17 @@ fn variables_view(window_tab_data: WindowWorkspaceData) -> impl View {
                 },
                 move |node| {
                     let local_terminal = local_terminal.clone();
-                    let level = node.level;
-                    let reference = node.item.reference();
-                    let name = node.item.name();
-                    let ty = node.item.ty();
-                    let type_exists = ty.map(|ty| !ty.is_empty()).unwrap_or(false);
-                    stack((
-                        svg(move || {
-                            let svg_str = match node.expanded {
-                                true => LapceIcons::ITEM_OPENED,
-                                false => LapceIcons::ITEM_CLOSED,
-                            };
-                            config.with_ui_svg(svg_str)
-                        })
-                        .style(move |s| {
-                            let (color, size) = config.signal(|config| {
-                                (
-                                    config.color(LapceColor::LAPCE_ICON_ACTIVE),
-                                    config.ui.icon_size.signal(),
-                                )
-                            });
-                            let color = if reference > 0 {
-                                color.get()
-                            } else {
-                                Color::TRANSPARENT
-                            };
-                            let size = size.get() as f32;
-                            s.size(size, size).margin_left(10.0).color(color)
-                        }),
-                        text(name),
-                        text(": ").style(move |s| {
-                            s.apply_if(!type_exists || reference == 0, |s| s.hide())
-                        }),
-                        text(node.item.ty().unwrap_or("")).style(move |s| {
-                            s.color(config.with_style_color("type").unwrap())
-                                .apply_if(!type_exists || reference == 0, |s| {
-                                    s.hide()
-                                })
-                        }),
-                        text(format!(" = {}", node.item.value().unwrap_or("")))
-                            .style(move |s| s.apply_if(reference > 0, |s| s.hide())),
-                    ))
-                    .on_click_stop(move |_| {
-                        if reference > 0 {
+                    let memory_terminal = local_terminal.clone();
+                    dap_variable_row(
+                        node,
+                        config,
+                        move |memory_reference| {
+                            if let Some(dap) = memory_terminal.get_active_dap(false)
+                            {
+                                dap.read_memory(memory_reference);
+                            }
+                        },
+                        move |parent, reference| {
                             let dap = local_terminal.get_active_dap(false);
                             if let Some(dap) = dap {
                                 let process_stopped = dap
@@ -440,27 +460,11 @@ fn variables_view(window_tab_data: WindowWorkspaceData) -> impl View {
                                     })
                                     .unwrap_or(true);
                                 if !process_stopped {
-                                    dap.toggle_expand(
-                                        node.parent.clone(),
-                                        reference,
-                                    );
+                                    dap.toggle_expand(parent, reference);
                                 }
                             }
-                        }
-                    })
-                    .style(move |s| {
-                        s.items_center()
-                            .padding_right(10.0)
-                            .padding_left((level * 10) as f32)
-                            .min_width_pct(100.0)
-                            .hover(|s| {
-                                s.apply_if(reference > 0, |s| {
-                                    s.background(config.with_color(
-                                        LapceColor::PANEL_HOVERED_BACKGROUND,
-                                    ))
-                                })
-                            })
-                    })
+                        },
+                    )
                 },
             )
             .style(|s| s.flex_col().min_width_full()),
@@ -471,6 +475,186 @@ fn variables_view(window_tab_data: WindowWorkspaceData) -> impl View {
     .debug_name("debug variables")
 }
 
+fn dap_variable_row(
+    node: DapVariableViewdata,
+    config: WithLapceConfig,
+    on_view_memory: impl Fn(String) + 'static,
+    on_expand: impl Fn(Vec<usize>, usize) + 'static,
+) -> impl View {
+    let level = node.level;
+    let reference = node.item.reference();
+    let memory_reference = node.item.memory_reference().map(|r| r.to_string());
+    let on_view_memory = Rc::new(on_view_memory);
+    let name = node.item.name();
+    let ty = node.item.ty();
+    let type_exists = ty.map(|ty| !ty.is_empty()).unwrap_or(false);
+    stack((
+        svg(move || {
+            let svg_str = match node.expanded {
+                true => LapceIcons::ITEM_OPENED,
+                false => LapceIcons::ITEM_CLOSED,
+            };
+            config.with_ui_svg(svg_str)
+        })
+        .style(move |s| {
+            let (color, size) = config.signal(|config| {
+                (
+                    config.color(LapceColor::LAPCE_ICON_ACTIVE),
+                    config.ui.icon_size.signal(),
+                )
+            });
+            let color = if reference > 0 {
+                color.get()
+            } else {
+                Color::TRANSPARENT
+            };
+            let size = size.get() as f32;
+            s.size(size, size).margin_left(10.0).color(color)
+        }),
+        text(name),
+        text(": ").style(move |s| {
+            s.apply_if(!type_exists || reference == 0, |s| s.hide())
+        }),
+        text(node.item.ty().unwrap_or("")).style(move |s| {
+            s.color(config.with_style_color("type").unwrap())
+                .apply_if(!type_exists || reference == 0, |s| s.hide())
+        }),
+        text(format!(" = {}", node.item.value().unwrap_or("")))
+            .style(move |s| s.apply_if(reference > 0, |s| s.hide())),
+    ))
+    .on_click_stop(move |_| {
+        if reference > 0 {
+            on_expand(node.parent.clone(), reference);
+        }
+    })
+    .on_event_cont(EventListener::PointerDown, move |event| {
+        if let Event::PointerDown(pointer_event) = event {
+            if pointer_event.button.is_secondary() {
+                if let Some(memory_reference) = memory_reference.clone() {
+                    let on_view_memory = on_view_memory.clone();
+                    let menu =
+                        Menu::new("").entry(MenuItem::new("View Memory").action(
+                            move || on_view_memory(memory_reference.clone()),
+                        ));
+                    show_context_menu(menu, None);
+                }
+            }
+        }
+    })
+    .style(move |s| {
+        s.items_center()
+            .padding_right(10.0)
+            .padding_left((level * 10) as f32)
+            .min_width_pct(100.0)
+            .hover(|s| {
+                s.apply_if(reference > 0, |s| {
+                    s.background(
+                        config.with_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                    )
+                })
+            })
+    })
+}
+
+fn debug_console_view(window_tab_data: WindowWorkspaceData) -> impl View {
+    let terminal = window_tab_data.terminal.clone();
+    let local_terminal = window_tab_data.terminal.clone();
+    let input_terminal = window_tab_data.terminal.clone();
+    let clear_terminal = window_tab_data.terminal.clone();
+    let config = window_tab_data.common.config;
+    let expression = create_rw_signal(String::new());
+
+    let input_view = text_input(expression)
+        .placeholder("Evaluate expression")
+        .on_event_stop(EventListener::KeyDown, move |event| {
+            if let Event::KeyDown(key_event) = event {
+                if let Key::Named(NamedKey::Enter) = key_event.key.logical_key {
+                    if let Some(dap) = input_terminal.get_active_dap(false) {
+                        dap.evaluate(expression.get_untracked());
+                        expression.set(String::new());
+                    }
+                }
+            }
+        })
+        .style(move |s| {
+            s.width_pct(100.0)
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(config.with_color(LapceColor::LAPCE_BORDER))
+        });
+
+    stack((
+        container(
+            scroll(
+                virtual_stack(
+                    move || {
+                        terminal
+                            .get_active_dap(true)
+                            .map(|dap| DebugConsoleRows(dap.console.get()))
+                            .unwrap_or_default()
+                    },
+                    |(index, node)| {
+                        (
+                            *index,
+                            node.item.name().to_string(),
+                            node.item.value().map(|v| v.to_string()),
+                            node.item.reference(),
+                            node.expanded,
+                            node.level,
+                        )
+                    },
+                    move |(_, node)| {
+                        let local_terminal = local_terminal.clone();
+                        let memory_terminal = local_terminal.clone();
+                        dap_variable_row(
+                            node,
+                            config,
+                            move |memory_reference| {
+                                if let Some(dap) =
+                                    memory_terminal.get_active_dap(false)
+                                {
+                                    dap.read_memory(memory_reference);
+                                }
+                            },
+                            move |parent, reference| {
+                                if let Some(dap) =
+                                    local_terminal.get_active_dap(false)
+                                {
+                                    if dap.stopped.get_untracked() {
+                                        dap.toggle_console_expand(parent, reference);
+                                    }
+                                }
+                            },
+                        )
+                    },
+                )
+                .style(|s| s.flex_col().min_width_full()),
+            )
+            .style(|s| s.absolute().size_full()),
+        )
+        .style(|s| s.width_full().line_height(1.6).flex_grow(1.0).flex_basis(0)),
+        stack((
+            input_view,
+            clickable_icon(
+                || LapceIcons::SEARCH_CLEAR,
+                move || {
+                    if let Some(dap) = clear_terminal.get_active_dap(false) {
+                        dap.clear_console();
+                    }
+                },
+                || false,
+                || false,
+                || "Clear Console",
+                config,
+            )
+            .style(|s| s.margin_left(6.0)),
+        ))
+        .style(|s| s.width_full().items_center().padding(6.0)),
+    ))
+    .style(|s| s.flex_col().size_full())
+    .debug_name("debug console")
+}
+
 fn debug_stack_frames(
     dap_id: DapId,
     thread_id: ThreadId,
@@ -506,6 +690,8 @@ fn debug_stack_frames(
             |frame| frame.id,
             move |frame| {
                 let full_path = frame.source.as_ref().and_then(|s| s.path.clone());
+                let instruction_pointer =
+                    frame.instruction_pointer_reference.clone();
                 let line = frame.line.saturating_sub(1);
                 let col = frame.column.saturating_sub(1);
 
@@ -553,6 +739,14 @@ fn debug_stack_frames(
                                 same_editor_tab: false,
                             },
                         });
+                    } else if let Some(memory_reference) =
+                        instruction_pointer.clone()
+                    {
+                        internal_command.send(InternalCommand::DapDisassemble {
+                            dap_id,
+                            memory_reference,
+                            reveal: true,
+                        });
                     }
                     internal_command.send(InternalCommand::DapFrameScopes {
                         dap_id,
@@ -650,6 +844,124 @@ fn debug_stack_traces(
     .debug_name("debug stack traces")
 }
 
+fn breakpoint_summary(breakpoint: &LapceBreakpoint) -> String {
+    let mut parts = Vec::new();
+    if let Some(condition) = &breakpoint.condition {
+        parts.push(format!("when {condition}"));
+    }
+    if let Some(hit_condition) = &breakpoint.hit_condition {
+        parts.push(format!("hit {hit_condition}"));
+    }
+    if let Some(log_message) = &breakpoint.log_message {
+        parts.push(format!("log \"{log_message}\""));
+    }
+    parts.join(", ")
+}
+
+fn breakpoint_editor_view(
+    window_tab_data: WindowWorkspaceData,
+    path: PathBuf,
+    breakpoint: LapceBreakpoint,
+) -> impl View {
+    let breakpoints = window_tab_data.common.breakpoints;
+    let proxy = window_tab_data.common.proxy.proxy_rpc.clone();
+    let daps = window_tab_data.terminal.debug.daps;
+    let config = window_tab_data.common.config;
+    let line = breakpoint.line;
+    let offset = breakpoint.offset;
+    let condition =
+        create_rw_signal(breakpoint.condition.clone().unwrap_or_default());
+    let hit_condition =
+        create_rw_signal(breakpoint.hit_condition.clone().unwrap_or_default());
+    let log_message =
+        create_rw_signal(breakpoint.log_message.clone().unwrap_or_default());
+    let key = (path.clone(), line);
+    let is_editing = move || {
+        breakpoints
+            .editing
+            .with(|editing| editing.as_ref() == Some(&key))
+    };
+
+    let on_key_down = move |event: &Event| {
+        let Event::KeyDown(key_event) = event else {
+            return;
+        };
+        match key_event.key.logical_key {
+            Key::Named(NamedKey::Enter) => {
+                update_breakpoints(
+                    daps,
+                    proxy.clone(),
+                    breakpoints,
+                    BreakpointAction::Edit {
+                        path: &path,
+                        line,
+                        offset,
+                        condition: Some(condition.get_untracked()),
+                        hit_condition: Some(hit_condition.get_untracked()),
+                        log_message: Some(log_message.get_untracked()),
+                    },
+                );
+                breakpoints.editing.set(None);
+                breakpoints.editing_new.set(false);
+            },
+            Key::Named(NamedKey::Escape) => {
+                // a breakpoint added for the edit only exists with a condition
+                if breakpoints.editing_new.get_untracked() {
+                    update_breakpoints(
+                        daps,
+                        proxy.clone(),
+                        breakpoints,
+                        BreakpointAction::Remove { path: &path, line },
+                    );
+                }
+                breakpoints.editing.set(None);
+                breakpoints.editing_new.set(false);
+            },
+            _ => {},
+        }
+    };
+    let input = move |value: RwSignal<String>, placeholder: &'static str| {
+        let on_key_down = on_key_down.clone();
+        text_input(value)
+            .placeholder(placeholder)
+            .on_event_stop(EventListener::KeyDown, move |event| on_key_down(event))
+            .style(move |s| {
+                s.width_pct(100.0)
+                    .margin_top(4.0)
+                    .border(1.0)
+                    .border_radius(6.0)
+                    .border_color(config.with_color(LapceColor::LAPCE_BORDER))
+            })
+    };
+
+    let condition_input = input(condition, "Condition, e.g. x > 5");
+    let log_message_input = input(log_message, "Log message, e.g. x = {x}");
+    let (condition_id, log_message_id) =
+        (condition_input.id(), log_message_input.id());
+    let is_editing_now = is_editing.clone();
+    create_effect(move |_| {
+        if is_editing_now() {
+            match breakpoints.editing_field.get() {
+                BreakpointEditField::Condition => condition_id.request_focus(),
+                BreakpointEditField::LogMessage => log_message_id.request_focus(),
+            }
+        }
+    });
+
+    stack((
+        condition_input,
+        input(hit_condition, "Hit count, e.g. >= 10"),
+        log_message_input,
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .width_pct(100.0)
+            .padding_horiz(10.0)
+            .padding_bottom(6.0)
+            .apply_if(!is_editing(), |s| s.hide())
+    })
+}
+
 fn breakpoints_view(window_tab_data: WindowWorkspaceData) -> impl View {
     let breakpoints = window_tab_data.common.breakpoints;
     let proxy = window_tab_data.common.proxy.clone();
@@ -663,13 +975,28 @@ fn breakpoints_view(window_tab_data: WindowWorkspaceData) -> impl View {
             dyn_stack(
                 move || breakpoints.view_data(),
                 move |(path, breakpoint)| {
-                    (path.clone(), breakpoint.line, breakpoint.active)
+                    (
+                        path.clone(),
+                        breakpoint.line,
+                        breakpoint.active,
+                        breakpoint.condition.clone(),
+                        breakpoint.hit_condition.clone(),
+                        breakpoint.log_message.clone(),
+                    )
                 },
                 move |(path, breakpoint)| {
                     let line = breakpoint.line;
                     let full_path = path.clone();
                     let full_path_for_jump = path.clone();
                     let full_path_for_close = path.clone();
+                    let full_path_for_edit = path.clone();
+                    let editor = breakpoint_editor_view(
+                        window_tab_data.clone(),
+                        path.clone(),
+                        breakpoint.clone(),
+                    );
+                    let summary = breakpoint_summary(&breakpoint);
+                    let summary_empty = summary.is_empty();
                     let path = if let Some(workspace_path) = workspace.path() {
                         path.strip_prefix(workspace_path)
                             .unwrap_or(&full_path)
@@ -686,116 +1013,271 @@ fn breakpoints_view(window_tab_data: WindowWorkspaceData) -> impl View {
                     let proxy = proxy.clone();
                     let toggle_proxy = proxy.clone();
                     stack((
-                        clickable_icon(
-                            move || LapceIcons::CLOSE,
-                            move || {
-                                update_breakpoints(
-                                    daps,
-                                    proxy.proxy_rpc.clone(),
-                                    breakpoints,
-                                    BreakpointAction::Remove {
-                                        path: &full_path_for_close,
-                                        line,
-                                    },
-                                );
-                                // breakpoints.update(|breakpoints| {
-                                //     if let Some(breakpoints) =
-                                //         breakpoints.get_mut(&
-                                // full_path_for_close)
-                                //     {
-                                //         breakpoints.remove(&line);
-                                //     }
-                                // });
-                            },
-                            || false,
-                            || false,
-                            || "Remove",
-                            config,
-                        )
-                        .on_event_stop(EventListener::PointerDown, |_| {}),
-                        checkbox(move || breakpoint.active, config)
-                            .style(|s| {
-                                s.margin_right(6.0).cursor(CursorStyle::Pointer)
-                            })
-                            .on_click_stop(move |_| {
-                                update_breakpoints(
-                                    daps,
-                                    toggle_proxy.proxy_rpc.clone(),
-                                    breakpoints,
-                                    BreakpointAction::Toggle {
-                                        path: &full_path,
-                                        line,
-                                    },
-                                );
-                                //
-                                // breakpoints.update(|breakpoints| {
-                                //     if let Some(breakpoints) =
-                                //         breakpoints.get_mut(&full_path)
-                                //     {
-                                //         if let Some(breakpoint) =
-                                //             breakpoints.get_mut(&line)
-                                //         {
-                                //             breakpoint.active =
-                                // !breakpoint.active;
-                                //         }
-                                //     }
-                                // });
+                        stack((
+                            clickable_icon(
+                                move || LapceIcons::CLOSE,
+                                move || {
+                                    update_breakpoints(
+                                        daps,
+                                        proxy.proxy_rpc.clone(),
+                                        breakpoints,
+                                        BreakpointAction::Remove {
+                                            path: &full_path_for_close,
+                                            line,
+                                        },
+                                    );
+                                    // breakpoints.update(|breakpoints| {
+                                    //     if let Some(breakpoints) =
+                                    //         breakpoints.get_mut(&
+                                    // full_path_for_close)
+                                    //     {
+                                    //         breakpoints.remove(&line);
+                                    //     }
+                                    // });
+                                },
+                                || false,
+                                || false,
+                                || "Remove",
+                                config,
+                            )
+                            .on_event_stop(EventListener::PointerDown, |_| {}),
+                            checkbox(move || breakpoint.active, config)
+                                .style(|s| {
+                                    s.margin_right(6.0).cursor(CursorStyle::Pointer)
+                                })
+                                .on_click_stop(move |_| {
+                                    update_breakpoints(
+                                        daps,
+                                        toggle_proxy.proxy_rpc.clone(),
+                                        breakpoints,
+                                        BreakpointAction::Toggle {
+                                            path: &full_path,
+                                            line,
+                                        },
+                                    );
+                                    //
+                                    // breakpoints.update(|breakpoints| {
+                                    //     if let Some(breakpoints) =
+                                    //         breakpoints.get_mut(&full_path)
+                                    //     {
+                                    //         if let Some(breakpoint) =
+                                    //             breakpoints.get_mut(&line)
+                                    //         {
+                                    //             breakpoint.active =
+                                    // !breakpoint.active;
+                                    //         }
+                                    //     }
+                                    // });
+                                }),
+                            text(format!("{file_name}:{}", breakpoint.line + 1))
+                                .style(move |s| {
+                                    let size = config.with_icon_size() as f32;
+                                    s.text_ellipsis().max_width(
+                                        available_width.get() as f32
+                                            - 20.0
+                                            - size
+                                            - 6.0
+                                            - size
+                                            - 8.0,
+                                    )
+                                }),
+                            text(folder).style(move |s| {
+                                s.text_ellipsis()
+                                    .flex_grow(1.0)
+                                    .flex_basis(0.0)
+                                    .color(config.with_color(LapceColor::EDITOR_DIM))
+                                    .min_width(0.0)
+                                    .margin_left(6.0)
+                                    .apply_if(folder_empty, |s| s.hide())
                             }),
-                        text(format!("{file_name}:{}", breakpoint.line + 1)).style(
-                            move |s| {
-                                let size = config.with_icon_size() as f32;
-                                s.text_ellipsis().max_width(
-                                    available_width.get() as f32
-                                        - 20.0
-                                        - size
-                                        - 6.0
-                                        - size
-                                        - 8.0,
-                                )
-                            },
-                        ),
-                        text(folder).style(move |s| {
+                            clickable_icon(
+                                move || LapceIcons::DEBUG_BREAKPOINT_EDIT,
+                                move || {
+                                    let key = (full_path_for_edit.clone(), line);
+                                    breakpoints.editing.update(|editing| {
+                                        if editing.as_ref() == Some(&key) {
+                                            *editing = None;
+                                        } else {
+                                            *editing = Some(key);
+                                        }
+                                    });
+                                    breakpoints.editing_new.set(false);
+                                    breakpoints
+                                        .editing_field
+                                        .set(BreakpointEditField::Condition);
+                                },
+                                || false,
+                                || false,
+                                || "Edit Condition",
+                                config,
+                            )
+                            .on_event_stop(EventListener::PointerDown, |_| {}),
+                        ))
+                        .style(move |s| {
+                            s.items_center()
+                                .padding_horiz(10.0)
+                                .width_pct(100.0)
+                                .hover(|s| {
+                                    s.background(config.with_color(
+                                        LapceColor::PANEL_HOVERED_BACKGROUND,
+                                    ))
+                                })
+                        })
+                        .on_click_stop(move |_| {
+                            internal_command.send(InternalCommand::JumpToLocation {
+                                location: EditorLocation {
+                                    path:               full_path_for_jump.clone(),
+                                    position:           Some(EditorPosition::Line(
+                                        line,
+                                    )),
+                                    scroll_offset:      None,
+                                    ignore_unconfirmed: false,
+                                    same_editor_tab:    false,
+                                },
+                            });
+                        }),
+                        text(summary).style(move |s| {
+                            let size = config.with_icon_size() as f32;
+                            s.text_ellipsis()
+                                .padding_left(10.0 + size + 6.0 + size + 8.0)
+                                .padding_right(10.0)
+                                .color(config.with_color(LapceColor::EDITOR_DIM))
+                                .apply_if(summary_empty, |s| s.hide())
+                        }),
+                        editor,
+                    ))
+                    .style(|s| s.flex_col().width_pct(100.0))
+                },
+            )
+            .style(|s| s.flex_col().line_height(1.6).width_pct(100.0)),
+        )
+        .on_resize(move |rect| {
+            let width = rect.width();
+            if available_width.get_untracked() != width {
+                available_width.set(width);
+            }
+        })
+        .style(|s| s.absolute().size_pct(100.0, 100.0)),
+    )
+    .style(|s| s.size_pct(100.0, 100.0))
+    .debug_name("debug breakpoints")
+}
+
+/// `None` stands for the built-in "break on `rust_panic`" toggle.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct ExceptionBreakpointRow {
+    filter:      Option<String>,
+    label:       String,
+    description: Option<String>,
+    checked:     bool,
+}
+
+fn exception_breakpoints_view(window_tab_data: WindowWorkspaceData) -> impl View {
+    let terminal = window_tab_data.terminal.clone();
+    let proxy = window_tab_data.common.proxy.proxy_rpc.clone();
+    let config = window_tab_data.common.config;
+    let rows = move || {
+        let Some(dap) = terminal.get_active_dap(true) else {
+            return Vec::new();
+        };
+        let choices = terminal.debug.exception_breakpoints_of(&dap);
+        let mut rows: Vec<ExceptionBreakpointRow> = dap.capabilities.with(|c| {
+            c.as_ref()
+                .map(|c| {
+                    c.exception_filters()
+                        .iter()
+                        .map(|f| ExceptionBreakpointRow {
+                            filter:      Some(f.filter.clone()),
+                            label:       f.label.clone(),
+                            description: f.description.clone(),
+                            checked:     choices.filters.contains(&f.filter),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        });
+        // track the capabilities, they arrive after the session is created
+        if dap.capabilities.with(|c| c.is_some()) && dap.supports_break_on_panic() {
+            rows.push(ExceptionBreakpointRow {
+                filter:      None,
+                label:       "Break on rust_panic".to_string(),
+                description: Some(
+                    "Stop in the panicking frame when a Rust panic occurs"
+                        .to_string(),
+                ),
+                checked:     choices.break_on_panic,
+            });
+        }
+        rows
+    };
+    let terminal = window_tab_data.terminal.clone();
+    container(
+        scroll(
+            dyn_stack(
+                rows,
+                |row| row.clone(),
+                move |row| {
+                    let terminal = terminal.clone();
+                    let proxy = proxy.clone();
+                    let filter = row.filter.clone();
+                    let description = row.description.clone().unwrap_or_default();
+                    let description_empty = description.is_empty();
+                    stack((
+                        checkbox(move || row.checked, config)
+                            .style(|s| s.margin_right(6.0)),
+                        text(row.label.clone()),
+                        text(description).style(move |s| {
                             s.text_ellipsis()
                                 .flex_grow(1.0)
                                 .flex_basis(0.0)
                                 .color(config.with_color(LapceColor::EDITOR_DIM))
                                 .min_width(0.0)
                                 .margin_left(6.0)
-                                .apply_if(folder_empty, |s| s.hide())
+                                .apply_if(description_empty, |s| s.hide())
                         }),
                     ))
+                    .on_click_stop(move |_| {
+                        let Some(dap) = terminal.get_active_dap(false) else {
+                            return;
+                        };
+                        terminal.debug.update_exception_breakpoints(
+                            &dap,
+                            &proxy,
+                            |choices| match &filter {
+                                Some(filter) => {
+                                    if let Some(index) = choices
+                                        .filters
+                                        .iter()
+                                        .position(|f| f == filter)
+                                    {
+                                        choices.filters.remove(index);
+                                    } else {
+                                        choices.filters.push(filter.clone());
+                                    }
+                                },
+                                None => {
+                                    choices.break_on_panic = !choices.break_on_panic;
+                                },
+                            },
+                        );
+                    })
                     .style(move |s| {
-                        s.items_center().padding_horiz(10.0).width_pct(100.0).hover(
-                            |s| {
+                        s.items_center()
+                            .padding_horiz(10.0)
+                            .width_pct(100.0)
+                            .cursor(CursorStyle::Pointer)
+                            .hover(|s| {
                                 s.background(config.with_color(
                                     LapceColor::PANEL_HOVERED_BACKGROUND,
                                 ))
-                            },
-                        )
-                    })
-                    .on_click_stop(move |_| {
-                        internal_command.send(InternalCommand::JumpToLocation {
-                            location: EditorLocation {
-                                path:               full_path_for_jump.clone(),
-                                position:           Some(EditorPosition::Line(line)),
-                                scroll_offset:      None,
-                                ignore_unconfirmed: false,
-                                same_editor_tab:    false,
-                            },
-                        });
+                            })
                     })
                 },
             )
             .style(|s| s.flex_col().line_height(1.6).width_pct(100.0)),
         )
-        .on_resize(move |rect| {
-            let width = rect.width();
-            if available_width.get_untracked() != width {
-                available_width.set(width);
-            }
-        })
         .style(|s| s.absolute().size_pct(100.0, 100.0)),
     )
     .style(|s| s.size_pct(100.0, 100.0))
-    .debug_name("debug breakpoints")
+    .debug_name("debug exception breakpoints")
 }
@@ -25,6 +25,7 @@ use crate::{
     focus_text::focus_text,
     global_search::{GlobalSearchData, SearchItem},
     listener::Listener,
+    settings::checkbox,
     window_workspace::{Focus, WindowWorkspaceData},
 };
 
@@ -40,6 +41,7 @@ pub fn global_search_panel(
     let is_regex = global_search.common.find.is_regex;
 
     let focus = global_search.common.focus;
+    let search_str = global_search.search_str;
     // let is_focused = move || focus.get() == Focus::Panel(PanelKind::Search);
     let input_view = text_input(global_search.search_str)
         .style(|s| s.width_pct(100.0))
@@ -55,6 +57,56 @@ pub fn global_search_panel(
         });
     global_search.view_id.set(input_view.id());
 
+    let glob_input = move |glob: RwSignal<String>, placeholder: &'static str| {
+        container(
+            text_input(glob)
+                .placeholder(placeholder)
+                .on_event_stop(EventListener::FocusGained, move |_| {
+                    focus.set(Focus::Panel(PanelKind::Search))
+                })
+                .style(|s| s.width_pct(100.0)),
+        )
+        .style(move |s| {
+            s.width_pct(100.0)
+                .margin_top(6.0)
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(config.with_color(LapceColor::LAPCE_BORDER))
+        })
+    };
+    let replace_view = {
+        let global_search = global_search.clone();
+        stack((
+            text_input(global_search.replace_str)
+                .placeholder("Replace")
+                .on_event_stop(EventListener::FocusGained, move |_| {
+                    focus.set(Focus::Panel(PanelKind::Search))
+                })
+                .style(|s| s.width_pct(100.0)),
+            clickable_icon(
+                || LapceIcons::SEARCH_REPLACE_ALL,
+                move || {
+                    global_search.replace_all();
+                },
+                || false,
+                move || search_str.with(|s| s.is_empty()),
+                || "Replace All",
+                config,
+            ),
+        ))
+        .style(move |s| {
+            s.width_pct(100.0)
+                .margin_top(6.0)
+                .padding_right(6.0)
+                .items_center()
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(config.with_color(LapceColor::LAPCE_BORDER))
+        })
+    };
+    let include_view = glob_input(global_search.include, "Files to include");
+    let exclude_view = glob_input(global_search.exclude, "Files to exclude");
+
     stack((
         container(
             stack((
@@ -114,17 +166,70 @@ pub fn global_search_panel(
             }),
         )
         .style(|s| s.width_pct(100.0).padding(10.0)),
+        stack((replace_view, include_view, exclude_view))
+            .style(|s| s.flex_col().width_pct(100.0).padding_horiz(10.0)),
+        search_progress(global_search.clone(), config),
         search_result(global_search, internal_command, config),
     ))
     .style(|s| s.absolute().size_pct(100.0, 100.0).flex_col())
     .debug_name("Global Search Panel")
 }
 
+fn search_progress(
+    global_search: GlobalSearchData,
+    config: WithLapceConfig,
+) -> impl View {
+    let progress = global_search.progress;
+    let search_result = global_search.search_result;
+    let search_str = global_search.search_str;
+    stack((
+        label(move || {
+            let progress = progress.get();
+            let files = search_result.with(|result| result.len());
+            let mut status = format!(
+                "{} results in {files} files, {} files searched",
+                progress.match_count, progress.files_searched
+            );
+            if progress.searching {
+                status.push_str("...");
+            } else if progress.limit_reached {
+                status.push_str(", match limit reached");
+            }
+            status
+        })
+        .style(move |s| {
+            s.flex_grow(1.0)
+                .min_width(0.0)
+                .text_ellipsis()
+                .color(config.with_color(LapceColor::EDITOR_DIM))
+        }),
+        clickable_icon(
+            || LapceIcons::DEBUG_STOP,
+            move || {
+                global_search.cancel();
+            },
+            || false,
+            || false,
+            || "Cancel Search",
+            config,
+        )
+        .style(move |s| s.apply_if(!progress.get().searching, |s| s.hide())),
+    ))
+    .style(move |s| {
+        s.width_pct(100.0)
+            .items_center()
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .apply_if(search_str.with(|s| s.is_empty()), |s| s.hide())
+    })
+}
+
 fn search_result(
     global_search_data: GlobalSearchData,
     internal_command: Listener<InternalCommand>,
     config: WithLapceConfig,
 ) -> impl View {
+    let global_search = global_search_data.clone();
     container({
         scroll({
             virtual_stack(
@@ -133,19 +238,32 @@ fn search_result(
                 move |item| match item {
                     SearchItem::Folder {
                         expanded,
+                        included,
                         file_name,
                         folder,
                         path,
                     } => container(result_fold(
+                        global_search.clone(),
                         config,
                         expanded,
+                        included,
                         file_name,
                         folder,
                         path.clone(),
                     )),
-                    SearchItem::Item { path, m } => {
-                        container(result_item(config, path, m, internal_command))
-                    },
+                    SearchItem::Item {
+                        path,
+                        m,
+                        included,
+                        excluded,
+                    } => container(result_item(
+                        config,
+                        path,
+                        m,
+                        included,
+                        excluded,
+                        internal_command,
+                    )),
                 },
             )
             .style(|s| s.flex_col().min_width_pct(100.0).line_height(1.8))
@@ -159,14 +277,37 @@ fn result_item(
     config: WithLapceConfig,
     path: PathBuf,
     m: SearchMatch,
+    included: RwSignal<bool>,
+    excluded: RwSignal<im::HashSet<(usize, usize)>>,
     internal_command: Listener<InternalCommand>,
 ) -> impl View {
     let line_number = m.line;
     let start = m.start;
     let end = m.end;
     let line_content = m.line_content.clone();
+    let key = (line_number, start);
 
-    focus_text(
+    let checkbox_view = checkbox(
+        move || included.get() && !excluded.with(|excluded| excluded.contains(&key)),
+        config,
+    )
+    .on_click_stop(move |_| {
+        if included.get_untracked() {
+            excluded.update(|excluded| {
+                if excluded.remove(&key).is_none() {
+                    excluded.insert(key);
+                }
+            });
+        } else {
+            included.set(true);
+            excluded.update(|excluded| {
+                excluded.remove(&key);
+            });
+        }
+    })
+    .style(|s| s.margin_right(6.0));
+
+    let text_view = focus_text(
         move || {
             let content = if config
                 .signal(|config| config.ui.trim_search_results_whitespace.signal())
@@ -195,15 +336,9 @@ fn result_item(
         move || config.with_color(LapceColor::EDITOR_FOCUS),
     )
     .style(move |s| {
-        let (hbg, icon_size) = config.signal(|config| {
-            (
-                config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
-                config.ui.icon_size.signal(),
-            )
-        });
-        let icon_size = icon_size.get() as f32;
-        s.margin_left(10.0 + icon_size + 6.0)
-            .hover(|s| s.cursor(CursorStyle::Pointer).background(hbg.get()))
+        let hbg = config.with_color(LapceColor::PANEL_HOVERED_BACKGROUND);
+        s.flex_grow(1.0)
+            .hover(|s| s.cursor(CursorStyle::Pointer).background(hbg))
     })
     .on_click_stop(move |_| {
         internal_command.send(InternalCommand::JumpToLocation {
@@ -217,18 +352,32 @@ fn result_item(
                 same_editor_tab:    false,
             },
         });
+    });
+
+    stack((checkbox_view, text_view)).style(move |s| {
+        let icon_size = config.signal(|config| config.ui.icon_size.signal());
+        let icon_size = icon_size.get() as f32;
+        s.items_center().margin_left(10.0 + icon_size + 6.0)
     })
 }
 
 fn result_fold(
+    global_search: GlobalSearchData,
     config: WithLapceConfig,
     expanded: RwSignal<bool>,
+    included: RwSignal<bool>,
     file_name: String,
     folder: String,
     path: PathBuf,
 ) -> impl View {
     let style_path = path.clone();
+    let preview_path = path.clone();
     stack((
+        checkbox(move || included.get(), config)
+            .on_click_stop(move |_| {
+                included.update(|included| *included = !*included);
+            })
+            .style(|s| s.margin_left(10.0).margin_right(6.0)),
         svg(move || {
             config.with_ui_svg(if expanded.get() {
                 LapceIcons::ITEM_OPENED
@@ -244,8 +393,7 @@ fn result_fold(
                 )
             });
             let size = size.get() as f32;
-            s.margin_left(10.0)
-                .margin_right(6.0)
+            s.margin_right(6.0)
                 .size(size, size)
                 .min_size(size, size)
                 .color(border_color.get())
@@ -272,7 +420,18 @@ fn result_fold(
                     .text_ellipsis()
             }),
         ))
-        .style(move |s| s.min_width(0.0).items_center()),
+        .style(move |s| s.min_width(0.0).flex_grow(1.0).items_center()),
+        clickable_icon(
+            || LapceIcons::SEARCH_REPLACE,
+            move || {
+                global_search.preview_replace(preview_path.clone());
+            },
+            || false,
+            || false,
+            || "Preview Replace",
+            config,
+        )
+        .style(|s| s.margin_right(6.0)),
     ))
     .on_click_stop(move |_| {
         expanded.update(|expanded| *expanded = !*expanded);
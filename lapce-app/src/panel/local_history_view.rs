@@ -0,0 +1,140 @@
+use floem::{
+    View,
+    reactive::{SignalGet, SignalWith},
+    style::CursorStyle,
+    views::{Decorators, container, dyn_stack, label, scroll, stack},
+};
+use lapce_core::{icon::LapceIcons, panel::PanelContainerPosition};
+
+use crate::{
+    app::clickable_icon,
+    config::color::LapceColor,
+    local_history::{LocalHistoryData, LocalHistoryEntry},
+    window_workspace::WindowWorkspaceData,
+};
+
+pub fn local_history_panel(
+    window_tab_data: WindowWorkspaceData,
+    _position: PanelContainerPosition,
+) -> impl View {
+    let local_history = window_tab_data.local_history.clone();
+    let config = local_history.common.config;
+    let path = local_history.path;
+
+    stack((
+        label(move || {
+            path.with(|path| match path {
+                Some(path) => path
+                    .file_name()
+                    .map(|x| x.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                None => "Open a file to see its local history".to_string(),
+            })
+        })
+        .style(move |s| {
+            s.padding_horiz(10.0)
+                .padding_vert(4.0)
+                .width_pct(100.0)
+                .text_ellipsis()
+                .color(config.with_color(LapceColor::EDITOR_DIM))
+                .selectable(false)
+        }),
+        entry_list(local_history),
+    ))
+    .style(|s| s.flex_col().size_full())
+}
+
+fn entry_list(local_history: LocalHistoryData) -> impl View {
+    let config = local_history.common.config;
+    let ui_line_height = local_history.common.ui_line_height;
+    let entries = local_history.entries;
+    let path = local_history.path;
+
+    container(
+        scroll(
+            stack((
+                dyn_stack(
+                    move || entries.get(),
+                    |entry| entry.clone(),
+                    move |entry| entry_row(local_history.clone(), entry),
+                )
+                .style(|s| s.flex_col().width_pct(100.0)),
+                label(|| "No snapshots yet, saving the file takes one".to_string())
+                    .style(move |s| {
+                        s.padding_horiz(10.0)
+                            .color(config.with_color(LapceColor::EDITOR_DIM))
+                            .selectable(false)
+                            .apply_if(
+                                path.with(|path| path.is_none())
+                                    || !entries.with(|entries| entries.is_empty()),
+                                |s| s.hide(),
+                            )
+                    }),
+            ))
+            .style(move |s| {
+                s.flex_col()
+                    .width_pct(100.0)
+                    .line_height(1.6)
+                    .min_height(ui_line_height.get())
+            }),
+        )
+        .style(|s| s.absolute().size_pct(100.0, 100.0)),
+    )
+    .style(|s| s.flex_grow(1.0).flex_basis(0.0).width_pct(100.0))
+}
+
+fn entry_row(
+    local_history: LocalHistoryData,
+    entry: LocalHistoryEntry,
+) -> impl View {
+    let config = local_history.common.config;
+    let date = entry.date();
+    let reason = entry.reason.label();
+
+    stack((
+        label(move || date.clone())
+            .style(|s| s.margin_right(6.0).text_ellipsis().selectable(false)),
+        label(move || reason).style(move |s| {
+            s.flex_grow(1.0)
+                .min_width(0.0)
+                .text_ellipsis()
+                .color(config.with_color(LapceColor::EDITOR_DIM))
+                .selectable(false)
+        }),
+        {
+            let local_history = local_history.clone();
+            let entry = entry.clone();
+            clickable_icon(
+                || LapceIcons::LOCAL_HISTORY_COMPARE,
+                move || local_history.compare(&entry),
+                || false,
+                || false,
+                || "Compare with Current",
+                config,
+            )
+        },
+        {
+            let local_history = local_history.clone();
+            let entry = entry.clone();
+            clickable_icon(
+                || LapceIcons::LOCAL_HISTORY_RESTORE,
+                move || local_history.restore(&entry),
+                || false,
+                || false,
+                || "Restore",
+                config,
+            )
+        },
+    ))
+    .on_click_stop(move |_| local_history.compare(&entry))
+    .style(move |s| {
+        s.padding_horiz(10.0)
+            .width_pct(100.0)
+            .items_center()
+            .hover(|s| {
+                s.cursor(CursorStyle::Pointer).background(
+                    config.with_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            })
+    })
+}
@@ -4,6 +4,7 @@ pub mod debug_view;
 pub mod document_symbol;
 pub mod global_search_view;
 pub mod implementation_view;
+mod local_history_view;
 pub mod plugin_view;
 pub mod position;
 pub mod problem_view;
@@ -11,4 +12,5 @@ pub mod references_view;
 mod rust_build_panel;
 pub mod source_control_view;
 pub mod terminal_view;
+mod test_view;
 pub mod view;
@@ -1,14 +1,108 @@
 use cozy_floem::views::tree_with_panel::tree_with_panel;
-use floem::{View, prelude::Decorators};
-use lapce_core::panel::PanelContainerPosition;
+use floem::{
+    View,
+    prelude::Decorators,
+    reactive::SignalGet,
+    style::CursorStyle,
+    views::{dyn_stack, label, stack},
+};
+use lapce_core::{icon::LapceIcons, panel::PanelContainerPosition};
 
-use crate::window_workspace::WindowWorkspaceData;
+use crate::{
+    app::clickable_icon, config::color::LapceColor, rust_build::CargoCommand,
+    window_workspace::WindowWorkspaceData,
+};
 
-#[allow(dead_code)]
 pub fn build_panel(
     window_tab_data: WindowWorkspaceData,
     _position: PanelContainerPosition,
 ) -> impl View {
-    let data = window_tab_data.build_data;
-    tree_with_panel(data).style(|x| x.size_full())
+    let data = window_tab_data.build_data.clone();
+    stack((
+        build_toolbar(window_tab_data),
+        tree_with_panel(data.tree).style(|x| x.size_full()),
+    ))
+    .style(|s| s.flex_col().size_full())
+}
+
+fn build_toolbar(window_tab_data: WindowWorkspaceData) -> impl View {
+    let config = window_tab_data.common.config;
+    let build_data = window_tab_data.build_data.clone();
+    let running = build_data.running;
+    let exit_code = build_data.exit_code;
+    let rev = build_data.rev;
+
+    stack((
+        dyn_stack(
+            || CargoCommand::ALL,
+            |command| *command,
+            move |command| {
+                let window_tab_data = window_tab_data.clone();
+                label(move || command.name().to_string())
+                    .on_click_stop(move |_| {
+                        window_tab_data.run_cargo_command(command);
+                    })
+                    .style(move |s| {
+                        let (border, bg, abg) = config.signal(|config| {
+                            (
+                                config.color(LapceColor::LAPCE_BORDER),
+                                config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                                config.color(
+                                    LapceColor::PANEL_HOVERED_ACTIVE_BACKGROUND,
+                                ),
+                            )
+                        });
+                        s.margin_right(6.0)
+                            .padding_horiz(8.0)
+                            .border(1.0)
+                            .border_radius(6.0)
+                            .border_color(border.get())
+                            .hover(|s| {
+                                s.cursor(CursorStyle::Pointer).background(bg.get())
+                            })
+                            .active(|s| s.background(abg.get()))
+                            .selectable(false)
+                    })
+            },
+        )
+        .style(|s| s.items_center()),
+        label(move || {
+            if let Some(command) = running.get() {
+                format!("Running cargo {}", command.name())
+            } else if rev.get() == 0 {
+                String::new()
+            } else {
+                match exit_code.get() {
+                    Some(0) => "Finished".to_string(),
+                    Some(code) => format!("Failed with exit code {code}"),
+                    None => "Stopped".to_string(),
+                }
+            }
+        })
+        .style(move |s| {
+            s.flex_grow(1.0)
+                .min_width(0.0)
+                .margin_left(6.0)
+                .text_ellipsis()
+                .color(config.with_color(LapceColor::EDITOR_DIM))
+                .selectable(false)
+        }),
+        clickable_icon(
+            || LapceIcons::DEBUG_STOP,
+            move || {
+                build_data.cancel();
+            },
+            || false,
+            move || running.get().is_none(),
+            || "Stop",
+            config,
+        )
+        .style(|s| s.margin_right(4.0)),
+    ))
+    .style(|s| {
+        s.padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_pct(100.0)
+            .items_center()
+    })
 }
@@ -10,27 +10,27 @@ use floem::{
     peniko::kurbo::Rect,
     reactive::{Scope, SignalGet, SignalTrack, SignalUpdate, SignalWith},
     style::{CursorStyle, Style},
-    views::{Decorators, container, dyn_stack, label, scroll, stack, svg, text},
+    views::{Decorators, container, dyn_stack, label, scroll, stack, svg},
 };
 use lapce_core::{
     icon::LapceIcons,
     panel::{PanelContainerPosition, PanelKind, PanelSection},
 };
-use lapce_rpc::source_control::FileDiff;
+use lapce_rpc::source_control::{DiffInfo, FileDiff, GitCommitInfo, StageSelection};
 use log::error;
 
-use super::view::foldable_panel_section;
+use super::view::PanelBuilder;
 use crate::{
+    app::clickable_icon,
     command::{CommandKind, InternalCommand, LapceCommand, LapceWorkbenchCommand},
     config::color::LapceColor,
     editor::view::editor_view,
-    settings::checkbox,
-    source_control::SourceControlData,
+    source_control::{SourceControlData, commit_date},
     window_workspace::{Focus, WindowWorkspaceData},
 };
 pub fn source_control_panel(
     window_tab_data: WindowWorkspaceData,
-    _position: PanelContainerPosition,
+    position: PanelContainerPosition,
 ) -> impl View {
     let scope = window_tab_data.scope;
     let config = window_tab_data.common.config;
@@ -54,6 +54,7 @@ pub fn source_control_panel(
         doc.with(|b| b.len() == 0)
     });
     let debug_breakline = scope.create_memo(move |_| None);
+    let multi_root = source_control.common.workspace.is_multi_root();
 
     stack((
         stack((
@@ -229,13 +230,35 @@ pub fn source_control_panel(
             },
         ))
         .style(|s| s.flex_col().width_pct(100.0).padding(10.0)),
-        foldable_panel_section(
-            text("Changes"),
-            file_diffs_view(source_control, scope),
-            window_tab_data.panel.section_open(PanelSection::Changes),
-            config,
-        )
-        .style(|s| s.flex_col().size_pct(100.0, 100.0)),
+        PanelBuilder::new(config, position)
+            .add_height_style(
+                "Repositories",
+                100.0,
+                repositories_view(source_control.clone()),
+                window_tab_data
+                    .panel
+                    .section_open(PanelSection::Repositories),
+                move |s| s.apply_if(!multi_root, |s| s.hide()),
+            )
+            .add(
+                "Staged Changes",
+                file_diffs_view(source_control.clone(), scope, true),
+                window_tab_data
+                    .panel
+                    .section_open(PanelSection::StagedChanges),
+            )
+            .add(
+                "Changes",
+                file_diffs_view(source_control.clone(), scope, false),
+                window_tab_data.panel.section_open(PanelSection::Changes),
+            )
+            .add(
+                "History",
+                history_view(source_control),
+                window_tab_data.panel.section_open(PanelSection::History),
+            )
+            .build()
+            .style(|s| s.flex_col().size_pct(100.0, 100.0)),
     ))
     .on_event_stop(EventListener::PointerDown, move |_| {
         if focus.get_untracked() != Focus::Panel(PanelKind::SourceControl) {
@@ -246,8 +269,60 @@ pub fn source_control_panel(
     .debug_name("Source Control Panel")
 }
 
-fn file_diffs_view(source_control: SourceControlData, scope: Scope) -> impl View {
-    let file_diffs = source_control.file_diffs;
+/// The branch and the number of changes of the repository of each folder of
+/// a multi-root workspace.
+fn repositories_view(source_control: SourceControlData) -> impl View {
+    let config = source_control.common.config;
+    let repositories = source_control.repositories;
+
+    let view_fn = move |(root, diff): (PathBuf, DiffInfo)| {
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let detail = match diff.diffs.len() {
+            0 => diff.head.clone(),
+            1 => format!("{}, 1 change", diff.head),
+            changes => format!("{}, {changes} changes", diff.head),
+        };
+        stack((
+            label(move || name.clone())
+                .style(|s| s.margin_right(6.0).text_ellipsis().selectable(false)),
+            label(move || detail.clone()).style(move |s| {
+                s.flex_grow(1.0)
+                    .min_width(0.0)
+                    .text_ellipsis()
+                    .color(config.with_color(LapceColor::EDITOR_DIM))
+                    .selectable(false)
+            }),
+        ))
+        .style(|s| s.padding_horiz(10.0).width_pct(100.0).items_center())
+    };
+
+    container({
+        scroll({
+            dyn_stack(
+                move || repositories.get(),
+                |(root, diff)| (root.clone(), diff.head.clone(), diff.diffs.len()),
+                view_fn,
+            )
+            .style(|s| s.line_height(1.6).flex_col().width_pct(100.0))
+        })
+        .style(|s| s.absolute().size_pct(100.0, 100.0))
+    })
+    .style(|s| s.size_pct(100.0, 100.0))
+}
+
+fn file_diffs_view(
+    source_control: SourceControlData,
+    scope: Scope,
+    staged: bool,
+) -> impl View {
+    let file_diffs = if staged {
+        source_control.staged_diffs
+    } else {
+        source_control.unstaged_diffs
+    };
     let config = source_control.common.config;
     let workspace = source_control.common.workspace.clone();
     let panel_rect = scope.create_rw_signal(Rect::ZERO);
@@ -255,19 +330,15 @@ fn file_diffs_view(source_control: SourceControlData, scope: Scope) -> impl View
     let lapce_command = source_control.common.lapce_command;
     let internal_command = source_control.common.internal_command;
 
-    let view_fn = move |(path, (diff, checked)): (PathBuf, (FileDiff, bool))| {
+    let view_fn = move |(path, diff): (PathBuf, FileDiff)| {
         let diff_for_style = diff.clone();
         let full_path = path.clone();
         let diff_for_menu = diff.clone();
+        let diff_for_stage = diff.clone();
         let path_for_click = full_path.clone();
+        let source_control = source_control.clone();
 
-        let path = if let Some(workspace_path) = workspace.path() {
-            path.strip_prefix(workspace_path)
-                .unwrap_or(&full_path)
-                .to_path_buf()
-        } else {
-            path
-        };
+        let path = workspace.relative_path(&path).unwrap_or(path);
         let file_name = path
             .file_name()
             .and_then(|s| s.to_str())
@@ -280,15 +351,6 @@ fn file_diffs_view(source_control: SourceControlData, scope: Scope) -> impl View
             .to_string();
         let style_path = path.clone();
         stack((
-            checkbox(move || checked, config)
-                .style(|s| s.hover(|s| s.cursor(CursorStyle::Pointer)))
-                .on_click_stop(move |_| {
-                    file_diffs.update(|diffs| {
-                        if let Some((_, checked)) = diffs.get_mut(&full_path) {
-                            *checked = !*checked;
-                        }
-                    });
-                }),
             svg(move || config.with_file_svg(&path).0).style(move |s| {
                 let (size, file_svg) = config.signal(|config| {
                     (config.ui.icon_size.signal(), config.icon_theme.signal())
@@ -307,6 +369,7 @@ fn file_diffs_view(source_control: SourceControlData, scope: Scope) -> impl View
                     - size
                     - 6.0
                     - size
+                    - 10.0
                     - 6.0
                     - 10.0
                     - size
@@ -324,6 +387,45 @@ fn file_diffs_view(source_control: SourceControlData, scope: Scope) -> impl View
                     .min_width(0.0)
                     .selectable(false)
             }),
+            clickable_icon(
+                move || {
+                    if staged {
+                        LapceIcons::SCM_CHANGE_REMOVE
+                    } else {
+                        LapceIcons::SCM_CHANGE_ADD
+                    }
+                },
+                move || {
+                    // Both sides of a rename move between the index and the
+                    // working copy together
+                    let paths = match &diff_for_stage {
+                        FileDiff::Renamed(new, old) => {
+                            vec![new.clone(), old.clone()]
+                        },
+                        FileDiff::Modified(p)
+                        | FileDiff::Added(p)
+                        | FileDiff::Deleted(p) => vec![p.clone()],
+                    };
+                    for path in paths {
+                        if staged {
+                            source_control.unstage(path, StageSelection::File);
+                        } else {
+                            source_control.stage(path, StageSelection::File);
+                        }
+                    }
+                },
+                || false,
+                || false,
+                move || {
+                    if staged {
+                        "Unstage Changes"
+                    } else {
+                        "Stage Changes"
+                    }
+                },
+                config,
+            )
+            .style(|s| s.margin_right(6.0)),
             container({
                 svg(move || {
                     let svg = match &diff {
@@ -361,6 +463,7 @@ fn file_diffs_view(source_control: SourceControlData, scope: Scope) -> impl View
         .on_click_stop(move |_| {
             internal_command.send(InternalCommand::OpenFileChanges {
                 path: path_for_click.clone(),
+                staged,
             });
         })
         .on_event_cont(EventListener::PointerDown, move |event| {
@@ -376,7 +479,7 @@ fn file_diffs_view(source_control: SourceControlData, scope: Scope) -> impl View
             };
 
             if let Event::PointerDown(pointer_event) = event {
-                if pointer_event.button.is_secondary() {
+                if pointer_event.button.is_secondary() && !staged {
                     let menu = Menu::new("")
                         .entry(MenuItem::new("Discard Changes").action(discard));
                     show_context_menu(menu, None);
@@ -403,9 +506,7 @@ fn file_diffs_view(source_control: SourceControlData, scope: Scope) -> impl View
         scroll({
             dyn_stack(
                 move || file_diffs.get(),
-                |(path, (diff, checked))| {
-                    (path.to_path_buf(), diff.clone(), *checked)
-                },
+                |(path, diff)| (path.to_path_buf(), diff.clone()),
                 view_fn,
             )
             .style(|s| s.line_height(1.6).flex_col().width_pct(100.0))
@@ -417,3 +518,217 @@ fn file_diffs_view(source_control: SourceControlData, scope: Scope) -> impl View
     })
     .style(|s| s.size_pct(100.0, 100.0))
 }
+
+fn history_view(source_control: SourceControlData) -> impl View {
+    let config = source_control.common.config;
+    let workspace = source_control.common.workspace.clone();
+    let internal_command = source_control.common.internal_command;
+    let history = source_control.history;
+    let history_file = source_control.history_file;
+    let history_done = source_control.history_done;
+    let history_commit = source_control.history_commit;
+
+    let header = stack((
+        label(move || match history_file.get() {
+            Some(path) => path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_string(),
+            None => "Workspace".to_string(),
+        })
+        .style(|s| s.flex_grow(1.0).min_width(0.0).text_ellipsis()),
+        {
+            let source_control = source_control.clone();
+            clickable_icon(
+                || LapceIcons::CLOSE,
+                move || {
+                    source_control.show_history(None);
+                },
+                || false,
+                || false,
+                || "Show Workspace History",
+                config,
+            )
+            .style(move |s| s.apply_if(history_file.get().is_none(), |s| s.hide()))
+        },
+    ))
+    .style(move |s| {
+        s.padding_horiz(10.0)
+            .width_pct(100.0)
+            .items_center()
+            .color(config.with_color(LapceColor::EDITOR_DIM))
+    });
+
+    let commit_view = move |commit: GitCommitInfo| {
+        let id = commit.id.clone();
+        let summary = commit.summary().to_string();
+        let detail = format!("{}, {}", commit.author, commit_date(commit.time));
+        let files = commit.files.clone();
+        let workspace = workspace.clone();
+        let is_selected = {
+            let id = id.clone();
+            move || history_commit.with(|c| c.as_ref() == Some(&id))
+        };
+
+        let open_all = {
+            let id = id.clone();
+            let files = files.clone();
+            move || {
+                for diff in files.iter() {
+                    internal_command.send(InternalCommand::OpenCommitFileChanges {
+                        commit: id.clone(),
+                        diff:   diff.clone(),
+                    });
+                }
+            }
+        };
+
+        let file_view = {
+            let id = id.clone();
+            move |diff: FileDiff| {
+                let full_path = diff.path().clone();
+                let path = workspace
+                    .path()
+                    .and_then(|w| full_path.strip_prefix(w).ok())
+                    .unwrap_or(&full_path)
+                    .to_path_buf();
+                let file_name = path
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("")
+                    .to_string();
+                let folder = path
+                    .parent()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("")
+                    .to_string();
+                let id = id.clone();
+                let diff_for_click = diff.clone();
+                stack((
+                    svg(move || config.with_file_svg(&path).0).style(move |s| {
+                        let size = config.with_icon_size() as f32;
+                        s.min_width(size).size(size, size).margin(6.0)
+                    }),
+                    label(move || file_name.clone())
+                        .style(|s| s.margin_right(6.0).selectable(false)),
+                    label(move || folder.clone()).style(move |s| {
+                        s.text_ellipsis()
+                            .flex_grow(1.0)
+                            .flex_basis(0.0)
+                            .min_width(0.0)
+                            .color(config.with_color(LapceColor::EDITOR_DIM))
+                            .selectable(false)
+                    }),
+                ))
+                .on_click_stop(move |_| {
+                    internal_command.send(InternalCommand::OpenCommitFileChanges {
+                        commit: id.clone(),
+                        diff:   diff_for_click.clone(),
+                    });
+                })
+                .style(move |s| {
+                    s.padding_left(20.0)
+                        .padding_right(10.0)
+                        .width_pct(100.0)
+                        .items_center()
+                        .hover(|s| {
+                            s.background(
+                                config.with_color(
+                                    LapceColor::PANEL_HOVERED_BACKGROUND,
+                                ),
+                            )
+                        })
+                })
+            }
+        };
+
+        stack((
+            stack((
+                label(move || summary.clone()).style(|s| {
+                    s.text_ellipsis()
+                        .margin_right(6.0)
+                        .min_width(0.0)
+                        .selectable(false)
+                }),
+                label(move || detail.clone()).style(move |s| {
+                    s.text_ellipsis()
+                        .flex_grow(1.0)
+                        .flex_basis(0.0)
+                        .min_width(0.0)
+                        .color(config.with_color(LapceColor::EDITOR_DIM))
+                        .selectable(false)
+                }),
+            ))
+            .on_click_stop(move |_| {
+                history_commit.update(|c| {
+                    *c = if c.as_ref() == Some(&id) {
+                        None
+                    } else {
+                        Some(id.clone())
+                    };
+                });
+            })
+            .on_event_cont(EventListener::PointerDown, move |event| {
+                if let Event::PointerDown(pointer_event) = event {
+                    if pointer_event.button.is_secondary() {
+                        let open_all = open_all.clone();
+                        let menu = Menu::new("")
+                            .entry(MenuItem::new("Open Changes").action(open_all));
+                        show_context_menu(menu, None);
+                    }
+                }
+            })
+            .style(move |s| {
+                s.padding_horiz(10.0)
+                    .width_pct(100.0)
+                    .items_center()
+                    .hover(|s| {
+                        s.background(
+                            config.with_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+            }),
+            dyn_stack(move || files.clone(), |diff| diff.clone(), file_view).style(
+                move |s| {
+                    s.flex_col()
+                        .width_pct(100.0)
+                        .apply_if(!is_selected(), |s| s.hide())
+                },
+            ),
+        ))
+        .style(|s| s.flex_col().width_pct(100.0))
+    };
+
+    stack((
+        header,
+        container({
+            scroll(
+                stack((
+                    dyn_stack(
+                        move || history.get(),
+                        |commit| commit.id.clone(),
+                        commit_view,
+                    )
+                    .style(|s| s.flex_col().width_pct(100.0)),
+                    label(|| "Load More".to_string())
+                        .on_click_stop(move |_| {
+                            source_control.load_history(false);
+                        })
+                        .style(move |s| {
+                            s.padding_horiz(10.0)
+                                .width_pct(100.0)
+                                .color(config.with_color(LapceColor::EDITOR_LINK))
+                                .hover(|s| s.cursor(CursorStyle::Pointer))
+                                .selectable(false)
+                                .apply_if(history_done.get(), |s| s.hide())
+                        }),
+                ))
+                .style(|s| s.line_height(1.6).flex_col().width_pct(100.0)),
+            )
+            .style(|s| s.absolute().size_pct(100.0, 100.0))
+        })
+        .style(|s| s.size_pct(100.0, 100.0)),
+    ))
+    .style(|s| s.flex_col().size_pct(100.0, 100.0))
+}
@@ -1,3 +1,4 @@
+use alacritty_terminal::index::Direction;
 use floem::{
     View, ViewId,
     action::show_context_menu,
@@ -5,21 +6,29 @@ use floem::{
     kurbo::Size,
     menu::{Menu, MenuItem},
     prelude::palette,
-    reactive::{SignalGet, SignalUpdate, SignalWith, create_rw_signal},
+    reactive::{
+        SignalGet, SignalTrack, SignalUpdate, SignalWith, create_effect,
+        create_rw_signal,
+    },
     views::{
         Decorators, container, dyn_stack, empty, label,
         scroll::{Thickness, VerticalScrollAsHorizontal, scroll},
-        stack, svg, tab,
+        stack, svg, tab, text_input,
     },
 };
-use lapce_core::{icon::LapceIcons, id::TerminalTabId, panel::PanelKind};
+use lapce_core::{
+    icon::LapceIcons, id::TerminalTabId, main_split::SplitDirection,
+    panel::PanelKind,
+};
 
 use crate::{
     app::clickable_icon,
     command::{InternalCommand, LapceWorkbenchCommand},
     config::color::LapceColor,
     listener::Listener,
-    terminal::{data::TerminalData, panel::TerminalPanelData, view::terminal_view},
+    terminal::{
+        data::TerminalData, shell_integration::ShellCommand, view::terminal_view,
+    },
     window_workspace::{Focus, WindowWorkspaceData},
 };
 
@@ -49,22 +58,35 @@ fn terminal_tab_header(window_tab_data: WindowWorkspaceData) -> impl View {
     let icon_width = create_rw_signal(0.0);
     let scroll_size = create_rw_signal(Size::ZERO);
     let workbench_command = window_tab_data.common.workbench_command;
+    let internal_command = window_tab_data.common.internal_command;
 
     stack((
         scroll(dyn_stack(
             move || terminal.tab_infos.with(|info| info.tabs.clone()),
-            |tab| tab.term_id,
+            |tab| tab.terminal_tab_id,
             move |tab| {
                 let terminal = terminal.clone();
                 let local_terminal = terminal.clone();
-                let terminal_tab_id = tab.term_id;
+                let terminal_tab_id = tab.terminal_tab_id;
+                // the tab is named after its active terminal
+                let active_terminal = move || {
+                    tab_info.with(|info| {
+                        info.tab(terminal_tab_id)
+                            .and_then(|tab| tab.active_terminal().cloned())
+                    })
+                };
 
-                let title = {
-                    let tab = tab.clone();
-                    move || tab.content_tip().0
+                let title = move || {
+                    active_terminal()
+                        .map(|terminal| terminal.content_tip().0)
+                        .unwrap_or_default()
                 };
 
-                let svg_string = move || tab.icon();
+                let svg_string = move || {
+                    active_terminal()
+                        .map(|terminal| terminal.icon())
+                        .unwrap_or(LapceIcons::TERMINAL)
+                };
                 stack((
                     container({
                         stack((
@@ -169,15 +191,35 @@ fn terminal_tab_header(window_tab_data: WindowWorkspaceData) -> impl View {
             let size = scroll_size.get();
             s.size(size.width, size.height)
         }),
-        container(clickable_icon(
-            || LapceIcons::ADD,
-            move || {
-                workbench_command.send(LapceWorkbenchCommand::NewTerminalTab);
-            },
-            || false,
-            || false,
-            || "New Terminal",
-            config,
+        stack((
+            clickable_icon(
+                || LapceIcons::ADD,
+                move || {
+                    workbench_command.send(LapceWorkbenchCommand::NewTerminalTab);
+                },
+                || false,
+                || false,
+                || "New Terminal",
+                config,
+            ),
+            clickable_icon(
+                || LapceIcons::SPLIT_HORIZONTAL,
+                move || {
+                    if let Some(term_id) = tab_info.with_untracked(|info| {
+                        info.active_tab().map(|(_, terminal)| terminal.term_id)
+                    }) {
+                        internal_command.send(InternalCommand::SplitTerminal {
+                            term_id,
+                            direction: SplitDirection::Vertical,
+                        });
+                    }
+                },
+                || false,
+                move || tab_info.with(|info| info.tabs.is_empty()),
+                || "Split Terminal",
+                config,
+            )
+            .style(|s| s.margin_left(6.0)),
         ))
         .on_resize(move |rect| {
             let width = rect.size().width;
@@ -215,51 +257,206 @@ fn terminal_tab_header(window_tab_data: WindowWorkspaceData) -> impl View {
     })
 }
 
+/// The terminals of a tab, side by side or stacked depending on how the tab
+/// was split
+fn terminal_tab_splits(
+    window_tab_data: WindowWorkspaceData,
+    terminal_tab_id: TerminalTabId,
+) -> impl View {
+    let terminal_panel_data = window_tab_data.terminal.clone();
+    let config = terminal_panel_data.common.config;
+    let tab_info = terminal_panel_data.tab_infos;
+    let direction = move || {
+        tab_info.with(|info| info.tab(terminal_tab_id).map(|tab| tab.direction))
+    };
+    dyn_stack(
+        move || {
+            tab_info.with(|info| {
+                info.tab(terminal_tab_id)
+                    .map(|tab| tab.terminals.clone())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .enumerate()
+            })
+        },
+        |(_, terminal)| terminal.term_id,
+        move |(index, terminal)| {
+            terminal_tab_split(window_tab_data.clone(), terminal).style(move |s| {
+                s.apply_if(index > 0, |s| {
+                    let border = config.with_color(LapceColor::LAPCE_BORDER);
+                    match direction() {
+                        Some(SplitDirection::Horizontal) => {
+                            s.border_top(1.0).border_color(border)
+                        },
+                        _ => s.border_left(1.0).border_color(border),
+                    }
+                })
+            })
+        },
+    )
+    .style(move |s| {
+        s.size_pct(100.0, 100.0)
+            .apply_if(direction() == Some(SplitDirection::Horizontal), |s| {
+                s.flex_col()
+            })
+    })
+}
+
 fn terminal_tab_split(
-    terminal_panel_data: TerminalPanelData,
+    window_tab_data: WindowWorkspaceData,
     terminal: TerminalData,
 ) -> impl View {
+    let terminal_panel_data = window_tab_data.terminal.clone();
     let internal_command = terminal_panel_data.common.internal_command;
     let workspace = terminal_panel_data.workspace.clone();
-    let terminal_panel_data = terminal_panel_data.clone();
     let config = terminal.common.config;
+    let find = terminal_find(window_tab_data, terminal.clone());
 
     let bg = config.signal(|config| config.color(LapceColor::TERMINAL_BACKGROUND));
-    container({
-        let terminal_id = terminal.term_id;
-        let terminal_view = terminal_view(
-            terminal.term_id,
-            terminal_panel_data,
-            internal_command,
-            workspace.clone(),
-            terminal.clone(),
-        );
-        let view_id = terminal_view.id();
-        let have_task = terminal.data.with_untracked(|x| x.run_debug.is_some());
-        terminal_view
-            .on_secondary_click_stop(move |_| {
-                if have_task {
-                    tab_secondary_click(internal_command, view_id, terminal_id);
-                }
-            })
-            .on_event(EventListener::PointerWheel, move |event| {
-                if let Event::PointerWheel(pointer_event) = event {
-                    terminal.clone().wheel_scroll(pointer_event.delta.y);
-                    EventPropagation::Stop
-                } else {
-                    EventPropagation::Continue
-                }
-            })
-            .style(|s| s.size_pct(100.0, 100.0))
+    stack((
+        container({
+            let terminal_id = terminal.term_id;
+            let terminal_view = terminal_view(
+                terminal.term_id,
+                terminal_panel_data,
+                internal_command,
+                workspace.clone(),
+                terminal.clone(),
+            );
+            let view_id = terminal_view.id();
+            let have_task = terminal.data.with_untracked(|x| x.run_debug.is_some());
+            let data = terminal.clone();
+            terminal_view
+                .on_secondary_click_stop(move |event| {
+                    let command = event
+                        .point()
+                        .and_then(|point| data.command_at(point.y))
+                        .map(|command| (data.clone(), command));
+                    if have_task || command.is_some() {
+                        tab_secondary_click(
+                            internal_command,
+                            view_id,
+                            terminal_id,
+                            have_task,
+                            command,
+                        );
+                    }
+                })
+                .on_event(EventListener::PointerWheel, move |event| {
+                    if let Event::PointerWheel(pointer_event) = event {
+                        terminal.clone().wheel_scroll(pointer_event.delta.y);
+                        EventPropagation::Stop
+                    } else {
+                        EventPropagation::Continue
+                    }
+                })
+                .style(|s| s.size_pct(100.0, 100.0))
+        })
+        .style(move |s| {
+            s.size_pct(100.0, 100.0)
+                .padding_horiz(10.0)
+                .background(bg.get())
+        }),
+        find,
+    ))
+    .style(|s| {
+        s.flex_grow(1.0)
+            .flex_basis(0.0)
+            .min_width(0.0)
+            .min_height(0.0)
     })
+}
+
+/// The find bar over the top right corner of a terminal
+fn terminal_find(
+    window_tab_data: WindowWorkspaceData,
+    terminal: TerminalData,
+) -> impl View {
+    let config = terminal.common.config;
+    let find = terminal.find;
+    let term_id = terminal.term_id;
+    let terminal_panel_data = window_tab_data.terminal.clone();
+
+    let input = text_input(find.pattern)
+        .keyboard_navigable()
+        .on_event_stop(EventListener::KeyDown, move |event| {
+            if let Event::KeyDown(key_event) = event {
+                window_tab_data.key_down(key_event);
+            }
+        })
+        .on_event_cont(EventListener::FocusGained, move |_| {
+            find.focus.set(true);
+            // the keys typed in the find bar go to the active terminal
+            terminal_panel_data.focus_terminal(term_id);
+        })
+        .on_event_cont(EventListener::FocusLost, move |_| {
+            find.focus.set(false);
+        })
+        .style(|s| s.flex_grow(1.0).min_width(0.0));
+    find.view_id.set(Some(input.id()));
+
+    {
+        let terminal = terminal.clone();
+        let pattern = find.pattern;
+        create_effect(move |_| {
+            pattern.track();
+            terminal.update_search();
+        });
+    }
+
+    let next = terminal.clone();
+    let previous = terminal.clone();
+    stack((
+        input,
+        clickable_icon(
+            || LapceIcons::SEARCH_BACKWARD,
+            move || previous.search_next(Direction::Left),
+            || false,
+            || false,
+            || "Previous Match",
+            config,
+        ),
+        clickable_icon(
+            || LapceIcons::SEARCH_FORWARD,
+            move || next.search_next(Direction::Right),
+            || false,
+            || false,
+            || "Next Match",
+            config,
+        ),
+        clickable_icon(
+            || LapceIcons::SEARCH_CLEAR,
+            move || terminal.close_find(),
+            || false,
+            || false,
+            || "Close",
+            config,
+        )
+        .style(|s| s.padding_right(6.0)),
+    ))
     .style(move |s| {
-        s.size_pct(100.0, 100.0)
-            .padding_horiz(10.0)
+        let (border_color, error_color, bg) = config.signal(|config| {
+            (
+                config.color(LapceColor::LAPCE_BORDER),
+                config.color(LapceColor::LAPCE_ERROR),
+                config.color(LapceColor::EDITOR_BACKGROUND),
+            )
+        });
+        s.absolute()
+            .inset_top(4.0)
+            .inset_right(16.0)
+            .width(260.0)
+            .height(25.0)
+            .items_center()
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(if find.error.with(|error| error.is_some()) {
+                error_color.get()
+            } else {
+                border_color.get()
+            })
             .background(bg.get())
-        // .apply_if(index.get() > 0, |s| {
-        //     s.border_left(1.0)
-        //         .border_color(config.with_color(LapceColor::LAPCE_BORDER))
-        // })
+            .apply_if(!find.visible.get(), |s| s.hide())
     })
 }
 
@@ -273,8 +470,8 @@ fn terminal_tab_content(window_tab_data: WindowWorkspaceData) -> impl View {
             })
         },
         move || terminal.tab_infos.with(|info| info.tabs.clone()),
-        |tab| tab.term_id,
-        move |tab| terminal_tab_split(terminal.clone(), tab),
+        |tab| tab.terminal_tab_id,
+        move |tab| terminal_tab_splits(window_tab_data.clone(), tab.terminal_tab_id),
     )
     .style(|s| s.size_pct(100.0, 100.0))
     .debug_name("terminal_tab_content")
@@ -284,20 +481,50 @@ fn tab_secondary_click(
     internal_command: Listener<InternalCommand>,
     view_id: ViewId,
     terminal_id: TerminalTabId,
+    have_task: bool,
+    command: Option<(TerminalData, ShellCommand)>,
 ) {
     let mut menu = Menu::new("");
-    menu = menu
-        .entry(MenuItem::new("Stop").action(move || {
-            internal_command.send(InternalCommand::StopTerminal { terminal_id });
-        }))
-        .entry(MenuItem::new("Restart").action(move || {
-            internal_command.send(InternalCommand::RestartTerminal { terminal_id });
-        }))
-        .entry(MenuItem::new("Clear All").action(move || {
-            internal_command.send(InternalCommand::ClearTerminalBuffer {
-                view_id,
-                terminal_id,
-            });
-        }));
+    if let Some((terminal, command)) = command {
+        let copy_command = {
+            let terminal = terminal.clone();
+            let command = command.clone();
+            move || terminal.copy_command(&command)
+        };
+        let copy_output = {
+            let terminal = terminal.clone();
+            let command = command.clone();
+            move || terminal.copy_command_output(&command)
+        };
+        let finished = command.is_finished();
+        menu = menu
+            .entry(MenuItem::new("Copy Command").action(copy_command))
+            .entry(
+                MenuItem::new("Copy Output")
+                    .enabled(finished)
+                    .action(copy_output),
+            )
+            .entry(
+                MenuItem::new("Rerun Command")
+                    .enabled(finished)
+                    .action(move || terminal.rerun_command(&command)),
+            );
+    }
+    if have_task {
+        menu = menu
+            .entry(MenuItem::new("Stop").action(move || {
+                internal_command.send(InternalCommand::StopTerminal { terminal_id });
+            }))
+            .entry(MenuItem::new("Restart").action(move || {
+                internal_command
+                    .send(InternalCommand::RestartTerminal { terminal_id });
+            }))
+            .entry(MenuItem::new("Clear All").action(move || {
+                internal_command.send(InternalCommand::ClearTerminalBuffer {
+                    view_id,
+                    terminal_id,
+                });
+            }));
+    }
     show_context_menu(menu, None);
 }
@@ -0,0 +1,359 @@
+use floem::{
+    View,
+    action::show_context_menu,
+    event::{Event, EventListener},
+    menu::{Menu, MenuItem},
+    prelude::palette,
+    reactive::{SignalGet, SignalUpdate, SignalWith},
+    style::CursorStyle,
+    views::{Decorators, container, dyn_stack, empty, label, scroll, stack, svg},
+};
+use lapce_core::{
+    debug::RunDebugMode, icon::LapceIcons, panel::PanelContainerPosition,
+};
+
+use crate::{
+    app::clickable_icon,
+    config::color::LapceColor,
+    test_explorer::{TestExplorerData, TestNode, TestRow, TestRunKind, TestState},
+    window_workspace::WindowWorkspaceData,
+};
+
+pub fn test_panel(
+    window_tab_data: WindowWorkspaceData,
+    _position: PanelContainerPosition,
+) -> impl View {
+    let test_explorer = window_tab_data.test_explorer.clone();
+    stack((
+        test_toolbar(test_explorer.clone()),
+        stack((
+            test_tree(test_explorer.clone()),
+            failure_output(test_explorer),
+        ))
+        .style(|s| s.size_full().min_height(0.0)),
+    ))
+    .style(|s| s.flex_col().size_full())
+}
+
+fn test_toolbar(test_explorer: TestExplorerData) -> impl View {
+    let config = test_explorer.common.config;
+    let running = test_explorer.running;
+    let exit_code = test_explorer.exit_code;
+    let run_id = test_explorer.run_id;
+    let results = test_explorer.results;
+
+    stack((
+        {
+            let test_explorer = test_explorer.clone();
+            clickable_icon(
+                || LapceIcons::TEST_REFRESH,
+                move || {
+                    test_explorer.refresh();
+                },
+                || false,
+                || false,
+                || "Refresh Tests",
+                config,
+            )
+        },
+        {
+            let test_explorer = test_explorer.clone();
+            clickable_icon(
+                || LapceIcons::START,
+                move || {
+                    test_explorer.run(None);
+                },
+                || false,
+                || false,
+                || "Run All Tests",
+                config,
+            )
+        },
+        clickable_icon(
+            || LapceIcons::DEBUG_STOP,
+            move || {
+                test_explorer.cancel();
+            },
+            || false,
+            move || running.get().is_none(),
+            || "Stop",
+            config,
+        ),
+        label(move || match running.get() {
+            Some(TestRunKind::Discover) => "Finding tests".to_string(),
+            Some(TestRunKind::Run) => "Running tests".to_string(),
+            None if run_id.get() == 0 => String::new(),
+            None => {
+                let (passed, failed, ignored) = results.with(|results| {
+                    results.values().fold(
+                        (0, 0, 0),
+                        |(p, f, i), result| match result.state {
+                            TestState::Passed => (p + 1, f, i),
+                            TestState::Failed => (p, f + 1, i),
+                            TestState::Ignored => (p, f, i + 1),
+                            _ => (p, f, i),
+                        },
+                    )
+                });
+                let summary =
+                    format!("{passed} passed, {failed} failed, {ignored} ignored");
+                match exit_code.get() {
+                    // cargo exits with 101 when a test fails
+                    Some(0) | Some(101) => summary,
+                    Some(code) => format!("{summary}, exit code {code}"),
+                    None => format!("{summary}, stopped"),
+                }
+            },
+        })
+        .style(move |s| {
+            s.flex_grow(1.0)
+                .min_width(0.0)
+                .margin_left(6.0)
+                .text_ellipsis()
+                .color(config.with_color(LapceColor::EDITOR_DIM))
+                .selectable(false)
+        }),
+    ))
+    .style(|s| {
+        s.padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_pct(100.0)
+            .items_center()
+    })
+}
+
+fn test_tree(test_explorer: TestExplorerData) -> impl View {
+    let config = test_explorer.common.config;
+    let ui_line_height = test_explorer.common.ui_line_height;
+    let suites = test_explorer.suites;
+    let running = test_explorer.running;
+    let data = test_explorer.clone();
+
+    container(
+        scroll(
+            stack((
+                dyn_stack(
+                    move || data.rows(),
+                    |row| row.clone(),
+                    move |row| test_row(test_explorer.clone(), row),
+                )
+                .style(|s| s.flex_col().width_pct(100.0)),
+                label(|| "Refresh to find the tests of the workspace".to_string())
+                    .style(move |s| {
+                        s.padding_horiz(10.0)
+                            .color(config.with_color(LapceColor::EDITOR_DIM))
+                            .selectable(false)
+                            .apply_if(
+                                !suites.with(|suites| suites.is_empty())
+                                    || running.get().is_some(),
+                                |s| s.hide(),
+                            )
+                    }),
+            ))
+            .style(move |s| {
+                s.flex_col()
+                    .width_pct(100.0)
+                    .line_height(1.6)
+                    .min_height(ui_line_height.get())
+            }),
+        )
+        .style(|s| s.absolute().size_pct(100.0, 100.0)),
+    )
+    .style(|s| s.flex_grow(1.0).flex_basis(0.0).height_pct(100.0))
+}
+
+fn test_row(test_explorer: TestExplorerData, row: TestRow) -> impl View {
+    let config = test_explorer.common.config;
+    let collapsed = test_explorer.collapsed;
+    let selected = test_explorer.selected;
+    let TestRow {
+        node,
+        level,
+        label: text,
+        has_children,
+    } = row;
+    let test_id = match &node {
+        TestNode::Test(id) => Some(id.clone()),
+        _ => None,
+    };
+    let is_selected = {
+        let test_id = test_id.clone();
+        move || test_id.is_some() && selected.with(|s| s == &test_id)
+    };
+
+    let state = {
+        let test_explorer = test_explorer.clone();
+        let node = node.clone();
+        move || test_explorer.state(&node)
+    };
+    let duration = {
+        let test_explorer = test_explorer.clone();
+        let test_id = test_id.clone();
+        move || {
+            test_id
+                .as_ref()
+                .and_then(|id| test_explorer.result(id).duration)
+                .map(|duration| format!("{duration:.2}s"))
+                .unwrap_or_default()
+        }
+    };
+
+    let run = {
+        let test_explorer = test_explorer.clone();
+        let node = node.clone();
+        move || test_explorer.run(Some(&node))
+    };
+    let debug = {
+        let test_explorer = test_explorer.clone();
+        let test_id = test_id.clone();
+        move |mode: RunDebugMode| {
+            if let Some(id) = test_id.as_ref() {
+                test_explorer.debug(id, mode);
+            }
+        }
+    };
+
+    stack((
+        container(
+            svg({
+                let node = node.clone();
+                move || {
+                    let icon = if !has_children {
+                        LapceIcons::EMPTY
+                    } else if collapsed.with(|c| c.contains(&node)) {
+                        LapceIcons::ITEM_CLOSED
+                    } else {
+                        LapceIcons::ITEM_OPENED
+                    };
+                    config.with_ui_svg(icon)
+                }
+            })
+            .style(move |s| {
+                let size = config.with_icon_size() as f32;
+                s.size(size, size)
+                    .color(config.with_color(LapceColor::LAPCE_ICON_ACTIVE))
+            }),
+        )
+        .style(|s| s.padding(4.0).margin_right(2.0)),
+        svg({
+            let state = state.clone();
+            move || config.with_ui_svg(state().icon())
+        })
+        .style(move |s| {
+            let size = config.with_icon_size() as f32;
+            let color = match state() {
+                TestState::Passed => palette::css::GREEN,
+                TestState::Failed => config.with_color(LapceColor::LAPCE_ERROR),
+                _ => config.with_color(LapceColor::LAPCE_ICON_ACTIVE),
+            };
+            s.min_width(size)
+                .size(size, size)
+                .margin_right(6.0)
+                .color(color)
+        }),
+        label(move || text.clone())
+            .style(|s| s.margin_right(6.0).text_ellipsis().selectable(false)),
+        label(duration).style(move |s| {
+            s.flex_grow(1.0)
+                .color(config.with_color(LapceColor::EDITOR_DIM))
+                .selectable(false)
+        }),
+        clickable_icon(
+            || LapceIcons::START,
+            run.clone(),
+            || false,
+            || false,
+            || "Run",
+            config,
+        ),
+        if test_id.is_some() {
+            let debug = debug.clone();
+            clickable_icon(
+                || LapceIcons::DEBUG_ALT,
+                move || debug(RunDebugMode::Debug),
+                || false,
+                || false,
+                || "Debug",
+                config,
+            )
+            .into_any()
+        } else {
+            empty().into_any()
+        },
+    ))
+    .on_click_stop({
+        let test_explorer = test_explorer.clone();
+        move |_| match &node {
+            TestNode::Test(id) => selected.set(Some(id.clone())),
+            node => test_explorer.toggle_collapsed(node),
+        }
+    })
+    .on_event_cont(EventListener::PointerDown, move |event| {
+        if let Event::PointerDown(pointer_event) = event {
+            if pointer_event.button.is_secondary() {
+                let mut menu =
+                    Menu::new("").entry(MenuItem::new("Run").action(run.clone()));
+                if test_id.is_some() {
+                    let run_in_terminal = debug.clone();
+                    let debug = debug.clone();
+                    menu = menu
+                        .entry(MenuItem::new("Debug").action(move || {
+                            debug(RunDebugMode::Debug);
+                        }))
+                        .entry(MenuItem::new("Run in Terminal").action(move || {
+                            run_in_terminal(RunDebugMode::Run);
+                        }));
+                }
+                show_context_menu(menu, None);
+            }
+        }
+    })
+    .style(move |s| {
+        s.padding_left((level * 10) as f32 + 10.0)
+            .padding_right(10.0)
+            .width_pct(100.0)
+            .items_center()
+            .hover(|s| {
+                s.cursor(CursorStyle::Pointer).background(
+                    config.with_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            })
+            .apply_if(is_selected(), |s| {
+                s.background(config.with_color(LapceColor::PANEL_CURRENT_BACKGROUND))
+            })
+    })
+}
+
+/// What the selected test printed, if it failed
+fn failure_output(test_explorer: TestExplorerData) -> impl View {
+    let config = test_explorer.common.config;
+    let selected = test_explorer.selected;
+    let stdout = move || {
+        selected
+            .get()
+            .map(|id| test_explorer.result(&id))
+            .filter(|result| result.state == TestState::Failed)
+            .map(|result| result.stdout)
+    };
+    let output = stdout.clone();
+
+    container(
+        scroll(label(move || output().unwrap_or_default()).style(move |s| {
+            let font_family = config
+                .signal(|config| config.editor.font_family.signal())
+                .get();
+            s.padding_horiz(10.0)
+                .font_family(font_family.1)
+                .line_height(1.4)
+        }))
+        .style(|s| s.absolute().size_pct(100.0, 100.0)),
+    )
+    .style(move |s| {
+        s.flex_grow(1.0)
+            .flex_basis(0.0)
+            .height_pct(100.0)
+            .border_left(1.0)
+            .border_color(config.with_color(LapceColor::LAPCE_BORDER))
+            .apply_if(stdout().is_none(), |s| s.hide())
+    })
+}
@@ -18,16 +18,20 @@ use lapce_core::{
 
 use super::{
     debug_view::debug_panel, global_search_view::global_search_panel,
-    plugin_view::plugin_panel, problem_view::problem_panel,
+    local_history_view::local_history_panel, plugin_view::plugin_panel,
+    problem_view::problem_panel, rust_build_panel::build_panel,
     source_control_view::source_control_panel, terminal_view::terminal_panel,
+    test_view::test_panel,
 };
 use crate::{
     app::{clickable_icon, clickable_icon_base},
     config::{WithLapceConfig, color::LapceColor},
     file_explorer::view::file_explorer_panel,
     panel::{
-        call_hierarchy_view::show_hierarchy_panel, data::PanelData,
-        document_symbol::symbol_panel, implementation_view::implementation_panel,
+        call_hierarchy_view::{show_hierarchy_panel, show_type_hierarchy_panel},
+        data::PanelData,
+        document_symbol::symbol_panel,
+        implementation_view::implementation_panel,
         references_view::references_panel,
     },
     window_workspace::{DragContent, WindowWorkspaceData},
@@ -396,6 +400,9 @@ fn panel_view_by_kind(
         PanelKind::CallHierarchy => {
             show_hierarchy_panel(window_tab_data.clone(), position).into_any()
         },
+        PanelKind::TypeHierarchy => {
+            show_type_hierarchy_panel(window_tab_data.clone(), position).into_any()
+        },
         PanelKind::DocumentSymbol => {
             symbol_panel(window_tab_data.clone(), position).into_any()
         },
@@ -406,8 +413,11 @@ fn panel_view_by_kind(
             implementation_panel(window_tab_data.clone(), position).into_any()
         },
         PanelKind::Build => {
-            empty().into_any()
-            // build_panel(window_tab_data.clone(), position).into_any()
+            build_panel(window_tab_data.clone(), position).into_any()
+        },
+        PanelKind::Test => test_panel(window_tab_data.clone(), position).into_any(),
+        PanelKind::LocalHistory => {
+            local_history_panel(window_tab_data.clone(), position).into_any()
         },
     }
 }
@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use cozy_floem::views::{
+    panel::{DocStyle, Hyperlink},
+    tree_with_panel::data::{Level, TreePanelData},
+};
+use floem::reactive::{RwSignal, Scope, SignalGet, SignalUpdate, SignalWith};
+use lapce_rpc::core::RustBuildOutput;
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
+use rust_resolve::{
+    BuildDiagnostic, resolve_diagnostic, resolve_stderr, resolve_stdout,
+};
+
+use crate::{
+    command::InternalCommand,
+    config::{WithLapceConfig, color::LapceColor},
+    editor::location::{EditorLocation, EditorPosition},
+    window_workspace::CommonData,
+};
+
+/// The `source` of the diagnostics reported by cargo commands, which tells
+/// them apart from the language server's in the same file.
+pub const BUILD_DIAGNOSTIC_SOURCE: &str = "cargo";
+
+pub fn is_build_diagnostic(diagnostic: &Diagnostic) -> bool {
+    diagnostic.source.as_deref() == Some(BUILD_DIAGNOSTIC_SOURCE)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CargoCommand {
+    Check,
+    Build,
+    Clippy,
+    Test,
+}
+
+impl CargoCommand {
+    pub const ALL: [CargoCommand; 4] = [
+        CargoCommand::Check,
+        CargoCommand::Build,
+        CargoCommand::Clippy,
+        CargoCommand::Test,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CargoCommand::Check => "check",
+            CargoCommand::Build => "build",
+            CargoCommand::Clippy => "clippy",
+            CargoCommand::Test => "test",
+        }
+    }
+
+    fn arguments(&self) -> Vec<String> {
+        vec![
+            self.name().to_string(),
+            "--message-format=json-diagnostic-rendered-ansi".to_string(),
+        ]
+    }
+}
+
+#[derive(Clone)]
+pub struct RustBuildData {
+    pub tree:        TreePanelData,
+    /// Output of any other revision is from a command that was replaced
+    pub rev:         RwSignal<u64>,
+    pub running:     RwSignal<Option<CargoCommand>>,
+    /// The result of the last command that ran to completion
+    pub exit_code:   RwSignal<Option<i32>>,
+    /// Diagnostics reported by the current command, by file
+    pub diagnostics: RwSignal<HashMap<PathBuf, Vec<Diagnostic>>>,
+    pub common:      Rc<CommonData>,
+}
+
+impl RustBuildData {
+    pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
+        let tree = TreePanelData::new(cx, doc_style(common.config));
+        let data = Self {
+            tree,
+            rev: cx.create_rw_signal(0),
+            running: cx.create_rw_signal(None),
+            exit_code: cx.create_rw_signal(None),
+            diagnostics: cx.create_rw_signal(HashMap::new()),
+            common,
+        };
+
+        let clicked_hyperlink = data.tree.doc.clicked_hyperlink();
+        let build = data.clone();
+        cx.create_effect(move |_| {
+            if let Some(link) = clicked_hyperlink.get() {
+                build.open_hyperlink(link);
+            }
+        });
+
+        data
+    }
+
+    /// Runs `command` in the workspace, stopping the one that is running.
+    pub fn run(&self, command: CargoCommand) {
+        if let Some(rev) = self.running_rev() {
+            self.common.proxy.proxy_rpc.rust_build_cancel(rev);
+        }
+        let rev = self.rev.get_untracked() + 1;
+        self.rev.set(rev);
+        self.running.set(Some(command));
+        self.exit_code.set(None);
+        self.diagnostics.set(HashMap::new());
+        self.tree.clear();
+        self.common.proxy.proxy_rpc.rust_build(
+            rev,
+            "cargo".to_string(),
+            Some(command.arguments()),
+        );
+    }
+
+    pub fn cancel(&self) {
+        if let Some(rev) = self.running_rev() {
+            self.common.proxy.proxy_rpc.rust_build_cancel(rev);
+        }
+    }
+
+    fn running_rev(&self) -> Option<u64> {
+        self.running
+            .get_untracked()
+            .map(|_| self.rev.get_untracked())
+    }
+
+    /// Shows a line of output, returning the file whose diagnostics changed.
+    pub fn output(&self, rev: u64, output: RustBuildOutput) -> Option<PathBuf> {
+        if rev != self.rev.get_untracked() {
+            return None;
+        }
+        match output {
+            RustBuildOutput::StdOut(line) => {
+                // Only the compiler messages are json, test binaries print
+                // their results as they are.
+                if !line.starts_with('{') {
+                    self.tree.append(resolve_stderr(&line));
+                    return None;
+                }
+                if let Some(text) = resolve_stdout(&line) {
+                    self.tree.append(text);
+                }
+                let diagnostic = resolve_diagnostic(&line)?;
+                let path = self.resolve_path(&diagnostic.file_name);
+                let diagnostic = lsp_diagnostic(diagnostic);
+                self.diagnostics.update(|diagnostics| {
+                    let diagnostics = diagnostics.entry(path.clone()).or_default();
+                    // rustc repeats a diagnostic for every target it builds
+                    if !diagnostics.contains(&diagnostic) {
+                        diagnostics.push(diagnostic);
+                    }
+                });
+                Some(path)
+            },
+            RustBuildOutput::StdErr(line) => {
+                self.tree.append(resolve_stderr(&line));
+                None
+            },
+        }
+    }
+
+    pub fn stopped(&self, rev: u64, exit_code: Option<i32>) {
+        if rev != self.rev.get_untracked() {
+            return;
+        }
+        self.running.set(None);
+        self.exit_code.set(exit_code);
+    }
+
+    /// The diagnostics the current command reported for `path`.
+    pub fn diagnostics_of(&self, path: &Path) -> Vec<Diagnostic> {
+        self.diagnostics
+            .with_untracked(|d| d.get(path).cloned())
+            .unwrap_or_default()
+    }
+
+    /// cargo reports files relative to the directory it ran in.
+    fn resolve_path(&self, file_name: &str) -> PathBuf {
+        match self.common.workspace.path() {
+            Some(workspace) => workspace.join(file_name),
+            None => PathBuf::from(file_name),
+        }
+    }
+
+    fn open_hyperlink(&self, link: Hyperlink) {
+        match link {
+            Hyperlink::File {
+                src, line, column, ..
+            } => {
+                let position = Position {
+                    line:      line.saturating_sub(1) as u32,
+                    character: column.unwrap_or(1).saturating_sub(1) as u32,
+                };
+                self.common
+                    .internal_command
+                    .send(InternalCommand::JumpToLocation {
+                        location: EditorLocation {
+                            path:               self.resolve_path(&src),
+                            position:           Some(EditorPosition::Position(
+                                position,
+                            )),
+                            scroll_offset:      None,
+                            ignore_unconfirmed: false,
+                            same_editor_tab:    false,
+                        },
+                    });
+            },
+            Hyperlink::Url { url, .. } => {
+                if !url.is_empty() {
+                    self.common
+                        .internal_command
+                        .send(InternalCommand::OpenWebUri { uri: url });
+                }
+            },
+        }
+    }
+}
+
+fn doc_style(config: WithLapceConfig) -> DocStyle {
+    config.with_untracked(|config| DocStyle {
+        font_family:  config.editor.font_family.clone(),
+        font_size:    config.editor.font_size() as f32,
+        line_height:  config.editor.line_height() as f64,
+        selection_bg: config.color(LapceColor::EDITOR_SELECTION),
+        fg_color:     config.color(LapceColor::EDITOR_FOREGROUND),
+    })
+}
+
+fn lsp_diagnostic(diagnostic: BuildDiagnostic) -> Diagnostic {
+    let position = |line: usize, column: usize| Position {
+        line:      line.saturating_sub(1) as u32,
+        character: column.saturating_sub(1) as u32,
+    };
+    let severity = match diagnostic.level {
+        Level::Error => DiagnosticSeverity::ERROR,
+        Level::Warn => DiagnosticSeverity::WARNING,
+        Level::None => DiagnosticSeverity::INFORMATION,
+    };
+    Diagnostic::new(
+        Range {
+            start: position(diagnostic.line_start, diagnostic.column_start),
+            end:   position(diagnostic.line_end, diagnostic.column_end),
+        },
+        Some(severity),
+        diagnostic.code.map(NumberOrString::String),
+        Some(BUILD_DIAGNOSTIC_SOURCE.to_string()),
+        diagnostic.message,
+        None,
+        None,
+    )
+}
@@ -1,15 +1,38 @@
 use core::fmt;
-use std::{fmt::Display, str::FromStr};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+    mem,
+    path::Path,
+    str::FromStr,
+};
 
 use anyhow::Error;
+use doc::language::LapceLanguage;
+use inflector::Inflector;
+use lapce_rpc::plugin::PluginId;
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Documentation, InsertTextFormat,
+    InsertTextMode,
+};
 use once_cell::sync::Lazy;
-use regex::Regex;
+use regex::{Captures, Regex};
+use serde::Deserialize;
 
 #[derive(Debug, PartialEq)]
 pub enum SnippetElement {
     Text(String),
     PlaceHolder(usize, Vec<SnippetElement>),
     Tabstop(usize),
+    /// `${1|one,two|}`, inserted as its first option
+    Choice(usize, Vec<String>),
+    /// `$name`, `${name:default}` or `${name/regex/format/options}`, replaced
+    /// by its value in [`Snippet::resolve_variables`]
+    Variable {
+        name:      String,
+        default:   Vec<SnippetElement>,
+        transform: Option<Transform>,
+    },
 }
 
 impl Display for SnippetElement {
@@ -26,6 +49,32 @@ impl Display for SnippetElement {
                 f.write_str("}")
             },
             SnippetElement::Tabstop(tab) => write!(f, "${tab}"),
+            SnippetElement::Choice(tab, options) => {
+                write!(f, "${{{tab}|")?;
+                for (i, option) in options.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_escaped(f, option, &[',', '|', '\\'])?;
+                }
+                f.write_str("|}")
+            },
+            SnippetElement::Variable {
+                name,
+                default,
+                transform,
+            } => {
+                write!(f, "${{{name}")?;
+                if let Some(transform) = transform {
+                    fmt::Display::fmt(transform, f)?;
+                } else if !default.is_empty() {
+                    f.write_char(':')?;
+                    for child_snippet_elm in default {
+                        fmt::Display::fmt(child_snippet_elm, f)?;
+                    }
+                }
+                f.write_str("}")
+            },
         }
     }
 }
@@ -34,10 +83,14 @@ impl SnippetElement {
     pub fn len(&self) -> usize {
         match &self {
             SnippetElement::Text(text) => text.len(),
-            SnippetElement::PlaceHolder(_, elements) => {
-                elements.iter().map(|e| e.len()).sum()
-            },
+            SnippetElement::PlaceHolder(_, elements)
+            | SnippetElement::Variable {
+                default: elements, ..
+            } => elements.iter().map(|e| e.len()).sum(),
             SnippetElement::Tabstop(_) => 0,
+            SnippetElement::Choice(_, options) => {
+                options.first().map(|option| option.len()).unwrap_or(0)
+            },
         }
     }
 
@@ -57,7 +110,10 @@ impl SnippetElement {
     fn write_text_to<Buffer: fmt::Write>(&self, buf: &mut Buffer) -> fmt::Result {
         match self {
             SnippetElement::Text(text) => buf.write_str(text),
-            SnippetElement::PlaceHolder(_, elements) => {
+            SnippetElement::PlaceHolder(_, elements)
+            | SnippetElement::Variable {
+                default: elements, ..
+            } => {
                 for child_snippet_elm in elements {
                     // call ourselves recursively
                     child_snippet_elm.write_text_to(buf)?;
@@ -65,10 +121,195 @@ impl SnippetElement {
                 fmt::Result::Ok(())
             },
             SnippetElement::Tabstop(_) => fmt::Result::Ok(()),
+            SnippetElement::Choice(_, options) => {
+                buf.write_str(options.first().map(|x| x.as_str()).unwrap_or(""))
+            },
+        }
+    }
+}
+
+/// The `/regex/format/options` of a variable, which rewrites its value.
+#[derive(Debug, PartialEq)]
+pub struct Transform {
+    regex:   String,
+    format:  Vec<FormatElement>,
+    options: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FormatElement {
+    Text(String),
+    /// `$1` or `${1}`
+    Group(usize),
+    /// `${1:/upcase}`
+    Case(usize, CaseModifier),
+    /// `${1:+if}`, `${1:?if:else}`, `${1:-else}` and `${1:else}`
+    Conditional {
+        group:     usize,
+        if_text:   String,
+        else_text: String,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CaseModifier {
+    Upcase,
+    Downcase,
+    Capitalize,
+    Camelcase,
+    Pascalcase,
+}
+
+impl CaseModifier {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "upcase" => CaseModifier::Upcase,
+            "downcase" => CaseModifier::Downcase,
+            "capitalize" => CaseModifier::Capitalize,
+            "camelcase" => CaseModifier::Camelcase,
+            "pascalcase" => CaseModifier::Pascalcase,
+            _ => return None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            CaseModifier::Upcase => "upcase",
+            CaseModifier::Downcase => "downcase",
+            CaseModifier::Capitalize => "capitalize",
+            CaseModifier::Camelcase => "camelcase",
+            CaseModifier::Pascalcase => "pascalcase",
+        }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        match self {
+            CaseModifier::Upcase => text.to_uppercase(),
+            CaseModifier::Downcase => text.to_lowercase(),
+            CaseModifier::Capitalize => {
+                let mut chars = text.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            },
+            CaseModifier::Camelcase => text.to_camel_case(),
+            CaseModifier::Pascalcase => text.to_pascal_case(),
+        }
+    }
+}
+
+impl Display for Transform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_char('/')?;
+        write_escaped(f, &self.regex, &['/'])?;
+        f.write_char('/')?;
+        for element in &self.format {
+            match element {
+                FormatElement::Text(text) => {
+                    write_escaped(f, text, &['$', '/', '\\'])?
+                },
+                FormatElement::Group(group) => write!(f, "${{{group}}}")?,
+                FormatElement::Case(group, modifier) => {
+                    write!(f, "${{{group}:/{}}}", modifier.name())?
+                },
+                FormatElement::Conditional {
+                    group,
+                    if_text,
+                    else_text,
+                } => {
+                    if else_text.is_empty() {
+                        write!(f, "${{{group}:+")?;
+                        write_escaped(f, if_text, &['}', '\\'])?;
+                    } else if if_text.is_empty() {
+                        write!(f, "${{{group}:-")?;
+                        write_escaped(f, else_text, &['}', '\\'])?;
+                    } else {
+                        write!(f, "${{{group}:?")?;
+                        write_escaped(f, if_text, &[':', '}', '\\'])?;
+                        f.write_char(':')?;
+                        write_escaped(f, else_text, &['}', '\\'])?;
+                    }
+                    f.write_char('}')?;
+                },
+            }
+        }
+        write!(f, "/{}", self.options)
+    }
+}
+
+impl Transform {
+    /// Replaces the first match of the regex in `value`, or all of them with
+    /// the `g` option, by the format. An invalid regex leaves it as it is.
+    pub fn apply(&self, value: &str) -> String {
+        let flags: String = self
+            .options
+            .chars()
+            .filter(|option| matches!(option, 'i' | 'm' | 's'))
+            .collect();
+        let pattern = if flags.is_empty() {
+            self.regex.clone()
+        } else {
+            format!("(?{flags}){}", self.regex)
+        };
+        let Ok(regex) = Regex::new(&pattern) else {
+            return value.to_string();
+        };
+        let global = self.options.contains('g');
+        let mut result = String::new();
+        let mut last = 0;
+        for captures in regex.captures_iter(value) {
+            let Some(found) = captures.get(0) else {
+                continue;
+            };
+            result.push_str(&value[last..found.start()]);
+            self.format_to(&captures, &mut result);
+            last = found.end();
+            if !global {
+                break;
+            }
+        }
+        result.push_str(&value[last..]);
+        result
+    }
+
+    fn format_to(&self, captures: &Captures, result: &mut String) {
+        let group = |group: usize| {
+            captures.get(group).map(|x| x.as_str()).unwrap_or_default()
+        };
+        for element in &self.format {
+            match element {
+                FormatElement::Text(text) => result.push_str(text),
+                FormatElement::Group(n) => result.push_str(group(*n)),
+                FormatElement::Case(n, modifier) => {
+                    result.push_str(&modifier.apply(group(*n)))
+                },
+                FormatElement::Conditional {
+                    group: n,
+                    if_text,
+                    else_text,
+                } => {
+                    if group(*n).is_empty() {
+                        result.push_str(else_text)
+                    } else {
+                        result.push_str(if_text)
+                    }
+                },
+            }
         }
     }
 }
 
+fn write_escaped(f: &mut impl fmt::Write, text: &str, escs: &[char]) -> fmt::Result {
+    for char in text.chars() {
+        if escs.contains(&char) {
+            f.write_char('\\')?;
+        }
+        f.write_char(char)?;
+    }
+    fmt::Result::Ok(())
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Snippet {
     elements: Vec<SnippetElement>,
@@ -112,11 +353,25 @@ impl Snippet {
             } else if let Some((ele, end)) = Self::extract_placeholder(s, pos) {
                 elements.push(ele);
                 pos = end;
+            } else if let Some((ele, end)) = Self::extract_choice(s, pos) {
+                elements.push(ele);
+                pos = end;
+            } else if let Some((ele, end)) = Self::extract_variable(s, pos) {
+                elements.push(ele);
+                pos = end;
             } else if let Some((ele, end)) =
                 Self::extract_text(s, pos, escs, loose_escs)
             {
                 elements.push(ele);
                 pos = end;
+            } else if s[pos..].starts_with(['$', '\\']) {
+                // a `$` or `\` which starts nothing is taken as it is
+                let char = s[pos..pos + 1].to_string();
+                match elements.last_mut() {
+                    Some(SnippetElement::Text(text)) => text.push_str(&char),
+                    _ => elements.push(SnippetElement::Text(char)),
+                }
+                pos += 1;
             } else {
                 break;
             }
@@ -133,6 +388,10 @@ impl Snippet {
         // `${1}`)
         static REGEX_SECOND: Lazy<Regex> =
             Lazy::new(|| Regex::new(r"^\$\{(\d+)\}").unwrap());
+        // Regex for the start of `${.../regex/format/options}`, a tabstop with a
+        // transform
+        static REGEX_TRANSFORM: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^\$\{(\d+)/").unwrap());
 
         let str = &str[pos..];
         if let Some(matched) = REGEX_FIRST.find(str) {
@@ -170,6 +429,13 @@ impl Snippet {
             let end = pos + matched.len();
             return Some((SnippetElement::Tabstop(n), end));
         }
+        if let Some(caps) = REGEX_TRANSFORM.captures(str) {
+            // the transform applies to what is typed at the tabstop, which is not
+            // followed, so it is a plain tabstop
+            let n = caps.get(1)?.as_str().parse::<usize>().ok()?;
+            let (_, end) = Self::extract_transform(str, caps.get(0)?.end())?;
+            return Some((SnippetElement::Tabstop(n), pos + end + 1));
+        }
         None
     }
 
@@ -200,6 +466,199 @@ impl Snippet {
         Some((SnippetElement::PlaceHolder(tab, els), pos + 1))
     }
 
+    #[inline]
+    fn extract_choice(s: &str, pos: usize) -> Option<(SnippetElement, usize)> {
+        // Regex for the start of `${num|one,two|}`
+        static REGEX: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^\$\{(\d+)\|").unwrap());
+
+        let caps = REGEX.captures(&s[pos..])?;
+        let tab = caps.get(1)?.as_str().parse::<usize>().ok()?;
+
+        let start = pos + caps.get(0)?.end();
+        let mut options = vec![String::new()];
+        let mut chars_iter = s[start..].char_indices();
+        while let Some((i, char)) = chars_iter.next() {
+            match char {
+                '\\' => {
+                    let (_, next) = chars_iter.next()?;
+                    if !matches!(next, ',' | '|' | '\\') {
+                        options.last_mut()?.push('\\');
+                    }
+                    options.last_mut()?.push(next);
+                },
+                ',' => options.push(String::new()),
+                '|' => {
+                    let end = start + i + 1;
+                    return s[end..]
+                        .starts_with('}')
+                        .then_some((SnippetElement::Choice(tab, options), end + 1));
+                },
+                _ => options.last_mut()?.push(char),
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn extract_variable(s: &str, pos: usize) -> Option<(SnippetElement, usize)> {
+        // Regex for `$name`
+        static REGEX_FIRST: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^\$([A-Za-z_][A-Za-z0-9_]*)").unwrap());
+        // Regex for the start of `${name}`, `${name:default}` and
+        // `${name/regex/format/options}`
+        static REGEX_SECOND: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^\$\{([A-Za-z_][A-Za-z0-9_]*)([}:/])").unwrap()
+        });
+
+        let variable = |name: &str, default, transform| SnippetElement::Variable {
+            name: name.to_string(),
+            default,
+            transform,
+        };
+        let str = &s[pos..];
+        if let Some(caps) = REGEX_SECOND.captures(str) {
+            let name = caps.get(1)?.as_str();
+            let start = pos + caps.get(0)?.end();
+            return match caps.get(2)?.as_str() {
+                "}" => Some((variable(name, Vec::new(), None), start)),
+                ":" => {
+                    let (default, end) =
+                        Self::extract_elements(s, start, &['$', '}', '\\'], &[]);
+                    s[end..]
+                        .starts_with('}')
+                        .then(|| (variable(name, default, None), end + 1))
+                },
+                _ => {
+                    let (transform, end) = Self::extract_transform(s, start)?;
+                    Some((variable(name, Vec::new(), Some(transform)), end + 1))
+                },
+            };
+        }
+        let caps = REGEX_FIRST.captures(str)?;
+        let name = caps.get(1)?.as_str();
+        Some((variable(name, Vec::new(), None), pos + caps.get(0)?.end()))
+    }
+
+    /// Extracts the `regex/format/options` after the first `/`, up to the
+    /// closing `}`, returning the position of the `}`.
+    fn extract_transform(s: &str, pos: usize) -> Option<(Transform, usize)> {
+        let (regex, pos) = Self::extract_until(s, pos, &['/'], false)?;
+        let mut format = Vec::new();
+        let mut pos = pos + 1;
+        loop {
+            let (text, end) = Self::extract_until(s, pos, &['/', '$'], true)?;
+            if !text.is_empty() {
+                format.push(FormatElement::Text(text));
+            }
+            pos = end;
+            if s[pos..].starts_with('/') {
+                break;
+            }
+            match Self::extract_format(s, pos) {
+                Some((element, end)) => {
+                    format.push(element);
+                    pos = end;
+                },
+                None => {
+                    format.push(FormatElement::Text("$".to_string()));
+                    pos += 1;
+                },
+            }
+        }
+        let options_len = s[pos + 1..].find('}')?;
+        let options = &s[pos + 1..pos + 1 + options_len];
+        if !options.chars().all(|option| option.is_ascii_alphabetic()) {
+            return None;
+        }
+        Some((
+            Transform {
+                regex,
+                format,
+                options: options.to_string(),
+            },
+            pos + 1 + options_len,
+        ))
+    }
+
+    /// Extracts a `$1`, `${1}` or `${1:...}` of a format.
+    fn extract_format(s: &str, pos: usize) -> Option<(FormatElement, usize)> {
+        static REGEX_GROUP: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^\$(\d+)|^\$\{(\d+)\}").unwrap());
+        static REGEX_CASE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^\$\{(\d+):/([a-z]+)\}").unwrap());
+        static REGEX_CONDITIONAL: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^\$\{(\d+):([+?-]?)").unwrap());
+
+        let str = &s[pos..];
+        if let Some(caps) = REGEX_GROUP.captures(str) {
+            let group =
+                caps.get(1).or_else(|| caps.get(2))?.as_str().parse().ok()?;
+            return Some((FormatElement::Group(group), pos + caps.get(0)?.end()));
+        }
+        if let Some(caps) = REGEX_CASE.captures(str) {
+            let group = caps.get(1)?.as_str().parse().ok()?;
+            let modifier = CaseModifier::from_name(caps.get(2)?.as_str())?;
+            return Some((
+                FormatElement::Case(group, modifier),
+                pos + caps.get(0)?.end(),
+            ));
+        }
+        let caps = REGEX_CONDITIONAL.captures(str)?;
+        let group = caps.get(1)?.as_str().parse().ok()?;
+        let start = pos + caps.get(0)?.end();
+        let (if_text, else_text, end) = match caps.get(2)?.as_str() {
+            "+" => {
+                let (if_text, end) = Self::extract_until(s, start, &['}'], true)?;
+                (if_text, String::new(), end)
+            },
+            "?" => {
+                let (if_text, end) = Self::extract_until(s, start, &[':'], true)?;
+                let (else_text, end) =
+                    Self::extract_until(s, end + 1, &['}'], true)?;
+                (if_text, else_text, end)
+            },
+            _ => {
+                let (else_text, end) = Self::extract_until(s, start, &['}'], true)?;
+                (String::new(), else_text, end)
+            },
+        };
+        Some((
+            FormatElement::Conditional {
+                group,
+                if_text,
+                else_text,
+            },
+            end + 1,
+        ))
+    }
+
+    /// Extracts the text up to one of the `ends`, returning the position of
+    /// it. A `\` escapes an end, and any char when `unescape`.
+    fn extract_until(
+        s: &str,
+        pos: usize,
+        ends: &[char],
+        unescape: bool,
+    ) -> Option<(String, usize)> {
+        let mut text = String::new();
+        let mut chars_iter = s[pos..].char_indices();
+        while let Some((i, char)) = chars_iter.next() {
+            if char == '\\' {
+                let (_, next) = chars_iter.next()?;
+                if !unescape && !ends.contains(&next) {
+                    text.push('\\');
+                }
+                text.push(next);
+            } else if ends.contains(&char) {
+                return Some((text, pos + i));
+            } else {
+                text.push(char);
+            }
+        }
+        None
+    }
+
     #[inline]
     fn extract_text(
         s: &str,
@@ -276,10 +735,301 @@ impl Snippet {
                 SnippetElement::Tabstop(tab) => {
                     tabs.push((*tab, (pos, pos)));
                 },
+                SnippetElement::Choice(tab, _) => {
+                    let end = pos + el.len();
+                    tabs.push((*tab, (pos, end)));
+                    pos = end;
+                },
+                SnippetElement::Variable { default, .. } => {
+                    tabs.extend(Self::elements_tabs(default, pos));
+                    pos += el.len();
+                },
             }
         }
         tabs
     }
+
+    /// The options of each choice, by its tab number.
+    pub fn choices(&self) -> HashMap<usize, Vec<String>> {
+        let mut choices = HashMap::new();
+        Self::elements_choices(&self.elements, &mut choices);
+        choices
+    }
+
+    fn elements_choices(
+        elements: &[SnippetElement],
+        choices: &mut HashMap<usize, Vec<String>>,
+    ) {
+        for el in elements {
+            match el {
+                SnippetElement::Choice(tab, options) => {
+                    choices.insert(*tab, options.clone());
+                },
+                SnippetElement::PlaceHolder(_, els)
+                | SnippetElement::Variable { default: els, .. } => {
+                    Self::elements_choices(els, choices);
+                },
+                SnippetElement::Text(_) | SnippetElement::Tabstop(_) => {},
+            }
+        }
+    }
+
+    /// Replaces the variables with their values, the unknown ones and the
+    /// empty ones with their defaults. An unknown variable without a default
+    /// is replaced by its name.
+    pub fn resolve_variables(&mut self, value: &impl Fn(&str) -> Option<String>) {
+        self.elements = Self::resolve_elements(mem::take(&mut self.elements), value);
+    }
+
+    fn resolve_elements(
+        elements: Vec<SnippetElement>,
+        value: &impl Fn(&str) -> Option<String>,
+    ) -> Vec<SnippetElement> {
+        let mut resolved = Vec::new();
+        for element in elements {
+            match element {
+                SnippetElement::PlaceHolder(tab, els) => {
+                    resolved.push(SnippetElement::PlaceHolder(
+                        tab,
+                        Self::resolve_elements(els, value),
+                    ))
+                },
+                SnippetElement::Variable {
+                    name,
+                    default,
+                    transform,
+                } => {
+                    let text = match &transform {
+                        Some(transform) => {
+                            Some(transform.apply(&value(&name).unwrap_or_default()))
+                        },
+                        None => value(&name),
+                    };
+                    match text {
+                        Some(text) if !text.is_empty() => {
+                            resolved.push(SnippetElement::Text(text))
+                        },
+                        None if default.is_empty() => {
+                            resolved.push(SnippetElement::Text(name))
+                        },
+                        _ => resolved.extend(Self::resolve_elements(default, value)),
+                    }
+                },
+                element => resolved.push(element),
+            }
+        }
+        resolved
+    }
+
+    /// Indents the lines after the first like `indent`, the indentation of
+    /// the line the snippet is inserted on, with a tab standing for one
+    /// `indent_unit`.
+    pub fn adjust_indentation(&mut self, indent: &str, indent_unit: &str) {
+        fn adjust(elements: &mut [SnippetElement], indent: &str, indent_unit: &str) {
+            for element in elements {
+                match element {
+                    SnippetElement::Text(text) => {
+                        *text = text
+                            .replace('\t', indent_unit)
+                            .replace('\n', &format!("\n{indent}"));
+                    },
+                    SnippetElement::PlaceHolder(_, els)
+                    | SnippetElement::Variable { default: els, .. } => {
+                        adjust(els, indent, indent_unit)
+                    },
+                    SnippetElement::Tabstop(_) | SnippetElement::Choice(..) => {},
+                }
+            }
+        }
+        adjust(&mut self.elements, indent, indent_unit);
+    }
+}
+
+/// The plugin id of the completion items of the user snippets, which no
+/// plugin has as they count from 1.
+pub const USER_SNIPPETS_PLUGIN_ID: PluginId = PluginId(0);
+
+/// The plugin id of the options of a snippet choice listed in the
+/// completion, which replace the choice as they are.
+pub const SNIPPET_CHOICE_PLUGIN_ID: PluginId = PluginId(u64::MAX);
+
+/// The completion items of the options of a snippet choice.
+pub fn choice_completion_items(
+    options: &[String],
+) -> impl Iterator<Item = CompletionItem> + '_ {
+    options
+        .iter()
+        .enumerate()
+        .map(|(i, option)| CompletionItem {
+            label: option.clone(),
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            // listed in the order of the snippet
+            sort_text: Some(format!("{i:04}")),
+            insert_text: Some(option.clone()),
+            insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+            ..Default::default()
+        })
+}
+
+/// A snippet of the `snippets` directory of the config directory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserSnippet {
+    pub name:        String,
+    pub prefixes:    Vec<String>,
+    pub body:        String,
+    pub description: Option<String>,
+    /// `None` for the snippets of every language
+    pub languages:   Option<Vec<LapceLanguage>>,
+}
+
+impl UserSnippet {
+    pub fn is_for(&self, language: LapceLanguage) -> bool {
+        self.languages
+            .as_ref()
+            .is_none_or(|languages| languages.contains(&language))
+    }
+
+    pub fn completion_items(&self) -> impl Iterator<Item = CompletionItem> + '_ {
+        self.prefixes.iter().map(|prefix| CompletionItem {
+            label: prefix.clone(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(
+                self.description
+                    .clone()
+                    .unwrap_or_else(|| self.name.clone()),
+            ),
+            documentation: Some(Documentation::String(self.body.clone())),
+            insert_text: Some(self.body.clone()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            insert_text_mode: Some(InsertTextMode::ADJUST_INDENTATION),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct SnippetDefinition {
+    #[serde(default)]
+    prefix:      OneOrMany,
+    body:        OneOrMany,
+    description: Option<String>,
+    /// Comma separated languages, only read from `.code-snippets` files
+    scope:       Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Default for OneOrMany {
+    fn default() -> Self {
+        OneOrMany::Many(Vec::new())
+    }
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(one) => vec![one],
+            OneOrMany::Many(many) => many,
+        }
+    }
+}
+
+/// The user snippets, in the format of VS Code: `snippets/<language>.json`
+/// for the snippets of a language, and `snippets/*.code-snippets` for the
+/// snippets of the languages of their `scope`, or of every language.
+#[derive(Clone, Debug, Default)]
+pub struct UserSnippets(Vec<UserSnippet>);
+
+impl UserSnippets {
+    pub fn load(config_directory: &Path) -> Self {
+        let Ok(entries) = std::fs::read_dir(config_directory.join("snippets"))
+        else {
+            return Self::default();
+        };
+        let mut snippets = Vec::new();
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let (Some(stem), Some(extension)) = (
+                path.file_stem().and_then(|x| x.to_str()),
+                path.extension().and_then(|x| x.to_str()),
+            ) else {
+                continue;
+            };
+            let language = match extension {
+                "json" => match LapceLanguage::from_name(stem) {
+                    Some(language) => Some(language),
+                    None => {
+                        log::warn!("no language for the snippets of {path:?}");
+                        continue;
+                    },
+                },
+                "code-snippets" => None,
+                _ => continue,
+            };
+            match std::fs::read_to_string(&path)
+                .map_err(Error::from)
+                .and_then(|content| Self::parse(&content, language))
+            {
+                Ok(file_snippets) => snippets.extend(file_snippets),
+                Err(err) => {
+                    log::error!("failed to load the snippets {path:?}: {err}")
+                },
+            }
+        }
+        snippets.sort_by(|a, b| a.name.cmp(&b.name));
+        Self(snippets)
+    }
+
+    /// Parses a snippets file of `language`, or of the scopes of the snippets
+    /// when `None`.
+    fn parse(
+        content: &str,
+        language: Option<LapceLanguage>,
+    ) -> anyhow::Result<Vec<UserSnippet>> {
+        let definitions: indexmap::IndexMap<String, SnippetDefinition> =
+            serde_json::from_str(content)?;
+        Ok(definitions
+            .into_iter()
+            .map(|(name, definition)| UserSnippet {
+                name,
+                prefixes: definition.prefix.into_vec(),
+                body: definition.body.into_vec().join("\n"),
+                description: definition.description,
+                languages: match language {
+                    Some(language) => Some(vec![language]),
+                    None => definition.scope.map(|scope| {
+                        scope
+                            .split(',')
+                            .filter_map(|name| LapceLanguage::from_name(name.trim()))
+                            .collect()
+                    }),
+                },
+            })
+            .collect())
+    }
+
+    pub fn for_language(
+        &self,
+        language: LapceLanguage,
+    ) -> impl Iterator<Item = &UserSnippet> {
+        self.0
+            .iter()
+            .filter(move |snippet| snippet.is_for(language))
+    }
+
+    /// The snippet of `language` with the name, or else with the prefix.
+    pub fn find(&self, language: LapceLanguage, name: &str) -> Option<&UserSnippet> {
+        self.for_language(language)
+            .find(|snippet| snippet.name == name)
+            .or_else(|| {
+                self.for_language(language)
+                    .find(|snippet| snippet.prefixes.iter().any(|x| x == name))
+            })
+    }
 }
 
 #[cfg(test)]
@@ -858,4 +1608,108 @@ mod tests {
             Snippet::extract_text(s, end + 1, &['$', '{', '}', '\\'], &[])
         );
     }
+
+    #[test]
+    fn test_choice_and_variables() {
+        use SnippetElement::*;
+
+        let s = r"${1|one,t\,wo|} ${TM_FILENAME/(.*)\..+$/${1:/upcase}/} $UNKNOWN ${TM_SELECTED_TEXT:${2:none}} \a $";
+        let mut parsed = Snippet::from_str(s).unwrap();
+        assert_eq!(
+            r"${1|one,t\,wo|} ${TM_FILENAME/(.*)\..+$/${1:/upcase}/} ${UNKNOWN} ${TM_SELECTED_TEXT:${2:none}} \a $",
+            parsed.to_string()
+        );
+        assert_eq!("one   none \\a $", parsed.text());
+        assert_eq!(vec![(1, (0, 3)), (2, (6, 10))], parsed.tabs(0));
+
+        parsed.resolve_variables(&|name| match name {
+            "TM_FILENAME" => Some("snippet.rs".to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        });
+        assert_eq!("one SNIPPET UNKNOWN none \\a $", parsed.text());
+        assert_eq!(vec![(1, (0, 3)), (2, (20, 24))], parsed.tabs(0));
+        assert_eq!(
+            parsed.elements[0..2],
+            [
+                Choice(1, vec!["one".into(), "t,wo".into()]),
+                Text(" ".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_choices() {
+        let snippet = Snippet::from_str("${1|a,b|} ${2:x ${3|c,d,e|}} $4").unwrap();
+        assert_eq!(
+            snippet.choices(),
+            HashMap::from([
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (3, vec!["c".to_string(), "d".to_string(), "e".to_string()]),
+            ])
+        );
+
+        let options = &snippet.choices()[&3];
+        let items: Vec<_> = choice_completion_items(options).collect();
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| (item.label.as_str(), item.sort_text.as_deref()))
+                .collect::<Vec<_>>(),
+            [
+                ("c", Some("0000")),
+                ("d", Some("0001")),
+                ("e", Some("0002"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transform() {
+        let transform = |s: &str, value: &str| {
+            let (transform, end) = Snippet::extract_transform(s, 1).unwrap();
+            assert_eq!(s.len() - 1, end);
+            assert_eq!(&s[..end], transform.to_string());
+            transform.apply(value)
+        };
+        assert_eq!(transform("/a/b/}", "aaa"), "baa");
+        assert_eq!(transform("/a/b/g}", "aaa"), "bbb");
+        assert_eq!(transform("/A/b/gi}", "aAa"), "bbb");
+        assert_eq!(
+            transform(
+                "/(\\w+)_(\\w+)/${2:/capitalize}${1:/pascalcase}/}",
+                "foo_bar"
+            ),
+            "BarFoo"
+        );
+        assert_eq!(transform("/(x)?y/${1:?has\\: x:no x}/}", "y"), "no x");
+        assert_eq!(transform("/(x)?y/${1:+x}${1:-none}/}", "xy"), "x");
+        assert_eq!(transform("/\\//\\$1/g}", "a/b"), "a$1b");
+    }
+
+    #[test]
+    fn test_user_snippets() {
+        let snippets = UserSnippets::parse(
+            r#"{
+                "Log": {
+                    "prefix": ["log", "print"],
+                    "body": ["console.log($1);", "\t$0"],
+                    "scope": "javascript, typescript"
+                },
+                "Header": { "body": "// $TM_FILENAME" }
+            }"#,
+            None,
+        )
+        .unwrap();
+        assert_eq!(snippets[0].body, "console.log($1);\n\t$0");
+        assert!(snippets[0].is_for(LapceLanguage::Javascript));
+        assert!(!snippets[0].is_for(LapceLanguage::Rust));
+        assert_eq!(snippets[0].completion_items().count(), 2);
+        assert!(snippets[1].is_for(LapceLanguage::Rust));
+        assert!(snippets[1].prefixes.is_empty());
+
+        let mut snippet = Snippet::from_str(&snippets[0].body).unwrap();
+        snippet.adjust_indentation("  ", "    ");
+        assert_eq!(snippet.text(), "console.log();\n      ");
+    }
 }
@@ -1,30 +1,67 @@
 use std::{path::PathBuf, rc::Rc};
 
-use doc::lines::{editor_command::CommandExecuted, mode::Mode};
+use doc::lines::{
+    buffer::rope_text::RopeText, editor_command::CommandExecuted, mode::Mode,
+};
 use floem::{
+    ext_event::create_ext_action,
     keyboard::Modifiers,
-    reactive::{RwSignal, Scope, SignalWith},
+    reactive::{RwSignal, Scope, SignalGet, SignalUpdate, SignalWith},
 };
 use indexmap::IndexMap;
-use lapce_rpc::source_control::FileDiff;
+use lapce_core::doc::DocContent;
+use lapce_rpc::{
+    RpcError,
+    proxy::ProxyResponse,
+    source_control::{DiffInfo, FileDiff, GitCommitInfo, StageSelection},
+};
 
 use crate::{
-    command::CommandKind,
+    command::{CommandKind, InternalCommand},
     editor::EditorData,
     keypress::{KeyPressFocus, condition::Condition},
     main_split::Editors,
     window_workspace::CommonData,
 };
 
+/// The local date of a commit time in seconds since the Unix epoch
+pub fn commit_date(time: i64) -> String {
+    chrono::DateTime::from_timestamp(time, 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// How many commits of the history are loaded at a time
+const HISTORY_PAGE_SIZE: usize = 50;
+
 #[derive(Clone, Debug)]
 pub struct SourceControlData {
-    // VCS modified files & whether they should be included in the next commit
-    pub file_diffs: RwSignal<IndexMap<PathBuf, (FileDiff, bool)>>,
-    pub branch:     RwSignal<String>,
-    pub branches:   RwSignal<im::Vector<String>>,
-    pub tags:       RwSignal<im::Vector<String>>,
-    pub editor:     EditorData,
-    pub common:     Rc<CommonData>,
+    // VCS modified files, staged or not
+    pub file_diffs:     RwSignal<IndexMap<PathBuf, FileDiff>>,
+    // Files whose changes are in the index
+    pub staged_diffs:   RwSignal<IndexMap<PathBuf, FileDiff>>,
+    // Files whose working copy differs from the index
+    pub unstaged_diffs: RwSignal<IndexMap<PathBuf, FileDiff>>,
+    /// The git status of the repository of each folder of the workspace
+    pub repositories:   RwSignal<IndexMap<PathBuf, DiffInfo>>,
+    /// The branch of the first folder
+    pub branch:         RwSignal<String>,
+    pub branches:       RwSignal<im::Vector<String>>,
+    pub tags:           RwSignal<im::Vector<String>>,
+    /// The commits loaded so far, newest first
+    pub history:        RwSignal<im::Vector<GitCommitInfo>>,
+    /// Show the history of this file rather than of the workspace
+    pub history_file:   RwSignal<Option<PathBuf>>,
+    /// Whether every commit has been loaded
+    pub history_done:   RwSignal<bool>,
+    /// The commit whose changed files are shown
+    pub history_commit: RwSignal<Option<String>>,
+    pub editor:         EditorData,
+    pub common:         Rc<CommonData>,
 }
 
 impl KeyPressFocus for SourceControlData {
@@ -64,9 +101,16 @@ impl SourceControlData {
     pub fn new(cx: Scope, editors: Editors, common: Rc<CommonData>) -> Self {
         Self {
             file_diffs: cx.create_rw_signal(IndexMap::new()),
+            staged_diffs: cx.create_rw_signal(IndexMap::new()),
+            unstaged_diffs: cx.create_rw_signal(IndexMap::new()),
+            repositories: cx.create_rw_signal(IndexMap::new()),
             branch: cx.create_rw_signal("".to_string()),
             branches: cx.create_rw_signal(im::Vector::new()),
             tags: cx.create_rw_signal(im::Vector::new()),
+            history: cx.create_rw_signal(im::Vector::new()),
+            history_file: cx.create_rw_signal(None),
+            history_done: cx.create_rw_signal(false),
+            history_commit: cx.create_rw_signal(None),
             editor: editors.make_local_with_name(
                 cx,
                 common.clone(),
@@ -76,19 +120,194 @@ impl SourceControlData {
         }
     }
 
-    pub fn commit(&self) {
-        let diffs: Vec<FileDiff> = self.file_diffs.with_untracked(|file_diffs| {
-            file_diffs
-                .iter()
-                .filter_map(
-                    |(_, (diff, checked))| {
-                        if *checked { Some(diff) } else { None }
-                    },
-                )
-                .cloned()
-                .collect()
+    /// Takes the git status of the repository of `root`, a folder of the
+    /// workspace. The changes shown are the ones of every repository.
+    pub fn set_diff(&self, root: PathBuf, diff: DiffInfo) {
+        if self.common.workspace.path() == Some(&root) {
+            self.branch.set(diff.head.clone());
+            self.branches.set(diff.branches.iter().cloned().collect());
+            self.tags.set(diff.tags.iter().cloned().collect());
+            // the history is of the first folder, and only changes with HEAD
+            let head_moved = self.repositories.with_untracked(|repositories| {
+                repositories.get(&root).map(|diff| &diff.head_commit)
+                    != Some(&diff.head_commit)
+            });
+            if head_moved {
+                self.reload_history();
+            }
+        }
+
+        let folders = self.common.workspace.folders();
+        self.repositories.update(|repositories| {
+            repositories.insert(root, diff);
+            repositories.sort_by_cached_key(|root, _| {
+                folders.iter().position(|folder| folder == root)
+            });
+        });
+        let mut file_diffs = IndexMap::new();
+        let mut staged_diffs = IndexMap::new();
+        let mut unstaged_diffs = IndexMap::new();
+        self.repositories.with_untracked(|repositories| {
+            let by_path = |diffs: &[FileDiff]| {
+                diffs
+                    .iter()
+                    .map(|diff| (diff.path().clone(), diff.clone()))
+                    .collect::<Vec<_>>()
+            };
+            for diff in repositories.values() {
+                file_diffs.extend(by_path(&diff.diffs));
+                staged_diffs.extend(by_path(&diff.staged));
+                unstaged_diffs.extend(by_path(&diff.unstaged));
+            }
         });
-        if diffs.is_empty() {
+        self.file_diffs.set(file_diffs);
+        self.staged_diffs.set(staged_diffs);
+        self.unstaged_diffs.set(unstaged_diffs);
+    }
+
+    /// Loads the next page of the history, or the first one on `reset`.
+    pub fn load_history(&self, reset: bool) {
+        let skip = if reset {
+            0
+        } else {
+            self.history.with_untracked(|history| history.len())
+        };
+        self.fetch_history(skip, HISTORY_PAGE_SIZE, reset);
+    }
+
+    /// Loads the history again from the newest commit, as many pages as
+    /// were loaded.
+    pub fn reload_history(&self) {
+        let pages = self
+            .history
+            .with_untracked(|history| history.len().div_ceil(HISTORY_PAGE_SIZE))
+            .max(1);
+        self.fetch_history(0, pages * HISTORY_PAGE_SIZE, true);
+    }
+
+    /// Loads `count` commits of the history after the first `skip`, in place
+    /// of the loaded ones on `reset`.
+    fn fetch_history(&self, skip: usize, count: usize, reset: bool) {
+        let path = self.history_file.get_untracked();
+        let history = self.history;
+        let history_file = self.history_file;
+        let history_done = self.history_done;
+        let send = {
+            let path = path.clone();
+            create_ext_action(self.common.scope, move |result| {
+                // The history was switched to another file meanwhile
+                if history_file.get_untracked() != path {
+                    return;
+                }
+                if let Ok(ProxyResponse::GitLogResponse { commits }) = result {
+                    history_done.set(commits.len() < count);
+                    history.update(|history| {
+                        if reset {
+                            history.clear();
+                        }
+                        history.extend(commits);
+                    });
+                }
+            })
+        };
+        self.common.proxy.proxy_rpc.git_log(
+            path,
+            skip,
+            count,
+            move |(_, result)| {
+                send(result);
+            },
+        );
+    }
+
+    /// Switches the history to the one of `path`, or of the workspace.
+    pub fn show_history(&self, path: Option<PathBuf>) {
+        self.history_file.set(path);
+        self.history_commit.set(None);
+        self.history.set(im::Vector::new());
+        self.history_done.set(false);
+        self.load_history(true);
+    }
+
+    pub fn stage(&self, path: PathBuf, selection: StageSelection) {
+        let send = self.reload_history_docs_action();
+        self.common.proxy.proxy_rpc.git_stage(
+            path,
+            selection,
+            move |(_, result)| send(result),
+        );
+    }
+
+    pub fn unstage(&self, path: PathBuf, selection: StageSelection) {
+        let send = self.reload_history_docs_action();
+        self.common.proxy.proxy_rpc.git_unstage(
+            path,
+            selection,
+            move |(_, result)| send(result),
+        );
+    }
+
+    /// Reloads the index shown in diff editors once the proxy has changed it.
+    fn reload_history_docs_action(
+        &self,
+    ) -> impl FnOnce(Result<ProxyResponse, RpcError>) + Send + 'static {
+        let internal_command = self.common.internal_command;
+        create_ext_action(self.common.scope, move |result| {
+            if result.is_ok() {
+                internal_command.send(InternalCommand::ReloadHistoryDocs);
+            }
+        })
+    }
+
+    /// Stages the lines selected in a diff editor, or the hunks they touch,
+    /// from the working copy side. Unstaging works on the index side.
+    pub fn stage_editor_selection(
+        &self,
+        editor: &EditorData,
+        hunks: bool,
+        stage: bool,
+    ) {
+        let doc = editor.doc();
+        let path = match doc.content.get_untracked() {
+            DocContent::File { path, .. } if stage => path,
+            DocContent::History(history) if !stage && history.version == "index" => {
+                history.path
+            },
+            _ => return,
+        };
+        let (start, end) = editor.cursor().with_untracked(|c| {
+            c.get_selection()
+                .map(|(start, end)| (start.min(end), start.max(end)))
+                .unwrap_or((c.offset(), c.offset()))
+        });
+        let (start, end) = doc.lines.with_untracked(|x| {
+            let buffer = x.buffer();
+            // A selection ending at the start of a line doesn't include it
+            let end = if end > start { end - 1 } else { end };
+            (buffer.line_of_offset(start), buffer.line_of_offset(end))
+        });
+        let selection = if hunks {
+            StageSelection::Hunks { start, end }
+        } else {
+            StageSelection::Lines { start, end }
+        };
+        if stage {
+            self.stage(path, selection);
+        } else {
+            self.unstage(path, selection);
+        }
+    }
+
+    /// Commits the index, or every change when nothing is staged.
+    pub fn commit(&self) {
+        let has_staged = self.staged_diffs.with_untracked(|diffs| !diffs.is_empty());
+        let diffs: Vec<FileDiff> = if has_staged {
+            Vec::new()
+        } else {
+            self.unstaged_diffs
+                .with_untracked(|diffs| diffs.values().cloned().collect())
+        };
+        if !has_staged && diffs.is_empty() {
             return;
         }
 
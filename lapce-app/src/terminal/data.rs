@@ -3,6 +3,7 @@ use std::{collections::HashMap, path::PathBuf, rc::Rc, sync::Arc};
 use alacritty_terminal::{
     Term,
     grid::{Dimensions, Scroll},
+    index::{Direction, Line},
     selection::{Selection, SelectionType},
     term::{TermMode, test::TermSize},
     vi_mode::ViMotion,
@@ -24,6 +25,7 @@ use floem::{
 use lapce_core::{
     debug::{RunDebugMode, RunDebugProcess},
     icon::LapceIcons,
+    main_split::SplitDirection,
     workspace::LapceWorkspace,
 };
 use lapce_rpc::{
@@ -33,9 +35,12 @@ use lapce_rpc::{
 use parking_lot::RwLock;
 use url::Url;
 
-use super::raw::{EventProxy, RawTerminal};
+use super::{
+    raw::{EventProxy, RawTerminal},
+    shell_integration::ShellCommand,
+};
 use crate::{
-    command::CommandKind,
+    command::{CommandKind, InternalCommand, LapceWorkbenchCommand},
     keypress::{KeyPressFocus, condition::Condition},
     window_workspace::CommonData,
 };
@@ -47,6 +52,34 @@ pub struct TerminalData {
     pub workspace: Arc<LapceWorkspace>,
     pub common:    Rc<CommonData>,
     pub data:      RwSignal<TerminalSignalData>,
+    pub find:      TerminalFindData,
+}
+
+/// The find bar of a terminal, which searches its whole scrollback
+#[derive(Clone, Copy, Debug)]
+pub struct TerminalFindData {
+    pub visible: RwSignal<bool>,
+    pub focus:   RwSignal<bool>,
+    pub pattern: RwSignal<String>,
+    /// Why the pattern is not a valid regex
+    pub error:   RwSignal<Option<String>>,
+    pub view_id: RwSignal<Option<ViewId>>,
+}
+
+impl TerminalFindData {
+    fn new(cx: Scope) -> Self {
+        Self {
+            visible: cx.create_rw_signal(false),
+            focus:   cx.create_rw_signal(false),
+            pattern: cx.create_rw_signal(String::new()),
+            error:   cx.create_rw_signal(None),
+            view_id: cx.create_rw_signal(None),
+        }
+    }
+
+    fn is_focused(&self) -> bool {
+        self.visible.get_untracked() && self.focus.get_untracked()
+    }
 }
 
 #[derive(Clone)]
@@ -94,11 +127,19 @@ impl TerminalData {
 
 impl KeyPressFocus for TerminalData {
     fn get_mode(&self) -> Mode {
-        self.data.with_untracked(|x| x.mode)
+        if self.find.is_focused() {
+            Mode::Insert
+        } else {
+            self.data.with_untracked(|x| x.mode)
+        }
     }
 
     fn check_condition(&self, condition: Condition) -> bool {
-        matches!(condition, Condition::TerminalFocus | Condition::PanelFocus)
+        match condition {
+            Condition::TerminalFocus | Condition::PanelFocus => true,
+            Condition::InputFocus | Condition::SearchFocus => self.find.is_focused(),
+            _ => false,
+        }
     }
 
     fn run_command(
@@ -275,43 +316,79 @@ impl KeyPressFocus for TerminalData {
                 _ => return CommandExecuted::No,
             },
             CommandKind::Focus(cmd) => match cmd {
-                FocusCommand::SplitVertical
-                | FocusCommand::SplitHorizontal
-                | FocusCommand::SplitLeft
-                | FocusCommand::SplitRight
-                | FocusCommand::SplitExchange
-                | FocusCommand::SearchForward => {
-                    // if let Some(search_string) =
-                    // self.find.search_string.as_ref() {
-                    //     let mut raw = self.terminal.raw.lock();
-                    //     let term = &mut raw.term;
-                    //     self.terminal.search_next(
-                    //         term,
-                    //         search_string,
-                    //         Direction::Right,
-                    //     );
-                    // }
+                FocusCommand::SplitVertical => {
+                    self.common.internal_command.send(
+                        InternalCommand::SplitTerminal {
+                            term_id:   self.term_id,
+                            direction: SplitDirection::Vertical,
+                        },
+                    );
+                },
+                FocusCommand::SplitHorizontal => {
+                    self.common.internal_command.send(
+                        InternalCommand::SplitTerminal {
+                            term_id:   self.term_id,
+                            direction: SplitDirection::Horizontal,
+                        },
+                    );
+                },
+                FocusCommand::SplitLeft | FocusCommand::SplitUp => {
+                    self.common.internal_command.send(
+                        InternalCommand::SplitTerminalPrevious {
+                            term_id: self.term_id,
+                        },
+                    );
+                },
+                FocusCommand::SplitRight | FocusCommand::SplitDown => {
+                    self.common.internal_command.send(
+                        InternalCommand::SplitTerminalNext {
+                            term_id: self.term_id,
+                        },
+                    );
+                },
+                FocusCommand::SplitExchange => {
+                    self.common.internal_command.send(
+                        InternalCommand::SplitTerminalExchange {
+                            term_id: self.term_id,
+                        },
+                    );
+                },
+                FocusCommand::SplitClose => {
+                    self.common.internal_command.send(
+                        InternalCommand::CloseTerminal {
+                            term_id: self.term_id,
+                        },
+                    );
+                },
+                FocusCommand::Search => {
+                    self.open_find();
+                },
+                FocusCommand::ClearSearch => {
+                    self.close_find();
+                },
+                FocusCommand::SearchForward => {
+                    self.search_next(Direction::Right);
                 },
                 FocusCommand::SearchBackward => {
-                    // if let Some(search_string) =
-                    // self.find.search_string.as_ref() {
-                    //     let mut raw = self.terminal.raw.lock();
-                    //     let term = &mut raw.term;
-                    //     self.terminal.search_next(
-                    //         term,
-                    //         search_string,
-                    //         Direction::Left,
-                    //     );
-                    // }
+                    self.search_next(Direction::Left);
                 },
                 _ => return CommandExecuted::No,
             },
+            CommandKind::Workbench(cmd) => {
+                if !self.run_shell_command(cmd) {
+                    return CommandExecuted::No;
+                }
+            },
             _ => return CommandExecuted::No,
         };
         CommandExecuted::Yes
     }
 
     fn receive_char(&self, c: &str) {
+        // the find input takes the text itself
+        if self.find.is_focused() {
+            return;
+        }
         self.data.with_untracked(|x| {
             if x.mode == Mode::Terminal {
                 self.common.proxy.proxy_rpc.terminal_write(
@@ -378,6 +455,7 @@ impl TerminalData {
             data: cx.create_rw_signal(data),
             workspace,
             common,
+            find: TerminalFindData::new(cx),
         }
     }
 
@@ -397,7 +475,10 @@ impl TerminalData {
             common.clone(),
         )));
 
-        let mut profile = profile.unwrap_or_default();
+        let mut profile = profile.unwrap_or_else(|| TerminalProfile {
+            shell_integration: true,
+            ..Default::default()
+        });
 
         if profile.workdir.is_none() {
             profile.workdir = url::Url::from_file_path(
@@ -406,8 +487,11 @@ impl TerminalData {
             .ok();
         }
 
+        // an attach session has no program of its own, so a plain shell hosts it
         let exp_run_debug = run_debug
-            .as_ref()
+            .filter(|run_debug| {
+                run_debug.is_prelaunch || !run_debug.config.is_attach()
+            })
             .map(|run_debug| {
                 ExpandedRunDebug::expand(&run_debug.config, run_debug.is_prelaunch)
             })
@@ -433,6 +517,7 @@ impl TerminalData {
 
             profile.command = Some(run_debug.program);
             profile.arguments = run_debug.args;
+            profile.shell_integration = false;
         }
 
         {
@@ -656,6 +741,132 @@ impl TerminalData {
         }
     }
 
+    pub fn open_find(&self) {
+        self.find.visible.set(true);
+        self.find.focus.set(true);
+        if let Some(view_id) = self.find.view_id.get_untracked() {
+            view_id.request_focus();
+        }
+    }
+
+    pub fn close_find(&self) {
+        self.find.visible.set(false);
+        self.find.focus.set(false);
+        let raw = self.data.with_untracked(|x| x.raw.clone());
+        let _ = raw.write().set_search("");
+        self.request_paint();
+    }
+
+    /// Compiles the pattern of the find bar, and moves to its last match
+    pub fn update_search(&self) {
+        let pattern = self.find.pattern.get_untracked();
+        let raw = self.data.with_untracked(|x| x.raw.clone());
+        let mut raw = raw.write();
+        match raw.set_search(&pattern) {
+            Ok(()) => {
+                self.find.error.set(None);
+                raw.search_next(Direction::Left);
+            },
+            Err(err) => self.find.error.set(Some(err)),
+        }
+        self.request_paint();
+    }
+
+    pub fn search_next(&self, direction: Direction) {
+        self.data
+            .with_untracked(|x| x.raw.write().search_next(direction));
+        self.request_paint();
+    }
+
+    /// Runs the workbench commands which act on the prompts and commands
+    /// marked by the shell, returning whether `cmd` is one of them.
+    pub fn run_shell_command(&self, cmd: &LapceWorkbenchCommand) -> bool {
+        match cmd {
+            LapceWorkbenchCommand::TerminalPreviousPrompt => {
+                self.scroll_to_prompt(Direction::Left);
+            },
+            LapceWorkbenchCommand::TerminalNextPrompt => {
+                self.scroll_to_prompt(Direction::Right);
+            },
+            LapceWorkbenchCommand::TerminalCopyLastOutput => {
+                if let Some(command) = self.last_finished_command() {
+                    self.copy_command_output(&command);
+                }
+            },
+            LapceWorkbenchCommand::TerminalRerunLastCommand => {
+                if let Some(command) = self.last_finished_command() {
+                    self.rerun_command(&command);
+                }
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn scroll_to_prompt(&self, direction: Direction) {
+        self.data
+            .with_untracked(|x| x.raw.write().scroll_to_prompt(direction));
+        self.request_paint();
+    }
+
+    pub fn last_finished_command(&self) -> Option<ShellCommand> {
+        self.data
+            .with_untracked(|x| x.raw.read().last_finished_command().cloned())
+    }
+
+    /// The command whose prompt or output is at `y` of the terminal view
+    pub fn command_at(&self, y: f64) -> Option<ShellCommand> {
+        let line_height = self
+            .common
+            .config
+            .with_untracked(|config| config.terminal_line_height() as f64);
+        let raw = self.data.with_untracked(|x| x.raw.clone());
+        let raw = raw.read();
+        let line =
+            Line((y / line_height) as i32 - raw.term.grid().display_offset() as i32);
+        raw.command_at(line).cloned()
+    }
+
+    pub fn copy_command(&self, command: &ShellCommand) {
+        let text = self
+            .data
+            .with_untracked(|x| x.raw.read().command_text(command));
+        if let Some(text) = text {
+            SystemClipboard::new().put_string(text);
+        }
+    }
+
+    pub fn copy_command_output(&self, command: &ShellCommand) {
+        let text = self
+            .data
+            .with_untracked(|x| x.raw.read().output_text(command));
+        if let Some(text) = text.filter(|text| !text.is_empty()) {
+            SystemClipboard::new().put_string(text);
+        }
+    }
+
+    /// Types the command line of `command` at the prompt, and runs it
+    pub fn rerun_command(&self, command: &ShellCommand) {
+        self.data.with_untracked(|x| {
+            let mut raw = x.raw.write();
+            let Some(text) = raw.command_text(command) else {
+                return;
+            };
+            self.common.proxy.proxy_rpc.terminal_write(
+                self.term_id,
+                x.raw_id,
+                format!("{text}\r"),
+            );
+            raw.term.scroll_display(Scroll::Bottom);
+        });
+    }
+
+    fn request_paint(&self) {
+        if let Some(view_id) = self.data.with_untracked(|x| x.view_id) {
+            view_id.request_paint();
+        }
+    }
+
     pub fn wheel_scroll(&self, delta: f64) {
         let step = self
             .common
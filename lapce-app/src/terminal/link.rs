@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+
+use regex::Regex;
+use url::Url;
+
+use crate::config::terminal::TerminalLinkAction;
+
+/// What ctrl+click on the output of a terminal opens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TerminalLink {
+    File {
+        /// As printed, possibly relative to the directory of the shell
+        path:   PathBuf,
+        /// 1-based, as compilers print it
+        line:   Option<u32>,
+        column: Option<u32>,
+    },
+    Url(String),
+}
+
+impl TerminalLink {
+    /// The target of an OSC 8 hyperlink, where a `file://` uri is opened in
+    /// the editor.
+    pub fn from_hyperlink(uri: &str) -> Self {
+        Url::parse(uri)
+            .ok()
+            .filter(|url| url.scheme() == "file")
+            .and_then(|mut url| {
+                // the host is the one the program ran on, which is this one
+                url.set_host(None).ok()?;
+                url.to_file_path().ok()
+            })
+            .map(|path| TerminalLink::File {
+                path,
+                line: None,
+                column: None,
+            })
+            .unwrap_or_else(|| TerminalLink::Url(uri.to_string()))
+    }
+}
+
+/// Finds the link of the first matcher with a match over the char at
+/// `index` of the line `text`.
+pub fn find_link(
+    text: &str,
+    index: usize,
+    matchers: &[(Regex, TerminalLinkAction)],
+) -> Option<TerminalLink> {
+    // the regexes work with byte offsets
+    let offset = text
+        .char_indices()
+        .nth(index)
+        .map(|(offset, _)| offset)
+        .unwrap_or(text.len());
+    matchers.iter().find_map(|(regex, action)| {
+        let captures = regex.captures_iter(text).find(|captures| {
+            captures
+                .get(0)
+                .is_some_and(|found| found.start() <= offset && offset < found.end())
+        })?;
+        match action {
+            TerminalLinkAction::OpenFile => {
+                let number = |name: &str| {
+                    captures.name(name).and_then(|x| x.as_str().parse().ok())
+                };
+                Some(TerminalLink::File {
+                    path:   captures.name("path")?.as_str().into(),
+                    line:   number("line"),
+                    column: number("column"),
+                })
+            },
+            TerminalLinkAction::OpenUrl => {
+                let url = captures.name("url").or_else(|| captures.get(0))?;
+                Some(TerminalLink::Url(url.as_str().to_string()))
+            },
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::terminal::TerminalLinkMatcher;
+
+    fn default_matchers() -> Vec<(Regex, TerminalLinkAction)> {
+        let settings: toml::Value =
+            toml::from_str(include_str!("../../../defaults/settings.toml")).unwrap();
+        let matchers: Vec<TerminalLinkMatcher> = settings["terminal"]
+            ["link-matchers"]
+            .clone()
+            .try_into()
+            .unwrap();
+        matchers
+            .into_iter()
+            .map(|matcher| (Regex::new(&matcher.regex).unwrap(), matcher.action))
+            .collect()
+    }
+
+    fn file(path: &str, line: u32, column: Option<u32>) -> Option<TerminalLink> {
+        Some(TerminalLink::File {
+            path: path.into(),
+            line: Some(line),
+            column,
+        })
+    }
+
+    #[test]
+    fn test_find_link() {
+        let matchers = default_matchers();
+        let link = |text: &str, at: &str| {
+            find_link(text, text.find(at).unwrap(), &matchers)
+        };
+        assert_eq!(
+            link("  --> src/main.rs:10:5", "main"),
+            file("src/main.rs", 10, Some(5))
+        );
+        assert_eq!(
+            link(r#"  File "/tmp/app.py", line 3, in <module>"#, "app"),
+            file("/tmp/app.py", 3, None)
+        );
+        assert_eq!(
+            link("\t/home/u/go/main.go:12 +0x1d", "main.go"),
+            file("/home/u/go/main.go", 12, None)
+        );
+        assert_eq!(
+            link("src/index.ts(4,13): error TS2322", "index"),
+            file("src/index.ts", 4, Some(13))
+        );
+        assert_eq!(
+            link("    at run (/app/src/a.ts:7:21)", "a.ts"),
+            file("/app/src/a.ts", 7, Some(21))
+        );
+        assert_eq!(
+            link("see https://example.com/a?b=1.", "example"),
+            Some(TerminalLink::Url("https://example.com/a?b=1".to_string()))
+        );
+        assert_eq!(link("  --> src/main.rs:10:5", "-->"), None);
+    }
+
+    #[test]
+    fn test_from_hyperlink() {
+        assert_eq!(
+            TerminalLink::from_hyperlink("https://example.com"),
+            TerminalLink::Url("https://example.com".to_string())
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            TerminalLink::from_hyperlink("file://host/tmp/a%20b.txt"),
+            TerminalLink::File {
+                path:   "/tmp/a b.txt".into(),
+                line:   None,
+                column: None,
+            }
+        );
+    }
+}
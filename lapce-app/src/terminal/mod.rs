@@ -1,5 +1,7 @@
 pub mod data;
+pub mod link;
 pub mod panel;
 pub mod raw;
+pub mod shell_integration;
 pub mod tab;
 pub mod view;
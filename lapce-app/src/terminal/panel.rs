@@ -10,6 +10,7 @@ use floem::{
 use lapce_core::{
     debug::{RunDebugMode, RunDebugProcess, ScopeOrVar},
     id::TerminalTabId,
+    main_split::SplitDirection,
     panel::PanelKind,
     workspace::LapceWorkspace,
 };
@@ -24,7 +25,7 @@ use log::{error, warn};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
-use super::data::TerminalData;
+use super::{data::TerminalData, tab::TerminalTabData};
 use crate::{
     debug::{DapData, DapVariable, RunDebugData},
     keypress::{EventRef, KeyPressData, KeyPressFocus, KeyPressHandle},
@@ -34,19 +35,52 @@ use crate::{
 };
 pub struct TerminalTabInfo {
     pub active: Option<TerminalTabId>,
-    pub tabs:   im::Vector<TerminalData>,
+    pub tabs:   im::Vector<TerminalTabData>,
 }
 
 impl TerminalTabInfo {
+    /// The index of the active tab and its active terminal
     pub fn active_tab(&self) -> Option<(usize, &TerminalData)> {
-        self.active.and_then(|active| {
-            self.tabs
-                .iter()
-                .enumerate()
-                .find(|(_index, tab)| tab.term_id == active)
+        self.active
+            .and_then(|active| {
+                self.tabs
+                    .iter()
+                    .enumerate()
+                    .find(|(_index, tab)| tab.terminal_tab_id == active)
+            })
+            .and_then(|(index, tab)| {
+                tab.active_terminal().map(|terminal| (index, terminal))
+            })
+    }
+
+    pub fn tab(&self, terminal_tab_id: TerminalTabId) -> Option<&TerminalTabData> {
+        self.tabs
+            .iter()
+            .find(|tab| tab.terminal_tab_id == terminal_tab_id)
+    }
+
+    /// The indexes of the tab containing the terminal and of the terminal in
+    /// it
+    fn find_terminal(&self, term_id: TermId) -> Option<(usize, usize)> {
+        self.tabs.iter().enumerate().find_map(|(tab_index, tab)| {
+            tab.index_of(term_id).map(|index| (tab_index, index))
         })
     }
 
+    pub fn terminals(&self) -> impl Iterator<Item = &TerminalData> {
+        self.tabs.iter().flat_map(|tab| tab.terminals.iter())
+    }
+
+    /// Makes the terminal the active one of its tab, and the tab the active
+    /// one
+    pub fn focus(&mut self, term_id: TermId) {
+        if let Some((tab_index, _)) = self.find_terminal(term_id) {
+            let tab = &mut self.tabs[tab_index];
+            tab.active = term_id;
+            self.active = Some(tab.terminal_tab_id);
+        }
+    }
+
     pub fn next_tab(&mut self) {
         let mut active_index = self.active_tab().map(|x| x.0).unwrap_or_default();
         if active_index >= self.tabs.len().saturating_sub(1) {
@@ -54,7 +88,7 @@ impl TerminalTabInfo {
         } else {
             active_index += 1;
         }
-        self.active = self.tabs.get(active_index).map(|x| x.term_id);
+        self.active = self.tabs.get(active_index).map(|x| x.terminal_tab_id);
     }
 
     pub fn previous_tab(&mut self) {
@@ -64,7 +98,7 @@ impl TerminalTabInfo {
         } else {
             active_index -= 1;
         }
-        self.active = self.tabs.get(active_index).map(|x| x.term_id);
+        self.active = self.tabs.get(active_index).map(|x| x.terminal_tab_id);
     }
 }
 
@@ -102,7 +136,7 @@ impl TerminalPanelData {
         );
         let cx = common.scope;
         let active = Some(terminal_data.term_id);
-        let tabs = im::vector![terminal_data];
+        let tabs = im::vector![TerminalTabData::new(terminal_data)];
         let tab_info = TerminalTabInfo { active, tabs };
         let tab_info = cx.create_rw_signal(tab_info);
 
@@ -119,12 +153,9 @@ impl TerminalPanelData {
                 };
 
                 let term = tab_info.with_untracked(|info| {
-                    for terminal in &info.tabs {
-                        if terminal.term_id == active_term {
-                            return Some(terminal.clone());
-                        }
-                    }
-                    None
+                    info.terminals()
+                        .find(|terminal| terminal.term_id == active_term)
+                        .cloned()
                 });
                 let term = match term {
                     Some(term) => term,
@@ -221,11 +252,10 @@ impl TerminalPanelData {
             profile,
             self.common.clone(),
         );
-        let tab_id = terminal.term_id;
-        let update_terminal = terminal.clone();
+        let tab = TerminalTabData::new(terminal.clone());
         self.tab_infos.update(|info| {
-            info.tabs.push_back(update_terminal);
-            info.active = Some(tab_id);
+            info.active = Some(tab.terminal_tab_id);
+            info.tabs.push_back(tab);
         });
 
         terminal
@@ -254,7 +284,7 @@ impl TerminalPanelData {
                 if let Some(terminal_tab_id) = terminal_tab_id {
                     if let Some(index) =
                         info.tabs.iter().enumerate().find_map(|(index, t)| {
-                            if t.term_id == terminal_tab_id {
+                            if t.terminal_tab_id == terminal_tab_id {
                                 Some(index)
                             } else {
                                 None
@@ -280,7 +310,9 @@ impl TerminalPanelData {
             })
             .flatten()
         {
-            close_tab.stop();
+            for terminal in close_tab.terminals {
+                terminal.stop();
+            }
         }
         self.update_debug_active_term();
     }
@@ -297,79 +329,128 @@ impl TerminalPanelData {
 
     pub fn get_terminal(&self, term_id: TermId) -> Option<TerminalData> {
         self.tab_infos.with_untracked(|info| {
-            for tab in &info.tabs {
-                if tab.term_id == term_id {
-                    return Some(tab.clone());
-                }
-            }
-            None
+            info.terminals()
+                .find(|terminal| terminal.term_id == term_id)
+                .cloned()
         })
     }
 
-    fn get_terminal_in_tab(&self, term_id: &TermId) -> Option<TerminalData> {
+    /// The tab containing the terminal and the index of the terminal in it
+    fn get_terminal_in_tab(
+        &self,
+        term_id: &TermId,
+    ) -> Option<(TerminalTabData, usize)> {
         self.tab_infos.with_untracked(|info| {
-            for tab in info.tabs.iter() {
-                if tab.term_id == *term_id {
-                    return Some(tab.clone());
+            info.find_terminal(*term_id)
+                .map(|(tab_index, index)| (info.tabs[tab_index].clone(), index))
+        })
+    }
+
+    /// Opens a new terminal next to the given one, in the same tab. The
+    /// direction applies to every terminal of the tab.
+    pub fn split(&self, term_id: TermId, direction: SplitDirection) {
+        if let Some((tab, index)) = self.get_terminal_in_tab(&term_id) {
+            let profile = self
+                .common
+                .config
+                .with_untracked(|config| config.terminal.get_default_profile());
+            let terminal = TerminalData::new(
+                self.common.scope.create_child(),
+                self.workspace.clone(),
+                profile,
+                self.common.clone(),
+            );
+            let new_term_id = terminal.term_id;
+            self.tab_infos.update(|info| {
+                if let Some(tab) = info
+                    .tabs
+                    .iter_mut()
+                    .find(|x| x.terminal_tab_id == tab.terminal_tab_id)
+                {
+                    tab.terminals.insert(index + 1, terminal);
+                    tab.direction = direction;
                 }
+                info.focus(new_term_id);
+            });
+            self.update_debug_active_term();
+        }
+    }
+
+    pub fn split_next(&self, term_id: TermId) {
+        if let Some((tab, index)) = self.get_terminal_in_tab(&term_id) {
+            if let Some(next) = tab.terminals.get(index + 1) {
+                self.focus_split(next.term_id);
             }
-            None
-        })
+        }
+    }
+
+    pub fn split_previous(&self, term_id: TermId) {
+        if let Some((tab, index)) = self.get_terminal_in_tab(&term_id) {
+            if let Some(previous) = index
+                .checked_sub(1)
+                .and_then(|index| tab.terminals.get(index))
+            {
+                self.focus_split(previous.term_id);
+            }
+        }
+    }
+
+    /// Swaps the terminal with the next one in its tab
+    pub fn split_exchange(&self, term_id: TermId) {
+        if let Some((tab, index)) = self.get_terminal_in_tab(&term_id) {
+            if index + 1 < tab.terminals.len() {
+                self.tab_infos.update(|info| {
+                    if let Some(tab) = info
+                        .tabs
+                        .iter_mut()
+                        .find(|x| x.terminal_tab_id == tab.terminal_tab_id)
+                    {
+                        tab.terminals.swap(index, index + 1);
+                    }
+                });
+            }
+        }
     }
 
-    // pub fn split(&self, term_id: TermId) {
-    //     if let Some((_, tab, index, _)) = self.get_terminal_in_tab(&term_id) {
-    //         let terminal_data = TerminalData::new(
-    //             tab.scope,
-    //             self.workspace.clone(),
-    //             None,
-    //             self.common.clone(),
-    //         );
-    //         let i = terminal_data.scope.create_rw_signal(0);
-    //         tab.terminal.update(|terminals| {
-    //             terminals.insert(index + 1, (i, terminal_data));
-    //         });
-    //     }
-    // }
-
-    // pub fn split_next(&self, term_id: TermId) {
-    //     if let Some((_, tab, index, _)) = self.get_terminal_in_tab(&term_id) {
-    //         let max = tab.terminal.with_untracked(|t| t.len() - 1);
-    //         let new_index = (index + 1).min(max);
-    //         if new_index != index {
-    //             tab.active.set(new_index);
-    //             self.update_debug_active_term();
-    //         }
-    //     }
-    // }
-
-    // pub fn split_previous(&self, term_id: TermId) {
-    //     if let Some((_, tab, index, _)) = self.get_terminal_in_tab(&term_id) {
-    //         let new_index = index.saturating_sub(1);
-    //         if new_index != index {
-    //             tab.active.set(new_index);
-    //             self.update_debug_active_term();
-    //         }
-    //     }
-    // }
-    //
-    // pub fn split_exchange(&self, term_id: TermId) {
-    //     if let Some((_, tab, index, _)) = self.get_terminal_in_tab(&term_id) {
-    //         let max = tab.terminal.with_untracked(|t| t.len() - 1);
-    //         if index < max {
-    //             tab.terminal.update(|terminals| {
-    //                 terminals.swap(index, index + 1);
-    //             });
-    //             self.update_debug_active_term();
-    //         }
-    //     }
-    // }
+    fn focus_split(&self, term_id: TermId) {
+        self.tab_infos.update(|info| {
+            info.focus(term_id);
+        });
+        self.update_debug_active_term();
+    }
 
+    /// Closes the terminal, and its tab if it was the last terminal in it
     pub fn close_terminal(&self, term_id: &TermId) {
-        // todo close tab directly
-        if let Some(_terminal_data) = self.get_terminal_in_tab(term_id) {
-            self.close_tab(Some(_terminal_data.term_id));
+        let Some((tab, index)) = self.get_terminal_in_tab(term_id) else {
+            return;
+        };
+        if tab.terminals.len() == 1 {
+            self.close_tab(Some(tab.terminal_tab_id));
+            return;
         }
+        let terminal = self.tab_infos.try_update(|info| {
+            let tab = info
+                .tabs
+                .iter_mut()
+                .find(|x| x.terminal_tab_id == tab.terminal_tab_id)?;
+            let terminal = tab.terminals.remove(index);
+            if tab.active == terminal.term_id {
+                let next = tab.terminals[index.min(tab.terminals.len() - 1)].term_id;
+                tab.active = next;
+            }
+            // the tab is known by the id of a terminal it still has
+            if tab.terminal_tab_id == terminal.term_id {
+                tab.terminal_tab_id = tab.active;
+                if info.active == Some(terminal.term_id) {
+                    info.active = Some(tab.active);
+                }
+            }
+            Some(terminal)
+        });
+        if let Some(terminal) = terminal.flatten() {
+            terminal.stop();
+        }
+        self.update_debug_active_term();
     }
 
     pub fn launch_failed(&self, term_id: &TermId, error: &str) {
@@ -443,6 +524,8 @@ impl TerminalPanelData {
                     // if let Some(mut run_debug) = run_debug {
                     if run_debug.mode == RunDebugMode::Debug {
                         update_executable(&mut run_debug, raw);
+                        self.debug
+                            .apply_exception_breakpoints(&mut run_debug.config);
 
                         self.common.proxy.proxy_rpc.dap_start(
                             run_debug.config,
@@ -454,6 +537,11 @@ impl TerminalPanelData {
                     // }
                 } else if !was_prelaunch && run_debug.mode == RunDebugMode::Debug {
                     terminal.common.breakpoints.update_by_stopped();
+                    if let Some(dap) =
+                        self.debug.daps.with_untracked(|x| x.get(&dap_id).cloned())
+                    {
+                        dap.continued();
+                    }
                 }
             } else {
                 todo!("???")
@@ -468,7 +556,7 @@ impl TerminalPanelData {
         config: &RunDebugConfig,
     ) -> Option<TerminalData> {
         self.tab_infos.with_untracked(|info| {
-            for terminal in &info.tabs {
+            for terminal in info.terminals() {
                 if terminal.data.with_untracked(|x| {
                     if let Some(run_debug) = x.run_debug.as_ref() {
                         if run_debug.stopped && &run_debug.mode == mode {
@@ -497,7 +585,7 @@ impl TerminalPanelData {
 
     pub fn focus_terminal(&self, terminal_id: TerminalTabId) {
         self.tab_infos.update(|info| {
-            info.active = Some(terminal_id);
+            info.focus(terminal_id);
         });
         self.common.focus.set(Focus::Panel(PanelKind::Terminal));
 
@@ -594,9 +682,10 @@ impl TerminalPanelData {
     pub fn run_debug_process_tracked(&self) -> Vec<(TermId, RunDebugProcess)> {
         let mut processes = Vec::new();
         self.tab_infos.with(|info| {
-            for tab in &info.tabs {
-                if let Some(run_debug) = tab.data.with(|x| x.run_debug.clone()) {
-                    processes.push((tab.term_id, run_debug));
+            for terminal in info.terminals() {
+                if let Some(run_debug) = terminal.data.with(|x| x.run_debug.clone())
+                {
+                    processes.push((terminal.term_id, run_debug));
                 }
             }
         });
@@ -608,7 +697,9 @@ impl TerminalPanelData {
         if let Some(terminal) = self.get_terminal(*term_id) {
             terminal.data.with_untracked(|x| {
                 if let Some(run_debug) = x.run_debug.as_ref() {
-                    if run_debug.config.debug_command.is_some() {
+                    if run_debug.config.debug_command.is_some()
+                        || (run_debug.config.is_attach() && !run_debug.is_prelaunch)
+                    {
                         let dap_id = run_debug.config.dap_id;
                         self.common
                             .proxy
@@ -626,8 +717,7 @@ impl TerminalPanelData {
             .daps
             .with_untracked(|daps| daps.get(dap_id).cloned());
         if let Some(dap) = dap {
-            dap.thread_id.set(None);
-            dap.stopped.set(false);
+            dap.continued();
         }
     }
 
@@ -662,7 +752,8 @@ impl TerminalPanelData {
         Some(())
     }
 
-    pub fn dap_start(&self, config: RunDebugConfig) {
+    pub fn dap_start(&self, mut config: RunDebugConfig) {
+        self.debug.apply_exception_breakpoints(&mut config);
         self.common
             .proxy
             .proxy_rpc
@@ -697,6 +788,23 @@ impl TerminalPanelData {
         Some(())
     }
 
+    pub fn dap_step_instruction(&self, term_id: TermId) -> Option<()> {
+        let terminal = self.get_terminal(term_id)?;
+        let dap_id = terminal
+            .data
+            .with_untracked(|r| r.run_debug.as_ref().map(|r| r.config.dap_id))?;
+        let thread_id = self.debug.daps.with_untracked(|daps| {
+            daps.get(&dap_id)
+                .and_then(|dap| dap.thread_id.get_untracked())
+        });
+        let thread_id = thread_id.unwrap_or_default();
+        self.common
+            .proxy
+            .proxy_rpc
+            .dap_step_instruction(dap_id, thread_id);
+        Some(())
+    }
+
     pub fn dap_step_into(&self, term_id: TermId) -> Option<()> {
         let terminal = self.get_terminal(term_id)?;
         let dap_id = terminal
@@ -761,6 +869,7 @@ impl TerminalPanelData {
 
     pub fn dap_frame_scopes(&self, dap_id: DapId, frame_id: usize) {
         if let Some(dap) = self.debug.daps.get_untracked().get(&dap_id) {
+            dap.select_frame(Some(frame_id));
             let variables = dap.variables;
             let send = create_ext_action(self.common.scope, move |result| {
                 if let Ok(ProxyResponse::DapGetScopesResponse { scopes }) = result {
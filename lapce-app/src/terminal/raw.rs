@@ -3,10 +3,10 @@ use std::rc::Rc;
 use alacritty_terminal::{
     Term,
     event::EventListener,
-    grid::Dimensions,
-    index::{Column, Direction, Line, Point},
+    grid::{Dimensions, Scroll},
+    index::{Boundary, Column, Direction, Line, Point, Side},
     term::{
-        cell::{Flags, LineLength},
+        cell::{Cell, Flags, LineLength},
         search::{Match, RegexIter, RegexSearch},
         test::TermSize,
     },
@@ -14,6 +14,7 @@ use alacritty_terminal::{
 };
 use lapce_rpc::terminal::TermId;
 
+use super::shell_integration::{ShellCommand, ShellIntegration, ShellPoint};
 use crate::window_workspace::CommonData;
 
 pub struct EventProxy {
@@ -51,6 +52,17 @@ pub struct RawTerminal {
     pub parser:       ansi::Processor,
     pub term:         Term<EventProxy>,
     pub scroll_delta: f64,
+    /// The pattern of the find bar
+    pub search:       Option<RegexSearch>,
+    /// The match the find bar last moved to
+    pub search_match: Option<Match>,
+    /// The text of `search_match`, to tell whether it is still there
+    search_text:      String,
+    /// The prompts and commands marked by the shell
+    pub shell:        ShellIntegration,
+    /// How many lines dropped off the top of the full scrollback, which the
+    /// shell points count
+    evicted:          usize,
 }
 
 impl RawTerminal {
@@ -74,13 +86,205 @@ impl RawTerminal {
             parser,
             term,
             scroll_delta: 0.0,
+            search: None,
+            search_match: None,
+            search_text: String::new(),
+            shell: ShellIntegration::default(),
+            evicted: 0,
         }
     }
 
+    /// Sets the pattern of the find bar, an empty one clears it.
+    pub fn set_search(&mut self, pattern: &str) -> Result<(), String> {
+        self.search_match = None;
+        self.search = None;
+        if !pattern.is_empty() {
+            self.search =
+                Some(RegexSearch::new(pattern).map_err(|err| err.to_string())?);
+        }
+        Ok(())
+    }
+
+    /// Moves to the next match of the find bar in the whole scrollback,
+    /// wrapping around at either end, and scrolls it into view.
+    pub fn search_next(&mut self, direction: Direction) -> Option<Match> {
+        let regex = self.search.as_mut()?;
+        let term = &self.term;
+        let (origin, side) = match (&self.search_match, direction) {
+            (Some(current), Direction::Right) => {
+                let end = *current.end();
+                (end.add(term, Boundary::None, 1), Side::Left)
+            },
+            (Some(current), Direction::Left) => {
+                let start = *current.start();
+                (start.sub(term, Boundary::None, 1), Side::Right)
+            },
+            // without a match yet, start from either end of the scrollback
+            (None, Direction::Right) => {
+                (Point::new(term.topmost_line(), Column(0)), Side::Left)
+            },
+            (None, Direction::Left) => (
+                Point::new(term.bottommost_line(), term.last_column()),
+                Side::Right,
+            ),
+        };
+        let found = term.search_next(regex, origin, direction, side, None)?;
+        self.term.scroll_to_point(*found.start());
+        self.search_text = self.term.bounds_to_string(*found.start(), *found.end());
+        self.search_match = Some(found.clone());
+        Some(found)
+    }
+
+    /// Resizes the grid, which reflows the lines the find bar match is on.
+    pub fn resize(&mut self, size: TermSize) {
+        self.term.resize(size);
+        self.search_match = None;
+    }
+
     pub fn update_content(&mut self, content: &Vec<u8>) {
+        let history_size = self.term.grid().history_size();
         for byte in content {
+            let top = self.top_row();
+            let before = self.term.grid().history_size();
             self.parser.advance(&mut self.term, *byte);
+            // once the scrollback is full, what scrolls into it pushes as
+            // many of its oldest lines out
+            let grown = self.term.grid().history_size().saturating_sub(before);
+            self.evicted += self.scrolled_since(top).saturating_sub(grown);
+            if let Some(mark) = self.shell.advance(*byte) {
+                let point = self.shell_point(self.term.grid().cursor.point);
+                self.shell.mark(mark, point);
+            }
+        }
+        self.reanchor_search_match(history_size);
+    }
+
+    /// Moves the find bar match up with the lines the output pushed into
+    /// the scrollback, and drops it once its text is no longer there, as
+    /// when it scrolled out of a full scrollback or was overwritten.
+    fn reanchor_search_match(&mut self, history_size: usize) {
+        let Some(found) = self.search_match.take() else {
+            return;
+        };
+        let shift = self.term.grid().history_size() as i32 - history_size as i32;
+        let start =
+            Point::new(Line(found.start().line.0 - shift), found.start().column);
+        let end = Point::new(Line(found.end().line.0 - shift), found.end().column);
+        if start.line >= self.term.topmost_line()
+            && self.term.bounds_to_string(start, end) == self.search_text
+        {
+            self.search_match = Some(start..=end);
+        }
+    }
+
+    /// The first cell of the top line of the screen. The grid scrolls by
+    /// rotating its rows, so the cells of a row stay where they are.
+    fn top_row(&self) -> *const Cell {
+        &self.term.grid()[Line(0)][Column(0)]
+    }
+
+    /// How many lines the screen scrolled up since `top` was its top row,
+    /// at most a screen as one byte of output scrolls no more.
+    fn scrolled_since(&self, top: *const Cell) -> usize {
+        if std::ptr::eq(self.top_row(), top) {
+            return 0;
+        }
+        let grid = self.term.grid();
+        let scrolled = grid.history_size().min(grid.screen_lines());
+        (1..=scrolled)
+            .find(|lines| {
+                std::ptr::eq(&grid[Line(-(*lines as i32))][Column(0)], top)
+            })
+            .unwrap_or(0)
+    }
+
+    fn shell_point(&self, point: Point) -> ShellPoint {
+        let history_size = self.term.grid().history_size() as i32;
+        ShellPoint {
+            line:   (point.line.0 + history_size).max(0) as usize + self.evicted,
+            column: point.column.0,
+        }
+    }
+
+    /// The point of the grid at a shell point, if it is still in the
+    /// scrollback.
+    pub fn grid_point(&self, point: ShellPoint) -> Option<Point> {
+        let history_size = self.term.grid().history_size() as i32;
+        let line = Line(point.line as i32 - self.evicted as i32 - history_size);
+        if line < self.term.topmost_line() || line > self.term.bottommost_line() {
+            return None;
+        }
+        Some(Point::new(line, Column(point.column)))
+    }
+
+    /// Scrolls the prompt before, or after, the top of the viewport to the
+    /// top. Past the last prompt, it scrolls to the bottom.
+    pub fn scroll_to_prompt(&mut self, direction: Direction) {
+        let top = Line(-(self.term.grid().display_offset() as i32));
+        let mut prompts = self
+            .shell
+            .commands
+            .iter()
+            .filter_map(|command| self.grid_point(command.prompt))
+            .map(|point| point.line);
+        let target = match direction {
+            Direction::Left => prompts.filter(|line| *line < top).last(),
+            Direction::Right => prompts.find(|line| *line > top),
+        };
+        let scroll = match (target, direction) {
+            (Some(line), _) => Scroll::Delta(top.0 - line.0),
+            (None, Direction::Right) => Scroll::Bottom,
+            (None, Direction::Left) => return,
+        };
+        self.term.scroll_display(scroll);
+    }
+
+    /// The command whose prompt or output is at the line of the grid
+    pub fn command_at(&self, line: Line) -> Option<&ShellCommand> {
+        self.shell.commands.iter().rev().find(|command| {
+            let Some(prompt) = self.grid_point(command.prompt) else {
+                return false;
+            };
+            let end = command.end.and_then(|end| self.grid_point(end));
+            prompt.line <= line && end.is_none_or(|end| line <= end.line)
+        })
+    }
+
+    pub fn last_finished_command(&self) -> Option<&ShellCommand> {
+        self.shell
+            .commands
+            .iter()
+            .rev()
+            .find(|command| command.is_finished())
+    }
+
+    /// The command line of a command which ran, as reported by the shell
+    /// or else as typed at its prompt
+    pub fn command_text(&self, command: &ShellCommand) -> Option<String> {
+        if let Some(command_line) = &command.command_line {
+            return Some(command_line.clone());
+        }
+        let start = self.grid_point(command.command?)?;
+        let end = self.grid_point(command.output?)?;
+        let text = self.text_between(start, end);
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    /// What a finished command printed
+    pub fn output_text(&self, command: &ShellCommand) -> Option<String> {
+        let start = self.grid_point(command.output?)?;
+        let end = self.grid_point(command.end?)?;
+        Some(self.text_between(start, end).trim_end().to_string())
+    }
+
+    /// The text from `start` up to, but not including, `end`
+    fn text_between(&self, start: Point, end: Point) -> String {
+        if end <= start {
+            return String::new();
         }
+        let end = end.sub(&self.term, Boundary::Grid, 1);
+        self.term.bounds_to_string(start, end)
     }
 
     pub fn output(&self, line_num: usize) -> Vec<String> {
@@ -0,0 +1,309 @@
+use std::path::PathBuf;
+
+use url::Url;
+
+/// The longest OSC 7 / OSC 133 / OSC 633 sequence kept, a command line is the
+/// only long one.
+const MAX_SEQUENCE_LEN: usize = 64 * 1024;
+/// How many commands of a terminal are remembered
+const MAX_COMMANDS: usize = 1000;
+
+/// What a shell integration sequence marks, at the position of the cursor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellMark {
+    /// `A`, the prompt is about to be printed
+    PromptStart,
+    /// `B`, the prompt is printed and the user types the command after it
+    CommandStart,
+    /// `C`, the command runs and its output follows
+    CommandExecuted,
+    /// `D`, the command finished
+    CommandFinished { exit_code: Option<i32> },
+    /// `E` of OSC 633, the command line as the shell sees it
+    CommandLine(String),
+    /// The working directory of the shell, from OSC 7 or `P;Cwd=` of OSC 633
+    Cwd(PathBuf),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ParserState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Finds the OSC 7, OSC 133 and OSC 633 sequences in the output of a
+/// terminal. The terminal itself ignores them, so the output is fed to both.
+#[derive(Default)]
+pub struct ShellMarkParser {
+    state:    ParserState,
+    sequence: Vec<u8>,
+    /// The OSC sequence is not one of ours, and is skipped until its end
+    skip:     bool,
+}
+
+impl ShellMarkParser {
+    /// Feeds the next byte of output, returning the mark of the sequence it
+    /// ends, if any.
+    pub fn advance(&mut self, byte: u8) -> Option<ShellMark> {
+        match (self.state, byte) {
+            (ParserState::Ground, 0x1b) => self.state = ParserState::Escape,
+            (ParserState::Ground, _) => {},
+            (ParserState::Escape | ParserState::OscEscape, b']') => {
+                self.sequence.clear();
+                self.skip = false;
+                self.state = ParserState::Osc;
+            },
+            (ParserState::Escape, 0x1b) => {},
+            (ParserState::Escape, _) => self.state = ParserState::Ground,
+            // BEL ends an OSC sequence, and so does ST (`ESC \`)
+            (ParserState::Osc, 0x07) | (ParserState::OscEscape, b'\\') => {
+                self.state = ParserState::Ground;
+                if !self.skip {
+                    return parse_mark(&String::from_utf8_lossy(&self.sequence));
+                }
+            },
+            (ParserState::Osc, 0x1b) => self.state = ParserState::OscEscape,
+            (ParserState::Osc, _) => {
+                if !self.skip {
+                    self.sequence.push(byte);
+                    let len = self.sequence.len();
+                    let is_ours =
+                        [&b"7;"[..], b"133;", b"633;"].iter().any(|prefix| {
+                            let prefix_len = prefix.len().min(len);
+                            prefix[..prefix_len] == self.sequence[..prefix_len]
+                        });
+                    if !is_ours || len > MAX_SEQUENCE_LEN {
+                        self.skip = true;
+                        self.sequence.clear();
+                    }
+                }
+            },
+            (ParserState::OscEscape, _) => self.state = ParserState::Ground,
+        }
+        None
+    }
+}
+
+/// Parses the content of an OSC sequence, like `133;D;0`.
+fn parse_mark(sequence: &str) -> Option<ShellMark> {
+    let (kind, rest) = sequence.split_once(';')?;
+    if kind == "7" {
+        // `file://host/path`, the host being the one the shell runs on
+        let mut url = Url::parse(rest).ok()?;
+        url.set_host(None).ok()?;
+        return url.to_file_path().ok().map(ShellMark::Cwd);
+    }
+    if kind != "133" && kind != "633" {
+        return None;
+    }
+    let mut params = rest.split(';');
+    Some(match params.next()? {
+        "A" => ShellMark::PromptStart,
+        "B" => ShellMark::CommandStart,
+        "C" => ShellMark::CommandExecuted,
+        "D" => ShellMark::CommandFinished {
+            exit_code: params.next().and_then(|code| code.parse().ok()),
+        },
+        "E" if kind == "633" => ShellMark::CommandLine(unescape(params.next()?)),
+        "P" if kind == "633" => {
+            let cwd = params.next()?.strip_prefix("Cwd=")?;
+            ShellMark::Cwd(unescape(cwd).into())
+        },
+        _ => return None,
+    })
+}
+
+/// Undoes the escaping of the values of OSC 633, where `\\` is a backslash and
+/// `\xAB` is the byte 0xAB, which the shell uses for `;` and control
+/// characters.
+fn unescape(escaped: &str) -> String {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut rest = escaped.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match rest {
+            [b'\\', tail @ ..] => {
+                bytes.push(b'\\');
+                rest = tail;
+            },
+            [b'x', high, low, tail @ ..] => {
+                match std::str::from_utf8(&[*high, *low])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(byte) => {
+                        bytes.push(byte);
+                        rest = tail;
+                    },
+                    None => bytes.push(b'\\'),
+                }
+            },
+            _ => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// A point of the terminal, with the line counted from the first line of
+/// its output, including the lines since dropped from the scrollback, so
+/// that it stays put while the output scrolls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShellPoint {
+    pub line:   usize,
+    pub column: usize,
+}
+
+/// A prompt and the command run at it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShellCommand {
+    pub prompt:       ShellPoint,
+    /// Where the command typed at the prompt starts
+    pub command:      Option<ShellPoint>,
+    /// Where the output starts, once the command runs
+    pub output:       Option<ShellPoint>,
+    /// Where the output ends, once the command finished
+    pub end:          Option<ShellPoint>,
+    pub exit_code:    Option<i32>,
+    /// The command line reported by the shell, only OSC 633 has it
+    pub command_line: Option<String>,
+}
+
+impl ShellCommand {
+    pub fn is_finished(&self) -> bool {
+        self.end.is_some()
+    }
+}
+
+/// The commands run in a terminal, as marked by its shell.
+#[derive(Default)]
+pub struct ShellIntegration {
+    parser:       ShellMarkParser,
+    pub commands: Vec<ShellCommand>,
+    /// The working directory last reported by the shell
+    pub cwd:      Option<PathBuf>,
+}
+
+impl ShellIntegration {
+    pub fn advance(&mut self, byte: u8) -> Option<ShellMark> {
+        self.parser.advance(byte)
+    }
+
+    pub fn mark(&mut self, mark: ShellMark, point: ShellPoint) {
+        if let ShellMark::Cwd(cwd) = mark {
+            self.cwd = Some(cwd);
+            return;
+        }
+        if let ShellMark::PromptStart = mark {
+            // a prompt left without running anything is not worth keeping
+            if self
+                .commands
+                .last()
+                .is_some_and(|command| command.output.is_none())
+            {
+                self.commands.pop();
+            }
+            if self.commands.len() >= MAX_COMMANDS {
+                self.commands.remove(0);
+            }
+            self.commands.push(ShellCommand {
+                prompt: point,
+                ..Default::default()
+            });
+            return;
+        }
+        let Some(command) = self.commands.last_mut() else {
+            return;
+        };
+        match mark {
+            ShellMark::PromptStart | ShellMark::Cwd(_) => {},
+            ShellMark::CommandStart => command.command = Some(point),
+            ShellMark::CommandExecuted => command.output = Some(point),
+            ShellMark::CommandFinished { exit_code } => {
+                if command.output.is_some() && command.end.is_none() {
+                    command.end = Some(point);
+                    command.exit_code = exit_code;
+                }
+            },
+            ShellMark::CommandLine(command_line) => {
+                command.command_line = Some(command_line)
+            },
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marks(output: &[u8]) -> Vec<ShellMark> {
+        let mut parser = ShellMarkParser::default();
+        output
+            .iter()
+            .filter_map(|byte| parser.advance(*byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_shell_mark_parser() {
+        assert_eq!(
+            marks(b"\x1b]133;A\x07$ \x1b]133;B\x1b\\ls\r\n\x1b]133;C\x07"),
+            vec![
+                ShellMark::PromptStart,
+                ShellMark::CommandStart,
+                ShellMark::CommandExecuted
+            ]
+        );
+        assert_eq!(
+            marks(b"\x1b]0;title\x07\x1b]633;D;127\x07\x1b]133;D\x07"),
+            vec![
+                ShellMark::CommandFinished {
+                    exit_code: Some(127),
+                },
+                ShellMark::CommandFinished { exit_code: None }
+            ]
+        );
+        assert_eq!(
+            marks(b"\x1b]633;E;echo a\\x3bb \\\\n\x07"),
+            vec![ShellMark::CommandLine("echo a;b \\n".to_string())]
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            marks(
+                b"\x1b]7;file://host/tmp/a%20b\x07\x1b]633;P;Cwd=/tmp/c\\x3bd\x07"
+            ),
+            vec![
+                ShellMark::Cwd("/tmp/a b".into()),
+                ShellMark::Cwd("/tmp/c;d".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_integration_mark() {
+        let point = |line| ShellPoint { line, column: 0 };
+        let mut shell = ShellIntegration::default();
+        shell.mark(ShellMark::PromptStart, point(0));
+        // nothing ran at the first prompt
+        shell.mark(ShellMark::PromptStart, point(1));
+        shell.mark(ShellMark::CommandStart, point(1));
+        shell.mark(ShellMark::CommandExecuted, point(2));
+        shell.mark(ShellMark::CommandFinished { exit_code: Some(1) }, point(4));
+        shell.mark(ShellMark::PromptStart, point(4));
+        assert_eq!(shell.commands.len(), 2);
+        assert_eq!(shell.commands[0].prompt, point(1));
+        assert_eq!(shell.commands[0].exit_code, Some(1));
+        assert!(shell.commands[0].is_finished());
+        assert!(!shell.commands[1].is_finished());
+    }
+}
@@ -1,55 +1,39 @@
-use std::{rc::Rc, sync::Arc};
-
-use floem::reactive::{RwSignal, Scope, SignalWith};
-use lapce_core::{
-    debug::RunDebugProcess, id::TerminalTabId, workspace::LapceWorkspace,
-};
-use lapce_rpc::terminal::TerminalProfile;
+use lapce_core::{id::TerminalTabId, main_split::SplitDirection};
+use lapce_rpc::terminal::TermId;
 
 use super::data::TerminalData;
-use crate::window_workspace::CommonData;
 
+/// A terminal tab, which can be split into multiple terminals.
 #[derive(Clone)]
 pub struct TerminalTabData {
-    pub scope:           Scope,
+    /// The id of one of its terminals, the one it was created with until
+    /// that is closed
     pub terminal_tab_id: TerminalTabId,
-    pub terminal:        RwSignal<TerminalData>,
+    pub terminals:       im::Vector<TerminalData>,
+    pub active:          TermId,
+    pub direction:       SplitDirection,
 }
 
 impl TerminalTabData {
-    pub fn new(
-        workspace: Arc<LapceWorkspace>,
-        profile: Option<TerminalProfile>,
-        common: Rc<CommonData>,
-    ) -> Self {
-        TerminalTabData::new_run_debug(workspace, None, profile, common)
-    }
-
-    /// Create the information for a terminal tab, which can contain multiple
-    /// terminals.
-    pub fn new_run_debug(
-        workspace: Arc<LapceWorkspace>,
-        run_debug: Option<RunDebugProcess>,
-        profile: Option<TerminalProfile>,
-        common: Rc<CommonData>,
-    ) -> Self {
-        let cx = common.scope.create_child();
-        let terminal_data =
-            TerminalData::new_run_debug(cx, workspace, run_debug, profile, common);
-        let terminal_tab_id = terminal_data.term_id;
-        let terminals = cx.create_rw_signal(terminal_data);
+    pub fn new(terminal: TerminalData) -> Self {
         Self {
-            scope: cx,
-            terminal_tab_id,
-            terminal: terminals,
+            terminal_tab_id: terminal.term_id,
+            active:          terminal.term_id,
+            terminals:       im::vector![terminal],
+            direction:       SplitDirection::Vertical,
         }
     }
 
-    pub fn active_terminal(&self, tracked: bool) -> TerminalData {
-        if tracked {
-            self.terminal.with(|t| t.clone())
-        } else {
-            self.terminal.with_untracked(|t| t.clone())
-        }
+    pub fn active_terminal(&self) -> Option<&TerminalData> {
+        self.terminals
+            .iter()
+            .find(|terminal| terminal.term_id == self.active)
+            .or_else(|| self.terminals.front())
+    }
+
+    pub fn index_of(&self, term_id: TermId) -> Option<usize> {
+        self.terminals
+            .iter()
+            .position(|terminal| terminal.term_id == term_id)
     }
 }
@@ -1,10 +1,14 @@
-use std::{path::PathBuf, sync::Arc, time::SystemTime};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
 
 use alacritty_terminal::{
     grid::Dimensions,
     index::Side,
     selection::{Selection, SelectionType},
-    term::{RenderableContent, cell::Flags, test::TermSize},
+    term::{RenderableContent, cell::Flags, search::Match, test::TermSize},
 };
 use doc::lines::{mode::Mode, register::Clipboard, text::SystemClipboard};
 use floem::{
@@ -23,7 +27,6 @@ use floem::{
 use lapce_core::{panel::PanelKind, workspace::LapceWorkspace};
 use lapce_rpc::{proxy::ProxyRpcHandler, terminal::TermId};
 use lsp_types::Position;
-use regex::Regex;
 
 use super::panel::TerminalPanelData;
 use crate::{
@@ -32,7 +35,11 @@ use crate::{
     config::{LapceConfig, WithLapceConfig, color::LapceColor},
     editor::location::{EditorLocation, EditorPosition},
     listener::Listener,
-    terminal::data::TerminalData,
+    terminal::{
+        data::TerminalData,
+        link::{TerminalLink, find_link},
+        raw::{RawTerminal, visible_regex_match_iter},
+    },
     window_workspace::Focus,
 };
 
@@ -65,7 +72,6 @@ pub struct TerminalView {
     // launch_error: RwSignal<Option<String>>,
     internal_command:      Listener<InternalCommand>,
     workspace:             Arc<LapceWorkspace>,
-    hyper_regs:            Vec<Regex>,
     previous_mouse_action: MouseAction,
     current_mouse_action:  MouseAction,
     terminal_data:         TerminalData,
@@ -123,9 +129,6 @@ pub fn terminal_view(
         is_focused
     });
 
-    // for rust
-    let reg =
-        regex::Regex::new(r"([:\.\w\\/-]+\.(rs|toml)?):(\d+)(:(\d+))?").unwrap();
     // let raw = raw_data.with_untracked(|x| x.raw.clone());
 
     TerminalView {
@@ -139,7 +142,6 @@ pub fn terminal_view(
         is_focused: false,
         internal_command,
         workspace,
-        hyper_regs: vec![reg],
         previous_mouse_action: Default::default(),
         current_mouse_action: Default::default(),
     }
@@ -171,61 +173,109 @@ impl TerminalView {
         (width.max(1), height.max(1))
     }
 
+    /// Opens the link under the ctrl+click: an OSC 8 hyperlink, or else the
+    /// first match of the link matchers of the config.
     fn click(&self, pos: Point) -> Option<()> {
         let raw = self.terminal_data.data.with_untracked(|x| x.raw.clone());
         let raw = raw.read();
-        let position = self.get_terminal_point(pos);
-        let start_point = raw.term.semantic_search_left(position);
-        let end_point = raw.term.semantic_search_right(position);
+        let term = &raw.term;
+        let mut position = self.get_terminal_point(pos);
+        if position.line < term.topmost_line()
+            || position.line > term.bottommost_line()
+        {
+            return None;
+        }
+        position.column = position.column.min(term.last_column());
+
+        if let Some(hyperlink) = term.grid()[position].hyperlink() {
+            self.open_link(
+                TerminalLink::from_hyperlink(hyperlink.uri()),
+                raw.shell.cwd.as_deref(),
+            );
+            return Some(());
+        }
+
+        // the whole line, which may wrap over multiple rows of the grid
+        let start = term.line_search_left(position);
+        let end = term.line_search_right(position);
+        let text = term.bounds_to_string(start, end);
+        let index = term
+            .bounds_to_string(start, position)
+            .chars()
+            .count()
+            .saturating_sub(1);
+        let link = self.config.with_untracked(|config| {
+            find_link(&text, index, &config.terminal.compiled_link_matchers)
+        });
+        if let Some(link) = link {
+            self.open_link(link, raw.shell.cwd.as_deref());
+            return Some(());
+        }
+
+        let start_point = term.semantic_search_left(position);
+        let end_point = term.semantic_search_right(position);
         let mut selection =
             Selection::new(SelectionType::Simple, start_point, Side::Left);
         selection.update(end_point, Side::Right);
         selection.include_all();
-        if let Some(selection) = selection.to_range(&raw.term) {
-            let content = raw.term.bounds_to_string(selection.start, selection.end);
-
-            if let Some(rs) =
-                self.hyper_regs.iter().find_map(|x| x.captures(&content))
-            {
-                if let (Some(path), Some(line), col) =
-                    (rs.get(1), rs.get(3), rs.get(5))
-                {
-                    let mut file: PathBuf = path.as_str().into();
-                    let line = line.as_str().parse::<u32>().ok()?;
-                    let col = col
-                        .and_then(|x| x.as_str().parse::<u32>().ok())
-                        .unwrap_or(0);
-
-                    if !file.exists() {
-                        log::info!("{file:?} is not exists");
-                        file = self.workspace.path()?.join(file);
-                    }
-                    self.internal_command.send(InternalCommand::JumpToLocation {
-                        location: EditorLocation {
-                            path:               file,
-                            position:           Some(EditorPosition::Position(
-                                Position::new(
-                                    line.saturating_sub(1),
-                                    col.saturating_sub(1),
-                                ),
-                            )),
-                            scroll_offset:      None,
-                            ignore_unconfirmed: false,
-                            same_editor_tab:    false,
-                        },
-                    });
-                }
-                return Some(());
-            } else {
-                self.proxy.find_file_from_log(
-                    content,
-                    find_file_call_back(self.scope, self.internal_command),
-                );
-            }
+        if let Some(selection) = selection.to_range(term) {
+            let content = term.bounds_to_string(selection.start, selection.end);
+            self.proxy.find_file_from_log(
+                content,
+                find_file_call_back(self.scope, self.internal_command),
+            );
         }
         None
     }
 
+    fn open_link(&self, link: TerminalLink, cwd: Option<&Path>) {
+        let (path, line, column) = match link {
+            TerminalLink::Url(uri) => {
+                self.internal_command
+                    .send(InternalCommand::OpenWebUri { uri });
+                return;
+            },
+            TerminalLink::File { path, line, column } => (path, line, column),
+        };
+        // a relative path is looked up where the shell is, then in the
+        // workspace
+        let path = if path.is_absolute() {
+            path
+        } else {
+            let found = cwd
+                .into_iter()
+                .chain(self.workspace.path().map(PathBuf::as_path))
+                .map(|dir| dir.join(&path))
+                .find(|candidate| candidate.exists());
+            match found {
+                Some(found) => found,
+                None => {
+                    log::info!("{path:?} is not exists");
+                    self.proxy.find_file_from_log(
+                        path.to_string_lossy().into_owned(),
+                        find_file_call_back(self.scope, self.internal_command),
+                    );
+                    return;
+                },
+            }
+        };
+        let position = line.map(|line| {
+            EditorPosition::Position(Position::new(
+                line.saturating_sub(1),
+                column.unwrap_or(1).saturating_sub(1),
+            ))
+        });
+        self.internal_command.send(InternalCommand::JumpToLocation {
+            location: EditorLocation {
+                path,
+                position,
+                scroll_offset: None,
+                ignore_unconfirmed: false,
+                same_editor_tab: false,
+            },
+        });
+    }
+
     fn update_mouse_action_by_down(&mut self, mouse: &PointerInputEvent) {
         let mut next_action = MouseAction::None;
         match self.current_mouse_action {
@@ -424,6 +474,24 @@ impl TerminalView {
                 }
             }
 
+            // the text of an OSC 8 hyperlink is underlined
+            if cell.hyperlink().is_some() {
+                let y = char_y + char_size.height;
+                match line_content.underline.last_mut() {
+                    Some((_, end, color, _))
+                        if *color == fg && *end == point.column.0 =>
+                    {
+                        *end += 1;
+                    },
+                    _ => line_content.underline.push((
+                        point.column.0,
+                        point.column.0 + 1,
+                        fg,
+                        y,
+                    )),
+                }
+            }
+
             if cursor_point == &point {
                 line_content.cursor = Some((cell.c, x));
             }
@@ -651,7 +719,7 @@ impl View for TerminalView {
             self.size = size;
             let (width, height) = self.terminal_size();
             let term_size = TermSize::new(width, height);
-            raw.write().term.resize(term_size);
+            raw.write().resize(term_size);
             self.proxy.terminal_resize(self.term_id, width, height);
             log::debug!("{:?} {}-{}", self.term_id, width, height);
         }
@@ -690,13 +758,54 @@ impl View for TerminalView {
             return;
         }
 
-        let raw = raw.read();
-        let term = &raw.term;
+        let mut raw = raw.write();
+        let RawTerminal {
+            term,
+            search,
+            search_match,
+            shell,
+            ..
+        } = &mut *raw;
+        let term = &*term;
         let content = term.renderable_content();
 
-        // let mut search = RegexSearch::new("[\\w\\\\?]+\\.rs:\\d+:\\d+").unwrap();
-        // self.hyper_matches = visible_regex_match_iter(term, &mut
-        // search).collect();
+        if let Some(search) = search.as_mut() {
+            let match_color = config.color(LapceColor::EDITOR_DOCUMENT_HIGHLIGHT);
+            let current_color = config.color(LapceColor::EDITOR_SELECTION);
+            for found in visible_regex_match_iter(term, search) {
+                let color = if search_match.as_ref() == Some(&found) {
+                    current_color
+                } else {
+                    match_color
+                };
+                paint_match(
+                    cx,
+                    &found,
+                    content.display_offset,
+                    term.last_column().0,
+                    char_width,
+                    line_height,
+                    color,
+                );
+            }
+        }
+
+        // the markers of the prompts sit in the padding left of the terminal
+        let history_size = term.grid().history_size() as i32;
+        for command in &shell.commands {
+            let line = command.prompt.line as i32 - history_size
+                + content.display_offset as i32;
+            if line < 0 || line > term.bottommost_line().0 {
+                continue;
+            }
+            let color = match command.exit_code {
+                Some(0) => config.color(LapceColor::TERMINAL_GREEN),
+                Some(_) => config.color(LapceColor::TERMINAL_RED),
+                None => config.color(LapceColor::EDITOR_DIM),
+            };
+            let y = line as f64 * line_height;
+            cx.fill(&Rect::new(-7.0, y, -4.0, y + line_height), color, 0.0);
+        }
 
         if let Some(selection) = content.selection.as_ref() {
             let start_line = selection.start.line.0 + content.display_offset as i32;
@@ -775,6 +884,42 @@ impl View for TerminalView {
     }
 }
 
+/// Fills the cells of a match of the find bar, line by line
+fn paint_match(
+    cx: &mut PaintCx,
+    found: &Match,
+    display_offset: usize,
+    last_column: usize,
+    char_width: f64,
+    line_height: f64,
+    color: Color,
+) {
+    let (start, end) = (found.start(), found.end());
+    for line in start.line.0..=end.line.0 {
+        let left_col = if line == start.line.0 {
+            start.column.0
+        } else {
+            0
+        };
+        let right_col = if line == end.line.0 {
+            end.column.0 + 1
+        } else {
+            last_column + 1
+        };
+        let y0 = (line + display_offset as i32) as f64 * line_height;
+        cx.fill(
+            &Rect::new(
+                left_col as f64 * char_width,
+                y0,
+                right_col as f64 * char_width,
+                y0 + line_height,
+            ),
+            color,
+            0.0,
+        );
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone)]
 enum MouseAction {
     #[default]
@@ -0,0 +1,662 @@
+use std::{collections::VecDeque, path::PathBuf, rc::Rc};
+
+use floem::reactive::{RwSignal, Scope, SignalGet, SignalUpdate, SignalWith};
+use im::{HashMap, HashSet, Vector};
+use lapce_core::{debug::RunDebugMode, icon::LapceIcons};
+use lapce_rpc::{
+    core::RustBuildOutput,
+    dap_types::{ConfigSource, DebugRequest, RunDebugConfig, RunDebugProgram},
+    proxy::TestCommand,
+};
+use rust_resolve::libtest::{
+    TestBinary, TestEvent, parse_listed_test, parse_test_artifact, parse_test_event,
+};
+
+use crate::{command::InternalCommand, window_workspace::CommonData};
+
+/// A test of a test binary, the binary is identified by its executable as
+/// a library and a binary of the same package share a name.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TestId {
+    pub executable: String,
+    pub name:       String,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TestState {
+    #[default]
+    NotRun,
+    Running,
+    Passed,
+    Failed,
+    Ignored,
+}
+
+impl TestState {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            TestState::NotRun => LapceIcons::TEST_NOT_RUN,
+            TestState::Running => LapceIcons::TEST_RUNNING,
+            TestState::Passed => LapceIcons::TEST_PASSED,
+            TestState::Failed => LapceIcons::TEST_FAILED,
+            TestState::Ignored => LapceIcons::TEST_IGNORED,
+        }
+    }
+
+    /// The state of a module or a binary from the states of its tests
+    fn merge(self, other: TestState) -> TestState {
+        let rank = |state: TestState| match state {
+            TestState::Failed => 4,
+            TestState::Running => 3,
+            TestState::NotRun => 2,
+            TestState::Passed => 1,
+            TestState::Ignored => 0,
+        };
+        if rank(other) > rank(self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TestResult {
+    pub state:    TestState,
+    /// In seconds
+    pub duration: Option<f64>,
+    /// What a failed test printed
+    pub stdout:   String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestSuite {
+    pub binary: TestBinary,
+    pub tests:  Vector<String>,
+}
+
+/// A node of the tree of crates, modules and tests
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TestNode {
+    Binary {
+        executable: String,
+    },
+    /// `path` is the module path, like `a::tests`
+    Module {
+        executable: String,
+        path:       String,
+    },
+    Test(TestId),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TestRow {
+    pub node:         TestNode,
+    pub level:        usize,
+    pub label:        String,
+    pub has_children: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestRunKind {
+    /// Listing the tests of the workspace
+    Discover,
+    Run,
+}
+
+#[derive(Clone)]
+pub struct TestExplorerData {
+    pub suites:     RwSignal<Vector<TestSuite>>,
+    pub results:    RwSignal<HashMap<TestId, TestResult>>,
+    pub collapsed:  RwSignal<HashSet<TestNode>>,
+    pub selected:   RwSignal<Option<TestId>>,
+    /// Output of any other run is from a run that was replaced
+    pub run_id:     RwSignal<u64>,
+    pub running:    RwSignal<Option<TestRunKind>>,
+    pub exit_code:  RwSignal<Option<i32>>,
+    /// The binary that is running, which the output belongs to, none while
+    /// cargo builds the binaries
+    current_binary: RwSignal<Option<TestBinary>>,
+    /// The binaries cargo built that are still to be run
+    pending:        RwSignal<VecDeque<TestBinary>>,
+    /// What each binary of the run is passed
+    test_arguments: RwSignal<Vec<String>>,
+    /// The suites found by the discovery that is running
+    discovered:     RwSignal<Vector<TestSuite>>,
+    pub common:     Rc<CommonData>,
+}
+
+impl TestExplorerData {
+    pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
+        Self {
+            suites: cx.create_rw_signal(Vector::new()),
+            results: cx.create_rw_signal(HashMap::new()),
+            collapsed: cx.create_rw_signal(HashSet::new()),
+            selected: cx.create_rw_signal(None),
+            run_id: cx.create_rw_signal(0),
+            running: cx.create_rw_signal(None),
+            exit_code: cx.create_rw_signal(None),
+            current_binary: cx.create_rw_signal(None),
+            pending: cx.create_rw_signal(VecDeque::new()),
+            test_arguments: cx.create_rw_signal(Vec::new()),
+            discovered: cx.create_rw_signal(Vector::new()),
+            common,
+        }
+    }
+
+    /// Lists the tests of the workspace with `--list` of each test binary.
+    pub fn refresh(&self) {
+        self.discovered.set(Vector::new());
+        self.start(
+            TestRunKind::Discover,
+            workspace_targets(),
+            vec![
+                "--list".to_string(),
+                "--format".to_string(),
+                "terse".to_string(),
+            ],
+        );
+    }
+
+    /// Runs the tests under `node`, or all of them.
+    pub fn run(&self, node: Option<&TestNode>) {
+        let binary = node.and_then(|node| self.binary(node.executable()));
+        let (targets, filters, exact) = match (node, binary) {
+            (Some(TestNode::Binary { .. }), Some(binary)) => {
+                (binary.target_arguments(), Vec::new(), false)
+            },
+            (Some(TestNode::Module { path, .. }), Some(binary)) => {
+                (binary.target_arguments(), vec![format!("{path}::")], false)
+            },
+            (Some(TestNode::Test(id)), Some(binary)) => {
+                (binary.target_arguments(), vec![id.name.clone()], true)
+            },
+            _ => (workspace_targets(), Vec::new(), false),
+        };
+        self.results.update(|results| {
+            results.retain(|id, _| node.is_some_and(|node| !node.contains(id)));
+        });
+
+        let mut arguments = filters;
+        if exact {
+            arguments.push("--exact".to_string());
+        }
+        arguments.extend(
+            [
+                "-Z",
+                "unstable-options",
+                "--format",
+                "json",
+                "--report-time",
+            ]
+            .map(String::from),
+        );
+        self.start(TestRunKind::Run, targets, arguments);
+    }
+
+    /// Builds the test binaries of `targets` with cargo, and then runs each
+    /// of them with `test_arguments`. Cargo itself only builds, so that the
+    /// unstable libtest options are allowed for the tests and not the build.
+    fn start(
+        &self,
+        kind: TestRunKind,
+        targets: Vec<String>,
+        test_arguments: Vec<String>,
+    ) {
+        if let Some(run_id) = self.running_id() {
+            self.common.proxy.proxy_rpc.test_run_cancel(run_id);
+        }
+        let run_id = self.run_id.get_untracked() + 1;
+        self.run_id.set(run_id);
+        self.running.set(Some(kind));
+        self.exit_code.set(None);
+        self.current_binary.set(None);
+        self.pending.set(VecDeque::new());
+        self.test_arguments.set(test_arguments);
+
+        let mut arguments = vec![
+            "test".to_string(),
+            "--no-run".to_string(),
+            "--message-format=json".to_string(),
+        ];
+        arguments.extend(targets);
+        self.common
+            .proxy
+            .proxy_rpc
+            .test_run(run_id, TestCommand::Build { arguments });
+    }
+
+    /// Runs the next binary cargo built, returns false when there is none.
+    fn run_next_binary(&self, run_id: u64) -> bool {
+        let mut next = None;
+        self.pending.update(|pending| next = pending.pop_front());
+        let Some(binary) = next else {
+            return false;
+        };
+        let executable = PathBuf::from(&binary.executable);
+        let cwd = PathBuf::from(&binary.manifest_path)
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        self.current_binary.set(Some(binary));
+        self.common.proxy.proxy_rpc.test_run(
+            run_id,
+            TestCommand::Binary {
+                executable,
+                cwd,
+                arguments: self.test_arguments.get_untracked(),
+            },
+        );
+        true
+    }
+
+    pub fn cancel(&self) {
+        if let Some(run_id) = self.running_id() {
+            self.pending.set(VecDeque::new());
+            self.common.proxy.proxy_rpc.test_run_cancel(run_id);
+        }
+    }
+
+    fn running_id(&self) -> Option<u64> {
+        self.running
+            .get_untracked()
+            .map(|_| self.run_id.get_untracked())
+    }
+
+    pub fn output(&self, run_id: u64, output: RustBuildOutput) {
+        if run_id != self.run_id.get_untracked() {
+            return;
+        }
+        let RustBuildOutput::StdOut(line) = output else {
+            return;
+        };
+        let Some(binary) = self.current_binary.get_untracked() else {
+            // cargo is building, and lists the binaries it built
+            if let Some(binary) = parse_test_artifact(&line) {
+                self.pending.update(|pending| pending.push_back(binary));
+            }
+            return;
+        };
+        match self.running.get_untracked() {
+            Some(TestRunKind::Discover) => {
+                if let Some(name) = parse_listed_test(&line) {
+                    self.discovered.update(|suites| {
+                        add_test(suites, &binary, name);
+                    });
+                }
+            },
+            Some(TestRunKind::Run) => {
+                if let Some(event) = parse_test_event(&line) {
+                    self.test_event(&binary, event);
+                }
+            },
+            None => {},
+        }
+    }
+
+    fn test_event(&self, binary: &TestBinary, event: TestEvent) {
+        let (name, result) = match event {
+            TestEvent::Started { name } => (
+                name,
+                TestResult {
+                    state: TestState::Running,
+                    ..Default::default()
+                },
+            ),
+            TestEvent::Passed { name, exec_time } => (
+                name,
+                TestResult {
+                    state:    TestState::Passed,
+                    duration: exec_time,
+                    stdout:   String::new(),
+                },
+            ),
+            TestEvent::Failed {
+                name,
+                exec_time,
+                stdout,
+            } => (
+                name,
+                TestResult {
+                    state: TestState::Failed,
+                    duration: exec_time,
+                    stdout,
+                },
+            ),
+            TestEvent::Ignored { name } => (
+                name,
+                TestResult {
+                    state: TestState::Ignored,
+                    ..Default::default()
+                },
+            ),
+        };
+        // tests added since the last refresh still show up
+        if !self.contains_test(&binary.executable, &name) {
+            self.suites.update(|suites| add_test(suites, binary, &name));
+        }
+        let id = TestId {
+            executable: binary.executable.clone(),
+            name,
+        };
+        self.results.update(|results| {
+            results.insert(id, result);
+        });
+    }
+
+    pub fn stopped(&self, run_id: u64, exit_code: Option<i32>) {
+        if run_id != self.run_id.get_untracked() {
+            return;
+        }
+        let building = self.current_binary.get_untracked().is_none();
+        // the first failure is the one reported
+        if self.exit_code.get_untracked().is_none_or(|code| code == 0) {
+            self.exit_code.set(exit_code);
+        }
+        if (!building || exit_code == Some(0)) && self.run_next_binary(run_id) {
+            return;
+        }
+        let exit_code = self.exit_code.get_untracked();
+        match self.running.get_untracked() {
+            Some(TestRunKind::Discover) => {
+                let discovered = self.discovered.get_untracked();
+                // keep the old tree if the workspace doesn't build
+                if exit_code == Some(0) || !discovered.is_empty() {
+                    self.suites.set(discovered);
+                }
+                self.discovered.set(Vector::new());
+            },
+            Some(TestRunKind::Run) => {
+                self.results.update(|results| {
+                    results.retain(|_, result| result.state != TestState::Running);
+                });
+            },
+            None => {},
+        }
+        self.running.set(None);
+        self.current_binary.set(None);
+        self.pending.set(VecDeque::new());
+    }
+
+    fn binary(&self, executable: &str) -> Option<TestBinary> {
+        self.suites.with_untracked(|suites| {
+            suites
+                .iter()
+                .find(|suite| suite.binary.executable == executable)
+                .map(|suite| suite.binary.clone())
+        })
+    }
+
+    fn contains_test(&self, executable: &str, name: &str) -> bool {
+        self.suites.with_untracked(|suites| {
+            suites.iter().any(|suite| {
+                suite.binary.executable == executable
+                    && suite.tests.iter().any(|test| test == name)
+            })
+        })
+    }
+
+    pub fn result(&self, id: &TestId) -> TestResult {
+        self.results
+            .with(|results| results.get(id).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn state(&self, node: &TestNode) -> TestState {
+        if let TestNode::Test(id) = node {
+            return self.result(id).state;
+        }
+        let tests = self.suites.with(|suites| {
+            suites
+                .iter()
+                .flat_map(|suite| {
+                    suite.tests.iter().map(|name| TestId {
+                        executable: suite.binary.executable.clone(),
+                        name:       name.clone(),
+                    })
+                })
+                .filter(|id| node.contains(id))
+                .collect::<Vec<_>>()
+        });
+        self.results.with(|results| {
+            tests
+                .iter()
+                .map(|id| results.get(id).map(|r| r.state).unwrap_or_default())
+                .reduce(TestState::merge)
+                .unwrap_or_default()
+        })
+    }
+
+    /// The visible rows of the tree, skipping what is under collapsed nodes.
+    pub fn rows(&self) -> Vector<TestRow> {
+        let collapsed = self.collapsed.get();
+        let workspace = self.common.workspace.clone();
+        let mut rows = Vector::new();
+        self.suites.with(|suites| {
+            for suite in suites.iter() {
+                let executable = suite.binary.executable.clone();
+                let hidden = |modules: &[&str]| {
+                    (1..=modules.len()).any(|i| {
+                        collapsed.contains(&TestNode::Module {
+                            executable: executable.clone(),
+                            path:       modules[..i].join("::"),
+                        })
+                    })
+                };
+                let node = TestNode::Binary {
+                    executable: executable.clone(),
+                };
+                let is_collapsed = collapsed.contains(&node);
+                let src = PathBuf::from(&suite.binary.src);
+                let src = workspace.relative_path(&src).unwrap_or(src);
+                rows.push_back(TestRow {
+                    node,
+                    level: 0,
+                    label: format!("{} ({})", suite.binary.name, src.display()),
+                    has_children: !suite.tests.is_empty(),
+                });
+                if is_collapsed {
+                    continue;
+                }
+                // libtest lists the tests sorted, so the tests of a module
+                // are next to each other
+                let mut modules: Vec<&str> = Vec::new();
+                for name in suite.tests.iter() {
+                    let segments: Vec<&str> = name.split("::").collect();
+                    let Some((test, parents)) = segments.split_last() else {
+                        continue;
+                    };
+                    let common = modules
+                        .iter()
+                        .zip(parents.iter())
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    modules.truncate(common);
+                    for segment in &parents[common..] {
+                        let parent_hidden = hidden(&modules);
+                        modules.push(*segment);
+                        if !parent_hidden {
+                            rows.push_back(TestRow {
+                                node:         TestNode::Module {
+                                    executable: executable.clone(),
+                                    path:       modules.join("::"),
+                                },
+                                level:        modules.len(),
+                                label:        segment.to_string(),
+                                has_children: true,
+                            });
+                        }
+                    }
+                    if !hidden(parents) {
+                        rows.push_back(TestRow {
+                            node:         TestNode::Test(TestId {
+                                executable: executable.clone(),
+                                name:       name.clone(),
+                            }),
+                            level:        parents.len() + 1,
+                            label:        test.to_string(),
+                            has_children: false,
+                        });
+                    }
+                }
+            }
+        });
+        rows
+    }
+
+    pub fn toggle_collapsed(&self, node: &TestNode) {
+        self.collapsed.update(|collapsed| {
+            if collapsed.remove(node).is_none() {
+                collapsed.insert(node.clone());
+            }
+        });
+    }
+
+    /// The function names of the failed tests by the file that defines
+    /// them, to mark them in the gutter.
+    pub fn failed_functions(&self) -> HashMap<PathBuf, HashSet<String>> {
+        let failed: Vec<TestId> = self.results.with(|results| {
+            results
+                .iter()
+                .filter(|(_, result)| result.state == TestState::Failed)
+                .map(|(id, _)| id.clone())
+                .collect()
+        });
+        let mut functions = HashMap::<PathBuf, HashSet<String>>::new();
+        for id in failed {
+            let (Some(binary), Some(function)) =
+                (self.binary(&id.executable), id.name.rsplit("::").next())
+            else {
+                continue;
+            };
+            let file = binary.test_source_file(&id.name, |path| path.exists());
+            functions
+                .entry(file)
+                .or_default()
+                .insert(function.to_string());
+        }
+        functions
+    }
+
+    /// Runs a single test in the terminal, or under the debugger, the same
+    /// way the run configurations are run.
+    pub fn debug(&self, id: &TestId, mode: RunDebugMode) {
+        let Some(binary) = self.binary(&id.executable) else {
+            return;
+        };
+        let cwd = PathBuf::from(&binary.manifest_path)
+            .parent()
+            .map(|dir| dir.to_string_lossy().to_string());
+        let mut build = ["test", "--no-run"].map(String::from).to_vec();
+        build.extend(binary.target_arguments());
+        let config = RunDebugConfig {
+            ty: (mode == RunDebugMode::Debug).then(|| "lldb".to_string()),
+            name: id.name.clone(),
+            request: DebugRequest::Launch,
+            program: id.executable.clone(),
+            args: Some(vec![
+                id.name.clone(),
+                "--exact".to_string(),
+                "--nocapture".to_string(),
+            ]),
+            cwd,
+            env: None,
+            // rebuild the binary, whose path stays the same
+            prelaunch: Some(RunDebugProgram {
+                program: "cargo".to_string(),
+                args:    Some(build),
+            }),
+            debug_command: None,
+            dap_id: Default::default(),
+            tracing_output: false,
+            config_source: ConfigSource::Palette,
+            exception_filters: None,
+            break_on_panic: false,
+            pid: None,
+        };
+        self.common
+            .internal_command
+            .send(InternalCommand::RunAndDebug { mode, config });
+    }
+}
+
+impl TestNode {
+    pub fn executable(&self) -> &str {
+        match self {
+            TestNode::Binary { executable } => executable,
+            TestNode::Module { executable, .. } => executable,
+            TestNode::Test(id) => &id.executable,
+        }
+    }
+
+    pub fn contains(&self, id: &TestId) -> bool {
+        match self {
+            TestNode::Binary { executable } => executable == &id.executable,
+            TestNode::Module { executable, path } => {
+                executable == &id.executable
+                    && id
+                        .name
+                        .strip_prefix(path.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            },
+            TestNode::Test(test) => test == id,
+        }
+    }
+}
+
+/// The arguments of `cargo test` for the tests of every target.
+fn workspace_targets() -> Vec<String> {
+    ["--workspace", "--all-targets"].map(String::from).to_vec()
+}
+
+/// The name of the function a line of rust declares, like `fn it_works() {`.
+pub fn test_function_name(line: &str) -> Option<&str> {
+    let mut line = line.trim_start();
+    while let Some(rest) = ["pub ", "async "]
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+    {
+        line = rest.trim_start();
+    }
+    let rest = line.strip_prefix("fn ")?.trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+fn add_test(suites: &mut Vector<TestSuite>, binary: &TestBinary, name: &str) {
+    match suites
+        .iter_mut()
+        .find(|suite| suite.binary.executable == binary.executable)
+    {
+        Some(suite) => {
+            let index = suite
+                .tests
+                .iter()
+                .position(|test| test.as_str() > name)
+                .unwrap_or(suite.tests.len());
+            suite.tests.insert(index, name.to_string());
+        },
+        None => suites.push_back(TestSuite {
+            binary: binary.clone(),
+            tests:  Vector::unit(name.to_string()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_function_name;
+
+    #[test]
+    fn test_test_function_name() {
+        assert_eq!(test_function_name("    fn it_works() {"), Some("it_works"));
+        assert_eq!(
+            test_function_name("pub async fn runs_async() -> Result<()> {"),
+            Some("runs_async")
+        );
+        assert_eq!(test_function_name("    // fn commented() {"), None);
+        assert_eq!(test_function_name("let f = fn_pointer;"), None);
+    }
+}
@@ -205,6 +205,9 @@ fn middle(
                 .entry(MenuItem::new("Open Folder").action(move || {
                     workbench_command.send(LapceWorkbenchCommand::OpenFolder);
                 }))
+                .entry(MenuItem::new("Open Workspace File").action(move || {
+                    workbench_command.send(LapceWorkbenchCommand::OpenWorkspaceFile);
+                }))
                 .entry(MenuItem::new("Open Recent Workspace").action(move || {
                     workbench_command.send(LapceWorkbenchCommand::PaletteWorkspace);
                 }))
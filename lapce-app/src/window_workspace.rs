@@ -9,7 +9,6 @@ use std::{
 
 use alacritty_terminal::vte::ansi::Handler;
 use anyhow::{Result, anyhow, bail};
-use cozy_floem::views::{panel::DocStyle, tree_with_panel::data::TreePanelData};
 use doc::lines::{
     buffer::rope_text::RopeText, command::FocusCommand,
     editor_command::CommandExecuted, mode::Mode, register::Register,
@@ -44,9 +43,11 @@ use lapce_core::{
     workspace::{LapceWorkspace, LapceWorkspaceType, WorkspaceInfo},
 };
 use lapce_rpc::{
-    RpcError,
-    core::CoreNotification,
-    dap_types::{ConfigSource, RunDebugConfig, SourceBreakpoint},
+    RequestId, RpcError,
+    core::{CoreNotification, CoreRequest, CoreResponse, CoreRpcHandler},
+    dap_types::{
+        ConfigSource, DapId, DebugRequest, RunDebugConfig, SourceBreakpoint,
+    },
     file::{Naming, PathObject},
     plugin::PluginId,
     proxy::{ProxyResponse, ProxyStatus},
@@ -56,9 +57,10 @@ use lapce_rpc::{
 use lapce_xi_rope::Rope;
 use log::{debug, error, trace, warn};
 use lsp_types::{
-    CodeActionOrCommand, CodeLens, Diagnostic, DiagnosticSeverity, MessageType,
-    NumberOrString, ProgressParams, ProgressToken, ShowMessageParams,
-    WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressEnd,
+    CodeActionOrCommand, CodeLens, Diagnostic, DiagnosticSeverity,
+    MessageActionItem, MessageType, NumberOrString, ProgressParams, ProgressToken,
+    Range, ShowDocumentParams, ShowMessageParams, WorkDoneProgress,
+    WorkDoneProgressBegin, WorkDoneProgressEnd,
 };
 use serde_json::Value;
 
@@ -70,11 +72,14 @@ use crate::{
         CommandKind, InternalCommand, LapceCommand, LapceWorkbenchCommand,
         WindowCommand,
     },
-    common::call_back::find_log_modules_call_back,
+    common::{Tab, call_back::find_log_modules_call_back},
     completion::{CompletionData, CompletionStatus},
     config::{LapceConfig, WithLapceConfig},
     db::LapceDb,
-    debug::{BreakPoints, DapData, update_breakpoints},
+    debug::{
+        BreakPoints, BreakpointEditField, DISASSEMBLY_DOC, DapData,
+        update_breakpoints,
+    },
     doc::Doc,
     editor::location::{EditorLocation, EditorPosition},
     editor_tab::EditorTabChildId,
@@ -85,19 +90,26 @@ use crate::{
     inline_completion::InlineCompletionData,
     keypress::{EventRef, KeyPressData, KeyPressFocus, condition::Condition},
     listener::Listener,
+    local_history::LocalHistoryData,
     local_task::LocalTaskRequester,
     lsp::path_from_url,
     main_split::{MainSplitData, SplitData},
     palette::{DEFAULT_RUN_TOML, PaletteData, PaletteStatus, kind::PaletteKind},
     panel::{
-        call_hierarchy_view::CallHierarchyItemData, data::PanelData,
+        call_hierarchy_view::{
+            HierarchyData, HierarchyDirection, HierarchyItem, HierarchyItemData,
+        },
+        data::PanelData,
         document_symbol::MatchDocumentSymbol,
     },
     plugin::PluginData,
     proxy::{ProxyData, new_proxy},
     rename::RenameData,
+    rust_build::{CargoCommand, RustBuildData, is_build_diagnostic},
+    snippet::UserSnippets,
     source_control::SourceControlData,
     terminal::panel::TerminalPanelData,
+    test_explorer::TestExplorerData,
     window::{CursorBlink, WindowCommonData},
 };
 
@@ -218,6 +230,9 @@ pub struct CommonData {
     pub proxy_status:          RwSignal<Option<ProxyStatus>>,
     pub mouse_hover_timer:     RwSignal<TimerToken>,
     pub breakpoints:           BreakPoints,
+    /// The debug session and stack frame the debuggee is stopped at, used to
+    /// evaluate the expression under the mouse
+    pub stopped_frame:         RwSignal<Option<(DapId, usize)>>,
     // the current focused view which will receive keyboard events
     pub keyboard_focus:        RwSignal<Option<ViewId>>,
     pub window_common:         Rc<WindowCommonData>,
@@ -228,6 +243,10 @@ pub struct CommonData {
     pub find_view_id:          RwSignal<Option<ViewId>>,
     pub inspect_info:          RwSignal<String>,
     pub run_debug_configs:     RwSignal<RunDebugConfigs>,
+    /// The snippets of the user, from the `snippets` directory of the config
+    pub snippets:              RwSignal<UserSnippets>,
+    /// The file a snapshot was last added to the local history of
+    pub local_history_updated: RwSignal<Option<PathBuf>>,
 }
 
 impl std::fmt::Debug for CommonData {
@@ -274,6 +293,34 @@ impl CommonData {
     }
 }
 
+/// A message shown in the corner of the window, with the buttons of the
+/// actions a language server offers for it.
+#[derive(Clone)]
+pub struct WindowMessage {
+    pub title:   String,
+    pub message: ShowMessageParams,
+    pub actions: Vec<MessageActionItem>,
+    /// The `window/showMessageRequest` waiting for the action the user picks
+    pub request: Option<RequestId>,
+}
+
+impl WindowMessage {
+    /// Answers the request of the message, if any, with the picked action or
+    /// none when the message is dismissed.
+    pub fn answer(
+        &self,
+        core_rpc: &CoreRpcHandler,
+        action: Option<MessageActionItem>,
+    ) {
+        if let Some(id) = self.request {
+            core_rpc.handle_response(
+                id,
+                Ok(CoreResponse::ShowMessageRequestResponse { action }),
+            );
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct WindowWorkspaceData {
     pub scope:                     Scope,
@@ -299,10 +346,12 @@ pub struct WindowWorkspaceData {
     pub set_config:                WriteSignal<LapceConfig>,
     pub update_in_progress:        RwSignal<bool>,
     pub progresses:                RwSignal<IndexMap<ProgressToken, WorkProgress>>,
-    pub messages:                  RwSignal<Vec<(String, ShowMessageParams)>>,
+    pub messages:                  RwSignal<Vec<WindowMessage>>,
     pub common:                    Rc<CommonData>,
     pub document_symbol_scroll_to: RwSignal<Option<f64>>,
-    pub build_data:                TreePanelData,
+    pub build_data:                RustBuildData,
+    pub test_explorer:             TestExplorerData,
+    pub local_history:             LocalHistoryData,
     pub cursor_blink:              CursorBlink,
     pub keymap_query:              RwSignal<String>,
     pub setting_query:             RwSignal<String>,
@@ -510,8 +559,12 @@ impl WindowWorkspaceData {
             mouse_hover_timer: cx.create_rw_signal(TimerToken::INVALID),
             window_origin: cx.create_rw_signal(Point::ZERO),
             breakpoints: BreakPoints {
-                breakpoints: cx.create_rw_signal(BTreeMap::new()),
+                breakpoints:   cx.create_rw_signal(BTreeMap::new()),
+                editing:       cx.create_rw_signal(None),
+                editing_new:   cx.create_rw_signal(false),
+                editing_field: cx.create_rw_signal(BreakpointEditField::Condition),
             },
+            stopped_frame: cx.create_rw_signal(None),
             keyboard_focus: cx.create_rw_signal(None),
             window_common: window_common.clone(),
             directory: directory.clone(),
@@ -521,6 +574,9 @@ impl WindowWorkspaceData {
             find_view_id: cx.create_rw_signal(None),
             inspect_info: cx.create_rw_signal(String::new()),
             run_debug_configs: cx.create_rw_signal(RunDebugConfigs::default()),
+            snippets: cx
+                .create_rw_signal(UserSnippets::load(&directory.config_directory)),
+            local_history_updated: cx.create_rw_signal(None),
         });
 
         let main_split = MainSplitData::new(cx, common.clone());
@@ -635,6 +691,10 @@ impl WindowWorkspaceData {
                     })
                     .collect(),
             );
+            terminal
+                .debug
+                .exception_breakpoints
+                .set(workspace_info.exception_breakpoints.clone());
         }
 
         let rename = RenameData::new(cx, common.clone());
@@ -669,7 +729,9 @@ impl WindowWorkspaceData {
 
         let about_data = AboutData::new(cx, common.focus);
         let alert_data = AlertBoxData::new(cx, common.clone());
-        let build_data = TreePanelData::new(cx, DocStyle::default());
+        let build_data = RustBuildData::new(cx, common.clone());
+        let test_explorer = TestExplorerData::new(cx, common.clone());
+        let local_history = LocalHistoryData::new(cx, main_split.clone());
         let cursor_blink_timer = cx.create_rw_signal(TimerToken::INVALID);
         let cursor_blink = CursorBlink {
             hide_cursor,
@@ -716,6 +778,8 @@ impl WindowWorkspaceData {
             common,
             document_symbol_scroll_to: cx.create_rw_signal(None),
             build_data,
+            test_explorer,
+            local_history,
             cursor_blink,
             keymap_query: cx.create_rw_signal(String::new()),
             setting_query: cx.create_rw_signal(String::new()),
@@ -776,6 +840,18 @@ impl WindowWorkspaceData {
             });
         }
 
+        {
+            let window_tab_data = window_tab_data.clone();
+            let request = window_tab_data.proxy.request;
+            cx.create_effect(move |_| {
+                request.with(|rpc| {
+                    if let Some((id, rpc)) = rpc.as_ref() {
+                        window_tab_data.handle_core_request(*id, rpc);
+                    }
+                });
+            });
+        }
+
         Ok(window_tab_data)
     }
 
@@ -799,6 +875,9 @@ impl WindowWorkspaceData {
         self.common.keypress.update(|keypress| {
             keypress.update_keymaps(&config);
         });
+        self.common
+            .snippets
+            .set(UserSnippets::load(&self.common.directory.config_directory));
 
         let mut change_plugins = Vec::new();
         for (key, configs) in self
@@ -921,6 +1000,34 @@ impl WindowWorkspaceData {
                     });
                 }
             }
+            OpenWorkspaceFile => {
+                if !self.workspace.kind().is_remote() {
+                    let window_command = self.common.window_common.window_command;
+                    let options = FileDialogOptions::new();
+                    open_file(options, move |file| {
+                        let Some(path) = file.and_then(|mut file| file.path.pop()) else {
+                            return;
+                        };
+                        match LapceWorkspace::from_file(
+                            LapceWorkspaceType::Local,
+                            path,
+                            std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .unwrap()
+                                .as_secs(),
+                        ) {
+                            Ok(workspace) => {
+                                window_command.send(WindowCommand::SetWorkspace {
+                                    workspace: workspace.into(),
+                                });
+                            }
+                            Err(err) => {
+                                log::error!("failed to open the workspace file: {err:?}");
+                            }
+                        }
+                    });
+                }
+            }
             CloseFolder => {
                 if !self.workspace.kind().is_remote() {
                     let window_command = self.common.window_common.window_command;
@@ -1221,6 +1328,14 @@ impl WindowWorkspaceData {
                 }
                 self.common.focus.set(Focus::Panel(PanelKind::Terminal));
             }
+            TerminalPreviousPrompt
+            | TerminalNextPrompt
+            | TerminalCopyLastOutput
+            | TerminalRerunLastCommand => {
+                if let Some(terminal) = self.terminal.active_tab_untracked() {
+                    terminal.run_shell_command(&cmd);
+                }
+            }
 
             // ==== Remote ====
             ConnectSshHost => {
@@ -1275,6 +1390,16 @@ impl WindowWorkspaceData {
             ChangeFileLineEnding => {
                 self.palette.run(PaletteKind::LineEnding);
             }
+            ChangeFileEncoding => {
+                self.palette.run(PaletteKind::Encoding);
+            }
+            MergeChangesOnDisk => {
+                self.main_split.merge_changes_on_disk();
+            }
+            ShowLocalHistory => {
+                self.local_history.load();
+                self.show_panel(PanelKind::LocalHistory);
+            }
             DiffFiles => self.palette.run(PaletteKind::DiffFiles),
 
             // ==== Running / Debugging ====
@@ -1439,6 +1564,32 @@ impl WindowWorkspaceData {
             FocusTerminal => {
                 self.common.focus.set(Focus::Panel(PanelKind::Terminal));
             }
+            CargoCheck => {
+                self.run_cargo_command(CargoCommand::Check);
+            }
+            CargoBuild => {
+                self.run_cargo_command(CargoCommand::Build);
+            }
+            CargoClippy => {
+                self.run_cargo_command(CargoCommand::Clippy);
+            }
+            CargoTest => {
+                self.run_cargo_command(CargoCommand::Test);
+            }
+            CargoStop => {
+                self.build_data.cancel();
+            }
+            TestRefresh => {
+                self.test_explorer.refresh();
+                self.show_panel(PanelKind::Test);
+            }
+            TestRunAll => {
+                self.test_explorer.run(None);
+                self.show_panel(PanelKind::Test);
+            }
+            TestStop => {
+                self.test_explorer.cancel();
+            }
             OpenUIInspector => {
                 crate::log::log(self);
                 self.common.view_id.get_untracked().inspect();
@@ -1493,6 +1644,39 @@ impl WindowWorkspaceData {
             SourceControlDiscardWorkspaceChanges => {
                 // TODO:
             }
+            SourceControlStageSelectedLines
+            | SourceControlStageHunk
+            | SourceControlUnstageSelectedLines
+            | SourceControlUnstageHunk => {
+                if let Some(editor_data) =
+                    self.main_split.active_editor.get_untracked()
+                {
+                    let hunks = matches!(
+                        cmd,
+                        SourceControlStageHunk | SourceControlUnstageHunk
+                    );
+                    let stage = matches!(
+                        cmd,
+                        SourceControlStageSelectedLines | SourceControlStageHunk
+                    );
+                    self.source_control
+                        .stage_editor_selection(&editor_data, hunks, stage);
+                }
+            }
+            SourceControlShowFileHistory => {
+                if let Some(editor_data) =
+                    self.main_split.active_editor.get_untracked()
+                {
+                    if let DocContent::File { path, .. } = editor_data.doc().content.get_untracked() {
+                        self.source_control.show_history(Some(path));
+                        self.show_panel(PanelKind::SourceControl);
+                    }
+                }
+            }
+            SourceControlShowHistory => {
+                self.source_control.show_history(None);
+                self.show_panel(PanelKind::SourceControl);
+            }
 
             // ==== UI ====
             ShowAbout => {
@@ -1689,6 +1873,13 @@ impl WindowWorkspaceData {
                     editor_data.call_hierarchy(self.clone())?;
                 }
             }
+            ShowTypeHierarchy => {
+                if let Some(editor_data) =
+                    self.main_split.active_editor.get_untracked()
+                {
+                    editor_data.type_hierarchy(self.clone())?;
+                }
+            }
             FindReferences => {
                 if let Some(editor_data) =
                     self.main_split.active_editor.get_untracked()
@@ -1721,6 +1912,7 @@ impl WindowWorkspaceData {
                         let config = RunDebugConfig {
                             ty: None,
                             name,
+                            request: DebugRequest::Launch,
                             program,
                             args,
                             cwd: None,
@@ -1730,6 +1922,9 @@ impl WindowWorkspaceData {
                             dap_id: Default::default(),
                             tracing_output: false,
                             config_source: ConfigSource::RunInTerminal,
+                            exception_filters: None,
+                            break_on_panic: false,
+                            pid: None,
                         };
                         self.common
                             .internal_command
@@ -1911,8 +2106,11 @@ impl WindowWorkspaceData {
                                     None,
                                 );
                             }
-            InternalCommand::OpenFileChanges { path } => {
-                                self.main_split.open_file_changes(path);
+            InternalCommand::OpenFileChanges { path, staged } => {
+                                self.main_split.open_file_changes(path, staged);
+                            }
+            InternalCommand::OpenCommitFileChanges { commit, diff } => {
+                                self.main_split.open_commit_file_changes(commit, diff);
                             }
             InternalCommand::ReloadFileExplorer => {
                                 self.file_explorer.reload();
@@ -2148,7 +2346,14 @@ impl WindowWorkspaceData {
                                 self.main_split.run_code_action(plugin_id, action);
                             }
             InternalCommand::ApplyWorkspaceEdit { edit } => {
-                                self.main_split.apply_workspace_edit(&edit);
+                                let common = self.common.clone();
+                                self.main_split.apply_workspace_edit(&edit, move |result| {
+                                    if let Err(message) = result {
+                                        common
+                                            .internal_command
+                                            .send(InternalCommand::ShowStatusMessage { message });
+                                    }
+                                });
                             }
             InternalCommand::SaveJumpLocation {
                                 path,
@@ -2273,6 +2478,31 @@ impl WindowWorkspaceData {
             InternalCommand::DapFrameScopes { dap_id, frame_id } => {
                                 self.terminal.dap_frame_scopes(dap_id, frame_id);
                             }
+            InternalCommand::DapDisassemble {
+                                dap_id,
+                                memory_reference,
+                                reveal,
+                            } => {
+                                let shown = self.main_split.virtual_docs.with_untracked(|docs| {
+                                    docs.contains_key(DISASSEMBLY_DOC)
+                                });
+                                let dap = self
+                                    .terminal
+                                    .debug
+                                    .daps
+                                    .with_untracked(|daps| daps.get(&dap_id).cloned());
+                                if let Some(dap) = dap.filter(|_| reveal || shown) {
+                                    dap.disassemble(memory_reference, reveal);
+                                }
+                            }
+            InternalCommand::OpenVirtualDoc {
+                                name,
+                                content,
+                                line,
+                                reveal,
+                            } => {
+                                self.main_split.open_virtual_doc(name, content, line, reveal);
+                            }
             InternalCommand::OpenVoltView { volt_id } => {
                                 self.main_split.save_current_jump_location();
                                 self.main_split.open_volt_view(volt_id);
@@ -2331,22 +2561,16 @@ impl WindowWorkspaceData {
                                 view_id,
                                 terminal_id
                             } => {
-                                let Some(tab) = self.terminal.tab_infos.with_untracked(|x| {
-                                    x.tabs.iter().find_map(|data| {
-                                        if data.term_id == terminal_id {
-                                            Some(data.clone())
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                }) else {
+                                let Some(tab) = self.terminal.get_terminal(terminal_id) else {
                                     bail!(
                                         "cound not find terminal tab data: \
                          terminal_id={terminal_id:?}"
                                     );
                                 };
                                 let raw = tab.data.with_untracked(|x| x.raw.clone());
-                                raw.write().term.reset_state();
+                                let mut raw = raw.write();
+                                raw.term.reset_state();
+                                raw.shell.clear();
                                 view_id.request_paint();
                             }
             InternalCommand::StopTerminal { terminal_id } => {
@@ -2360,14 +2584,29 @@ impl WindowWorkspaceData {
             InternalCommand::NewTerminal { profile } => {
                                 self.terminal.new_tab(profile);
                             }
+            InternalCommand::SplitTerminal { term_id, direction } => {
+                                self.terminal.split(term_id, direction);
+                            }
+            InternalCommand::SplitTerminalNext { term_id } => {
+                                self.terminal.split_next(term_id);
+                            }
+            InternalCommand::SplitTerminalPrevious { term_id } => {
+                                self.terminal.split_previous(term_id);
+                            }
+            InternalCommand::SplitTerminalExchange { term_id } => {
+                                self.terminal.split_exchange(term_id);
+                            }
+            InternalCommand::CloseTerminal { term_id } => {
+                                self.terminal.close_terminal(&term_id);
+                            }
             InternalCommand::RunAndDebug { mode, mut config } => {
                                 if let Some(workspace) = self.workspace.path() {
                                     config.update_by_workspace(workspace.to_string_lossy().as_ref());
                                 }
                                 self.run_and_debug(cx, mode, config);
                             }
-            InternalCommand::CallHierarchyIncoming { item_id, root_id } => {
-                                self.call_hierarchy_incoming(root_id, item_id);
+            InternalCommand::HierarchyExpand { item_id, root_id } => {
+                                self.hierarchy_expand(root_id, item_id);
                             }
             InternalCommand::DocumentHighlight => {
                                 if let Some(e_data) = self.main_split.active_editor.get_untracked() {
@@ -2389,7 +2628,46 @@ impl WindowWorkspaceData {
                         update_breakpoints(daps, proxy, breakpoints, lapce_core::debug::BreakpointAction::AddOrRemove { path, line: line_num, offset  });
                         }
                     },
+            InternalCommand::EditBreakPoint {
+                doc,
+                line_num,
+                field,
+            } => {
+                let (offset, content) = doc.with_untracked(|x| {
+                    (
+                        x.lines.with_untracked(|x| x.buffer().offset_of_line(line_num)),
+                        x.content.get_untracked(),
+                    )
+                });
+                let offset = offset?;
+                if let Some(path) = content.path() {
+                    let breakpoints = self.common.breakpoints;
+                    let is_new =
+                        breakpoints.get_by_path_line_untracked(path, line_num).is_none();
+                    if is_new {
+                        update_breakpoints(
+                            self.terminal.debug.daps,
+                            self.common.proxy.proxy_rpc.clone(),
+                            breakpoints,
+                            lapce_core::debug::BreakpointAction::Edit {
+                                path,
+                                line: line_num,
+                                offset,
+                                condition: None,
+                                hit_condition: None,
+                                log_message: None,
+                            },
+                        );
+                    }
+                    breakpoints.editing.set(Some((path.to_path_buf(), line_num)));
+                    breakpoints.editing_new.set(is_new);
+                    breakpoints.editing_field.set(field);
+                    self.show_panel(PanelKind::Debug);
+                    self.panel.section_open(PanelSection::Breakpoint).set(true);
+                }
+            },
             InternalCommand::ShowStatusMessage { message } => self.show_status_message(message),
+            InternalCommand::ReloadHistoryDocs => self.main_split.reload_history_docs(),
         }
         Ok(())
     }
@@ -2400,31 +2678,14 @@ impl WindowWorkspaceData {
             CoreNotification::ProxyStatus { status } => {
                 self.common.proxy_status.set(Some(status.to_owned()));
             },
-            CoreNotification::DiffInfo { diff } => {
-                self.source_control.branch.set(diff.head.clone());
-                self.source_control
-                    .branches
-                    .set(diff.branches.iter().cloned().collect());
-                self.source_control
-                    .tags
-                    .set(diff.tags.iter().cloned().collect());
-                self.source_control.file_diffs.update(|file_diffs| {
-                    *file_diffs = diff
-                        .diffs
-                        .iter()
-                        .cloned()
-                        .map(|diff| {
-                            let checked =
-                                file_diffs.get(diff.path()).is_none_or(|(_, c)| *c);
-                            (diff.path().clone(), (diff, checked))
-                        })
-                        .collect();
-                });
+            CoreNotification::DiffInfo { root, diff } => {
+                self.source_control.set_diff(root.clone(), diff.clone());
 
                 let docs = self.main_split.docs.get_untracked();
                 for (_, doc) in docs {
                     doc.retrieve_head();
                 }
+                self.main_split.reload_history_docs();
             },
             CoreNotification::CompletionResponse {
                 request_id,
@@ -2451,10 +2712,20 @@ impl WindowWorkspaceData {
                 diagnostics: diagnostic_params,
             } => {
                 let path = path_from_url(&diagnostic_params.uri);
+                let diag = self.main_split.get_diagnostic_data(&path);
+                // the language server doesn't know about the build's diagnostics
+                let build_diagnostics: Vec<Diagnostic> =
+                    diag.diagnostics.with_untracked(|d| {
+                        d.iter()
+                            .filter(|d| is_build_diagnostic(d))
+                            .cloned()
+                            .collect()
+                    });
                 let diagnostics: im::Vector<Diagnostic> = diagnostic_params
                     .diagnostics
                     .clone()
                     .into_iter()
+                    .chain(build_diagnostics)
                     .sorted_by_key(|d| d.range.start)
                     .collect();
 
@@ -2471,7 +2742,6 @@ impl WindowWorkspaceData {
                 //     error!("{:?}", diag.data);
                 // }
                 log::debug!("PublishDiagnostics {path:?} {}", diagnostics.len());
-                let diag = self.main_split.get_diagnostic_data(&path);
                 let old_len = diag.diagnostics.with_untracked(|x| x.len());
                 let task_id = diag.id.with_untracked(|x| {
                     x.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -2530,6 +2800,16 @@ impl WindowWorkspaceData {
                     doc.init_diagnostics();
                 }
             },
+            CoreNotification::CodeLensRefresh => {
+                self.main_split.docs.with_untracked(|x| {
+                    for doc in x.values() {
+                        if doc.content.get_untracked().is_local() {
+                            continue;
+                        }
+                        doc.get_code_lens();
+                    }
+                });
+            },
             CoreNotification::ServerStatus { params } => {
                 if params.is_ok() {
                     // todo filter by language
@@ -2663,6 +2943,19 @@ impl WindowWorkspaceData {
             CoreNotification::DapContinued { dap_id } => {
                 self.terminal.dap_continued(dap_id);
             },
+            CoreNotification::DapCapabilities {
+                dap_id,
+                capabilities,
+            } => {
+                if let Some(dap) = self
+                    .terminal
+                    .debug
+                    .daps
+                    .with_untracked(|x| x.get(dap_id).cloned())
+                {
+                    dap.capabilities.set(Some(capabilities.clone()));
+                }
+            },
             CoreNotification::DapBreakpointsResp {
                 path, breakpoints, ..
             } => {
@@ -2742,10 +3035,48 @@ impl WindowWorkspaceData {
             CoreNotification::WorkspaceFileChange => {
                 self.file_explorer.reload();
             },
+            CoreNotification::GlobalSearchMatches {
+                search_id,
+                matches,
+                files_searched,
+                match_count,
+            } => {
+                self.global_search.receive_matches(
+                    *search_id,
+                    matches.clone(),
+                    *files_searched,
+                    *match_count,
+                );
+            },
+            CoreNotification::RustBuildOutput { rev, output } => {
+                if let Some(path) = self.build_data.output(*rev, output.clone()) {
+                    self.main_split.set_build_diagnostics(
+                        &path,
+                        self.build_data.diagnostics_of(&path),
+                    );
+                }
+            },
+            CoreNotification::RustBuildStopped { rev, exit_code } => {
+                self.build_data.stopped(*rev, *exit_code);
+            },
+            CoreNotification::TestRunOutput { run_id, output } => {
+                self.test_explorer.output(*run_id, output.clone());
+            },
+            CoreNotification::TestRunStopped { run_id, exit_code } => {
+                self.test_explorer.stopped(*run_id, *exit_code);
+            },
             _ => {},
         }
     }
 
+    /// Runs a cargo command in the build panel, replacing the diagnostics of
+    /// the last one.
+    pub fn run_cargo_command(&self, command: CargoCommand) {
+        self.main_split.clear_build_diagnostics();
+        self.build_data.run(command);
+        self.show_panel(PanelKind::Build);
+    }
+
     pub fn show_status_message(&self, message: String) {
         let msg = WorkDoneProgressBegin {
             title:       message,
@@ -2828,9 +3159,18 @@ impl WindowWorkspaceData {
             .cloned()
             .unwrap();
         WorkspaceInfo {
-            split:       main_split_data.get_untracked().split_info(self),
-            panel:       self.panel.panel_info(),
-            breakpoints: self.terminal.common.breakpoints.clone_for_hashmap(),
+            split:                 main_split_data.get_untracked().split_info(self),
+            panel:                 self.panel.panel_info(),
+            breakpoints:           self
+                .terminal
+                .common
+                .breakpoints
+                .clone_for_hashmap(),
+            exception_breakpoints: self
+                .terminal
+                .debug
+                .exception_breakpoints
+                .get_untracked(),
         }
     }
 
@@ -3055,10 +3395,13 @@ impl WindowWorkspaceData {
             | PanelKind::Problem
             | PanelKind::Debug
             | PanelKind::CallHierarchy
+            | PanelKind::TypeHierarchy
             | PanelKind::DocumentSymbol
             | PanelKind::References
             | PanelKind::Implementation
-            | PanelKind::Build => {
+            | PanelKind::Build
+            | PanelKind::Test
+            | PanelKind::LocalHistory => {
                 // Some panels don't accept focus (yet). Fall back to visibility
                 // check in those cases.
                 self.panel.is_panel_visible(&kind)
@@ -3139,8 +3482,14 @@ impl WindowWorkspaceData {
                 self.run_program_in_terminal(cx, &mode, &config, false);
             },
             RunDebugMode::Debug => {
+                if config.is_attach() && config.pid.is_none() {
+                    self.palette.attach_config.set(Some(config));
+                    self.palette.run(PaletteKind::AttachProcess);
+                    return;
+                }
                 let dap_id = config.dap_id;
-                let dap_data = DapData::new(cx, dap_id, None, self.common.clone());
+                let dap_data =
+                    DapData::new(cx, dap_id, None, &config, self.common.clone());
                 self.terminal.debug.daps.update(|x| {
                     x.insert(dap_id, dap_data);
                 });
@@ -3234,6 +3583,8 @@ impl WindowWorkspaceData {
                             workspace.to_string_lossy().as_ref(),
                         );
                     }
+                    // keep attaching to the process that was picked
+                    new_config.pid = new_config.pid.or(run_debug.origin_config.pid);
                     run_debug.origin_config = new_config.clone();
                     run_debug.config = new_config;
                 },
@@ -3262,6 +3613,7 @@ impl WindowWorkspaceData {
                     self.scope,
                     dap_id,
                     Some(terminal.term_id),
+                    &config,
                     self.common.clone(),
                 );
                 self.terminal.debug.daps.update(|x| {
@@ -3369,23 +3721,95 @@ impl WindowWorkspaceData {
         }
     }
 
+    /// Handles the requests a language server sends to the editor, each of
+    /// which is answered, else the server waits for it.
+    fn handle_core_request(&self, id: RequestId, rpc: &CoreRequest) {
+        let core_rpc = &self.proxy.core_rpc;
+        match rpc {
+            CoreRequest::ApplyWorkspaceEdit { edit, .. } => {
+                // the server waits for the edit to be applied
+                let core_rpc = core_rpc.clone();
+                self.main_split.apply_workspace_edit(edit, move |result| {
+                    let failure_reason = result.err();
+                    core_rpc.handle_response(
+                        id,
+                        Ok(CoreResponse::ApplyWorkspaceEditResponse {
+                            applied: failure_reason.is_none(),
+                            failure_reason,
+                        }),
+                    );
+                });
+            },
+            CoreRequest::ShowMessageRequest { title, message } => {
+                self.messages.update(|messages| {
+                    messages.push(WindowMessage {
+                        title:   title.clone(),
+                        message: ShowMessageParams {
+                            typ:     message.typ,
+                            message: message.message.clone(),
+                        },
+                        actions: message.actions.clone().unwrap_or_default(),
+                        request: Some(id),
+                    });
+                });
+            },
+            CoreRequest::ShowDocument { params } => {
+                let success = self.show_document(params);
+                core_rpc.handle_response(
+                    id,
+                    Ok(CoreResponse::ShowDocumentResponse { success }),
+                );
+            },
+        }
+    }
+
+    fn show_document(&self, params: &ShowDocumentParams) -> bool {
+        if params.external == Some(true) || params.uri.scheme() != "file" {
+            self.common
+                .internal_command
+                .send(InternalCommand::OpenWebUri {
+                    uri: params.uri.to_string(),
+                });
+            return true;
+        }
+        let Ok(path) = params.uri.to_file_path() else {
+            return false;
+        };
+        self.main_split.jump_to_location(
+            EditorLocation {
+                path,
+                position: params
+                    .selection
+                    .map(|range| EditorPosition::Position(range.start)),
+                scroll_offset: None,
+                ignore_unconfirmed: false,
+                same_editor_tab: false,
+            },
+            None,
+        );
+        true
+    }
+
     fn show_message(&self, title: &str, message: &ShowMessageParams) {
         self.messages.update(|messages| {
-            messages.push((title.to_string(), message.clone()));
+            messages.push(WindowMessage {
+                title:   title.to_string(),
+                message: message.clone(),
+                actions: Vec::new(),
+                request: None,
+            });
         });
     }
 
     #[allow(dead_code)]
     fn show_error_message(&self, title: String, message: String) {
-        self.messages.update(|messages| {
-            messages.push((
-                title,
-                ShowMessageParams {
-                    typ: MessageType::ERROR,
-                    message,
-                },
-            ));
-        });
+        self.show_message(
+            &title,
+            &ShowMessageParams {
+                typ: MessageType::ERROR,
+                message,
+            },
+        );
     }
 
     pub fn update_code_lens_id(&self, view_id: Option<ViewId>) {
@@ -3422,9 +3846,9 @@ impl WindowWorkspaceData {
             });
     }
 
-    pub fn call_hierarchy_incoming(&self, root_id: ViewId, item_id: ViewId) {
-        let Some(item) = self.main_split.hierarchy.tabs.with_untracked(|x| {
-            x.iter().find_map(move |item| {
+    pub fn hierarchy_expand(&self, root_id: ViewId, item_id: ViewId) {
+        let find_root = |tabs: &Vec<Tab<HierarchyData>>| {
+            tabs.iter().find_map(|item| {
                 let refe = item.references.get_untracked();
                 if refe.root_id == root_id {
                     Some(refe)
@@ -3432,57 +3856,106 @@ impl WindowWorkspaceData {
                     None
                 }
             })
-        }) else {
+        };
+        let Some(hierarchy) = self
+            .main_split
+            .hierarchy
+            .tabs
+            .with_untracked(find_root)
+            .or_else(|| {
+                self.main_split
+                    .type_hierarchy
+                    .tabs
+                    .with_untracked(find_root)
+            })
+        else {
             return;
         };
-        let Some(item) = CallHierarchyItemData::find_by_id(item.root, item_id)
+        let Some(item) = HierarchyItemData::find_by_id(hierarchy.root, item_id)
         else {
             return;
         };
+        let direction = hierarchy.direction.get_untracked();
         let root_item = item;
-        let path: PathBuf = item.get_untracked().item.uri.to_file_path().unwrap();
+        let hierarchy_item = item.get_untracked().item;
+        let Ok(path) = hierarchy_item.uri().to_file_path() else {
+            return;
+        };
         let scope = self.scope;
+        let new_item = move |item: Rc<HierarchyItem>, range: Range| {
+            scope.create_rw_signal(HierarchyItemData {
+                root_id,
+                view_id: floem::ViewId::new(),
+                item,
+                from_range: range,
+                init: false,
+                open: scope.create_rw_signal(false),
+                children: scope.create_rw_signal(Vec::new()),
+            })
+        };
         let send = create_ext_action(
             scope,
-            move |(_id, _rs): (u64, Result<ProxyResponse, RpcError>)| match _rs {
-                Ok(ProxyResponse::CallHierarchyIncomingResponse { items }) => {
-                    if let Some(items) = items {
+            move |(_id, _rs): (u64, Result<ProxyResponse, RpcError>)| {
+                let item_children = match _rs {
+                    Ok(ProxyResponse::CallHierarchyIncomingResponse { items }) => {
                         let mut item_children = Vec::new();
-                        for x in items {
-                            let item = Rc::new(x.from);
+                        for x in items.unwrap_or_default() {
+                            let item = Rc::new(HierarchyItem::Call(x.from));
                             for range in x.from_ranges {
-                                item_children.push(scope.create_rw_signal(
-                                    CallHierarchyItemData {
-                                        root_id,
-                                        view_id: floem::ViewId::new(),
-                                        item: item.clone(),
-                                        from_range: range,
-                                        init: false,
-                                        open: scope.create_rw_signal(false),
-                                        children: scope.create_rw_signal(Vec::new()),
-                                    },
-                                ))
+                                item_children.push(new_item(item.clone(), range))
                             }
                         }
-                        root_item.update(|x| {
-                            x.init = true;
-                            x.children.update(|children| {
-                                *children = item_children;
+                        item_children
+                    },
+                    Ok(ProxyResponse::CallHierarchyOutgoingResponse { items }) => {
+                        // the ranges are in the caller, so jump to the callee
+                        items
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|x| {
+                                let range = x.to.selection_range;
+                                new_item(Rc::new(HierarchyItem::Call(x.to)), range)
                             })
-                        });
-                    }
-                },
-                Err(err) => {
-                    log::error!("{:?}", err);
-                },
-                Ok(_) => {},
+                            .collect()
+                    },
+                    Ok(ProxyResponse::TypeHierarchyResponse { items }) => items
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|x| {
+                            let range = x.selection_range;
+                            new_item(Rc::new(HierarchyItem::Type(x)), range)
+                        })
+                        .collect(),
+                    Err(err) => {
+                        log::error!("{:?}", err);
+                        return;
+                    },
+                    Ok(_) => return,
+                };
+                root_item.update(|x| {
+                    x.init = true;
+                    x.children.update(|children| {
+                        *children = item_children;
+                    })
+                });
             },
         );
-        self.common.proxy.proxy_rpc.call_hierarchy_incoming(
-            path,
-            item.get_untracked().item.as_ref().clone(),
-            send,
-        );
+        let proxy_rpc = &self.common.proxy.proxy_rpc;
+        match (direction, hierarchy_item.as_ref().clone()) {
+            (HierarchyDirection::Incoming, HierarchyItem::Call(item)) => {
+                proxy_rpc.call_hierarchy_incoming(path, item, send);
+            },
+            (HierarchyDirection::Outgoing, HierarchyItem::Call(item)) => {
+                proxy_rpc.call_hierarchy_outgoing(path, item, send);
+            },
+            (HierarchyDirection::Supertypes, HierarchyItem::Type(item)) => {
+                proxy_rpc.type_hierarchy_supertypes(path, item, send);
+            },
+            (HierarchyDirection::Subtypes, HierarchyItem::Type(item)) => {
+                proxy_rpc.type_hierarchy_subtypes(path, item, send);
+            },
+            _ => {},
+        }
     }
 
     pub fn content_info(&self, data: &SplitContent) -> SplitContentInfo {
@@ -38,13 +38,79 @@ pub struct RunDebugConfigs {
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct LapceBreakpoint {
-    pub id:       Option<usize>,
-    pub verified: bool,
-    pub message:  Option<String>,
-    pub line:     usize,
-    pub offset:   usize,
-    pub dap_line: Option<usize>,
-    pub active:   bool,
+    pub id:            Option<usize>,
+    pub verified:      bool,
+    pub message:       Option<String>,
+    pub line:          usize,
+    pub offset:        usize,
+    pub dap_line:      Option<usize>,
+    pub active:        bool,
+    /// Expression that must evaluate to true for the breakpoint to stop.
+    #[serde(default)]
+    pub condition:     Option<String>,
+    /// Expression controlling how many hits are ignored before stopping.
+    #[serde(default)]
+    pub hit_condition: Option<String>,
+    /// When set, the breakpoint is a logpoint: the message is logged and the
+    /// debuggee keeps running.
+    #[serde(default)]
+    pub log_message:   Option<String>,
+}
+
+impl LapceBreakpoint {
+    pub fn new(line: usize, offset: usize) -> Self {
+        Self {
+            id: None,
+            verified: false,
+            message: None,
+            line,
+            offset,
+            dap_line: None,
+            active: true,
+            condition: None,
+            hit_condition: None,
+            log_message: None,
+        }
+    }
+
+    pub fn kind(&self) -> BreakpointKind {
+        if self.log_message.is_some() {
+            BreakpointKind::Log
+        } else if self.condition.is_some() {
+            BreakpointKind::Conditional
+        } else if self.hit_condition.is_some() {
+            BreakpointKind::HitCount
+        } else {
+            BreakpointKind::Plain
+        }
+    }
+
+    pub fn source_breakpoint(&self) -> dap_types::SourceBreakpoint {
+        dap_types::SourceBreakpoint {
+            line:          self.line + 1,
+            column:        None,
+            condition:     self.condition.clone(),
+            hit_condition: self.hit_condition.clone(),
+            log_message:   self.log_message.clone(),
+        }
+    }
+}
+
+/// The exception breakpoints chosen for a run configuration, saved with the
+/// workspace under the configuration's name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LapceExceptionBreakpoints {
+    pub filters:        Vec<String>,
+    #[serde(default)]
+    pub break_on_panic: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BreakpointKind {
+    Plain,
+    Conditional,
+    HitCount,
+    Log,
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -88,6 +154,13 @@ impl ScopeOrVar {
             ScopeOrVar::Var(var) => var.variables_reference,
         }
     }
+
+    pub fn memory_reference(&self) -> Option<&str> {
+        match self {
+            ScopeOrVar::Scope(_) => None,
+            ScopeOrVar::Var(var) => var.memory_reference.as_deref(),
+        }
+    }
 }
 
 pub struct DapVariableViewdata {
@@ -116,4 +189,14 @@ pub enum BreakpointAction<'a> {
         line:   usize,
         offset: usize,
     },
+    /// Set the condition, hit count and log message of the breakpoint,
+    /// creating it if the line has none. Empty values clear the field.
+    Edit {
+        path:          &'a Path,
+        line:          usize,
+        offset:        usize,
+        condition:     Option<String>,
+        hit_condition: Option<String>,
+        log_message:   Option<String>,
+    },
 }
@@ -13,6 +13,9 @@ pub enum DocContent {
     History(DocHistory),
     /// A new file which doesn't exist in the file system
     Scratch { id: BufferId, name: String },
+    /// A read-only document generated by Lapce, such as the disassembly of a
+    /// debug session
+    Virtual { name: String },
 }
 
 impl DocContent {
@@ -30,6 +33,7 @@ impl DocContent {
             DocContent::Local => false,
             DocContent::History(_) => true,
             DocContent::Scratch { .. } => false,
+            DocContent::Virtual { .. } => true,
         }
     }
 
@@ -39,6 +43,7 @@ impl DocContent {
             DocContent::Local => None,
             DocContent::History(_) => None,
             DocContent::Scratch { .. } => None,
+            DocContent::Virtual { .. } => None,
         }
     }
 }
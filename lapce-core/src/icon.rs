@@ -36,14 +36,21 @@ impl LapceIcons {
     pub const DEBUG: &'static str = "debug";
     pub const DEBUG_ALT: &'static str = "debug_alt";
     pub const DEBUG_BREAKPOINT: &'static str = "debug_breakpoint";
+    pub const DEBUG_BREAKPOINT_CONDITIONAL: &'static str =
+        "debug_breakpoint_conditional";
+    pub const DEBUG_BREAKPOINT_EDIT: &'static str = "debug_breakpoint_edit";
+    pub const DEBUG_BREAKPOINT_HIT: &'static str = "debug_breakpoint_hit";
     pub const DEBUG_BREAKPOINT_INACTIVE: &'static str = "debug_breakpoint_inactive";
+    pub const DEBUG_BREAKPOINT_LOG: &'static str = "debug_breakpoint_log";
     pub const DEBUG_BREAKPOINT_VERIFIED: &'static str = "debug_breakpoint_verified";
     pub const DEBUG_CONSOLE: &'static str = "debug_console";
     pub const DEBUG_CONTINUE: &'static str = "debug_continue";
+    pub const DEBUG_DISASSEMBLY: &'static str = "debug_disassembly";
     pub const DEBUG_DISCONNECT: &'static str = "debug_disconnect";
     pub const DEBUG_PAUSE: &'static str = "debug_pause";
     pub const DEBUG_RESTART: &'static str = "debug_restart";
     pub const DEBUG_SMALL: &'static str = "debug_small";
+    pub const DEBUG_STEP_INSTRUCTION: &'static str = "debug_step_instruction";
     pub const DEBUG_STEP_INTO: &'static str = "debug_step_into";
     pub const DEBUG_STEP_OUT: &'static str = "debug_step_out";
     pub const DEBUG_STEP_OVER: &'static str = "debug_step_over";
@@ -71,6 +78,7 @@ impl LapceIcons {
     pub const FOLD: &'static str = "fold";
     pub const FOLD_DOWN: &'static str = "fold.down";
     pub const FOLD_UP: &'static str = "fold.up";
+    pub const HIERARCHY_DIRECTION: &'static str = "hierarchy.direction";
     pub const IMAGE_ERROR: &'static str = "image_error";
     pub const IMAGE_LOADING: &'static str = "image_loading";
     pub const IMPLEMENTATION: &'static str = "document_symbol";
@@ -81,6 +89,9 @@ impl LapceIcons {
     pub const LAYOUT_PANEL_OFF: &'static str = "layout.panel.off";
     pub const LIGHTBULB: &'static str = "lightbulb";
     pub const LINK: &'static str = "link";
+    pub const LOCAL_HISTORY: &'static str = "local_history";
+    pub const LOCAL_HISTORY_COMPARE: &'static str = "local_history.compare";
+    pub const LOCAL_HISTORY_RESTORE: &'static str = "local_history.restore";
     pub const LOCATION_BACKWARD: &'static str = "location.backward";
     pub const LOCATION_FORWARD: &'static str = "location.forward";
     pub const LOGO: &'static str = "logo";
@@ -144,6 +155,13 @@ impl LapceIcons {
     pub const TAB_NEXT: &'static str = "tab.next";
     pub const TAB_PREVIOUS: &'static str = "tab.previous";
     pub const TERMINAL: &'static str = "terminal";
+    pub const TEST_EXPLORER: &'static str = "test.explorer";
+    pub const TEST_FAILED: &'static str = "test.failed";
+    pub const TEST_IGNORED: &'static str = "test.ignored";
+    pub const TEST_NOT_RUN: &'static str = "test.not_run";
+    pub const TEST_PASSED: &'static str = "test.passed";
+    pub const TEST_REFRESH: &'static str = "test.refresh";
+    pub const TEST_RUNNING: &'static str = "test.running";
     pub const TYPE_HIERARCHY: &'static str = "type_hierarchy";
     pub const UNSAVED: &'static str = "unsaved";
     pub const WARNING: &'static str = "warning";
@@ -10,6 +10,7 @@ pub mod debug;
 pub mod encoding;
 pub mod id;
 pub mod main_split;
+pub mod search;
 pub mod workspace;
 
 pub mod doc;
@@ -14,6 +14,7 @@ pub fn default_panel_order() -> PanelOrder {
             PanelKind::FileExplorer,
             PanelKind::Plugin,
             PanelKind::SourceControl,
+            PanelKind::LocalHistory,
             PanelKind::Debug,
         ],
     );
@@ -24,8 +25,11 @@ pub fn default_panel_order() -> PanelOrder {
             PanelKind::Search,
             PanelKind::Problem,
             PanelKind::CallHierarchy,
+            PanelKind::TypeHierarchy,
             PanelKind::References,
-            PanelKind::Implementation
+            PanelKind::Implementation,
+            PanelKind::Build,
+            PanelKind::Test
         ],
     );
     order.insert(
@@ -41,13 +45,18 @@ pub enum PanelSection {
     FileExplorer,
     Error,
     Warn,
+    Repositories,
     Changes,
+    StagedChanges,
+    History,
     Installed,
     Available,
     Process,
     Variable,
+    DebugConsole,
     StackFrame,
     Breakpoint,
+    ExceptionBreakpoint,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -129,10 +138,13 @@ pub enum PanelKind {
     Problem,
     Debug,
     CallHierarchy,
+    TypeHierarchy,
     DocumentSymbol,
     References,
     Implementation,
     Build,
+    Test,
+    LocalHistory,
 }
 
 impl PanelKind {
@@ -146,10 +158,13 @@ impl PanelKind {
             PanelKind::Problem => LapceIcons::PROBLEM,
             PanelKind::Debug => LapceIcons::DEBUG,
             PanelKind::CallHierarchy => LapceIcons::TYPE_HIERARCHY,
+            PanelKind::TypeHierarchy => LapceIcons::SYMBOL_KIND_INTERFACE,
             PanelKind::DocumentSymbol => LapceIcons::DOCUMENT_SYMBOL,
             PanelKind::References => LapceIcons::REFERENCES,
             PanelKind::Implementation => LapceIcons::IMPLEMENTATION,
             PanelKind::Build => LapceIcons::DEBUG,
+            PanelKind::Test => LapceIcons::TEST_EXPLORER,
+            PanelKind::LocalHistory => LapceIcons::LOCAL_HISTORY,
         }
     }
 
@@ -176,10 +191,13 @@ impl PanelKind {
             PanelKind::Problem => PanelContainerPosition::Bottom,
             PanelKind::Debug => PanelContainerPosition::Left,
             PanelKind::CallHierarchy => PanelContainerPosition::Bottom,
+            PanelKind::TypeHierarchy => PanelContainerPosition::Bottom,
             PanelKind::DocumentSymbol => PanelContainerPosition::Right,
             PanelKind::References => PanelContainerPosition::Bottom,
             PanelKind::Implementation => PanelContainerPosition::Bottom,
             PanelKind::Build => PanelContainerPosition::Bottom,
+            PanelKind::Test => PanelContainerPosition::Bottom,
+            PanelKind::LocalHistory => PanelContainerPosition::Left,
         }
     }
 
@@ -193,10 +211,13 @@ impl PanelKind {
             PanelKind::Problem => "Problems",
             PanelKind::Debug => "Debug",
             PanelKind::CallHierarchy => "Call Hierarchy",
+            PanelKind::TypeHierarchy => "Type Hierarchy",
             PanelKind::DocumentSymbol => "Document Symbol",
             PanelKind::References => "References",
             PanelKind::Implementation => "Implementation",
             PanelKind::Build => "Build",
+            PanelKind::Test => "Test Explorer",
+            PanelKind::LocalHistory => "Local History",
         }
     }
 }
@@ -0,0 +1,130 @@
+use std::{collections::HashSet, ops::Range};
+
+use regex::{Regex, RegexBuilder};
+
+/// Replaces the matches of a global search, line by line, the same way the
+/// search found them.
+#[derive(Clone, Debug)]
+pub struct Replacer {
+    regex:       Regex,
+    replacement: String,
+    /// Expand `$1` style capture group references in the replacement
+    expand:      bool,
+}
+
+impl Replacer {
+    pub fn new(
+        pattern: &str,
+        case_sensitive: bool,
+        whole_word: bool,
+        is_regex: bool,
+        replacement: String,
+    ) -> Result<Self, regex::Error> {
+        let pattern = if is_regex {
+            pattern.to_string()
+        } else {
+            regex::escape(pattern)
+        };
+        let pattern = if whole_word {
+            format!(r"\b(?:{pattern})\b")
+        } else {
+            pattern
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!case_sensitive)
+            .build()?;
+        Ok(Self {
+            regex,
+            replacement,
+            expand: is_regex,
+        })
+    }
+
+    /// The byte ranges to replace in `text` and what to replace them with,
+    /// limited to the `matches`, given by their 1-based line number and the
+    /// byte offset of their start in the line, as in a search match.
+    pub fn edits(
+        &self,
+        text: &str,
+        matches: &HashSet<(usize, usize)>,
+    ) -> Vec<(Range<usize>, String)> {
+        let lines = matches
+            .iter()
+            .map(|(line, _)| *line)
+            .collect::<HashSet<_>>();
+        let mut edits = Vec::new();
+        let mut offset = 0;
+        for (i, line) in text.split_inclusive('\n').enumerate() {
+            if lines.contains(&(i + 1)) {
+                let content = line.trim_end_matches('\n').trim_end_matches('\r');
+                for caps in self.regex.captures_iter(content) {
+                    let Some(m) = caps.get(0) else {
+                        continue;
+                    };
+                    if m.is_empty() || !matches.contains(&(i + 1, m.start())) {
+                        continue;
+                    }
+                    let new = if self.expand {
+                        let mut new = String::new();
+                        caps.expand(&self.replacement, &mut new);
+                        new
+                    } else {
+                        self.replacement.clone()
+                    };
+                    edits.push((offset + m.start()..offset + m.end(), new));
+                }
+            }
+            offset += line.len();
+        }
+        edits
+    }
+
+    /// `text` with the `matches` replaced.
+    pub fn replace(&self, text: &str, matches: &HashSet<(usize, usize)>) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for (range, new) in self.edits(text, matches) {
+            result.push_str(&text[last..range.start]);
+            result.push_str(&new);
+            last = range.end;
+        }
+        result.push_str(&text[last..]);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::Replacer;
+
+    #[test]
+    fn test_replace_literal() {
+        let replacer =
+            Replacer::new("a.b", true, false, false, "$1".to_string()).unwrap();
+        let text = "a.b axb\r\na.b a.b\nA.B\n";
+        let matches = HashSet::from([(1, 0), (2, 0), (2, 4), (3, 0)]);
+        assert_eq!(replacer.replace(text, &matches), "$1 axb\r\n$1 $1\nA.B\n");
+        // the second match of a line on its own
+        let matches = HashSet::from([(2, 4)]);
+        assert_eq!(replacer.replace(text, &matches), "a.b axb\r\na.b $1\nA.B\n");
+    }
+
+    #[test]
+    fn test_replace_regex_captures() {
+        let replacer =
+            Replacer::new(r"(\w+)_id", false, true, true, "${1}Id".to_string())
+                .unwrap();
+        let text = "user_id = USER_ID;\nmy_user_idx";
+        let matches = HashSet::from([(1, 0), (1, 10), (2, 3)]);
+        assert_eq!(
+            replacer.replace(text, &matches),
+            "userId = USERId;\nmy_user_idx"
+        );
+        assert_eq!(
+            replacer.edits(text, &matches),
+            vec![(0..7, "userId".to_string()), (10..17, "USERId".to_string())]
+        );
+    }
+}
@@ -1,11 +1,19 @@
-use std::{collections::HashMap, fmt::Display, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use notify::{RecursiveMode, Watcher};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
-use crate::{debug::LapceBreakpoint, main_split::SplitInfo, panel::PanelInfo};
+use crate::{
+    debug::{LapceBreakpoint, LapceExceptionBreakpoints},
+    main_split::SplitInfo,
+    panel::PanelInfo,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub struct SshHost {
@@ -104,10 +112,32 @@ impl std::fmt::Display for LapceWorkspaceType {
     }
 }
 
+/// The extension of the files listing the folders of a multi-root workspace
+pub const WORKSPACE_FILE_EXTENSION: &str = "lapce-workspace";
+
+/// A workspace file, such as
+///
+/// ```toml
+/// folders = ["api", "../web", "/home/me/docs"]
+/// ```
+///
+/// with the folders relative to the file.
+#[derive(Deserialize)]
+struct WorkspaceFile {
+    folders: Vec<PathBuf>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LapceWorkspace {
     kind:      LapceWorkspaceType,
+    /// The folder of the workspace, the first of `folders` when it has several
     path:      Option<PathBuf>,
+    /// The workspace file the folders were read from
+    #[serde(default)]
+    file:      Option<PathBuf>,
+    /// Every folder of a multi-root workspace, empty for a single folder
+    #[serde(default)]
+    folders:   Vec<PathBuf>,
     last_open: u64,
 }
 
@@ -120,9 +150,35 @@ impl LapceWorkspace {
         Self {
             kind,
             path,
+            file: None,
+            folders: Vec::new(),
             last_open,
         }
     }
+
+    /// The workspace of the folders listed in a workspace file.
+    pub fn from_file(
+        kind: LapceWorkspaceType,
+        file: PathBuf,
+        last_open: u64,
+    ) -> Result<Self> {
+        let content = std::fs::read_to_string(&file)?;
+        let dir = file.parent().unwrap_or(Path::new(""));
+        let folders = parse_workspace_file(&content, dir)?;
+        Ok(Self {
+            kind,
+            path: folders.first().cloned(),
+            file: Some(file),
+            folders,
+            last_open,
+        })
+    }
+
+    pub fn is_workspace_file(path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|extension| extension == WORKSPACE_FILE_EXTENSION)
+    }
+
     #[cfg(windows)]
     pub fn new_remote_wsl(wsl: WslHost) -> Self {
         Self::new(LapceWorkspaceType::RemoteWSL(wsl), None, 0)
@@ -155,13 +211,49 @@ impl LapceWorkspace {
         self.path.as_ref()
     }
 
+    pub fn file(&self) -> Option<&PathBuf> {
+        self.file.as_ref()
+    }
+
+    /// The root folders of the workspace, `path` being the first.
+    pub fn folders(&self) -> Vec<PathBuf> {
+        if self.folders.is_empty() {
+            self.path.iter().cloned().collect()
+        } else {
+            self.folders.clone()
+        }
+    }
+
+    pub fn is_multi_root(&self) -> bool {
+        self.folders.len() > 1
+    }
+
+    /// The innermost folder of the workspace that `path` is in.
+    pub fn folder_of(&self, path: &Path) -> Option<PathBuf> {
+        folder_of(&self.folders(), path).cloned()
+    }
+
+    /// `path` relative to its folder, starting with the name of the folder in
+    /// a multi-root workspace to tell the folders apart.
+    pub fn relative_path(&self, path: &Path) -> Option<PathBuf> {
+        let folder = self.folder_of(path)?;
+        let relative = path.strip_prefix(&folder).ok()?;
+        if self.is_multi_root() {
+            Some(folder.file_name().map(Path::new)?.join(relative))
+        } else {
+            Some(relative.to_path_buf())
+        }
+    }
+
     pub fn display(&self) -> Option<String> {
-        let path = self.path.as_ref()?;
-        let path = path
-            .file_name()
-            .unwrap_or(path.as_os_str())
-            .to_string_lossy()
-            .to_string();
+        let path = self.file.as_ref().or(self.path.as_ref())?;
+        let path_os_str = path.as_os_str();
+        let path = if self.file.is_some() {
+            path.file_stem()
+        } else {
+            path.file_name()
+        };
+        let path = path.unwrap_or(path_os_str).to_string_lossy().to_string();
         let remote = match &self.kind {
             LapceWorkspaceType::Local => String::new(),
             LapceWorkspaceType::RemoteSSH(remote) => {
@@ -224,6 +316,8 @@ impl Default for LapceWorkspace {
         Self {
             kind:      LapceWorkspaceType::Local,
             path:      None,
+            file:      None,
+            folders:   Vec::new(),
             last_open: 0,
         }
     }
@@ -231,18 +325,121 @@ impl Default for LapceWorkspace {
 
 impl std::fmt::Display for LapceWorkspace {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // a workspace file is told apart from its first folder opened alone
         write!(
             f,
             "{}:{}",
             self.kind,
-            self.path.as_ref().and_then(|p| p.to_str()).unwrap_or("")
+            self.file
+                .as_ref()
+                .or(self.path.as_ref())
+                .and_then(|p| p.to_str())
+                .unwrap_or("")
         )
     }
 }
 
+/// The innermost of `folders` that `path` is in.
+pub fn folder_of<'a>(folders: &'a [PathBuf], path: &Path) -> Option<&'a PathBuf> {
+    folders
+        .iter()
+        .filter(|folder| path.starts_with(folder))
+        .max_by_key(|folder| folder.components().count())
+}
+
+/// The folders of a workspace file, relative ones resolved against `dir`.
+fn parse_workspace_file(content: &str, dir: &Path) -> Result<Vec<PathBuf>> {
+    let file: WorkspaceFile = toml::from_str(content)?;
+    if file.folders.is_empty() {
+        bail!("the workspace file has no folders");
+    }
+    Ok(file
+        .folders
+        .into_iter()
+        // `..` makes the names shown for the folders useless
+        .map(|folder| normalize_path(&dir.join(folder)))
+        .collect())
+}
+
+/// `path` with its `.` and `..` resolved by name. Unlike canonicalizing it,
+/// this works for folders that don't exist yet and gives no `\\?\` prefix on
+/// Windows.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                },
+                // there is nothing above the root
+                Some(Component::RootDir | Component::Prefix(_)) => {},
+                _ => normalized.push(component),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct WorkspaceInfo {
-    pub split:       SplitInfo,
-    pub panel:       PanelInfo,
-    pub breakpoints: HashMap<PathBuf, Vec<LapceBreakpoint>>,
+    pub split:                 SplitInfo,
+    pub panel:                 PanelInfo,
+    pub breakpoints:           HashMap<PathBuf, Vec<LapceBreakpoint>>,
+    #[serde(default)]
+    pub exception_breakpoints: HashMap<String, LapceExceptionBreakpoints>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_workspace_file() {
+        let workspaces = Path::new("workspaces");
+        let dir = workspaces.join("product");
+        let root = std::env::current_dir().unwrap();
+        // literal strings keep the backslashes of Windows paths
+        let content = format!(
+            "folders = ['api', './docs/../lib', '../web', '{}']",
+            root.join("other").join("..").join("shared").display()
+        );
+        let folders = parse_workspace_file(&content, &dir).unwrap();
+        assert_eq!(
+            folders,
+            vec![
+                dir.join("api"),
+                dir.join("lib"),
+                workspaces.join("web"),
+                root.join("shared"),
+            ]
+        );
+        assert!(parse_workspace_file("folders = []", &dir).is_err());
+
+        assert_eq!(normalize_path(Path::new("../a/./b/..")), Path::new("../a"));
+        assert_eq!(normalize_path(Path::new("/../a")), Path::new("/a"));
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let mut workspace =
+            LapceWorkspace::new_with_path(Some(PathBuf::from("/src/api")));
+        let file = Path::new("/src/api/lib/main.rs");
+        assert_eq!(workspace.relative_path(file), Some("lib/main.rs".into()));
+
+        workspace.folders = vec![
+            PathBuf::from("/src/api"),
+            PathBuf::from("/src/api/lib"),
+            PathBuf::from("/src/web"),
+        ];
+        assert_eq!(workspace.folder_of(file), Some("/src/api/lib".into()));
+        assert_eq!(workspace.relative_path(file), Some("lib/main.rs".into()));
+        assert_eq!(
+            workspace.relative_path(Path::new("/src/web/index.ts")),
+            Some("web/index.ts".into())
+        );
+        assert_eq!(workspace.relative_path(Path::new("/etc/hosts")), None);
+    }
 }
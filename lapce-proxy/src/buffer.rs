@@ -1,9 +1,8 @@
 use std::{
     borrow::Cow,
-    ffi::OsString,
     fs,
     fs::File,
-    io::{Read, Write},
+    io::Read,
     path::{Path, PathBuf},
     time::SystemTime,
 };
@@ -11,10 +10,12 @@ use std::{
 use anyhow::{Result, anyhow, bail};
 use doc::lines::buffer::rope_text::CharIndicesJoin;
 use lapce_core::encoding::offset_utf8_to_utf16;
-use lapce_rpc::buffer::BufferId;
+use lapce_rpc::buffer::{BufferId, FileEncoding, SaveMode};
 use lapce_xi_rope::{RopeDelta, interval::IntervalBounds, rope::Rope};
 use lsp_types::*;
 
+use crate::{encoding, save};
+
 #[derive(Clone)]
 pub struct Buffer {
     pub language_id: &'static str,
@@ -24,21 +25,26 @@ pub struct Buffer {
     pub path:        PathBuf,
     pub rev:         u64,
     pub mod_time:    Option<SystemTime>,
+    /// What the file was decoded from and is saved as
+    pub encoding:    FileEncoding,
 }
 
 impl Buffer {
     pub fn new(id: BufferId, path: PathBuf) -> Buffer {
-        let (s, read_only) = match load_file(&path) {
-            Ok(s) => (s, false),
-            Err(err) => match err.downcast_ref::<std::io::Error>() {
-                Some(err) => match err.kind() {
-                    std::io::ErrorKind::PermissionDenied => {
-                        ("Permission Denied".to_string(), true)
+        let (s, encoding, read_only) = match load_file(&path, None) {
+            Ok((s, encoding)) => (s, encoding, false),
+            Err(err) => {
+                let (s, read_only) = match err.downcast_ref::<std::io::Error>() {
+                    Some(err) => match err.kind() {
+                        std::io::ErrorKind::PermissionDenied => {
+                            ("Permission Denied".to_string(), true)
+                        },
+                        std::io::ErrorKind::NotFound => ("".to_string(), false),
+                        _ => ("Not Supported".to_string(), true),
                     },
-                    std::io::ErrorKind::NotFound => ("".to_string(), false),
-                    _ => ("Not Supported".to_string(), true),
-                },
-                None => ("Not Supported".to_string(), true),
+                    None => ("Not Supported".to_string(), true),
+                };
+                (s, FileEncoding::default(), read_only)
             },
         };
         let rope = Rope::from(s);
@@ -53,10 +59,16 @@ impl Buffer {
             language_id,
             rev,
             mod_time,
+            encoding,
         }
     }
 
-    pub fn save(&mut self, rev: u64, create_parents: bool) -> Result<()> {
+    pub fn save(
+        &mut self,
+        rev: u64,
+        create_parents: bool,
+        save_mode: SaveMode,
+    ) -> Result<()> {
         log::warn!(
             "save request_rev={rev}, read_only={} buffer_rev={}",
             self.read_only,
@@ -73,25 +85,15 @@ impl Buffer {
                 rev
             ));
         }
-        let bak_extension = self.path.extension().map_or_else(
-            || OsString::from("bak"),
-            |ext| {
-                let mut ext = ext.to_os_string();
-                ext.push(".bak");
-                ext
-            },
-        );
+        // before touching the file, which stays as it is if the text can't be
+        // encoded
+        let content = encoding::encode(&self.rope.to_string(), &self.encoding)?;
+        // the link stays, the file it points to is written
         let path = if self.path.is_symlink() {
             self.path.canonicalize()?
         } else {
             self.path.clone()
         };
-        let new_file = !path.exists();
-
-        let bak_file_path = &path.with_extension(bak_extension);
-        if !new_file {
-            fs::copy(&path, bak_file_path)?;
-        }
 
         if create_parents {
             if let Some(parent) = path.parent() {
@@ -99,19 +101,8 @@ impl Buffer {
             }
         }
 
-        let mut f = fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&path)?;
-        for chunk in self.rope.iter_chunks(..self.rope.len()) {
-            f.write_all(chunk.as_bytes())?;
-        }
-
+        save::write_file(&path, &content, save_mode)?;
         self.mod_time = get_mod_time(&path);
-        if !new_file {
-            fs::remove_file(bak_file_path)?;
-        }
 
         Ok(())
     }
@@ -197,22 +188,17 @@ impl Buffer {
     }
 }
 
-pub fn load_file(path: &Path) -> Result<String> {
-    read_path_to_string(path)
-}
-
-pub fn read_path_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
-    let path = path.as_ref();
-
+/// Reads a file with `encoding`, or with the one detected from its content.
+pub fn load_file(
+    path: &Path,
+    encoding: Option<&FileEncoding>,
+) -> Result<(String, FileEncoding)> {
     let mut file = File::open(path)?;
     // Read the file in as bytes
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)?;
 
-    // Parse the file contents as utf8
-    let contents = String::from_utf8(buffer)?;
-
-    Ok(contents.to_string())
+    encoding::decode(&buffer, encoding)
 }
 
 pub fn language_id_from_path(path: &Path) -> Option<&'static str> {
@@ -7,7 +7,7 @@ use std::{
         atomic::{AtomicU64, Ordering},
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use alacritty_terminal::{event::WindowSize, event_loop::Msg};
@@ -16,22 +16,25 @@ use crossbeam_channel::Sender;
 use git2::{
     DiffOptions, ErrorCode::NotFound, Oid, Repository, build::CheckoutBuilder,
 };
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use grep_matcher::Matcher;
 use grep_regex::RegexMatcherBuilder;
 use grep_searcher::{SearcherBuilder, sinks::UTF8};
 use indexmap::IndexMap;
-use lapce_core::directory::Directory;
+use lapce_core::{directory::Directory, search::Replacer, workspace::folder_of};
 use lapce_rpc::{
     RequestId, RpcError, RpcResult,
-    buffer::BufferId,
+    buffer::{BufferId, FileEncoding, SaveMode},
     core::{CoreNotification, CoreRpcHandler, FileChanged},
     file::FileNodeItem,
     file_line::FileLine,
     proxy::{
-        ProxyHandler, ProxyNotification, ProxyRequest, ProxyResponse,
-        ProxyRpcHandler, SearchMatch,
+        FileReplace, ProcessInfo, ProxyHandler, ProxyNotification, ProxyRequest,
+        ProxyResponse, ProxyRpcHandler, SearchMatch, SearchOptions, TestCommand,
+    },
+    source_control::{
+        BlameCommit, DiffInfo, FileDiff, GitBlame, GitCommitInfo, StageSelection,
     },
-    source_control::{DiffInfo, FileDiff},
     style::{LineStyle, SemanticStyles},
 };
 use lapce_xi_rope::Rope;
@@ -45,8 +48,11 @@ use parking_lot::Mutex;
 
 use crate::{
     buffer::{Buffer, get_mod_time, load_file},
+    encoding,
     plugin::{PluginCatalogRpcHandler, catalog::PluginCatalog},
+    rust_build::RustBuildProcess,
     rust_module_resolve::{CargoContext, create_cargo_context},
+    save,
     terminal::{Terminal, TerminalSender, Terminals},
     watcher::{FileWatcher, Notify, WatchToken},
 };
@@ -54,18 +60,29 @@ use crate::{
 const OPEN_FILE_EVENT_TOKEN: WatchToken = WatchToken(1);
 const WORKSPACE_EVENT_TOKEN: WatchToken = WatchToken(2);
 
+/// The id of the global search that is allowed to keep running. Starting a
+/// new search or cancelling the current one expires the old job.
+static GLOBAL_SEARCH_ID: AtomicU64 = AtomicU64::new(0);
+/// How often matches found by a global search are sent to the core.
+const GLOBAL_SEARCH_BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct Dispatcher {
-    workspace:     Option<PathBuf>,
-    pub proxy_rpc: ProxyRpcHandler,
-    core_rpc:      CoreRpcHandler,
-    catalog_rpc:   PluginCatalogRpcHandler,
-    buffers:       HashMap<PathBuf, Buffer>,
-    terminals:     Terminals,
-    file_watcher:  FileWatcher,
-    window_id:     usize,
-    tab_id:        usize,
-    directory:     Directory,
-    cargo_context: Option<CargoContext>,
+    workspace:      Option<PathBuf>,
+    /// Every root folder of the workspace, `workspace` being the first
+    folders:        Vec<PathBuf>,
+    pub proxy_rpc:  ProxyRpcHandler,
+    core_rpc:       CoreRpcHandler,
+    catalog_rpc:    PluginCatalogRpcHandler,
+    buffers:        HashMap<PathBuf, Buffer>,
+    terminals:      Terminals,
+    file_watcher:   FileWatcher,
+    window_id:      usize,
+    tab_id:         usize,
+    directory:      Directory,
+    /// One for each folder with a `Cargo.toml`
+    cargo_contexts: Vec<CargoContext>,
+    rust_build:     Option<RustBuildProcess>,
+    test_run:       Option<RustBuildProcess>,
 }
 
 impl ProxyHandler for Dispatcher {
@@ -83,8 +100,8 @@ impl ProxyHandler for Dispatcher {
                         if get_mod_time(&buffer.path) == buffer.mod_time {
                             return;
                         }
-                        match load_file(&buffer.path) {
-                            Ok(content) => {
+                        match load_file(&buffer.path, Some(&buffer.encoding)) {
+                            Ok((content, _)) => {
                                 self.core_rpc.open_file_changed(
                                     path,
                                     FileChanged::Change(content),
@@ -139,20 +156,33 @@ impl ProxyHandler for Dispatcher {
                     log::error!("{:?}", err);
                 }
             },
+            CancelGlobalSearch { search_id } => {
+                let _ = GLOBAL_SEARCH_ID.compare_exchange(
+                    search_id,
+                    0,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                );
+            },
             NewTerminal {
                 term_id,
                 raw_id,
                 profile,
             } => {
-                let mut terminal =
-                    match Terminal::new(raw_id, term_id, profile, 50, 10) {
-                        Ok(terminal) => terminal,
-                        Err(e) => {
-                            self.core_rpc
-                                .terminal_launch_failed(term_id, e.to_string());
-                            return;
-                        },
-                    };
+                let mut terminal = match Terminal::new(
+                    raw_id,
+                    term_id,
+                    profile,
+                    50,
+                    10,
+                    &self.directory.proxy_directory,
+                ) {
+                    Ok(terminal) => terminal,
+                    Err(e) => {
+                        self.core_rpc.terminal_launch_failed(term_id, e.to_string());
+                        return;
+                    },
+                };
 
                 #[allow(unused)]
                 let mut child_id = None;
@@ -250,6 +280,13 @@ impl ProxyHandler for Dispatcher {
                     log::error!("{:?}", err);
                 }
             },
+            DapStepInstruction { dap_id, thread_id } => {
+                if let Err(err) =
+                    self.catalog_rpc.dap_step_instruction(dap_id, thread_id)
+                {
+                    log::error!("{:?}", err);
+                }
+            },
             DapStop { dap_id } => {
                 if let Err(err) = self.catalog_rpc.dap_stop(dap_id) {
                     log::error!("{:?}", err);
@@ -280,19 +317,48 @@ impl ProxyHandler for Dispatcher {
                     log::error!("{:?}", err);
                 }
             },
+            DapSetExceptionBreakpoints {
+                dap_id,
+                filters,
+                break_on_panic,
+            } => {
+                if let Err(err) = self.catalog_rpc.dap_set_exception_breakpoints(
+                    dap_id,
+                    filters,
+                    break_on_panic,
+                ) {
+                    log::error!("{:?}", err);
+                }
+            },
             GitCommit { message, diffs } => {
-                if let Some(workspace) = self.workspace.as_ref() {
-                    match git_commit(workspace, &message, diffs) {
-                        Ok(()) => (),
-                        Err(e) => {
-                            self.core_rpc.show_message(
-                                "Git Commit failure".to_owned(),
-                                ShowMessageParams {
-                                    typ:     MessageType::ERROR,
-                                    message: e.to_string(),
-                                },
-                            );
-                        },
+                // each repository gets a commit of its own changes, or of its
+                // index when there are none
+                let mut folder_diffs: IndexMap<&PathBuf, Vec<FileDiff>> =
+                    IndexMap::new();
+                if diffs.is_empty() {
+                    for folder in &self.folders {
+                        if git_diff_new(folder)
+                            .is_some_and(|diff| !diff.staged.is_empty())
+                        {
+                            folder_diffs.insert(folder, Vec::new());
+                        }
+                    }
+                } else {
+                    for diff in diffs {
+                        if let Some(folder) = self.folder_of(diff.path()) {
+                            folder_diffs.entry(folder).or_default().push(diff);
+                        }
+                    }
+                }
+                for (folder, diffs) in folder_diffs {
+                    if let Err(e) = git_commit(folder, &message, diffs) {
+                        self.core_rpc.show_message(
+                            "Git Commit failure".to_owned(),
+                            ShowMessageParams {
+                                typ:     MessageType::ERROR,
+                                message: e.to_string(),
+                            },
+                        );
                     }
                 }
             },
@@ -305,19 +371,20 @@ impl ProxyHandler for Dispatcher {
                 }
             },
             GitDiscardFilesChanges { files } => {
-                if let Some(workspace) = self.workspace.as_ref() {
-                    match git_discard_files_changes(
-                        workspace,
-                        files.iter().map(AsRef::as_ref),
-                    ) {
+                for folder in &self.folders {
+                    let files = files
+                        .iter()
+                        .filter(|file| self.folder_of(file) == Some(folder));
+                    match git_discard_files_changes(folder, files.map(AsRef::as_ref))
+                    {
                         Ok(()) => (),
                         Err(e) => eprintln!("{e:?}"),
                     }
                 }
             },
             GitDiscardWorkspaceChanges {} => {
-                if let Some(workspace) = self.workspace.as_ref() {
-                    match git_discard_workspace_changes(workspace) {
+                for folder in &self.folders {
+                    match git_discard_workspace_changes(folder) {
                         Ok(()) => (),
                         Err(e) => eprintln!("{e:?}"),
                     }
@@ -343,7 +410,122 @@ impl ProxyHandler for Dispatcher {
                     false,
                 );
             },
-            RustBuild { .. } => {},
+            RustBuild {
+                rev,
+                command,
+                arguments,
+            } => {
+                if let Some(build) = self.rust_build.take() {
+                    build.cancel();
+                }
+                let Some(workspace) = self.workspace.as_ref() else {
+                    self.core_rpc.rust_build_stopped(rev, None);
+                    return;
+                };
+                let output_rpc = self.core_rpc.clone();
+                let stopped_rpc = self.core_rpc.clone();
+                match RustBuildProcess::start(
+                    workspace,
+                    rev,
+                    &command,
+                    &arguments.unwrap_or_default(),
+                    &[],
+                    move |rev, output| output_rpc.rust_build_output(rev, output),
+                    move |rev, exit_code| {
+                        stopped_rpc.rust_build_stopped(rev, exit_code)
+                    },
+                ) {
+                    Ok(build) => self.rust_build = Some(build),
+                    Err(err) => {
+                        self.core_rpc.show_message(
+                            "Cargo failure".to_owned(),
+                            ShowMessageParams {
+                                typ:     MessageType::ERROR,
+                                message: format!("{command}: {err}"),
+                            },
+                        );
+                        self.core_rpc.rust_build_stopped(rev, None);
+                    },
+                }
+            },
+            RustBuildCancel { rev } => {
+                if let Some(build) = self.rust_build.as_ref() {
+                    if build.id == rev {
+                        build.cancel();
+                    }
+                }
+            },
+            TestRun { run_id, command } => {
+                if let Some(run) = self.test_run.take() {
+                    run.cancel();
+                }
+                let Some(workspace) = self.workspace.as_ref() else {
+                    self.core_rpc.test_run_stopped(run_id, None);
+                    return;
+                };
+                let output_rpc = self.core_rpc.clone();
+                let stopped_rpc = self.core_rpc.clone();
+                let on_output =
+                    move |run_id, output| output_rpc.test_run_output(run_id, output);
+                let on_stopped = move |run_id, exit_code| {
+                    stopped_rpc.test_run_stopped(run_id, exit_code)
+                };
+                let (program, started) = match &command {
+                    TestCommand::Build { arguments } => (
+                        PathBuf::from("cargo"),
+                        RustBuildProcess::start(
+                            workspace,
+                            run_id,
+                            "cargo",
+                            arguments,
+                            &[],
+                            on_output,
+                            on_stopped,
+                        ),
+                    ),
+                    TestCommand::Binary {
+                        executable,
+                        cwd,
+                        arguments,
+                    } => (
+                        executable.clone(),
+                        RustBuildProcess::start(
+                            cwd,
+                            run_id,
+                            executable,
+                            arguments,
+                            // `--format json` is still an unstable libtest
+                            // option
+                            &[
+                                ("RUSTC_BOOTSTRAP", "1"),
+                                ("CARGO_MANIFEST_DIR", &cwd.to_string_lossy()),
+                            ],
+                            on_output,
+                            on_stopped,
+                        ),
+                    ),
+                };
+                match started {
+                    Ok(run) => self.test_run = Some(run),
+                    Err(err) => {
+                        self.core_rpc.show_message(
+                            "Test failure".to_owned(),
+                            ShowMessageParams {
+                                typ:     MessageType::ERROR,
+                                message: format!("{}: {err}", program.display()),
+                            },
+                        );
+                        self.core_rpc.test_run_stopped(run_id, None);
+                    },
+                }
+            },
+            TestRunCancel { run_id } => {
+                if let Some(run) = self.test_run.as_ref() {
+                    if run.id == run_id {
+                        run.cancel();
+                    }
+                }
+            },
         }
     }
 
@@ -352,14 +534,43 @@ impl ProxyHandler for Dispatcher {
         log::debug!("dispatcher handle_request {:?}", rpc);
         match rpc {
             NewBuffer { buffer_id, path } => {
-                let (content, read_only) = self.new_buffer(id, buffer_id, path);
+                let (content, read_only, encoding) =
+                    self.new_buffer(id, buffer_id, path);
                 self.respond_rpc(
                     id,
-                    Ok(ProxyResponse::NewBufferResponse { content, read_only }),
+                    Ok(ProxyResponse::NewBufferResponse {
+                        content,
+                        read_only,
+                        encoding,
+                    }),
                 );
             },
+            ReopenWithEncoding { path, encoding } => {
+                let result = self
+                    .buffers
+                    .get_mut(&path)
+                    .ok_or_else(|| anyhow!("{path:?} is not open"))
+                    .and_then(|buffer| {
+                        let bytes = std::fs::read(&buffer.path)?;
+                        let (content, encoding, lossy) =
+                            encoding::decode_with(&bytes, &encoding)?;
+                        // saving what was replaced would lose it
+                        buffer.read_only = lossy;
+                        buffer.encoding = encoding.clone();
+                        Ok(ProxyResponse::NewBufferResponse {
+                            content,
+                            read_only: buffer.read_only,
+                            encoding,
+                        })
+                    })
+                    .map_err(|e| RpcError {
+                        code:    0,
+                        message: e.to_string(),
+                    });
+                self.respond_rpc(id, result);
+            },
             BufferHead { path } => {
-                let result = if let Some(workspace) = self.workspace.as_ref() {
+                let result = if let Some(workspace) = self.folder_of(&path) {
                     let result = file_get_head(workspace, &path);
                     if let Ok((_blob_id, content)) = result {
                         Ok(ProxyResponse::BufferHeadResponse {
@@ -380,16 +591,151 @@ impl ProxyHandler for Dispatcher {
                 };
                 self.respond_rpc(id, result);
             },
+            GitIndexContent { path } => {
+                let result = if let Some(workspace) = self.folder_of(&path) {
+                    match file_get_index(workspace, &path) {
+                        Ok(content) => Ok(ProxyResponse::BufferHeadResponse {
+                            version: "index".to_string(),
+                            content: content.unwrap_or_default(),
+                        }),
+                        Err(e) => Err(RpcError {
+                            code:    0,
+                            message: format!("can't get file index: {e}"),
+                        }),
+                    }
+                } else {
+                    Err(RpcError {
+                        code:    0,
+                        message: "no workspace set".to_string(),
+                    })
+                };
+                self.respond_rpc(id, result);
+            },
+            GitBlame { path } => {
+                let workspace = self.folder_of(&path).cloned();
+                let proxy_rpc = self.proxy_rpc.clone();
+                // Blame walks the history, keep it off the proxy thread
+                thread::spawn(move || {
+                    let result = match workspace {
+                        Some(workspace) => git_blame(&workspace, &path)
+                            .map(|blame| ProxyResponse::GitBlameResponse { blame })
+                            .map_err(|e| RpcError {
+                                code:    0,
+                                message: format!("can't get file blame: {e}"),
+                            }),
+                        None => Err(RpcError {
+                            code:    0,
+                            message: "no workspace set".to_string(),
+                        }),
+                    };
+                    proxy_rpc.handle_response(id, result);
+                });
+            },
+            GitStage { path, selection } => {
+                // the selected lines are the ones of the editor, saved or not
+                let working =
+                    self.buffers.get(&path).map(|buffer| buffer.get_document());
+                let result = match self.folder_of(&path) {
+                    Some(workspace) => {
+                        git_stage(workspace, &path, working, selection)
+                    },
+                    None => Err(anyhow!("{path:?} isn't in the workspace")),
+                };
+                let result =
+                    result.map(|_| ProxyResponse::Success {}).map_err(|e| {
+                        self.core_rpc.show_message(
+                            "Git Stage failure".to_owned(),
+                            ShowMessageParams {
+                                typ:     MessageType::ERROR,
+                                message: e.to_string(),
+                            },
+                        );
+                        RpcError {
+                            code:    0,
+                            message: e.to_string(),
+                        }
+                    });
+                self.respond_rpc(id, result);
+            },
+            GitUnstage { path, selection } => {
+                let result = match self.folder_of(&path) {
+                    Some(workspace) => git_unstage(workspace, &path, selection),
+                    None => Err(anyhow!("{path:?} isn't in the workspace")),
+                };
+                let result =
+                    result.map(|_| ProxyResponse::Success {}).map_err(|e| {
+                        self.core_rpc.show_message(
+                            "Git Unstage failure".to_owned(),
+                            ShowMessageParams {
+                                typ:     MessageType::ERROR,
+                                message: e.to_string(),
+                            },
+                        );
+                        RpcError {
+                            code:    0,
+                            message: e.to_string(),
+                        }
+                    });
+                self.respond_rpc(id, result);
+            },
+            GitLog { path, skip, limit } => {
+                // the history of the workspace is the one of its first folder
+                let workspace = match path.as_ref() {
+                    Some(path) => self.folder_of(path).cloned(),
+                    None => self.workspace.clone(),
+                };
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = match workspace {
+                        Some(workspace) => {
+                            git_log(&workspace, path.as_deref(), skip, limit)
+                                .map(|commits| ProxyResponse::GitLogResponse {
+                                    commits,
+                                })
+                                .map_err(|e| RpcError {
+                                    code:    0,
+                                    message: format!("can't get git log: {e}"),
+                                })
+                        },
+                        None => Err(RpcError {
+                            code:    0,
+                            message: "no workspace set".to_string(),
+                        }),
+                    };
+                    proxy_rpc.handle_response(id, result);
+                });
+            },
+            GitFileContent { rev, path } => {
+                let result = if let Some(workspace) = self.folder_of(&path) {
+                    match file_get_rev(workspace, &rev, &path) {
+                        Ok(content) => Ok(ProxyResponse::BufferHeadResponse {
+                            version: rev,
+                            content,
+                        }),
+                        Err(e) => Err(RpcError {
+                            code:    0,
+                            message: format!("can't get file content: {e}"),
+                        }),
+                    }
+                } else {
+                    Err(RpcError {
+                        code:    0,
+                        message: "no workspace set".to_string(),
+                    })
+                };
+                self.respond_rpc(id, result);
+            },
             GlobalSearch {
+                search_id,
                 pattern,
                 case_sensitive,
                 whole_word,
                 is_regex,
+                options,
             } => {
-                static WORKER_ID: AtomicU64 = AtomicU64::new(0);
-                let our_id = WORKER_ID.fetch_add(1, Ordering::SeqCst) + 1;
+                GLOBAL_SEARCH_ID.store(search_id, Ordering::SeqCst);
 
-                let workspace = self.workspace.clone();
+                let folders = self.folders.clone();
                 let buffers = self
                     .buffers
                     .iter()
@@ -397,6 +743,7 @@ impl ProxyHandler for Dispatcher {
                     .cloned()
                     .collect::<Vec<PathBuf>>();
                 let proxy_rpc = self.proxy_rpc.clone();
+                let core_rpc = self.core_rpc.clone();
 
                 // Perform the search on another thread to avoid blocking the proxy
                 // thread
@@ -404,9 +751,10 @@ impl ProxyHandler for Dispatcher {
                     proxy_rpc.handle_response(
                         id,
                         search_in_path(
-                            our_id,
-                            &WORKER_ID,
-                            workspace
+                            search_id,
+                            &GLOBAL_SEARCH_ID,
+                            &folders,
+                            folders
                                 .iter()
                                 .flat_map(|w| ignore::Walk::new(w).flatten())
                                 .chain(
@@ -419,6 +767,8 @@ impl ProxyHandler for Dispatcher {
                             case_sensitive,
                             whole_word,
                             is_regex,
+                            &options,
+                            &core_rpc,
                         ),
                     );
                 });
@@ -476,7 +826,7 @@ impl ProxyHandler for Dispatcher {
                 );
             },
             GitGetRemoteFileUrl { file } => {
-                if let Some(workspace) = self.workspace.as_ref() {
+                if let Some(workspace) = self.folder_of(&file) {
                     match git_get_remote_file_url(workspace, &file) {
                         Ok(s) => self.proxy_rpc.handle_response(
                             id,
@@ -573,6 +923,71 @@ impl ProxyHandler for Dispatcher {
                     id,
                 );
             },
+            CallHierarchyOutgoing {
+                path,
+                call_hierarchy_item,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.call_hierarchy_outgoing(
+                    &path,
+                    call_hierarchy_item,
+                    move |_, result| {
+                        let result = result.map(|items| {
+                            ProxyResponse::CallHierarchyOutgoingResponse { items }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                    id,
+                );
+            },
+            ShowTypeHierarchy { path, position } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.show_type_hierarchy(
+                    &path,
+                    position,
+                    move |_, result| {
+                        let result = result.map(|items| {
+                            ProxyResponse::ShowTypeHierarchyResponse { items }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                    id,
+                );
+            },
+            TypeHierarchySupertypes {
+                path,
+                type_hierarchy_item,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.type_hierarchy_supertypes(
+                    &path,
+                    type_hierarchy_item,
+                    move |_, result| {
+                        let result = result.map(|items| {
+                            ProxyResponse::TypeHierarchyResponse { items }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                    id,
+                );
+            },
+            TypeHierarchySubtypes {
+                path,
+                type_hierarchy_item,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.type_hierarchy_subtypes(
+                    &path,
+                    type_hierarchy_item,
+                    move |_, result| {
+                        let result = result.map(|items| {
+                            ProxyResponse::TypeHierarchyResponse { items }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                    id,
+                );
+            },
             GetInlayHints { path } => {
                 let proxy_rpc = self.proxy_rpc.clone();
                 let buffer = self.buffers.get(&path).unwrap();
@@ -785,6 +1200,37 @@ impl ProxyHandler for Dispatcher {
                     id,
                 );
             },
+            GetDocumentRangeFormatting { path, range } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_document_range_formatting(
+                    &path,
+                    range,
+                    move |_, result| {
+                        let result = result.map(|edits| {
+                            ProxyResponse::GetDocumentFormatting { edits }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                    id,
+                );
+            },
+            GetOnTypeFormatting { path, position, ch } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_on_type_formatting(
+                    &path,
+                    position,
+                    ch,
+                    move |_, result| {
+                        let result = result.map(|edits| {
+                            ProxyResponse::GetDocumentFormatting {
+                                edits: edits.unwrap_or_default(),
+                            }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                    id,
+                );
+            },
             PrepareRename { path, position } => {
                 let proxy_rpc = self.proxy_rpc.clone();
                 self.catalog_rpc.prepare_rename(
@@ -817,31 +1263,29 @@ impl ProxyHandler for Dispatcher {
                 );
             },
             GetFiles { .. } => {
-                let workspace = self.workspace.clone();
+                let folders = self.folders.clone();
                 let proxy_rpc = self.proxy_rpc.clone();
                 thread::spawn(move || {
-                    let result = if let Some(workspace) = workspace {
+                    let mut items = Vec::new();
+                    for folder in folders {
                         let git_folder =
-                            ignore::overrides::OverrideBuilder::new(&workspace)
+                            ignore::overrides::OverrideBuilder::new(&folder)
                                 .add("!.git/")
                                 .map(|git_folder| git_folder.build());
 
                         let walker = match git_folder {
-                            Ok(Ok(git_folder)) => {
-                                ignore::WalkBuilder::new(&workspace)
-                                    .hidden(false)
-                                    .parents(false)
-                                    .require_git(false)
-                                    .overrides(git_folder)
-                                    .build()
-                            },
-                            _ => ignore::WalkBuilder::new(&workspace)
+                            Ok(Ok(git_folder)) => ignore::WalkBuilder::new(&folder)
+                                .hidden(false)
+                                .parents(false)
+                                .require_git(false)
+                                .overrides(git_folder)
+                                .build(),
+                            _ => ignore::WalkBuilder::new(&folder)
                                 .parents(false)
                                 .require_git(false)
                                 .build(),
                         };
 
-                        let mut items = Vec::new();
                         for path in walker.flatten() {
                             if let Some(file_type) = path.file_type() {
                                 if file_type.is_file() {
@@ -849,10 +1293,8 @@ impl ProxyHandler for Dispatcher {
                                 }
                             }
                         }
-                        Ok(ProxyResponse::GetFilesResponse { items })
-                    } else {
-                        Ok(ProxyResponse::GetFilesResponse { items: Vec::new() })
-                    };
+                    }
+                    let result = Ok(ProxyResponse::GetFilesResponse { items });
                     proxy_rpc.handle_response(id, result);
                 });
             },
@@ -906,13 +1348,24 @@ impl ProxyHandler for Dispatcher {
                 rev,
                 path,
                 create_parents,
+                encoding,
+                save_mode,
             } => {
                 let buffer = self.buffers.get_mut(&path).unwrap();
-                let result = buffer
-                    .save(rev, create_parents)
+                let old_encoding = encoding.map(|encoding| {
+                    std::mem::replace(&mut buffer.encoding, encoding)
+                });
+                let result = buffer.save(rev, create_parents, save_mode);
+                if let (Err(_), Some(old_encoding)) = (&result, old_encoding) {
+                    buffer.encoding = old_encoding;
+                }
+                let result = result
                     .map(|_r| {
                         self.catalog_rpc
                             .did_save_text_document(&path, buffer.rope.clone());
+                        if save_mode == SaveMode::Atomic {
+                            self.file_watcher.rewatch(&path);
+                        }
                         ProxyResponse::SaveResponse {}
                     })
                     .map_err(|e| RpcError {
@@ -927,12 +1380,15 @@ impl ProxyHandler for Dispatcher {
                 rev,
                 content,
                 create_parents,
+                encoding,
+                save_mode,
             } => {
                 let mut buffer = Buffer::new(buffer_id, path.clone());
                 buffer.rope = Rope::from(content);
                 buffer.rev = rev;
+                buffer.encoding = encoding;
                 let result = buffer
-                    .save(rev, create_parents)
+                    .save(rev, create_parents, save_mode)
                     .map(|_| ProxyResponse::Success {})
                     .map_err(|e| RpcError {
                         code:    0,
@@ -941,6 +1397,51 @@ impl ProxyHandler for Dispatcher {
                 self.buffers.insert(path, buffer);
                 self.respond_rpc(id, result);
             },
+            ReadFiles { paths } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    // the ones that can't be read are left to whoever uses them
+                    let files = paths
+                        .into_iter()
+                        .filter_map(|path| {
+                            let (content, _) = load_file(&path, None).ok()?;
+                            Some((path, content))
+                        })
+                        .collect();
+                    proxy_rpc.handle_response(
+                        id,
+                        Ok(ProxyResponse::ReadFilesResponse { files }),
+                    );
+                });
+            },
+            ReplaceInFiles {
+                pattern,
+                case_sensitive,
+                whole_word,
+                is_regex,
+                replacement,
+                files,
+                save_mode,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = Replacer::new(
+                        &pattern,
+                        case_sensitive,
+                        whole_word,
+                        is_regex,
+                        replacement,
+                    )
+                    .map_err(|err| RpcError {
+                        code:    0,
+                        message: err.to_string(),
+                    })
+                    .and_then(|replacer| {
+                        replace_in_files(&replacer, files, save_mode)
+                    });
+                    proxy_rpc.handle_response(id, result);
+                });
+            },
             CreateFile { path } => {
                 let result = path
                     .parent()
@@ -1157,6 +1658,25 @@ impl ProxyHandler for Dispatcher {
                     id,
                 );
             },
+            ExecuteCommand {
+                plugin_id,
+                command,
+                arguments,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.execute_command(
+                    plugin_id,
+                    command,
+                    arguments,
+                    move |result| {
+                        let result = result.map(|result| {
+                            ProxyResponse::ExecuteCommandResponse { result }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                    id,
+                );
+            },
             DapVariable { dap_id, reference } => {
                 let proxy_rpc = self.proxy_rpc.clone();
                 self.catalog_rpc.dap_variable(
@@ -1187,6 +1707,88 @@ impl ProxyHandler for Dispatcher {
                     },
                 );
             },
+            DapEvaluate {
+                dap_id,
+                expression,
+                frame_id,
+                context,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_evaluate(
+                    dap_id,
+                    expression,
+                    frame_id,
+                    context,
+                    move |_, result| {
+                        proxy_rpc.handle_response(
+                            id,
+                            result.map(|resp| ProxyResponse::DapEvaluateResponse {
+                                result: resp,
+                            }),
+                        );
+                    },
+                );
+            },
+            DapDisassemble {
+                dap_id,
+                memory_reference,
+                instruction_offset,
+                instruction_count,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_disassemble(
+                    dap_id,
+                    memory_reference,
+                    instruction_offset,
+                    instruction_count,
+                    move |_, result| {
+                        proxy_rpc.handle_response(
+                            id,
+                            result.map(|resp| {
+                                ProxyResponse::DapDisassembleResponse {
+                                    instructions: resp.instructions,
+                                }
+                            }),
+                        );
+                    },
+                );
+            },
+            DapReadMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                count,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_read_memory(
+                    dap_id,
+                    memory_reference,
+                    offset,
+                    count,
+                    move |_, result| {
+                        proxy_rpc.handle_response(
+                            id,
+                            result.map(|resp| {
+                                ProxyResponse::DapReadMemoryResponse { result: resp }
+                            }),
+                        );
+                    },
+                );
+            },
+            ListProcesses {} => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = list_processes()
+                        .map(|processes| ProxyResponse::ListProcessesResponse {
+                            processes,
+                        })
+                        .map_err(|e| RpcError {
+                            code:    0,
+                            message: e.to_string(),
+                        });
+                    proxy_rpc.handle_response(id, result);
+                });
+            },
             GetCodeLens { path } => {
                 let proxy_rpc = self.proxy_rpc.clone();
                 self.catalog_rpc.get_code_lens(
@@ -1316,6 +1918,7 @@ impl ProxyHandler for Dispatcher {
             },
             Initialize {
                 workspace,
+                folders,
                 disabled_volts,
                 extra_plugin_paths,
                 plugin_configurations,
@@ -1326,18 +1929,18 @@ impl ProxyHandler for Dispatcher {
                 self.window_id = window_id;
                 self.tab_id = tab_id;
                 self.workspace = workspace;
+                self.folders = folders;
                 self.file_watcher.notify(FileWatchNotifier::new(
-                    self.workspace.clone(),
+                    self.folders.clone(),
                     self.core_rpc.clone(),
                     self.proxy_rpc.clone(),
                 ));
-                if let Some(workspace) = self.workspace.as_ref() {
-                    self.file_watcher
-                        .watch(workspace, true, WORKSPACE_EVENT_TOKEN);
-                    let manifest_path = workspace.join("Cargo.toml");
+                for folder in &self.folders {
+                    self.file_watcher.watch(folder, true, WORKSPACE_EVENT_TOKEN);
+                    let manifest_path = folder.join("Cargo.toml");
                     if manifest_path.exists() {
                         match create_cargo_context(&manifest_path) {
-                            Ok(context) => self.cargo_context = Some(context),
+                            Ok(context) => self.cargo_contexts.push(context),
                             Err(err) => {
                                 error!("{err:?}");
                             },
@@ -1347,11 +1950,13 @@ impl ProxyHandler for Dispatcher {
 
                 let plugin_rpc = self.catalog_rpc.clone();
                 let workspace = self.workspace.clone();
+                let folders = self.folders.clone();
                 let directory = self.directory.clone();
                 thread::spawn(move || {
                     let mut plugin = PluginCatalog::new(
                         id,
                         workspace,
+                        folders,
                         disabled_volts,
                         extra_plugin_paths,
                         plugin_configurations,
@@ -1372,25 +1977,29 @@ impl ProxyHandler for Dispatcher {
                 }
             },
             FindFileFromLog { log } => {
-                let rs = if let Some(context) = &self.cargo_context {
-                    context.find_file_by_log(&log)
-                } else {
-                    RpcResult::Err("cargo context is none".to_string())
-                };
+                let mut rs = RpcResult::Err("cargo context is none".to_string());
+                for context in &self.cargo_contexts {
+                    rs = context.find_file_by_log(&log);
+                    if matches!(rs, RpcResult::Ok(_)) {
+                        break;
+                    }
+                }
                 self.respond_rpc(
                     id,
                     Ok(ProxyResponse::FindFileFromLogResponse { rs }),
                 );
             },
             FindLogModulesFromPath { path } => {
-                let rs = if let Some(context) = &self.cargo_context {
-                    if let Some(modules) = context.file_path_to_module_path(&path) {
-                        RpcResult::Ok(modules)
-                    } else {
-                        RpcResult::Err(format!("find log modules fail: {path:?}"))
-                    }
-                } else {
+                let rs = if self.cargo_contexts.is_empty() {
                     RpcResult::Err("cargo context is none".to_string())
+                } else if let Some(modules) = self
+                    .cargo_contexts
+                    .iter()
+                    .find_map(|context| context.file_path_to_module_path(&path))
+                {
+                    RpcResult::Ok(modules)
+                } else {
+                    RpcResult::Err(format!("find log modules fail: {path:?}"))
                 };
 
                 self.respond_rpc(
@@ -1403,6 +2012,11 @@ impl ProxyHandler for Dispatcher {
 }
 
 impl Dispatcher {
+    /// The folder of the workspace whose repository `path` is in.
+    fn folder_of(&self, path: &Path) -> Option<&PathBuf> {
+        folder_of(&self.folders, path)
+    }
+
     pub fn new(
         core_rpc: CoreRpcHandler,
         proxy_rpc: ProxyRpcHandler,
@@ -1415,6 +2029,7 @@ impl Dispatcher {
 
         Self {
             workspace: None,
+            folders: Vec::new(),
             proxy_rpc,
             core_rpc,
             catalog_rpc: plugin_rpc,
@@ -1424,7 +2039,9 @@ impl Dispatcher {
             window_id: 1,
             tab_id: 1,
             directory,
-            cargo_context: None,
+            cargo_contexts: Vec::new(),
+            rust_build: None,
+            test_run: None,
         }
     }
 
@@ -1443,10 +2060,11 @@ impl Dispatcher {
         id: RequestId,
         buffer_id: BufferId,
         path: PathBuf,
-    ) -> (String, bool) {
+    ) -> (String, bool, FileEncoding) {
         let buffer = Buffer::new(buffer_id, path.clone());
         let content = buffer.rope.to_string();
         let read_only = buffer.read_only;
+        let encoding = buffer.encoding.clone();
         self.catalog_rpc.did_open_document(
             &path,
             buffer.language_id.to_string(),
@@ -1456,16 +2074,17 @@ impl Dispatcher {
         );
         self.file_watcher.watch(&path, false, OPEN_FILE_EVENT_TOKEN);
         self.buffers.insert(path, buffer);
-        (content, read_only)
+        (content, read_only, encoding)
     }
 }
 
 struct FileWatchNotifier {
     core_rpc:                    CoreRpcHandler,
     proxy_rpc:                   ProxyRpcHandler,
-    workspace:                   Option<PathBuf>,
+    folders:                     Vec<PathBuf>,
     workspace_fs_change_handler: Arc<Mutex<Option<Sender<bool>>>>,
-    last_diff:                   Arc<Mutex<DiffInfo>>,
+    /// The last diff sent for each folder
+    last_diffs:                  Arc<Mutex<HashMap<PathBuf, DiffInfo>>>,
 }
 
 impl Notify for FileWatchNotifier {
@@ -1476,26 +2095,24 @@ impl Notify for FileWatchNotifier {
 
 impl FileWatchNotifier {
     fn new(
-        workspace: Option<PathBuf>,
+        folders: Vec<PathBuf>,
         core_rpc: CoreRpcHandler,
         proxy_rpc: ProxyRpcHandler,
     ) -> Self {
         let notifier = Self {
-            workspace,
+            folders,
             core_rpc,
             proxy_rpc,
             workspace_fs_change_handler: Arc::new(Mutex::new(None)),
-            last_diff: Arc::new(Mutex::new(DiffInfo::default())),
+            last_diffs: Arc::new(Mutex::new(HashMap::new())),
         };
 
-        if let Some(workspace) = notifier.workspace.clone() {
+        if !notifier.folders.is_empty() {
+            let folders = notifier.folders.clone();
             let core_rpc = notifier.core_rpc.clone();
-            let last_diff = notifier.last_diff.clone();
+            let last_diffs = notifier.last_diffs.clone();
             thread::spawn(move || {
-                if let Some(diff) = git_diff_new(&workspace) {
-                    core_rpc.diff_info(diff.clone());
-                    *last_diff.lock() = diff;
-                }
+                send_diffs(&folders, &core_rpc, &last_diffs);
             });
         }
 
@@ -1561,8 +2178,8 @@ impl FileWatchNotifier {
 
         let local_handler = self.workspace_fs_change_handler.clone();
         let core_rpc = self.core_rpc.clone();
-        let workspace = self.workspace.clone().unwrap();
-        let last_diff = self.last_diff.clone();
+        let folders = self.folders.clone();
+        let last_diffs = self.last_diffs.clone();
         thread::spawn(move || {
             thread::sleep(Duration::from_millis(500));
 
@@ -1580,18 +2197,29 @@ impl FileWatchNotifier {
             if explorer_change {
                 core_rpc.workspace_file_change();
             }
-            if let Some(diff) = git_diff_new(&workspace) {
-                let mut last_diff = last_diff.lock();
-                if diff != *last_diff {
-                    core_rpc.diff_info(diff.clone());
-                    *last_diff = diff;
-                }
-            }
+            send_diffs(&folders, &core_rpc, &last_diffs);
         });
         *handler = Some(sender);
     }
 }
 
+/// Sends the git status of each folder that changed since it was last sent.
+fn send_diffs(
+    folders: &[PathBuf],
+    core_rpc: &CoreRpcHandler,
+    last_diffs: &Mutex<HashMap<PathBuf, DiffInfo>>,
+) {
+    for folder in folders {
+        if let Some(diff) = git_diff_new(folder) {
+            let mut last_diffs = last_diffs.lock();
+            if last_diffs.get(folder) != Some(&diff) {
+                core_rpc.diff_info(folder.clone(), diff.clone());
+                last_diffs.insert(folder.clone(), diff);
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct DiffHunk {
     pub old_start: u32,
@@ -1741,6 +2369,12 @@ fn git_diff_new(workspace_path: &Path) -> Option<DiffInfo> {
         Ok(head) => head.shorthand()?.to_string(),
         _ => "(No branch)".to_owned(),
     };
+    let head_commit = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .map(|oid| oid.to_string())
+        .unwrap_or_default();
 
     let mut branches = Vec::new();
     for branch in repo.branches(None).ok()? {
@@ -1754,7 +2388,7 @@ fn git_diff_new(workspace_path: &Path) -> Option<DiffInfo> {
         }
     }
 
-    let mut deltas = Vec::new();
+    let mut unstaged_deltas = Vec::new();
     let mut diff_options = DiffOptions::new();
     let diff = repo
         .diff_index_to_workdir(
@@ -1768,7 +2402,7 @@ fn git_diff_new(workspace_path: &Path) -> Option<DiffInfo> {
         .ok()?;
     for delta in diff.deltas() {
         if let Some(delta) = git_delta_format(workspace_path, &delta) {
-            deltas.push(delta);
+            unstaged_deltas.push(delta);
         }
     }
 
@@ -1781,13 +2415,33 @@ fn git_diff_new(workspace_path: &Path) -> Option<DiffInfo> {
         .diff_tree_to_index(repo.find_tree(oid).ok().as_ref(), None, None)
         .ok();
 
+    let mut staged_deltas = Vec::new();
     if let Some(cached_diff) = cached_diff {
         for delta in cached_diff.deltas() {
             if let Some(delta) = git_delta_format(workspace_path, &delta) {
-                deltas.push(delta);
+                staged_deltas.push(delta);
             }
         }
     }
+
+    let deltas = unstaged_deltas
+        .iter()
+        .chain(staged_deltas.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+    Some(DiffInfo {
+        head: name,
+        head_commit,
+        branches,
+        tags,
+        diffs: git_file_diffs(&deltas),
+        staged: git_file_diffs(&staged_deltas),
+        unstaged: git_file_diffs(&unstaged_deltas),
+    })
+}
+
+/// Pairs up added and deleted files with the same content as renames.
+fn git_file_diffs(deltas: &[(git2::Delta, git2::Oid, PathBuf)]) -> Vec<FileDiff> {
     let mut renames = Vec::new();
     let mut renamed_deltas = HashSet::new();
 
@@ -1829,12 +2483,7 @@ fn git_diff_new(workspace_path: &Path) -> Option<DiffInfo> {
         | FileDiff::Renamed(p, _)
         | FileDiff::Deleted(p) => p.clone(),
     });
-    Some(DiffInfo {
-        head: name,
-        branches,
-        tags,
-        diffs: file_diffs,
-    })
+    file_diffs
 }
 
 fn file_get_head(workspace_path: &Path, path: &Path) -> Result<(String, String)> {
@@ -1850,6 +2499,315 @@ fn file_get_head(workspace_path: &Path, path: &Path) -> Result<(String, String)>
     Ok((id, content))
 }
 
+/// The content of `path` in the git index, `None` if it isn't there.
+fn file_get_index(workspace_path: &Path, path: &Path) -> Result<Option<String>> {
+    let repo = Repository::discover(workspace_path)?;
+    let index = repo.index()?;
+    index_content(&repo, &index, path.strip_prefix(workspace_path)?)
+}
+
+fn index_content(
+    repo: &Repository,
+    index: &git2::Index,
+    rel: &Path,
+) -> Result<Option<String>> {
+    let Some(entry) = index.get_path(rel, 0) else {
+        return Ok(None);
+    };
+    let blob = repo.find_blob(entry.id)?;
+    let content = std::str::from_utf8(blob.content())
+        .with_context(|| "content bytes to string")?
+        .to_string();
+    Ok(Some(content))
+}
+
+/// Writes `content` as the index entry of `rel`, creating the entry if the
+/// file isn't tracked yet.
+fn index_write(index: &mut git2::Index, rel: &Path, content: &str) -> Result<()> {
+    let entry = match index.get_path(rel, 0) {
+        Some(entry) => entry,
+        None => git2::IndexEntry {
+            ctime:          git2::IndexTime::new(0, 0),
+            mtime:          git2::IndexTime::new(0, 0),
+            dev:            0,
+            ino:            0,
+            mode:           0o100644,
+            uid:            0,
+            gid:            0,
+            file_size:      0,
+            id:             Oid::zero(),
+            flags:          0,
+            flags_extended: 0,
+            path:           rel.to_string_lossy().replace('\\', "/").into_bytes(),
+        },
+    };
+    index.add_frombuffer(&entry, content.as_bytes())?;
+    Ok(())
+}
+
+/// Stages the `selection` of the changes of `path`, whose working copy is
+/// `working` when it's open in the editor.
+fn git_stage(
+    workspace_path: &Path,
+    path: &Path,
+    working: Option<String>,
+    selection: StageSelection,
+) -> Result<()> {
+    let repo = Repository::discover(workspace_path)?;
+    let mut index = repo.index()?;
+    let rel = path.strip_prefix(workspace_path)?;
+    if selection == StageSelection::File {
+        if path.exists() {
+            index.add_path(rel)?;
+        } else {
+            index.remove_path(rel)?;
+        }
+    } else {
+        let old = index_content(&repo, &index, rel)?.unwrap_or_default();
+        let new = match working {
+            Some(working) => working,
+            None => load_file(path, None)?.0,
+        };
+        let content = apply_changes(&old, &new, selection, true)?;
+        index_write(&mut index, rel, &content)?;
+    }
+    index.write()?;
+    Ok(())
+}
+
+fn git_unstage(
+    workspace_path: &Path,
+    path: &Path,
+    selection: StageSelection,
+) -> Result<()> {
+    let repo = Repository::discover(workspace_path)?;
+    let rel = path.strip_prefix(workspace_path)?;
+    let head = repo.head().and_then(|head| head.peel_to_commit()).ok();
+    if selection == StageSelection::File {
+        if let Some(head) = head {
+            repo.reset_default(Some(head.as_object()), [rel])?;
+        } else {
+            let mut index = repo.index()?;
+            index.remove_path(rel)?;
+            index.write()?;
+        }
+        return Ok(());
+    }
+
+    let mut index = repo.index()?;
+    let old = match head {
+        Some(head) => match head.tree()?.get_path(rel) {
+            Ok(entry) => {
+                let blob = repo.find_blob(entry.id())?;
+                Some(
+                    std::str::from_utf8(blob.content())
+                        .with_context(|| "content bytes to string")?
+                        .to_string(),
+                )
+            },
+            Err(_) => None,
+        },
+        None => None,
+    };
+    let new = index_content(&repo, &index, rel)?.unwrap_or_default();
+    let content =
+        apply_changes(old.as_deref().unwrap_or_default(), &new, selection, false)?;
+    if old.is_none() && content.is_empty() {
+        index.remove_path(rel)?;
+    } else {
+        index_write(&mut index, rel, &content)?;
+    }
+    index.write()?;
+    Ok(())
+}
+
+/// Applies part of the changes from `old` to `new`. When staging, the
+/// selected changes are applied; when unstaging, everything but the selected
+/// changes is, which takes them back out of `new`.
+fn apply_changes(
+    old: &str,
+    new: &str,
+    selection: StageSelection,
+    stage: bool,
+) -> Result<String> {
+    let mut opts = DiffOptions::new();
+    opts.context_lines(0);
+    let patch = git2::Patch::from_buffers(
+        old.as_bytes(),
+        None,
+        new.as_bytes(),
+        None,
+        Some(&mut opts),
+    )?;
+
+    let old_lines = old.split_inclusive('\n').collect::<Vec<_>>();
+    let mut result = String::with_capacity(new.len());
+    let mut old_line = 0;
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, num_lines) = patch.hunk(hunk_idx)?;
+        // A hunk that only inserts starts after its `old_start` line
+        let hunk_old = if hunk.old_lines() == 0 {
+            hunk.old_start() as usize
+        } else {
+            hunk.old_start() as usize - 1
+        };
+        // The lines of the newer side the hunk covers; a deletion sits
+        // between two of them
+        let new_range = if hunk.new_lines() == 0 {
+            (hunk.new_start() as usize).saturating_sub(1)..=hunk.new_start() as usize
+        } else {
+            hunk.new_start() as usize - 1
+                ..=(hunk.new_start() + hunk.new_lines()) as usize - 2
+        };
+        let in_hunk = |start: usize, end: usize| {
+            start <= *new_range.end() && end >= *new_range.start()
+        };
+
+        for line in &old_lines[old_line.min(old_lines.len())..hunk_old] {
+            result.push_str(line);
+        }
+        old_line = hunk_old;
+
+        for line_idx in 0..num_lines {
+            let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+            let selected = match selection {
+                StageSelection::File => true,
+                StageSelection::Hunks { start, end } => in_hunk(start, end),
+                StageSelection::Lines { start, end } => match line.new_lineno() {
+                    Some(n) => (start..=end).contains(&(n as usize - 1)),
+                    None => in_hunk(start, end),
+                },
+            };
+            let content = std::str::from_utf8(line.content())
+                .with_context(|| "content bytes to string")?;
+            match line.origin() {
+                '+' => {
+                    if selected == stage {
+                        result.push_str(content);
+                    }
+                },
+                '-' => {
+                    if selected != stage {
+                        result.push_str(content);
+                    }
+                    old_line += 1;
+                },
+                _ => {},
+            }
+        }
+    }
+    for line in &old_lines[old_line.min(old_lines.len())..] {
+        result.push_str(line);
+    }
+    Ok(result)
+}
+
+fn git_blame(workspace_path: &Path, path: &Path) -> Result<GitBlame> {
+    let repo = Repository::discover(workspace_path)?;
+    let blame = repo.blame_file(path.strip_prefix(workspace_path)?, None)?;
+
+    let mut commits = Vec::new();
+    let mut commit_indexes = HashMap::new();
+    let mut lines = Vec::new();
+    for hunk in blame.iter() {
+        let id = hunk.final_commit_id();
+        let index = match commit_indexes.get(&id) {
+            Some(index) => *index,
+            None => {
+                let commit = repo.find_commit(id)?;
+                commits.push(BlameCommit {
+                    id:      id.to_string(),
+                    author:  commit.author().name().unwrap_or_default().to_string(),
+                    time:    commit.time().seconds(),
+                    summary: commit.summary().unwrap_or_default().to_string(),
+                });
+                commit_indexes.insert(id, commits.len() - 1);
+                commits.len() - 1
+            },
+        };
+        lines.extend(std::iter::repeat_n(index, hunk.lines_in_hunk()));
+    }
+    Ok(GitBlame { commits, lines })
+}
+
+fn git_log(
+    workspace_path: &Path,
+    path: Option<&Path>,
+    skip: usize,
+    limit: usize,
+) -> Result<Vec<GitCommitInfo>> {
+    let repo = Repository::discover(workspace_path)?;
+    let rel = path.map(|p| p.strip_prefix(workspace_path)).transpose()?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(git2::Sort::TIME)?;
+
+    let mut commits = Vec::new();
+    let mut skipped = 0;
+    for oid in revwalk {
+        if commits.len() >= limit {
+            break;
+        }
+        let commit = repo.find_commit(oid?)?;
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        if let Some(rel) = rel {
+            let mut diff_options = DiffOptions::new();
+            diff_options.pathspec(rel);
+            let diff = repo.diff_tree_to_tree(
+                parent_tree.as_ref(),
+                Some(&tree),
+                Some(&mut diff_options),
+            )?;
+            if diff.deltas().len() == 0 {
+                continue;
+            }
+        }
+        if skipped < skip {
+            skipped += 1;
+            continue;
+        }
+
+        let diff =
+            repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        let deltas = diff
+            .deltas()
+            .filter_map(|delta| git_delta_format(workspace_path, &delta))
+            .collect::<Vec<_>>();
+        commits.push(GitCommitInfo {
+            id:      commit.id().to_string(),
+            author:  commit.author().name().unwrap_or_default().to_string(),
+            time:    commit.time().seconds(),
+            message: commit.message().unwrap_or_default().to_string(),
+            files:   git_file_diffs(&deltas),
+        });
+    }
+    Ok(commits)
+}
+
+/// The content of `path` at `rev`, empty if it doesn't exist there.
+fn file_get_rev(workspace_path: &Path, rev: &str, path: &Path) -> Result<String> {
+    let repo = Repository::discover(workspace_path)?;
+    // The parent of a root commit has no files
+    let Ok(object) = repo.revparse_single(rev) else {
+        return Ok(String::new());
+    };
+    let tree = object.peel_to_tree()?;
+    let entry = match tree.get_path(path.strip_prefix(workspace_path)?) {
+        Ok(entry) => entry,
+        Err(e) if e.code() == NotFound => return Ok(String::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let blob = repo.find_blob(entry.id())?;
+    let content = std::str::from_utf8(blob.content())
+        .with_context(|| "content bytes to string")?
+        .to_string();
+    Ok(content)
+}
+
 fn git_get_remote_file_url(workspace_path: &Path, file: &Path) -> Result<String> {
     let repo = Repository::discover(workspace_path)?;
     let head = repo.head()?;
@@ -1896,16 +2854,124 @@ fn git_get_remote_file_url(workspace_path: &Path, file: &Path) -> Result<String>
     Ok(url)
 }
 
+/// Lists the local processes a debugger can attach to, excluding the proxy.
+fn list_processes() -> Result<Vec<ProcessInfo>> {
+    let own_pid = std::process::id();
+    #[cfg(not(target_os = "windows"))]
+    let processes = {
+        let output = std::process::Command::new("ps")
+            .args(["-eo", "pid=,args="])
+            .output()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (pid, command) = line.trim_start().split_once(' ')?;
+                Some(ProcessInfo {
+                    pid:     pid.parse().ok()?,
+                    command: command.trim().to_string(),
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    #[cfg(target_os = "windows")]
+    let processes = {
+        let mut command = std::process::Command::new("tasklist");
+        command.args(["/fo", "csv", "/nh"]);
+        // CREATE_NO_WINDOW
+        std::os::windows::process::CommandExt::creation_flags(
+            &mut command,
+            0x08000000,
+        );
+        let output = command.output()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.trim_matches('"').split("\",\"");
+                let command = fields.next()?.to_string();
+                Some(ProcessInfo {
+                    pid: fields.next()?.parse().ok()?,
+                    command,
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    Ok(processes
+        .into_iter()
+        .filter(|p| p.pid != own_pid && !p.command.is_empty())
+        .collect())
+}
+
+fn replace_in_files(
+    replacer: &Replacer,
+    files: Vec<FileReplace>,
+    save_mode: SaveMode,
+) -> Result<ProxyResponse, RpcError> {
+    let mut replaced = Vec::new();
+    let mut errors = Vec::new();
+    for file in files {
+        let matches = file.matches.into_iter().collect::<HashSet<_>>();
+        // saved the way an open buffer of the file would be
+        let result = load_file(&file.path, None).and_then(|(content, encoding)| {
+            let new_content =
+                encoding::encode(&replacer.replace(&content, &matches), &encoding)?;
+            let path = if file.path.is_symlink() {
+                file.path.canonicalize()?
+            } else {
+                file.path.clone()
+            };
+            save::write_file(&path, &new_content, save_mode)
+        });
+        match result {
+            Ok(()) => replaced.push(file.path),
+            Err(err) => errors.push(format!("{}: {err}", file.path.display())),
+        }
+    }
+    Ok(ProxyResponse::ReplaceInFilesResponse { replaced, errors })
+}
+
+/// Build a glob set from user supplied globs. A bare name such as `target`
+/// matches that file or directory anywhere in the workspace.
+fn search_globset(globs: &[String]) -> Result<Option<GlobSet>, RpcError> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = glob.trim().trim_start_matches("./").trim_end_matches('/');
+        if glob.is_empty() {
+            continue;
+        }
+        let patterns = if glob.contains('/') || glob.starts_with("**") {
+            vec![glob.to_string(), format!("{glob}/**")]
+        } else {
+            vec![format!("**/{glob}"), format!("**/{glob}/**")]
+        };
+        for pattern in patterns {
+            builder.add(Glob::new(&pattern).map_err(|err| RpcError {
+                code:    0,
+                message: format!("invalid glob {glob}: {err}"),
+            })?);
+        }
+    }
+    builder.build().map(Some).map_err(|err| RpcError {
+        code:    0,
+        message: err.to_string(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 fn search_in_path(
     id: u64,
     current_id: &AtomicU64,
+    folders: &[PathBuf],
     paths: impl Iterator<Item = PathBuf>,
     pattern: &str,
     case_sensitive: bool,
     whole_word: bool,
     is_regex: bool,
+    options: &SearchOptions,
+    core_rpc: &CoreRpcHandler,
 ) -> Result<ProxyResponse, RpcError> {
-    let mut matches = IndexMap::new();
     let mut matcher = RegexMatcherBuilder::new();
     let matcher = matcher.case_insensitive(!case_sensitive).word(whole_word);
     let matcher = if is_regex {
@@ -1917,8 +2983,17 @@ fn search_in_path(
         code:    0,
         message: "can't build matcher".to_string(),
     })?;
+    let include = search_globset(&options.include)?;
+    let exclude = search_globset(&options.exclude)?;
     let mut searcher = SearcherBuilder::new().build();
 
+    let mut pending = IndexMap::new();
+    let mut last_batch = Instant::now();
+    let mut searched = HashSet::new();
+    let mut files_searched = 0;
+    let mut match_count = 0;
+    let mut limit_reached = false;
+
     for path in paths {
         if current_id.load(Ordering::SeqCst) != id {
             return Err(RpcError {
@@ -1927,20 +3002,57 @@ fn search_in_path(
             });
         }
 
-        if path.is_file() {
-            let mut line_matches = Vec::new();
-            if let Err(err) = searcher.search_path(
-                &matcher,
-                path.clone(),
-                UTF8(|lnum, line| {
-                    if current_id.load(Ordering::SeqCst) != id {
+        if !path.is_file() || searched.contains(&path) {
+            continue;
+        }
+        let relative_path = folder_of(folders, &path)
+            .and_then(|folder| path.strip_prefix(folder).ok())
+            .unwrap_or(&path);
+        if include
+            .as_ref()
+            .is_some_and(|include| !include.is_match(relative_path))
+            || exclude
+                .as_ref()
+                .is_some_and(|exclude| exclude.is_match(relative_path))
+        {
+            continue;
+        }
+        if options.max_file_size > 0
+            && path
+                .metadata()
+                .is_ok_and(|metadata| metadata.len() > options.max_file_size)
+        {
+            continue;
+        }
+        searched.insert(path.clone());
+        files_searched += 1;
+
+        let mut line_matches = Vec::new();
+        if let Err(err) = searcher.search_path(
+            &matcher,
+            path.clone(),
+            UTF8(|lnum, line| {
+                if current_id.load(Ordering::SeqCst) != id {
+                    return Ok(false);
+                }
+
+                // every match of the line, to be replaced one by one
+                let mut found = Vec::new();
+                matcher.find_iter(line.as_bytes(), |mymatch| {
+                    found.push(mymatch);
+                    true
+                })?;
+                for mymatch in found {
+                    if options.max_matches > 0
+                        && match_count + line_matches.len() >= options.max_matches
+                    {
+                        limit_reached = true;
                         return Ok(false);
                     }
 
-                    let mymatch = matcher.find(line.as_bytes())?.unwrap();
-                    let line = if line.len() > 200 {
-                        // Shorten the line to avoid sending over absurdly long-lines
-                        // (such as in minified javascript)
+                    let line_content = if line.len() > 200 {
+                        // Shorten the line to avoid sending over absurdly
+                        // long-lines (such as in minified javascript)
                         // Note that the start/end are column based, not absolute
                         // from the start of the file.
                         let left_keep = line[..mymatch.start()]
@@ -1961,23 +3073,127 @@ fn search_in_path(
                         line.to_string()
                     };
                     line_matches.push(SearchMatch {
-                        line:         lnum as usize,
-                        start:        mymatch.start(),
-                        end:          mymatch.end(),
-                        line_content: line,
+                        line: lnum as usize,
+                        start: mymatch.start(),
+                        end: mymatch.end(),
+                        line_content,
                     });
-                    Ok(true)
-                }),
-            ) {
-                {
-                    log::error!("{:?}", err);
                 }
-            }
-            if !line_matches.is_empty() {
-                matches.insert(path.clone(), line_matches);
-            }
+                Ok(true)
+            }),
+        ) {
+            log::error!("{:?}", err);
+        }
+        if !line_matches.is_empty() {
+            match_count += line_matches.len();
+            pending.insert(path, line_matches);
+        }
+        if limit_reached {
+            break;
+        }
+        if last_batch.elapsed() >= GLOBAL_SEARCH_BATCH_INTERVAL {
+            core_rpc.global_search_matches(
+                id,
+                std::mem::take(&mut pending),
+                files_searched,
+                match_count,
+            );
+            last_batch = Instant::now();
         }
     }
 
-    Ok(ProxyResponse::GlobalSearchResponse { matches })
+    if current_id.load(Ordering::SeqCst) != id {
+        return Err(RpcError {
+            code:    0,
+            message: "expired search job".to_string(),
+        });
+    }
+    core_rpc.global_search_matches(id, pending, files_searched, match_count);
+
+    Ok(ProxyResponse::GlobalSearchResponse {
+        files_searched,
+        match_count,
+        limit_reached,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::source_control::StageSelection;
+
+    use super::apply_changes;
+
+    const OLD: &str = "a\nb\nc\nd\ne\n";
+    const NEW: &str = "a\nB\nc\nd\nf\ne\n";
+
+    #[test]
+    fn test_stage_changes() {
+        assert_eq!(
+            apply_changes(OLD, NEW, StageSelection::File, true).unwrap(),
+            NEW
+        );
+        assert_eq!(
+            apply_changes(
+                OLD,
+                NEW,
+                StageSelection::Hunks { start: 1, end: 1 },
+                true
+            )
+            .unwrap(),
+            "a\nB\nc\nd\ne\n"
+        );
+        assert_eq!(
+            apply_changes(
+                OLD,
+                NEW,
+                StageSelection::Lines { start: 4, end: 5 },
+                true
+            )
+            .unwrap(),
+            "a\nb\nc\nd\nf\ne\n"
+        );
+    }
+
+    #[test]
+    fn test_unstage_changes() {
+        assert_eq!(
+            apply_changes(
+                OLD,
+                NEW,
+                StageSelection::Hunks { start: 4, end: 4 },
+                false
+            )
+            .unwrap(),
+            "a\nB\nc\nd\ne\n"
+        );
+        assert_eq!(
+            apply_changes(OLD, NEW, StageSelection::File, false).unwrap(),
+            OLD
+        );
+    }
+
+    #[test]
+    fn test_stage_deletion() {
+        let new = "a\nc\nd\ne\n";
+        assert_eq!(
+            apply_changes(
+                OLD,
+                new,
+                StageSelection::Lines { start: 1, end: 1 },
+                true
+            )
+            .unwrap(),
+            new
+        );
+        assert_eq!(
+            apply_changes(
+                OLD,
+                new,
+                StageSelection::Lines { start: 3, end: 3 },
+                true
+            )
+            .unwrap(),
+            OLD
+        );
+    }
 }
@@ -0,0 +1,203 @@
+use anyhow::{Result, anyhow, bail};
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use lapce_rpc::buffer::FileEncoding;
+
+/// How much of the start of a file is looked at to tell UTF-16 without a BOM
+const SNIFF_LEN: usize = 4096;
+
+/// Decodes the content of a file with `encoding`, or else with the encoding
+/// detected from its bytes, returning the text without the BOM and the
+/// encoding to save it with.
+///
+/// The detection goes by the BOM, then UTF-16 if every other byte is zero,
+/// then UTF-8 if the bytes are valid UTF-8. Other bytes with zeros are taken
+/// as binary and refused, and the rest is decoded with the legacy encoding
+/// chardetng guesses, like GBK or Shift_JIS. Bytes that aren't valid in that
+/// encoding are decoded as windows-1252, which maps every byte and so saves
+/// back unchanged until the file is reopened with its actual encoding.
+pub fn decode(
+    bytes: &[u8],
+    encoding: Option<&FileEncoding>,
+) -> Result<(String, FileEncoding)> {
+    if let Some(encoding) = encoding {
+        let (text, encoding, _) = decode_with(bytes, encoding)?;
+        return Ok((text, encoding));
+    }
+
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return Ok((text.into_owned(), FileEncoding::new(encoding.name(), true)));
+    }
+    // ASCII in UTF-16 is valid UTF-8 too
+    if let Some(encoding) = sniff_utf16(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(bytes);
+        return Ok((text.into_owned(), FileEncoding::new(encoding.name(), false)));
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Ok((text.to_string(), FileEncoding::default()));
+    }
+    if bytes.contains(&0) {
+        bail!("binary file");
+    }
+    let mut detector = EncodingDetector::new(Iso2022JpDetection::Deny);
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, Utf8Detection::Deny);
+    if encoding != WINDOWS_1252 {
+        let (text, had_errors) = encoding.decode_without_bom_handling(bytes);
+        if !had_errors {
+            return Ok((
+                text.into_owned(),
+                FileEncoding::new(encoding.name(), false),
+            ));
+        }
+    }
+    let (text, _) = WINDOWS_1252.decode_without_bom_handling(bytes);
+    Ok((
+        text.into_owned(),
+        FileEncoding::new(WINDOWS_1252.name(), false),
+    ))
+}
+
+/// Decodes the content of a file with `encoding`, returning the text without
+/// the BOM, the encoding to save it with, and whether some bytes weren't
+/// valid in it and were replaced, which saving would lose.
+pub fn decode_with(
+    bytes: &[u8],
+    encoding: &FileEncoding,
+) -> Result<(String, FileEncoding, bool)> {
+    let encoding = for_name(&encoding.name)?;
+    let (bom, content) = match Encoding::for_bom(bytes) {
+        Some((found, bom_len)) if found == encoding => (true, &bytes[bom_len..]),
+        _ => (false, bytes),
+    };
+    let (text, lossy) = encoding.decode_without_bom_handling(content);
+    Ok((
+        text.into_owned(),
+        FileEncoding::new(encoding.name(), bom),
+        lossy,
+    ))
+}
+
+/// Encodes the text of a buffer, failing rather than writing a file with
+/// characters the encoding can't represent.
+pub fn encode(text: &str, encoding: &FileEncoding) -> Result<Vec<u8>> {
+    let name = &encoding.name;
+    let bom = encoding.bom;
+    let encoding = for_name(name)?;
+    let mut bytes = Vec::with_capacity(text.len() + 3);
+    // the encoder of the standard only writes UTF-8 for UTF-16
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let units = bom.then_some(0xfeff).into_iter().chain(text.encode_utf16());
+        for unit in units {
+            if encoding == UTF_16LE {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            } else {
+                bytes.extend_from_slice(&unit.to_be_bytes());
+            }
+        }
+        return Ok(bytes);
+    }
+
+    if bom && encoding == UTF_8 {
+        bytes.extend_from_slice(b"\xef\xbb\xbf");
+    }
+    let (encoded, _, unmappable) = encoding.encode(text);
+    if unmappable {
+        bail!("the text has characters that {name} can't encode");
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+fn for_name(name: &str) -> Result<&'static Encoding> {
+    Encoding::for_label(name.as_bytes())
+        .ok_or_else(|| anyhow!("unknown encoding {name}"))
+}
+
+/// UTF-16 without a BOM, when mostly ASCII, has a zero byte in every pair,
+/// always on the same side.
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    if bytes.len() < 2 || bytes.len() % 2 != 0 {
+        return None;
+    }
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN)];
+    let pairs = sample.len() / 2;
+    let (mut even_zeros, mut odd_zeros) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        even_zeros += usize::from(pair[0] == 0);
+        odd_zeros += usize::from(pair[1] == 0);
+    }
+    if even_zeros == 0 && odd_zeros * 2 > pairs {
+        Some(UTF_16LE)
+    } else if odd_zeros == 0 && even_zeros * 2 > pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(bytes: &[u8]) -> (String, String) {
+        let (text, encoding) = decode(bytes, None).unwrap();
+        (text, encoding.to_string())
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(detect(b"caf\xc3\xa9"), ("café".into(), "UTF-8".into()));
+        assert_eq!(
+            detect(b"\xef\xbb\xbfcaf\xc3\xa9"),
+            ("café".into(), "UTF-8 with BOM".into())
+        );
+        assert_eq!(
+            detect(b"\xff\xfec\x00a\x00f\x00\xe9\x00"),
+            ("café".into(), "UTF-16LE".into())
+        );
+        assert_eq!(
+            detect(b"\x00c\x00a\x00f\x00\xe9"),
+            ("café".into(), "UTF-16BE".into())
+        );
+        assert_eq!(detect(b"caf\xe9"), ("café".into(), "windows-1252".into()));
+        assert_eq!(
+            detect(b"\xc4\xe3\xba\xc3\xa3\xac\xca\xc0\xbd\xe7"),
+            ("你好，世界".into(), "GBK".into())
+        );
+        assert_eq!(
+            detect(
+                b"\x93\xfa\x96\x7b\x8c\xea\x82\xcc\x83\x65\x83\x4c\x83\x58\x83\x67\x82\xc5\x82\xb7\x81\x42"
+            ),
+            ("日本語のテキストです。".into(), "Shift_JIS".into())
+        );
+        assert!(decode(b"\x7fELF\x02\x01\x00\x00\xff", None).is_err());
+
+        let gbk = FileEncoding::new("GBK", false);
+        let (text, encoding) = decode(b"\xc4\xe3\xba\xc3", Some(&gbk)).unwrap();
+        assert_eq!(text, "你好");
+        assert_eq!(encoding, gbk);
+
+        let utf8 = FileEncoding::default();
+        let (_, _, lossy) = decode_with(b"\xc4\xe3\xba\xc3", &utf8).unwrap();
+        assert!(lossy);
+        let (_, _, lossy) = decode_with(b"caf\xc3\xa9", &utf8).unwrap();
+        assert!(!lossy);
+    }
+
+    #[test]
+    fn test_encode() {
+        let roundtrip = |bytes: &[u8]| {
+            let (text, encoding) = decode(bytes, None).unwrap();
+            assert_eq!(encode(&text, &encoding).unwrap(), bytes);
+        };
+        roundtrip(b"\xef\xbb\xbfa\r\nb");
+        roundtrip(b"\xfe\xff\x00a\x00\n");
+        roundtrip(b"\x81\x8d\x8f\x90\x9d\xe9");
+
+        let shift_jis = FileEncoding::new("Shift_JIS", false);
+        assert_eq!(encode("日本", &shift_jis).unwrap(), b"\x93\xfa\x96\x7b");
+        assert!(encode("😀", &shift_jis).is_err());
+    }
+}
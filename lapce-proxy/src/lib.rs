@@ -3,9 +3,12 @@
 pub mod buffer;
 pub mod cli;
 pub mod dispatch;
+pub mod encoding;
 pub mod plugin;
 pub mod rust_build;
 pub mod rust_module_resolve;
+pub mod save;
+pub mod shell_integration;
 pub mod terminal;
 pub mod watcher;
 
@@ -46,6 +46,8 @@ use crate::plugin::{
 
 pub struct PluginCatalog {
     workspace:             Option<PathBuf>,
+    /// Every root folder of the workspace, `workspace` being the first
+    folders:               Vec<PathBuf>,
     plugin_rpc:            PluginCatalogRpcHandler,
     plugins:               HashMap<PluginId, PluginServerRpcHandler>,
     daps:                  HashMap<DapId, DapRpcHandler>,
@@ -60,6 +62,7 @@ impl PluginCatalog {
     pub fn new(
         _id: u64,
         workspace: Option<PathBuf>,
+        folders: Vec<PathBuf>,
         _disabled_volts: Vec<VoltID>,
         _extra_plugin_paths: Vec<PathBuf>,
         plugin_configurations: HashMap<String, HashMap<String, serde_json::Value>>,
@@ -69,6 +72,7 @@ impl PluginCatalog {
         let _plugin_dir = directory.plugins_directory.clone();
         let plugin = Self {
             workspace,
+            folders,
             plugin_rpc: plugin_rpc.clone(),
             plugin_configurations,
             plugins: HashMap::new(),
@@ -237,6 +241,7 @@ impl PluginCatalog {
     ) {
         for id in to_be_activated.iter() {
             let workspace = self.workspace.clone();
+            let folders = self.folders.clone();
             if let Some(meta) = self.unactivated_volts.remove(id) {
                 let configurations =
                     self.plugin_configurations.get(&meta.name).cloned();
@@ -245,6 +250,7 @@ impl PluginCatalog {
                 thread::spawn(move || {
                     if let Err(err) = start_volt(
                         workspace,
+                        folders,
                         configurations,
                         plugin_rpc,
                         meta,
@@ -276,7 +282,7 @@ impl PluginCatalog {
                     return Some(id.clone());
                 }
 
-                if let Some(workspace) = self.workspace.as_ref() {
+                if !self.folders.is_empty() {
                     if let Some(globs) = meta
                         .activation
                         .as_ref()
@@ -296,9 +302,12 @@ impl PluginCatalog {
                         match builder.build() {
                             Ok(matcher) => {
                                 if !matcher.is_empty() {
-                                    for entry in walkdir::WalkDir::new(workspace)
-                                        .into_iter()
-                                        .flatten()
+                                    for entry in
+                                        self.folders.iter().flat_map(|folder| {
+                                            walkdir::WalkDir::new(folder)
+                                                .into_iter()
+                                                .flatten()
+                                        })
                                     {
                                         if matcher.is_match(entry.path()) {
                                             return Some(id.clone());
@@ -526,6 +535,74 @@ impl PluginCatalog {
         }
     }
 
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        context: Option<String>,
+        f: Box<dyn RpcCallback<dap_types::EvaluateResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.evaluate_async(expression, frame_id, context, f);
+        } else {
+            f.call(
+                Id::Num(0),
+                Err(RpcError {
+                    code:    0,
+                    message: "plugin doesn't exist".to_string(),
+                }),
+            );
+        }
+    }
+
+    pub fn dap_disassemble(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: usize,
+        f: Box<dyn RpcCallback<dap_types::DisassembleResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.disassemble_async(
+                memory_reference,
+                instruction_offset,
+                instruction_count,
+                f,
+            );
+        } else {
+            f.call(
+                Id::Num(0),
+                Err(RpcError {
+                    code:    0,
+                    message: "plugin doesn't exist".to_string(),
+                }),
+            );
+        }
+    }
+
+    pub fn dap_read_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: Box<dyn RpcCallback<dap_types::ReadMemoryResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.read_memory_async(memory_reference, offset, count, f);
+        } else {
+            f.call(
+                Id::Num(0),
+                Err(RpcError {
+                    code:    0,
+                    message: "plugin doesn't exist".to_string(),
+                }),
+            );
+        }
+    }
+
     pub async fn handle_notification(
         &mut self,
         notification: PluginCatalogNotification,
@@ -585,6 +662,7 @@ impl PluginCatalog {
             },
             InstallVolt(volt, id) => {
                 let workspace = self.workspace.clone();
+                let folders = self.folders.clone();
                 let configurations =
                     self.plugin_configurations.get(&volt.name).cloned();
                 let catalog_rpc = self.plugin_rpc.clone();
@@ -594,6 +672,7 @@ impl PluginCatalog {
                     if let Err(err) = install_volt(
                         catalog_rpc,
                         workspace,
+                        folders,
                         configurations,
                         volt,
                         id,
@@ -731,6 +810,11 @@ impl PluginCatalog {
                     dap.step_out(thread_id);
                 }
             },
+            DapStepInstruction { dap_id, thread_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    dap.next_instruction(thread_id);
+                }
+            },
             DapStopByUser { dap_id } => {
                 if let Some(dap) = self.daps.remove(&dap_id) {
                     debug!("DapStop {dap_id:?}");
@@ -778,6 +862,15 @@ impl PluginCatalog {
                     );
                 }
             },
+            DapSetExceptionBreakpoints {
+                dap_id,
+                filters,
+                break_on_panic,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    dap.exception_breakpoints(filters, break_on_panic);
+                }
+            },
         }
     }
 
@@ -809,8 +902,10 @@ impl PluginCatalog {
                             log::error!("plugin_rpc.dap_loaded {:?}", err);
                         }
 
-                        if let Err(err) = dap_rpc.launch(&config) {
-                            log::error!("dap_rpc.launch {:?}", err);
+                        if let Err(err) =
+                            dap_rpc.start_session(&config, &plugin_rpc.core_rpc)
+                        {
+                            log::error!("dap_rpc.start_session {:?}", err);
                         }
                     },
                     Err(err) => {
@@ -15,17 +15,22 @@ use crossbeam_channel::{Receiver, Sender};
 use jsonrpc_lite::Id;
 use lapce_rpc::{
     RpcError,
+    core::CoreRpcHandler,
     dap_types::{
-        self, ConfigurationDone, Continue, ContinueArguments, DapEvent, DapId,
-        DapPayload, DapRequest, DapResponse, DapServer, DebuggerCapabilities,
-        Disconnect, Initialize, Launch, Next, NextArguments, Pause, PauseArguments,
-        Request, RunDebugConfig, RunInTerminal, RunInTerminalArguments,
-        RunInTerminalResponse, Scope, Scopes, ScopesArguments, ScopesResponse,
-        SetBreakpoints, SetBreakpointsArguments, SetBreakpointsResponse, Source,
-        SourceBreakpoint, StackTrace, StackTraceArguments, StackTraceResponse,
-        StepIn, StepInArguments, StepOut, StepOutArguments, Terminate, ThreadId,
-        Threads, ThreadsResponse, Variable, Variables, VariablesArguments,
-        VariablesResponse,
+        self, Attach, ConfigurationDone, Continue, ContinueArguments, DapEvent,
+        DapId, DapPayload, DapRequest, DapResponse, DapServer, DebuggerCapabilities,
+        Disassemble, DisassembleArguments, DisassembleResponse, Disconnect,
+        Evaluate, EvaluateArguments, EvaluateResponse, FunctionBreakpoint,
+        Initialize, Launch, Next, NextArguments, Pause, PauseArguments, ReadMemory,
+        ReadMemoryArguments, ReadMemoryResponse, Request, RunDebugConfig,
+        RunInTerminal, RunInTerminalArguments, RunInTerminalResponse, Scope, Scopes,
+        ScopesArguments, ScopesResponse, SetBreakpoints, SetBreakpointsArguments,
+        SetBreakpointsResponse, SetExceptionBreakpoints,
+        SetExceptionBreakpointsArguments, SetFunctionBreakpoints,
+        SetFunctionBreakpointsArguments, Source, SourceBreakpoint, StackTrace,
+        StackTraceArguments, StackTraceResponse, StepIn, StepInArguments, StepOut,
+        StepOutArguments, Terminate, ThreadId, Threads, ThreadsResponse, Variable,
+        Variables, VariablesArguments, VariablesResponse,
     },
     terminal::TermId,
 };
@@ -57,6 +62,8 @@ pub struct DapClient {
     disconnected:       bool,
     restarted:          bool,
     status:             DapStatus,
+    /// Whether the adapter has the breakpoint on panics of ours
+    panic_breakpoint:   bool,
 }
 
 impl DapClient {
@@ -80,6 +87,7 @@ impl DapClient {
             disconnected: false,
             restarted: false,
             status: DapStatus::Normal,
+            panic_breakpoint: false,
         })
     }
 
@@ -249,6 +257,21 @@ impl DapClient {
                             }
                         },)
                 }
+                let filters = self
+                    .capabilities
+                    .as_ref()
+                    .map(|capabilities| {
+                        self.config.exception_filters.clone().unwrap_or_else(|| {
+                            capabilities.default_exception_filters()
+                        })
+                    })
+                    .unwrap_or_default();
+                // a new session has no function breakpoints yet
+                self.panic_breakpoint = false;
+                self.send_exception_breakpoints(
+                    filters,
+                    Some(self.config.break_on_panic),
+                );
                 // send dap configurations here
                 self.dap_rpc
                     .request_async::<ConfigurationDone>((), |_, rs| {
@@ -370,7 +393,7 @@ impl DapClient {
             supports_variable_type: Some(true),
             supports_variable_paging: Some(false),
             supports_run_in_terminal_request: Some(true),
-            supports_memory_references: Some(false),
+            supports_memory_references: Some(true),
             supports_progress_reporting: Some(false),
             supports_invalidated_event: Some(false),
         };
@@ -379,6 +402,9 @@ impl DapClient {
             .dap_rpc
             .request::<Initialize>(params)
             .map_err(|e| anyhow!(e.message))?;
+        self.plugin_rpc
+            .core_rpc
+            .dap_capabilities(self.dap_rpc.dap_id, resp.clone());
         self.capabilities = Some(resp);
 
         Ok(())
@@ -386,7 +412,10 @@ impl DapClient {
 
     fn _stop_by_other(&mut self) {
         let dap_rpc = self.dap_rpc.clone();
-        if self
+        // terminating an attached session would kill a process we didn't start
+        if self.config.is_attach() {
+            dap_rpc.disconnect();
+        } else if self
             .capabilities
             .as_ref()
             .and_then(|c| c.supports_terminate_request)
@@ -426,8 +455,9 @@ impl DapClient {
 
         let dap_rpc = self.dap_rpc.clone();
         let config = self.config.clone();
+        let core_rpc = self.plugin_rpc.core_rpc.clone();
         thread::spawn(move || {
-            if let Err(err) = dap_rpc.launch(&config) {
+            if let Err(err) = dap_rpc.start_session(&config, &core_rpc) {
                 error!("{:?}", err);
             }
         });
@@ -435,6 +465,52 @@ impl DapClient {
         Ok(())
     }
 
+    /// Sends the exception filters and the breakpoint on panics, each only if
+    /// the adapter can take it. An empty list of function breakpoints clears
+    /// all of them, so it is only sent to drop the panic breakpoint we set.
+    fn send_exception_breakpoints(
+        &mut self,
+        filters: Vec<String>,
+        break_on_panic: Option<bool>,
+    ) {
+        let Some(capabilities) = self.capabilities.as_ref() else {
+            return;
+        };
+        if !capabilities.exception_filters().is_empty() {
+            self.dap_rpc.set_exception_breakpoints(filters);
+        }
+        let Some(break_on_panic) = break_on_panic else {
+            return;
+        };
+        if capabilities
+            .supports_function_breakpoints
+            .unwrap_or_default()
+            && break_on_panic != self.panic_breakpoint
+        {
+            let breakpoints = if break_on_panic {
+                vec![FunctionBreakpoint::rust_panic()]
+            } else {
+                Vec::new()
+            };
+            self.dap_rpc.set_function_breakpoints(breakpoints);
+            self.panic_breakpoint = break_on_panic;
+        }
+    }
+
+    /// Changes the exception breakpoints of the session, and of the sessions
+    /// it restarts into.
+    fn update_exception_breakpoints(
+        &mut self,
+        filters: Vec<String>,
+        break_on_panic: Option<bool>,
+    ) {
+        self.config.exception_filters = Some(filters.clone());
+        if let Some(break_on_panic) = break_on_panic {
+            self.config.break_on_panic = break_on_panic;
+        }
+        self.send_exception_breakpoints(filters, break_on_panic);
+    }
+
     fn restart(&mut self, breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>) {
         self.restarted = true;
         self.breakpoints = breakpoints;
@@ -477,6 +553,11 @@ pub enum DapRpc {
     EventFromDap(DapEvent),
     TerminateByUser,
     Restart(HashMap<PathBuf, Vec<SourceBreakpoint>>),
+    ExceptionBreakpoints {
+        filters:        Vec<String>,
+        break_on_panic: Option<bool>,
+    },
+    Attached(TermId),
     // Shutdown,
     DisconnectedByError,
 }
@@ -556,6 +637,15 @@ impl DapRpcHandler {
                 DapRpc::Restart(breakpoints) => {
                     dap_client.restart(breakpoints);
                 },
+                DapRpc::ExceptionBreakpoints {
+                    filters,
+                    break_on_panic,
+                } => {
+                    dap_client.update_exception_breakpoints(filters, break_on_panic);
+                },
+                DapRpc::Attached(term_id) => {
+                    dap_client.term_id = Some(term_id);
+                },
             }
             if dap_client.terminated {
                 break;
@@ -699,6 +789,47 @@ impl DapRpcHandler {
         Ok(())
     }
 
+    /// Attach to `config.pid`. The app still hosts the session in a terminal
+    /// so that it can be stopped and restarted like a launched program.
+    pub fn attach(
+        &self,
+        config: &RunDebugConfig,
+        core_rpc: &CoreRpcHandler,
+    ) -> Result<()> {
+        let pid = config
+            .pid
+            .ok_or_else(|| anyhow!("no process to attach to"))?;
+        core_rpc.dap_run_in_terminal(config.clone());
+        let (term_id, _) = self.termain_process_rx.recv()?;
+        if let Err(err) = self.rpc_tx.send(DapRpc::Attached(term_id)) {
+            error!("{:?}", err);
+        }
+
+        let mut params = serde_json::json!({
+            "pid": pid,
+            "cwd": config.cwd,
+        });
+        if !config.program.is_empty() {
+            params["program"] = config.program.clone().into();
+        }
+        let _resp = self
+            .request::<Attach>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    pub fn start_session(
+        &self,
+        config: &RunDebugConfig,
+        core_rpc: &CoreRpcHandler,
+    ) -> Result<()> {
+        if config.is_attach() {
+            self.attach(config, core_rpc)
+        } else {
+            self.launch(config)
+        }
+    }
+
     pub fn stop(&self) {
         if let Err(err) = self.rpc_tx.send(DapRpc::TerminateByUser) {
             error!("{:?}", err);
@@ -711,6 +842,21 @@ impl DapRpcHandler {
         }
     }
 
+    /// Sets the exception breakpoints on the thread of the client, which
+    /// knows what the adapter supports.
+    pub fn exception_breakpoints(
+        &self,
+        filters: Vec<String>,
+        break_on_panic: Option<bool>,
+    ) {
+        if let Err(err) = self.rpc_tx.send(DapRpc::ExceptionBreakpoints {
+            filters,
+            break_on_panic,
+        }) {
+            error!("{:?}", err);
+        }
+    }
+
     fn disconnected(&self) {
         if let Err(err) = self.rpc_tx.send(DapRpc::DisconnectedByError) {
             error!("{:?}", err);
@@ -756,6 +902,24 @@ impl DapRpcHandler {
         self.request_async::<SetBreakpoints>(params, f);
     }
 
+    pub fn set_exception_breakpoints(&self, filters: Vec<String>) {
+        let params = SetExceptionBreakpointsArguments { filters };
+        self.request_async::<SetExceptionBreakpoints>(params, |id, resp| {
+            if let Err(err) = resp {
+                error!("id={id:?} set_exception_breakpoints {err:?}");
+            }
+        });
+    }
+
+    pub fn set_function_breakpoints(&self, breakpoints: Vec<FunctionBreakpoint>) {
+        let params = SetFunctionBreakpointsArguments { breakpoints };
+        self.request_async::<SetFunctionBreakpoints>(params, |id, resp| {
+            if let Err(err) = resp {
+                error!("id={id:?} set_function_breakpoints {err:?}");
+            }
+        });
+    }
+
     pub fn continue_thread(
         &self,
         thread_id: ThreadId,
@@ -848,6 +1012,57 @@ impl DapRpcHandler {
         self.request_async::<Variables>(args, f);
     }
 
+    pub fn evaluate_async(
+        &self,
+        expression: String,
+        frame_id: Option<usize>,
+        context: Option<String>,
+        f: impl RpcCallback<EvaluateResponse, RpcError> + 'static,
+    ) {
+        let args = EvaluateArguments {
+            expression,
+            frame_id,
+            context,
+            format: None,
+        };
+
+        self.request_async::<Evaluate>(args, f);
+    }
+
+    pub fn disassemble_async(
+        &self,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: usize,
+        f: impl RpcCallback<DisassembleResponse, RpcError> + 'static,
+    ) {
+        let args = DisassembleArguments {
+            memory_reference,
+            offset: None,
+            instruction_offset: Some(instruction_offset),
+            instruction_count,
+            resolve_symbols: Some(true),
+        };
+
+        self.request_async::<Disassemble>(args, f);
+    }
+
+    pub fn read_memory_async(
+        &self,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: impl RpcCallback<ReadMemoryResponse, RpcError> + 'static,
+    ) {
+        let args = ReadMemoryArguments {
+            memory_reference,
+            offset: Some(offset),
+            count,
+        };
+
+        self.request_async::<ReadMemory>(args, f);
+    }
+
     pub fn next(&self, thread_id: ThreadId) {
         let args = NextArguments {
             thread_id,
@@ -857,6 +1072,16 @@ impl DapRpcHandler {
         self.request_async::<Next>(args, move |_, _| {});
     }
 
+    /// Step over a single instruction rather than a source line.
+    pub fn next_instruction(&self, thread_id: ThreadId) {
+        let args = NextArguments {
+            thread_id,
+            granularity: Some("instruction".to_string()),
+        };
+
+        self.request_async::<Next>(args, move |_, _| {});
+    }
+
     pub fn step_in(&self, thread_id: ThreadId) {
         let args = StepInArguments {
             thread_id,
@@ -31,8 +31,9 @@ use super::{
     psp::{
         PluginHandlerNotification, PluginHostHandler, PluginServerHandler,
         PluginServerRpcHandler, ResponseSender, RpcCallback,
-        handle_plugin_server_message,
+        handle_plugin_server_message, has_type_hierarchy_provider,
     },
+    workspace_folders,
 };
 use crate::{
     buffer::Buffer,
@@ -76,6 +77,10 @@ impl PluginServerHandler for LspClient {
         self.host.method_registered(method)
     }
 
+    fn request_registered(&mut self, method: &str, params: &Params) -> bool {
+        self.host.request_registered(method, params)
+    }
+
     fn document_supported(
         &mut self,
         lanaguage_id: Option<&str>,
@@ -93,8 +98,9 @@ impl PluginServerHandler for LspClient {
             Initialize(id) => {
                 self.initialize(id);
             },
-            InitializeResult(result) => {
+            InitializeResult(result, type_hierarchy) => {
                 self.host.server_capabilities = result.capabilities;
+                self.host.type_hierarchy = type_hierarchy;
                 self.server_rpc.server_notification(
                     Initialized::METHOD,
                     InitializedParams {},
@@ -195,6 +201,7 @@ impl LspClient {
         plugin_rpc: PluginCatalogRpcHandler,
         document_selector: DocumentSelector,
         workspace: Option<PathBuf>,
+        folders: Vec<PathBuf>,
         volt_id: VoltID,
         volt_display_name: String,
         spawned_by: Option<PluginId>,
@@ -203,6 +210,7 @@ impl LspClient {
         server_uri: Url,
         args: Vec<String>,
         options: Option<Value>,
+        configuration: Option<Value>,
         id: u64,
     ) -> Result<Self> {
         let server = match server_uri.scheme() {
@@ -337,8 +345,9 @@ impl LspClient {
             }
         });
 
-        let host = PluginHostHandler::new(
+        let mut host = PluginHostHandler::new(
             workspace.clone(),
+            folders,
             pwd,
             volt_id,
             volt_display_name,
@@ -347,6 +356,7 @@ impl LspClient {
             server_rpc.clone(),
             plugin_rpc.clone(),
         );
+        host.configuration = configuration;
 
         Ok(Self {
             plugin_rpc,
@@ -363,6 +373,7 @@ impl LspClient {
         plugin_rpc: PluginCatalogRpcHandler,
         document_selector: DocumentSelector,
         workspace: Option<PathBuf>,
+        folders: Vec<PathBuf>,
         volt_id: VoltID,
         volt_display_name: String,
         spawned_by: Option<PluginId>,
@@ -371,12 +382,14 @@ impl LspClient {
         server_uri: Url,
         args: Vec<String>,
         options: Option<Value>,
+        configuration: Option<Value>,
         id: u64,
     ) -> Result<PluginId> {
         let mut lsp = Self::new(
             plugin_rpc,
             document_selector,
             workspace,
+            folders,
             volt_id,
             volt_display_name,
             spawned_by,
@@ -385,6 +398,7 @@ impl LspClient {
             server_uri,
             args,
             options,
+            configuration,
             id,
         )?;
         let plugin_id = lsp.server_rpc.plugin_id;
@@ -402,26 +416,22 @@ impl LspClient {
             .workspace
             .clone()
             .map(|p| Url::from_directory_path(p).unwrap());
+        let workspace_folders = workspace_folders(&self.host.folders);
         log::debug!("initialization_options {:?}", self.options);
         #[allow(deprecated)]
         let params = InitializeParams {
-            process_id:                Some(process::id()),
-            root_uri:                  root_uri.clone(),
-            initialization_options:    self.options.clone(),
-            capabilities:              client_capabilities(),
-            trace:                     Some(TraceValue::Verbose),
-            workspace_folders:         root_uri.map(|uri| {
-                vec![WorkspaceFolder {
-                    name: uri.as_str().to_string(),
-                    uri,
-                }]
-            }),
-            client_info:               Some(ClientInfo {
+            process_id: Some(process::id()),
+            root_uri,
+            initialization_options: self.options.clone(),
+            capabilities: client_capabilities(),
+            trace: Some(TraceValue::Verbose),
+            workspace_folders,
+            client_info: Some(ClientInfo {
                 name:    meta::NAME.to_owned(),
                 version: Some(meta::VERSION.to_owned()),
             }),
-            locale:                    None,
-            root_path:                 None,
+            locale: None,
+            root_path: None,
             work_done_progress_params: WorkDoneProgressParams::default(),
         };
         let server_rpc = self.server_rpc.clone();
@@ -435,11 +445,15 @@ impl LspClient {
             move |_id, result| match result {
                 Ok(value) => {
                     log::debug!("{}", serde_json::to_string(&value).unwrap());
+                    let type_hierarchy = has_type_hierarchy_provider(&value);
                     let result: InitializeResult =
                         serde_json::from_value(value).unwrap();
 
                     server_rpc.handle_rpc(PluginServerRpc::Handler(
-                        PluginHandlerNotification::InitializeResult(result),
+                        PluginHandlerNotification::InitializeResult(
+                            result,
+                            type_hierarchy,
+                        ),
                     ));
                 },
                 Err(err) => {
@@ -33,21 +33,24 @@ use lapce_xi_rope::{Rope, RopeDelta};
 use log::error;
 use lsp_types::{
     CallHierarchyClientCapabilities, CallHierarchyIncomingCall,
-    CallHierarchyIncomingCallsParams, CallHierarchyItem, CallHierarchyPrepareParams,
+    CallHierarchyIncomingCallsParams, CallHierarchyItem, CallHierarchyOutgoingCall,
+    CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
     ClientCapabilities, CodeAction, CodeActionCapabilityResolveSupport,
     CodeActionClientCapabilities, CodeActionContext, CodeActionKind,
     CodeActionKindLiteralSupport, CodeActionLiteralSupport, CodeActionParams,
-    CodeActionResponse, CodeLens, CodeLensParams, CompletionClientCapabilities,
+    CodeActionResponse, CodeLens, CodeLensParams,
+    CodeLensWorkspaceClientCapabilities, CompletionClientCapabilities,
     CompletionItem, CompletionItemCapability,
     CompletionItemCapabilityResolveSupport, CompletionParams, CompletionResponse,
     Diagnostic, DocumentFormattingParams, DocumentHighlight,
     DocumentHighlightClientCapabilities, DocumentHighlightParams,
+    DocumentOnTypeFormattingParams, DocumentRangeFormattingParams,
     DocumentSymbolClientCapabilities, DocumentSymbolParams, DocumentSymbolResponse,
-    FoldingRange, FoldingRangeClientCapabilities, FoldingRangeParams,
-    FormattingOptions, GotoCapability, GotoDefinitionParams, GotoDefinitionResponse,
-    Hover, HoverClientCapabilities, HoverParams, InlayHint,
-    InlayHintClientCapabilities, InlayHintParams,
-    InlineCompletionClientCapabilities, InlineCompletionParams,
+    ExecuteCommandClientCapabilities, ExecuteCommandParams, FoldingRange,
+    FoldingRangeClientCapabilities, FoldingRangeParams, FormattingOptions,
+    GotoCapability, GotoDefinitionParams, GotoDefinitionResponse, Hover,
+    HoverClientCapabilities, HoverParams, InlayHint, InlayHintClientCapabilities,
+    InlayHintParams, InlineCompletionClientCapabilities, InlineCompletionParams,
     InlineCompletionResponse, InlineCompletionTriggerKind, Location, MarkupKind,
     MessageActionItemCapabilities, ParameterInformationSettings,
     PartialResultParams, Position, PrepareRenameResponse,
@@ -55,25 +58,30 @@ use lsp_types::{
     RenameParams, SelectionRange, SelectionRangeParams, SemanticTokens,
     SemanticTokensClientCapabilities, SemanticTokensClientCapabilitiesRequests,
     SemanticTokensDeltaParams, SemanticTokensFullDeltaResult,
-    SemanticTokensFullOptions, SemanticTokensParams,
+    SemanticTokensFullOptions, SemanticTokensParams, ShowDocumentClientCapabilities,
     ShowMessageRequestClientCapabilities, SignatureHelp,
     SignatureHelpClientCapabilities, SignatureHelpParams,
     SignatureInformationSettings, SymbolInformation, TextDocumentClientCapabilities,
     TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams,
-    TextDocumentSyncClientCapabilities, TextEdit, Url,
-    VersionedTextDocumentIdentifier, WindowClientCapabilities,
-    WorkDoneProgressParams, WorkspaceClientCapabilities, WorkspaceEdit,
-    WorkspaceSymbolClientCapabilities, WorkspaceSymbolParams,
+    TextDocumentSyncClientCapabilities, TextEdit, TypeHierarchyClientCapabilities,
+    TypeHierarchyItem, TypeHierarchyPrepareParams, TypeHierarchySubtypesParams,
+    TypeHierarchySupertypesParams, Url, VersionedTextDocumentIdentifier,
+    WindowClientCapabilities, WorkDoneProgressParams, WorkspaceClientCapabilities,
+    WorkspaceEdit, WorkspaceFolder, WorkspaceSymbolClientCapabilities,
+    WorkspaceSymbolParams,
     request::{
-        CallHierarchyIncomingCalls, CallHierarchyPrepare, CodeActionRequest,
-        CodeActionResolveRequest, CodeLensRequest, CodeLensResolve, Completion,
-        DocumentHighlightRequest, DocumentSymbolRequest, FoldingRangeRequest,
-        Formatting, GotoDefinition, GotoImplementation, GotoImplementationResponse,
+        CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls,
+        CallHierarchyPrepare, CodeActionRequest, CodeActionResolveRequest,
+        CodeLensRequest, CodeLensResolve, Completion, DocumentHighlightRequest,
+        DocumentSymbolRequest, ExecuteCommand, FoldingRangeRequest, Formatting,
+        GotoDefinition, GotoImplementation, GotoImplementationResponse,
         GotoTypeDefinition, GotoTypeDefinitionParams, GotoTypeDefinitionResponse,
-        HoverRequest, InlayHintRequest, InlineCompletionRequest,
-        PrepareRenameRequest, References, Rename, Request, ResolveCompletionItem,
-        SelectionRangeRequest, SemanticTokensFullDeltaRequest,
-        SemanticTokensFullRequest, SignatureHelpRequest, WorkspaceSymbolRequest,
+        HoverRequest, InlayHintRequest, InlineCompletionRequest, OnTypeFormatting,
+        PrepareRenameRequest, RangeFormatting, References, Rename, Request,
+        ResolveCompletionItem, SelectionRangeRequest,
+        SemanticTokensFullDeltaRequest, SemanticTokensFullRequest,
+        SignatureHelpRequest, TypeHierarchyPrepare, TypeHierarchySubtypes,
+        TypeHierarchySupertypes, WorkspaceSymbolRequest,
     },
 };
 use parking_lot::Mutex;
@@ -134,6 +142,27 @@ pub enum PluginCatalogRpc {
                 >,
         >,
     },
+    DapEvaluate {
+        dap_id:     DapId,
+        expression: String,
+        frame_id:   Option<usize>,
+        context:    Option<String>,
+        f:          Box<dyn RpcCallback<dap_types::EvaluateResponse, RpcError>>,
+    },
+    DapDisassemble {
+        dap_id:             DapId,
+        memory_reference:   String,
+        instruction_offset: i64,
+        instruction_count:  usize,
+        f: Box<dyn RpcCallback<dap_types::DisassembleResponse, RpcError>>,
+    },
+    DapReadMemory {
+        dap_id:           DapId,
+        memory_reference: String,
+        offset:           i64,
+        count:            usize,
+        f: Box<dyn RpcCallback<dap_types::ReadMemoryResponse, RpcError>>,
+    },
     DidOpenTextDocument {
         document: TextDocumentItem,
         id:       u64,
@@ -188,6 +217,10 @@ pub enum DapNotificationOfUser {
         dap_id:    DapId,
         thread_id: ThreadId,
     },
+    DapStepInstruction {
+        dap_id:    DapId,
+        thread_id: ThreadId,
+    },
     DapPause {
         dap_id:    DapId,
         thread_id: ThreadId,
@@ -207,6 +240,11 @@ pub enum DapNotificationOfUser {
         path:        PathBuf,
         breakpoints: Vec<SourceBreakpoint>,
     },
+    DapSetExceptionBreakpoints {
+        dap_id:         DapId,
+        filters:        Vec<String>,
+        break_on_panic: Option<bool>,
+    },
 }
 
 impl From<DapNotificationOfUser> for PluginCatalogNotification {
@@ -367,6 +405,45 @@ impl PluginCatalogRpcHandler {
                 } => {
                     plugin.dap_get_scopes(dap_id, frame_id, f);
                 },
+                PluginCatalogRpc::DapEvaluate {
+                    dap_id,
+                    expression,
+                    frame_id,
+                    context,
+                    f,
+                } => {
+                    plugin.dap_evaluate(dap_id, expression, frame_id, context, f);
+                },
+                PluginCatalogRpc::DapDisassemble {
+                    dap_id,
+                    memory_reference,
+                    instruction_offset,
+                    instruction_count,
+                    f,
+                } => {
+                    plugin.dap_disassemble(
+                        dap_id,
+                        memory_reference,
+                        instruction_offset,
+                        instruction_count,
+                        f,
+                    );
+                },
+                PluginCatalogRpc::DapReadMemory {
+                    dap_id,
+                    memory_reference,
+                    offset,
+                    count,
+                    f,
+                } => {
+                    plugin.dap_read_memory(
+                        dap_id,
+                        memory_reference,
+                        offset,
+                        count,
+                        f,
+                    );
+                },
                 PluginCatalogRpc::Shutdown => {
                     return;
                 },
@@ -666,6 +743,127 @@ impl PluginCatalogRpcHandler {
         );
     }
 
+    pub fn call_hierarchy_outgoing(
+        &self,
+        path: &Path,
+        item: CallHierarchyItem,
+        cb: impl FnOnce(
+            PluginId,
+            Result<Option<Vec<CallHierarchyOutgoingCall>>, RpcError>,
+        ) + Clone
+        + Send
+        + 'static,
+        id: u64,
+    ) {
+        let method = CallHierarchyOutgoingCalls::METHOD;
+        let params = CallHierarchyOutgoingCallsParams {
+            item,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: Default::default(),
+        };
+
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            id,
+            cb,
+        );
+    }
+
+    pub fn show_type_hierarchy(
+        &self,
+        path: &Path,
+        position: Position,
+        cb: impl FnOnce(PluginId, Result<Option<Vec<TypeHierarchyItem>>, RpcError>)
+        + Clone
+        + Send
+        + 'static,
+        id: u64,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = TypeHierarchyPrepare::METHOD;
+        let params = TypeHierarchyPrepareParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params:     WorkDoneProgressParams::default(),
+        };
+
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            id,
+            cb,
+        );
+    }
+
+    pub fn type_hierarchy_supertypes(
+        &self,
+        path: &Path,
+        item: TypeHierarchyItem,
+        cb: impl FnOnce(PluginId, Result<Option<Vec<TypeHierarchyItem>>, RpcError>)
+        + Clone
+        + Send
+        + 'static,
+        id: u64,
+    ) {
+        let method = TypeHierarchySupertypes::METHOD;
+        let params = TypeHierarchySupertypesParams {
+            item,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: Default::default(),
+        };
+
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            id,
+            cb,
+        );
+    }
+
+    pub fn type_hierarchy_subtypes(
+        &self,
+        path: &Path,
+        item: TypeHierarchyItem,
+        cb: impl FnOnce(PluginId, Result<Option<Vec<TypeHierarchyItem>>, RpcError>)
+        + Clone
+        + Send
+        + 'static,
+        id: u64,
+    ) {
+        let method = TypeHierarchySubtypes::METHOD;
+        let params = TypeHierarchySubtypesParams {
+            item,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: Default::default(),
+        };
+
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            id,
+            cb,
+        );
+    }
+
     pub fn show_call_hierarchy(
         &self,
         path: &Path,
@@ -1045,11 +1243,37 @@ impl PluginCatalogRpcHandler {
         let method = Formatting::METHOD;
         let params = DocumentFormattingParams {
             text_document:             TextDocumentIdentifier { uri },
-            options:                   FormattingOptions {
-                tab_size: 4,
-                insert_spaces: true,
-                ..Default::default()
-            },
+            options:                   formatting_options(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            id,
+            cb,
+        );
+    }
+
+    pub fn get_document_range_formatting(
+        &self,
+        path: &Path,
+        range: Range,
+        cb: impl FnOnce(PluginId, Result<Vec<TextEdit>, RpcError>)
+        + Clone
+        + Send
+        + 'static,
+        id: u64,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = RangeFormatting::METHOD;
+        let params = DocumentRangeFormattingParams {
+            text_document: TextDocumentIdentifier { uri },
+            range,
+            options: formatting_options(),
             work_done_progress_params: WorkDoneProgressParams::default(),
         };
         let language_id =
@@ -1064,6 +1288,40 @@ impl PluginCatalogRpcHandler {
         );
     }
 
+    /// Only the servers with `ch` among their trigger characters are asked
+    pub fn get_on_type_formatting(
+        &self,
+        path: &Path,
+        position: Position,
+        ch: String,
+        cb: impl FnOnce(PluginId, Result<Option<Vec<TextEdit>>, RpcError>)
+        + Clone
+        + Send
+        + 'static,
+        id: u64,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = OnTypeFormatting::METHOD;
+        let params = DocumentOnTypeFormattingParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            ch,
+            options: formatting_options(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            id,
+            cb,
+        );
+    }
+
     pub fn prepare_rename(
         &self,
         path: &Path,
@@ -1412,6 +1670,33 @@ impl PluginCatalogRpcHandler {
         );
     }
 
+    pub fn execute_command(
+        &self,
+        plugin_id: PluginId,
+        command: String,
+        arguments: Vec<Value>,
+        cb: impl FnOnce(Result<Value, RpcError>) + Send + Clone + 'static,
+        id: u64,
+    ) {
+        let method = ExecuteCommand::METHOD;
+        let params = ExecuteCommandParams {
+            command,
+            arguments,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        self.send_request(
+            Some(plugin_id),
+            None,
+            method,
+            params,
+            None,
+            None,
+            true,
+            id,
+            move |_, _, result| cb(result),
+        );
+    }
+
     pub fn did_open_document(
         &self,
         path: &Path,
@@ -1588,6 +1873,16 @@ impl PluginCatalogRpcHandler {
         )
     }
 
+    pub fn dap_step_instruction(
+        &self,
+        dap_id: DapId,
+        thread_id: ThreadId,
+    ) -> Result<()> {
+        self.catalog_notification(
+            DapNotificationOfUser::DapStepInstruction { dap_id, thread_id }.into(),
+        )
+    }
+
     pub fn dap_stop(&self, dap_id: DapId) -> Result<()> {
         self.catalog_notification(
             DapNotificationOfUser::DapStopByUser { dap_id }.into(),
@@ -1634,6 +1929,22 @@ impl PluginCatalogRpcHandler {
         )
     }
 
+    pub fn dap_set_exception_breakpoints(
+        &self,
+        dap_id: DapId,
+        filters: Vec<String>,
+        break_on_panic: Option<bool>,
+    ) -> Result<()> {
+        self.catalog_notification(
+            DapNotificationOfUser::DapSetExceptionBreakpoints {
+                dap_id,
+                filters,
+                break_on_panic,
+            }
+            .into(),
+        )
+    }
+
     pub fn dap_variable(
         &self,
         dap_id: DapId,
@@ -1668,6 +1979,69 @@ impl PluginCatalogRpcHandler {
         }
     }
 
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        context: Option<String>,
+        f: impl FnOnce(Id, Result<dap_types::EvaluateResponse, RpcError>)
+        + Send
+        + 'static,
+    ) {
+        if let Err(err) = self.plugin_tx.send(PluginCatalogRpc::DapEvaluate {
+            dap_id,
+            expression,
+            frame_id,
+            context,
+            f: Box::new(f),
+        }) {
+            log::error!("{:?}", err);
+        }
+    }
+
+    pub fn dap_disassemble(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: usize,
+        f: impl FnOnce(Id, Result<dap_types::DisassembleResponse, RpcError>)
+        + Send
+        + 'static,
+    ) {
+        if let Err(err) = self.plugin_tx.send(PluginCatalogRpc::DapDisassemble {
+            dap_id,
+            memory_reference,
+            instruction_offset,
+            instruction_count,
+            f: Box::new(f),
+        }) {
+            log::error!("{:?}", err);
+        }
+    }
+
+    pub fn dap_read_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: impl FnOnce(Id, Result<dap_types::ReadMemoryResponse, RpcError>)
+        + Send
+        + 'static,
+    ) {
+        if let Err(err) = self.plugin_tx.send(PluginCatalogRpc::DapReadMemory {
+            dap_id,
+            memory_reference,
+            offset,
+            count,
+            f: Box::new(f),
+        }) {
+            log::error!("{:?}", err);
+        }
+    }
+
     pub fn register_debugger_type(
         &self,
         debugger_type: String,
@@ -1775,6 +2149,7 @@ pub async fn download_volt(
 pub async fn install_volt(
     catalog_rpc: PluginCatalogRpcHandler,
     workspace: Option<PathBuf>,
+    folders: Vec<PathBuf>,
     configurations: Option<HashMap<String, serde_json::Value>>,
     volt: VoltInfo,
     id: u64,
@@ -1790,9 +2165,14 @@ pub async fn install_volt(
     let local_catalog_rpc = catalog_rpc.clone();
     let local_meta = meta.clone();
 
-    if let Err(err) =
-        start_volt(workspace, configurations, local_catalog_rpc, local_meta, id)
-    {
+    if let Err(err) = start_volt(
+        workspace,
+        folders,
+        configurations,
+        local_catalog_rpc,
+        local_meta,
+        id,
+    ) {
         log::error!("{:?}", err);
     }
     let icon = volt_icon(&meta);
@@ -1838,6 +2218,34 @@ pub fn remove_volt(
     Ok(())
 }
 
+fn formatting_options() -> FormattingOptions {
+    FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        ..Default::default()
+    }
+}
+
+/// The `workspaceFolders` given to servers, named like the folders.
+fn workspace_folders(folders: &[PathBuf]) -> Option<Vec<WorkspaceFolder>> {
+    if folders.is_empty() {
+        return None;
+    }
+    Some(
+        folders
+            .iter()
+            .filter_map(|folder| {
+                let uri = Url::from_directory_path(folder).ok()?;
+                let name = folder
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| uri.to_string());
+                Some(WorkspaceFolder { uri, name })
+            })
+            .collect(),
+    )
+}
+
 fn client_capabilities() -> ClientCapabilities {
     // https://github.com/rust-lang/rust-analyzer/blob/master/docs/dev/lsp-extensions.md#server-status
     let mut experimental = Map::new();
@@ -1945,6 +2353,9 @@ fn client_capabilities() -> ClientCapabilities {
             call_hierarchy: Some(CallHierarchyClientCapabilities {
                 dynamic_registration: Some(true),
             }),
+            type_hierarchy: Some(TypeHierarchyClientCapabilities {
+                dynamic_registration: Some(true),
+            }),
             document_symbol: Some(DocumentSymbolClientCapabilities {
                 hierarchical_document_symbol_support: Some(true),
                 ..Default::default()
@@ -1965,14 +2376,22 @@ fn client_capabilities() -> ClientCapabilities {
                     additional_properties_support: Some(true),
                 }),
             }),
+            show_document: Some(ShowDocumentClientCapabilities { support: true }),
             ..Default::default()
         }),
         workspace: Some(WorkspaceClientCapabilities {
+            apply_edit: Some(true),
             symbol: Some(WorkspaceSymbolClientCapabilities {
                 ..Default::default()
             }),
-            configuration: Some(false),
+            execute_command: Some(ExecuteCommandClientCapabilities {
+                dynamic_registration: Some(false),
+            }),
+            configuration: Some(true),
             workspace_folders: Some(true),
+            code_lens: Some(CodeLensWorkspaceClientCapabilities {
+                refresh_support: Some(true),
+            }),
             ..Default::default()
         }),
         experimental: Some(experimental.into()),
@@ -14,23 +14,25 @@ use jsonrpc_lite::{Id, JsonRpc, Params};
 use lapce_core::{encoding::offset_utf16_to_utf8, rope_text_pos::RopeTextPosition};
 use lapce_rpc::{
     RpcError,
-    core::{CoreRpcHandler, ServerStatusParams},
+    core::{CoreResponse, CoreRpcHandler, ServerStatusParams},
     plugin::{PluginId, VoltID},
     style::{LineStyle, Style},
 };
 use lapce_xi_rope::{Rope, RopeDelta};
 use log::{debug, error};
 use lsp_types::{
-    CancelParams, CodeActionProviderCapability, DidChangeTextDocumentParams,
-    DidSaveTextDocumentParams, DocumentSelector, FoldingRangeProviderCapability,
-    HoverProviderCapability, ImplementationProviderCapability, InitializeResult,
-    LogMessageParams, MessageType, OneOf, ProgressParams, PublishDiagnosticsParams,
-    Range, Registration, RegistrationParams, SemanticTokens,
-    SemanticTokensFullOptions, SemanticTokensLegend,
-    SemanticTokensServerCapabilities, ServerCapabilities, ShowMessageParams,
-    TextDocumentContentChangeEvent, TextDocumentIdentifier,
-    TextDocumentSaveRegistrationOptions, TextDocumentSyncCapability,
-    TextDocumentSyncKind, TextDocumentSyncSaveOptions,
+    ApplyWorkspaceEditParams, ApplyWorkspaceEditResponse, CancelParams,
+    CodeActionProviderCapability, ConfigurationParams, DidChangeTextDocumentParams,
+    DidSaveTextDocumentParams, DocumentOnTypeFormattingOptions, DocumentSelector,
+    FoldingRangeProviderCapability, HoverProviderCapability,
+    ImplementationProviderCapability, InitializeResult, LogMessageParams,
+    MessageType, OneOf, ProgressParams, PublishDiagnosticsParams, Range,
+    Registration, RegistrationParams, SemanticTokens, SemanticTokensFullOptions,
+    SemanticTokensLegend, SemanticTokensServerCapabilities, ServerCapabilities,
+    ShowDocumentParams, ShowDocumentResult, ShowMessageParams,
+    ShowMessageRequestParams, TextDocumentContentChangeEvent,
+    TextDocumentIdentifier, TextDocumentSaveRegistrationOptions,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncSaveOptions,
     VersionedTextDocumentIdentifier,
     notification::{
         Cancel, DidChangeTextDocument, DidOpenTextDocument, DidSaveTextDocument,
@@ -38,15 +40,19 @@ use lsp_types::{
         ShowMessage,
     },
     request::{
-        CallHierarchyIncomingCalls, CallHierarchyPrepare, CodeActionRequest,
-        CodeActionResolveRequest, CodeLensRequest, CodeLensResolve, Completion,
-        DocumentHighlightRequest, DocumentSymbolRequest, FoldingRangeRequest,
-        Formatting, GotoDefinition, GotoImplementation, GotoTypeDefinition,
-        HoverRequest, Initialize, InlayHintRequest, InlineCompletionRequest,
-        PrepareRenameRequest, References, RegisterCapability, Rename,
+        ApplyWorkspaceEdit, CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls,
+        CallHierarchyPrepare, CodeActionRequest, CodeActionResolveRequest,
+        CodeLensRefresh, CodeLensRequest, CodeLensResolve, Completion,
+        DocumentHighlightRequest, DocumentSymbolRequest, ExecuteCommand,
+        FoldingRangeRequest, Formatting, GotoDefinition, GotoImplementation,
+        GotoTypeDefinition, HoverRequest, Initialize, InlayHintRequest,
+        InlineCompletionRequest, OnTypeFormatting, PrepareRenameRequest,
+        RangeFormatting, References, RegisterCapability, Rename,
         ResolveCompletionItem, SelectionRangeRequest,
-        SemanticTokensFullDeltaRequest, SemanticTokensFullRequest,
-        SignatureHelpRequest, WorkDoneProgressCreate, WorkspaceSymbolRequest,
+        SemanticTokensFullDeltaRequest, SemanticTokensFullRequest, ShowDocument,
+        ShowMessageRequest, SignatureHelpRequest, TypeHierarchyPrepare,
+        TypeHierarchySubtypes, TypeHierarchySupertypes, WorkDoneProgressCreate,
+        WorkspaceConfiguration, WorkspaceFoldersRequest, WorkspaceSymbolRequest,
     },
 };
 use parking_lot::Mutex;
@@ -63,6 +69,7 @@ use serde_json::Value;
 use super::{
     PluginCatalogRpcHandler,
     lsp::{DocumentFilter, LspClient},
+    workspace_folders,
 };
 
 pub enum ResponseHandler<Resp, Error> {
@@ -106,7 +113,7 @@ impl<Resp, Error, F: Send + FnOnce(Id, Result<Resp, Error>)> RpcCallback<Resp, E
 #[allow(clippy::large_enum_variant)]
 pub enum PluginHandlerNotification {
     Initialize(u64),
-    InitializeResult(InitializeResult),
+    InitializeResult(InitializeResult, bool),
     Shutdown,
     SpawnedPluginLoaded { plugin_id: PluginId },
 }
@@ -219,6 +226,7 @@ pub trait PluginServerHandler {
         path: Option<&Path>,
     ) -> bool;
     fn method_registered(&mut self, method: &str) -> bool;
+    fn request_registered(&mut self, method: &str, params: &Params) -> bool;
     fn handle_host_notification(
         &mut self,
         method: String,
@@ -468,7 +476,7 @@ impl PluginServerRpcHandler {
                 } => {
                     if handler
                         .document_supported(language_id.as_deref(), path.as_deref())
-                        && handler.method_registered(&method)
+                        && handler.request_registered(&method, &params)
                     {
                         self.send_server_request(id, &method, params, rh);
                     } else {
@@ -628,7 +636,48 @@ struct SaveRegistration {
 
 #[derive(Default)]
 struct ServerRegistrations {
-    save: Option<SaveRegistration>,
+    save:           Option<SaveRegistration>,
+    type_hierarchy: bool,
+}
+
+/// Whether the capabilities in the response to `initialize` have
+/// `typeHierarchyProvider`, which `ServerCapabilities` has no field for.
+pub fn has_type_hierarchy_provider(initialize_result: &Value) -> bool {
+    initialize_result
+        .pointer("/capabilities/typeHierarchyProvider")
+        .is_some_and(|provider| {
+            !provider.is_null() && provider != &Value::Bool(false)
+        })
+}
+
+fn is_trigger_character(
+    options: &DocumentOnTypeFormattingOptions,
+    ch: &str,
+) -> bool {
+    options.first_trigger_character == ch
+        || options
+            .more_trigger_character
+            .as_ref()
+            .is_some_and(|chars| chars.iter().any(|c| c == ch))
+}
+
+/// The value of a dotted `section` of the settings, like `rust-analyzer.cargo`,
+/// for `workspace/configuration`, where a missing section is null.
+fn configuration_section(
+    configuration: Option<&Value>,
+    section: Option<&str>,
+) -> Value {
+    let Some(configuration) = configuration else {
+        return Value::Null;
+    };
+    let Some(section) = section.filter(|section| !section.is_empty()) else {
+        return configuration.clone();
+    };
+    section
+        .split('.')
+        .try_fold(configuration, |value, key| value.get(key))
+        .cloned()
+        .unwrap_or(Value::Null)
 }
 
 pub struct PluginHostHandler {
@@ -636,11 +685,17 @@ pub struct PluginHostHandler {
     pub volt_display_name:   String,
     pwd:                     Option<PathBuf>,
     pub(crate) workspace:    Option<PathBuf>,
+    /// Every root folder of the workspace, `workspace` being the first
+    pub(crate) folders:      Vec<PathBuf>,
     document_selector:       Vec<DocumentFilter>,
     core_rpc:                CoreRpcHandler,
     catalog_rpc:             PluginCatalogRpcHandler,
     pub server_rpc:          PluginServerRpcHandler,
     pub server_capabilities: ServerCapabilities,
+    /// The server has `typeHierarchyProvider` in its capabilities
+    pub type_hierarchy:      bool,
+    /// The settings of the plugin, served to `workspace/configuration`
+    pub configuration:       Option<Value>,
     server_registrations:    ServerRegistrations,
 
     /// Language servers that this plugin has spawned.  
@@ -652,6 +707,7 @@ impl PluginHostHandler {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         workspace: Option<PathBuf>,
+        folders: Vec<PathBuf>,
         pwd: Option<PathBuf>,
         volt_id: VoltID,
         volt_display_name: String,
@@ -667,6 +723,7 @@ impl PluginHostHandler {
         Self {
             pwd,
             workspace,
+            folders,
             volt_id,
             volt_display_name,
             document_selector,
@@ -674,6 +731,8 @@ impl PluginHostHandler {
             catalog_rpc,
             server_rpc,
             server_capabilities: ServerCapabilities::default(),
+            type_hierarchy: false,
+            configuration: None,
             server_registrations: ServerRegistrations::default(),
             spawned_lsp: HashMap::new(),
         }
@@ -706,6 +765,30 @@ impl PluginHostHandler {
         }
     }
 
+    /// Like `method_registered`, but on type formatting is only requested for
+    /// the trigger characters of the server.
+    pub fn request_registered(&mut self, method: &str, params: &Params) -> bool {
+        if method != OnTypeFormatting::METHOD {
+            return self.method_registered(method);
+        }
+        let Some(options) = self
+            .server_capabilities
+            .document_on_type_formatting_provider
+            .as_ref()
+        else {
+            return false;
+        };
+        serde_json::to_value(params)
+            .ok()
+            .and_then(|params| {
+                params
+                    .get("ch")?
+                    .as_str()
+                    .map(|ch| is_trigger_character(options, ch))
+            })
+            .unwrap_or(false)
+    }
+
     pub fn method_registered(&mut self, method: &str) -> bool {
         match method {
             Initialize::METHOD => true,
@@ -828,6 +911,19 @@ impl PluginHostHandler {
                     OneOf::Right(_) => true,
                 })
                 .unwrap_or(false),
+            RangeFormatting::METHOD => self
+                .server_capabilities
+                .document_range_formatting_provider
+                .as_ref()
+                .map(|f| match f {
+                    OneOf::Left(is_capable) => *is_capable,
+                    OneOf::Right(_) => true,
+                })
+                .unwrap_or(false),
+            OnTypeFormatting::METHOD => self
+                .server_capabilities
+                .document_on_type_formatting_provider
+                .is_some(),
             SemanticTokensFullRequest::METHOD => {
                 self.server_capabilities.semantic_tokens_provider.is_some()
             },
@@ -889,10 +985,21 @@ impl PluginHostHandler {
             CallHierarchyIncomingCalls::METHOD => {
                 self.server_capabilities.call_hierarchy_provider.is_some()
             },
+            CallHierarchyOutgoingCalls::METHOD => {
+                self.server_capabilities.call_hierarchy_provider.is_some()
+            },
+            TypeHierarchyPrepare::METHOD
+            | TypeHierarchySupertypes::METHOD
+            | TypeHierarchySubtypes::METHOD => {
+                self.type_hierarchy || self.server_registrations.type_hierarchy
+            },
             DocumentHighlightRequest::METHOD => self
                 .server_capabilities
                 .document_highlight_provider
                 .is_some(),
+            ExecuteCommand::METHOD => {
+                self.server_capabilities.execute_command_provider.is_some()
+            },
             _ => false,
         }
     }
@@ -964,6 +1071,9 @@ impl PluginHostHandler {
                         .unwrap_or_default(),
                 });
             },
+            TypeHierarchyPrepare::METHOD => {
+                self.server_registrations.type_hierarchy = true;
+            },
             _ => {
                 eprintln!(
                     "don't handle register capability for {}",
@@ -996,6 +1106,79 @@ impl PluginHostHandler {
             WorkDoneProgressCreate::METHOD => {
                 resp.send_null();
             },
+            WorkspaceConfiguration::METHOD => {
+                let params: ConfigurationParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                let items: Vec<Value> = params
+                    .items
+                    .iter()
+                    .map(|item| {
+                        configuration_section(
+                            self.configuration.as_ref(),
+                            item.section.as_deref(),
+                        )
+                    })
+                    .collect();
+                resp.send(items);
+            },
+            WorkspaceFoldersRequest::METHOD => {
+                resp.send(workspace_folders(&self.folders));
+            },
+            ApplyWorkspaceEdit::METHOD => {
+                let params: ApplyWorkspaceEditParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                let core_rpc = self.core_rpc.clone();
+                // core answers once the edit is applied, which the server
+                // waits for but the other messages of the plugin don't
+                thread::spawn(move || {
+                    let response =
+                        core_rpc.apply_workspace_edit(params.edit, params.label);
+                    let (applied, failure_reason) = match response {
+                        Ok(CoreResponse::ApplyWorkspaceEditResponse {
+                            applied,
+                            failure_reason,
+                        }) => (applied, failure_reason),
+                        Ok(_) => (false, None),
+                        Err(err) => (false, Some(err.message)),
+                    };
+                    resp.send(ApplyWorkspaceEditResponse {
+                        applied,
+                        failure_reason,
+                        failed_change: None,
+                    });
+                });
+            },
+            ShowMessageRequest::METHOD => {
+                let message: ShowMessageRequestParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                let title = format!("Plugin: {}", self.volt_display_name);
+                let core_rpc = self.core_rpc.clone();
+                // the user may take a while to pick an action
+                thread::spawn(move || {
+                    match core_rpc.show_message_request(title, message) {
+                        Ok(CoreResponse::ShowMessageRequestResponse { action }) => {
+                            resp.send(action);
+                        },
+                        _ => resp.send_null(),
+                    }
+                });
+            },
+            ShowDocument::METHOD => {
+                let params: ShowDocumentParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                let core_rpc = self.core_rpc.clone();
+                thread::spawn(move || {
+                    let success = matches!(
+                        core_rpc.show_document(params),
+                        Ok(CoreResponse::ShowDocumentResponse { success: true })
+                    );
+                    resp.send(ShowDocumentResult { success });
+                });
+            },
+            CodeLensRefresh::METHOD => {
+                self.core_rpc.code_lens_refresh();
+                resp.send_null();
+            },
             RegisterCapability::METHOD => {
                 let params: RegistrationParams =
                     serde_json::from_value(serde_json::to_value(params)?)?;
@@ -1029,10 +1212,12 @@ impl PluginHostHandler {
                 let params: StartLspServerParams =
                     serde_json::from_value(serde_json::to_value(params)?)?;
                 let workspace = self.workspace.clone();
+                let folders = self.folders.clone();
                 let pwd = self.pwd.clone();
                 let catalog_rpc = self.catalog_rpc.clone();
                 let volt_id = self.volt_id.clone();
                 let volt_display_name = self.volt_display_name.clone();
+                let configuration = self.configuration.clone();
 
                 let spawned_by = self.server_rpc.plugin_id;
                 let plugin_id = PluginId::next();
@@ -1043,6 +1228,7 @@ impl PluginHostHandler {
                         catalog_rpc,
                         params.document_selector,
                         workspace,
+                        folders,
                         volt_id,
                         volt_display_name,
                         Some(spawned_by),
@@ -1051,6 +1237,7 @@ impl PluginHostHandler {
                         params.server_uri,
                         params.server_args,
                         params.options,
+                        configuration,
                         0,
                     ) {
                         log::error!("{:?}", err);
@@ -1151,15 +1338,18 @@ impl PluginHostHandler {
                 let params: StartLspServerParams =
                     serde_json::from_value(serde_json::to_value(params)?)?;
                 let workspace = self.workspace.clone();
+                let folders = self.folders.clone();
                 let pwd = self.pwd.clone();
                 let catalog_rpc = self.catalog_rpc.clone();
                 let volt_id = self.volt_id.clone();
                 let volt_display_name = self.volt_display_name.clone();
+                let configuration = self.configuration.clone();
                 thread::spawn(move || {
                     if let Err(err) = LspClient::start(
                         catalog_rpc,
                         params.document_selector,
                         workspace,
+                        folders,
                         volt_id,
                         volt_display_name,
                         None,
@@ -1168,6 +1358,7 @@ impl PluginHostHandler {
                         params.server_uri,
                         params.server_args,
                         params.options,
+                        configuration,
                         0,
                     ) {
                         log::error!("{:?}", err);
@@ -1499,3 +1690,37 @@ fn semantic_tokens_legend(
         ) => &options.semantic_tokens_options.legend,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_configuration_section() {
+        let configuration = json!({
+            "rust-analyzer": { "cargo": { "features": "all" } },
+        });
+        let section = |section| configuration_section(Some(&configuration), section);
+        assert_eq!(section(None), configuration);
+        assert_eq!(
+            section(Some("rust-analyzer.cargo")),
+            json!({ "features": "all" })
+        );
+        assert_eq!(section(Some("rust-analyzer.check")), Value::Null);
+        assert_eq!(section(Some("files")), Value::Null);
+        assert_eq!(configuration_section(None, Some("files")), Value::Null);
+    }
+
+    #[test]
+    fn test_is_trigger_character() {
+        let options = DocumentOnTypeFormattingOptions {
+            first_trigger_character: "}".to_string(),
+            more_trigger_character:  Some(vec![";".to_string(), "\n".to_string()]),
+        };
+        assert!(is_trigger_character(&options, "}"));
+        assert!(is_trigger_character(&options, ";"));
+        assert!(!is_trigger_character(&options, "{"));
+    }
+}
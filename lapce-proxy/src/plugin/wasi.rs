@@ -23,7 +23,7 @@ use log::debug;
 use lsp_types::{
     DocumentFilter, InitializeParams, InitializedParams,
     TextDocumentContentChangeEvent, TextDocumentIdentifier, Url,
-    VersionedTextDocumentIdentifier, WorkDoneProgressParams, WorkspaceFolder,
+    VersionedTextDocumentIdentifier, WorkDoneProgressParams,
     notification::Initialized, request::Initialize,
 };
 use parking_lot::Mutex;
@@ -38,8 +38,9 @@ use super::{
     psp::{
         PluginHandlerNotification, PluginHostHandler, PluginServerHandler,
         PluginServerRpc, ResponseSender, RpcCallback, handle_plugin_server_message,
+        has_type_hierarchy_provider,
     },
-    volt_icon,
+    volt_icon, workspace_folders,
 };
 use crate::plugin::psp::PluginServerRpcHandler;
 
@@ -93,6 +94,10 @@ impl PluginServerHandler for Plugin {
         self.host.method_registered(method)
     }
 
+    fn request_registered(&mut self, method: &str, params: &Params) -> bool {
+        self.host.request_registered(method, params)
+    }
+
     fn document_supported(
         &mut self,
         language_id: Option<&str>,
@@ -110,8 +115,9 @@ impl PluginServerHandler for Plugin {
             Initialize(id) => {
                 self.initialize(id);
             },
-            InitializeResult(result) => {
+            InitializeResult(result, type_hierarchy) => {
                 self.host.server_capabilities = result.capabilities;
+                self.host.type_hierarchy = type_hierarchy;
             },
             Shutdown => {
                 self.shutdown();
@@ -198,25 +204,21 @@ impl Plugin {
         let workspace = self.host.workspace.clone();
         let configurations = self.configurations.as_ref().map(unflatten_map);
         let root_uri = workspace.map(|p| Url::from_directory_path(p).unwrap());
+        let workspace_folders = workspace_folders(&self.host.folders);
         let server_rpc = self.host.server_rpc.clone();
         self.host.server_rpc.server_request_async(
             Initialize::METHOD,
             #[allow(deprecated)]
             InitializeParams {
-                process_id:                Some(process::id()),
-                root_path:                 None,
-                root_uri:                  root_uri.clone(),
-                capabilities:              client_capabilities(),
-                trace:                     None,
-                client_info:               None,
-                locale:                    None,
-                initialization_options:    configurations,
-                workspace_folders:         root_uri.map(|uri| {
-                    vec![WorkspaceFolder {
-                        name: uri.as_str().to_string(),
-                        uri,
-                    }]
-                }),
+                process_id: Some(process::id()),
+                root_path: None,
+                root_uri,
+                capabilities: client_capabilities(),
+                trace: None,
+                client_info: None,
+                locale: None,
+                initialization_options: configurations,
+                workspace_folders,
                 work_done_progress_params: WorkDoneProgressParams::default(),
             },
             None,
@@ -225,9 +227,13 @@ impl Plugin {
             id,
             move |_id, value| match value {
                 Ok(value) => {
+                    let type_hierarchy = has_type_hierarchy_provider(&value);
                     if let Ok(result) = serde_json::from_value(value) {
                         server_rpc.handle_rpc(PluginServerRpc::Handler(
-                            PluginHandlerNotification::InitializeResult(result),
+                            PluginHandlerNotification::InitializeResult(
+                                result,
+                                type_hierarchy,
+                            ),
                         ));
                         server_rpc.server_notification(
                             Initialized::METHOD,
@@ -486,6 +492,7 @@ pub async fn enable_volt(
 
 pub fn start_volt(
     workspace: Option<PathBuf>,
+    folders: Vec<PathBuf>,
     configurations: Option<HashMap<String, serde_json::Value>>,
     plugin_rpc: PluginCatalogRpcHandler,
     meta: VoltMetadata,
@@ -639,6 +646,7 @@ pub fn start_volt(
         id,
         host: PluginHostHandler::new(
             workspace,
+            folders,
             meta.dir.clone(),
             meta.id(),
             meta.display_name.clone(),
@@ -669,6 +677,7 @@ pub fn start_volt(
         ),
         configurations,
     };
+    plugin.host.configuration = plugin.configurations.as_ref().map(unflatten_map);
     let local_rpc = rpc.clone();
     thread::spawn(move || {
         let handler_name = format!("plugin {}", plugin.host.volt_display_name);
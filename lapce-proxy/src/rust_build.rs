@@ -1 +1,135 @@
+use std::{
+    ffi::OsStr,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::Arc,
+    thread,
+};
 
+use anyhow::{Result, anyhow};
+use lapce_rpc::core::RustBuildOutput;
+use parking_lot::Mutex;
+
+/// A cargo command run for the build panel or the test explorer
+pub struct RustBuildProcess {
+    pub id: u64,
+    child:  Arc<Mutex<Child>>,
+}
+
+impl RustBuildProcess {
+    /// Spawns `command` in `cwd`, passing every line it prints to
+    /// `on_output` and then its exit code to `on_stopped`.
+    pub fn start(
+        cwd: &Path,
+        id: u64,
+        command: impl AsRef<OsStr>,
+        arguments: &[String],
+        envs: &[(&str, &str)],
+        on_output: impl Fn(u64, RustBuildOutput) + Send + Sync + 'static,
+        on_stopped: impl FnOnce(u64, Option<i32>) + Send + 'static,
+    ) -> Result<Self> {
+        let mut process = Command::new(command);
+        process
+            .current_dir(cwd)
+            .args(arguments)
+            .envs(envs.iter().copied());
+        // its own process group, for the build to be stopped as a whole
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut process, 0);
+        // CREATE_NO_WINDOW
+        #[cfg(target_os = "windows")]
+        std::os::windows::process::CommandExt::creation_flags(
+            &mut process,
+            0x08000000,
+        );
+        let mut child = process
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().ok_or(anyhow!("stdout is none"))?;
+        let stderr = child.stderr.take().ok_or(anyhow!("stderr is none"))?;
+        let child = Arc::new(Mutex::new(child));
+
+        let on_output = Arc::new(on_output);
+        let out_thread =
+            read_lines(on_output.clone(), id, stdout, RustBuildOutput::StdOut);
+        let err_thread = read_lines(on_output, id, stderr, RustBuildOutput::StdErr);
+        {
+            let child = child.clone();
+            thread::spawn(move || {
+                // the pipes close when the process exits, or is killed
+                let _ = out_thread.join();
+                let _ = err_thread.join();
+                let exit_code = match child.lock().wait() {
+                    Ok(status) => status.code(),
+                    Err(err) => {
+                        log::error!("{err:?}");
+                        None
+                    },
+                };
+                on_stopped(id, exit_code);
+            });
+        }
+        Ok(Self { id, child })
+    }
+
+    /// Kills cargo and what it runs, rustc and test binaries, which would
+    /// otherwise hold the output open and delay the stop.
+    pub fn cancel(&self) {
+        let mut child = self.child.lock();
+        if kill_tree(child.id()) {
+            return;
+        }
+        if let Err(err) = child.kill() {
+            log::error!("{err:?}");
+        }
+    }
+}
+
+/// Kills the process group led by `pid`.
+#[cfg(unix)]
+fn kill_tree(pid: u32) -> bool {
+    // SAFETY: a negative pid sends the signal to the group of that id
+    unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGKILL) == 0 }
+}
+
+/// Kills the process `pid` and its descendants.
+#[cfg(target_os = "windows")]
+fn kill_tree(pid: u32) -> bool {
+    let mut process = Command::new("taskkill");
+    process.args(["/F", "/T", "/PID", &pid.to_string()]);
+    // CREATE_NO_WINDOW
+    std::os::windows::process::CommandExt::creation_flags(&mut process, 0x08000000);
+    process
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn kill_tree(_pid: u32) -> bool {
+    false
+}
+
+fn read_lines(
+    on_output: Arc<impl Fn(u64, RustBuildOutput) + Send + Sync + 'static>,
+    id: u64,
+    reader: impl Read + Send + 'static,
+    output: fn(String) -> RustBuildOutput,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            match line {
+                Ok(line) => on_output(id, output(line)),
+                Err(err) => {
+                    log::error!("{err:?}");
+                    break;
+                },
+            }
+        }
+    })
+}
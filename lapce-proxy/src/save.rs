@@ -0,0 +1,190 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File, Metadata},
+    io::{self, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+use lapce_rpc::buffer::SaveMode;
+
+/// Writes the content of a file being saved. An atomic write falls back to
+/// writing in place when the file can't be replaced without losing something.
+pub fn write_file(path: &Path, content: &[u8], mode: SaveMode) -> Result<()> {
+    if mode == SaveMode::Atomic && write_atomic(path, content)? {
+        return Ok(());
+    }
+    write_in_place(path, content)
+}
+
+/// Writes a temporary file next to the file, with the permissions, owner and
+/// extended attributes of the file, and renames it over the file. Returns
+/// false, leaving the file alone, when the file is new, has other hard links
+/// or its directory or owner doesn't allow it.
+fn write_atomic(path: &Path, content: &[u8]) -> Result<bool> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        // a new file has no content to lose, nor permissions to keep
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    // the other names of the file would keep the old content
+    if hard_links(&metadata) > 1 {
+        return Ok(false);
+    }
+    let (Some(directory), Some(name)) = (path.parent(), path.file_name()) else {
+        return Ok(false);
+    };
+    let mut prefix = OsString::from(".");
+    prefix.push(name);
+    let mut temp = match tempfile::Builder::new()
+        .prefix(&prefix)
+        .suffix(".tmp")
+        .tempfile_in(directory)
+    {
+        Ok(temp) => temp,
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+            return Ok(false);
+        },
+        Err(err) => return Err(err.into()),
+    };
+    if !copy_owner(temp.as_file(), &metadata) {
+        return Ok(false);
+    }
+    temp.as_file().set_permissions(metadata.permissions())?;
+    copy_xattrs(path, temp.path());
+
+    temp.write_all(content)?;
+    temp.as_file().sync_all()?;
+    temp.persist(path).map_err(|err| err.error)?;
+    sync_directory(directory);
+    Ok(true)
+}
+
+/// Truncates the file and writes it, with a `.bak` copy of it in the meantime.
+fn write_in_place(path: &Path, content: &[u8]) -> Result<()> {
+    let new_file = !path.exists();
+    let mut bak_file_path = path.as_os_str().to_os_string();
+    bak_file_path.push(".bak");
+    if !new_file {
+        fs::copy(path, &bak_file_path)?;
+    }
+
+    let mut f = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+    f.write_all(content)?;
+
+    if !new_file {
+        fs::remove_file(&bak_file_path)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn hard_links(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink()
+}
+
+#[cfg(not(unix))]
+fn hard_links(_metadata: &Metadata) -> u64 {
+    1
+}
+
+/// Gives the temporary file the owner and group of the file, returning false
+/// when the user isn't allowed to.
+#[cfg(unix)]
+fn copy_owner(file: &File, metadata: &Metadata) -> bool {
+    use std::os::unix::fs::{MetadataExt, fchown};
+    let Ok(temp_metadata) = file.metadata() else {
+        return false;
+    };
+    if temp_metadata.uid() == metadata.uid() && temp_metadata.gid() == metadata.gid()
+    {
+        return true;
+    }
+    fchown(file, Some(metadata.uid()), Some(metadata.gid())).is_ok()
+}
+
+#[cfg(not(unix))]
+fn copy_owner(_file: &File, _metadata: &Metadata) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn copy_xattrs(from: &Path, to: &Path) {
+    // not every file system has them
+    let Ok(names) = xattr::list(from) else {
+        return;
+    };
+    for name in names {
+        let result = xattr::get(from, &name).and_then(|value| match value {
+            Some(value) => xattr::set(to, &name, &value),
+            None => Ok(()),
+        });
+        if let Err(err) = result {
+            log::warn!("failed to keep the attribute {name:?} of {from:?}: {err}");
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn copy_xattrs(_from: &Path, _to: &Path) {}
+
+/// Makes the rename durable.
+#[cfg(unix)]
+fn sync_directory(directory: &Path) {
+    if let Err(err) = File::open(directory).and_then(|dir| dir.sync_all()) {
+        log::warn!("failed to sync {directory:?}: {err}");
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_directory: &Path) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        assert!(!write_atomic(&path, b"new").unwrap());
+
+        fs::write(&path, "old").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+        }
+        assert!(write_atomic(&path, b"saved").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "saved");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o750);
+        }
+        // only the file is left
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_hard_linked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        let link = dir.path().join("b.txt");
+        fs::write(&path, "old").unwrap();
+        fs::hard_link(&path, &link).unwrap();
+
+        assert!(!write_atomic(&path, b"saved").unwrap());
+        write_file(&path, b"saved", SaveMode::Atomic).unwrap();
+        assert_eq!(fs::read_to_string(&link).unwrap(), "saved");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
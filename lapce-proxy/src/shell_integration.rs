@@ -0,0 +1,84 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use alacritty_terminal::tty::Shell;
+use anyhow::Result;
+use lapce_rpc::terminal::TerminalProfile;
+
+const BASH_SCRIPT: &str = include_str!("../../extra/shell-integration/lapce.bash");
+const ZSH_ENV_SCRIPT: &str =
+    include_str!("../../extra/shell-integration/zsh/.zshenv");
+const ZSH_PROFILE_SCRIPT: &str =
+    include_str!("../../extra/shell-integration/zsh/.zprofile");
+const ZSH_RC_SCRIPT: &str = include_str!("../../extra/shell-integration/zsh/.zshrc");
+
+/// Makes bash or zsh load the shell integration scripts, which mark the
+/// prompts and commands for the terminal, returning the shell to run in place
+/// of the one of the profile, if any. Other shells, and a shell started with
+/// arguments of its own, are left as they are.
+pub fn inject(
+    directory: &Path,
+    profile: &TerminalProfile,
+    env: &mut HashMap<String, String>,
+) -> Option<Shell> {
+    if profile
+        .arguments
+        .as_ref()
+        .is_some_and(|args| !args.is_empty())
+    {
+        return None;
+    }
+    let command = match &profile.command {
+        Some(command) => command.clone(),
+        None => std::env::var("SHELL").ok()?,
+    };
+    let scripts = directory.join("shell-integration");
+    let result = match Path::new(&command).file_name()?.to_str()? {
+        "bash" => write_script(&scripts, "lapce.bash", BASH_SCRIPT).map(|path| {
+            Some(Shell::new(
+                command.clone(),
+                vec!["--init-file".to_string(), path.to_string_lossy().into()],
+            ))
+        }),
+        "zsh" => {
+            let zdotdir = scripts.join("zsh");
+            write_script(&zdotdir, ".zshenv", ZSH_ENV_SCRIPT)
+                .and_then(|_| {
+                    write_script(&zdotdir, ".zprofile", ZSH_PROFILE_SCRIPT)
+                })
+                .and_then(|_| write_script(&zdotdir, ".zshrc", ZSH_RC_SCRIPT))
+                .map(|_| {
+                    // the scripts load the startup files of the user from there
+                    let user_zdotdir = env
+                        .get("ZDOTDIR")
+                        .cloned()
+                        .or_else(|| std::env::var("ZDOTDIR").ok());
+                    if let Some(user_zdotdir) = user_zdotdir {
+                        env.insert("LAPCE_USER_ZDOTDIR".to_string(), user_zdotdir);
+                    }
+                    env.insert(
+                        "ZDOTDIR".to_string(),
+                        zdotdir.to_string_lossy().into(),
+                    );
+                    None
+                })
+        },
+        _ => return None,
+    };
+    result.unwrap_or_else(|err| {
+        log::error!("failed to set up the shell integration: {err:?}");
+        None
+    })
+}
+
+fn write_script(directory: &Path, name: &str, content: &str) -> Result<PathBuf> {
+    fs::create_dir_all(directory)?;
+    let path = directory.join(name);
+    if fs::read_to_string(&path).ok().as_deref() != Some(content) {
+        fs::write(&path, content)?;
+    }
+    Ok(path)
+}
@@ -3,7 +3,7 @@ use std::{
     collections::{HashMap, VecDeque, hash_map::Iter},
     io::{self, ErrorKind, Read, Write},
     num::NonZeroUsize,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
 };
@@ -23,6 +23,8 @@ use lapce_rpc::{
 use log::{debug, info};
 use polling::PollMode;
 
+use crate::shell_integration;
+
 const READ_BUFFER_SIZE: usize = 0x10_0000;
 
 #[cfg(any(target_os = "linux", target_os = "macos"))]
@@ -136,14 +138,20 @@ impl Terminal {
         profile: TerminalProfile,
         width: usize,
         height: usize,
+        proxy_directory: &Path,
     ) -> Result<Terminal> {
         let poll = polling::Poller::new()?.into();
-        let shell = Terminal::program(&profile);
+        let mut env = profile.environment.clone().unwrap_or_default();
+        let shell = profile
+            .shell_integration
+            .then(|| shell_integration::inject(proxy_directory, &profile, &mut env))
+            .flatten()
+            .or_else(|| Terminal::program(&profile));
         let options = Options {
-            shell:             shell.clone(),
+            shell: shell.clone(),
             working_directory: Terminal::workdir(&profile),
-            hold:              false,
-            env:               profile.environment.unwrap_or_default(),
+            hold: false,
+            env,
         };
 
         setup_env();
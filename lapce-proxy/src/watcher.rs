@@ -200,6 +200,24 @@ impl FileWatcher {
         }
     }
 
+    /// Watches `path` again, after an atomic save renamed another file over
+    /// it. The watch of a file follows the file rather than its path, so it
+    /// went away with the file that was replaced.
+    pub fn rewatch(&mut self, path: &Path) {
+        let Ok(path) = path.canonicalize() else {
+            return;
+        };
+        let state = self.state.lock();
+        let Some(w) = state.watchees.iter().find(|w| w.path == path) else {
+            return;
+        };
+        // the watch of the old file may already be gone
+        let _ = self.inner.unwatch(&path);
+        if let Err(err) = self.inner.watch(&path, mode_from_bool(w.recursive)) {
+            log::error!("{:?}", err);
+        }
+    }
+
     /// Takes ownership of this `Watcher`'s current event queue.
     pub fn take_events(&self) -> VecDeque<(WatchToken, Event)> {
         let mut state = self.state.lock();
@@ -272,3 +290,50 @@ fn mode_from_bool(is_recursive: bool) -> RecursiveMode {
         RecursiveMode::NonRecursive
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use crossbeam_channel::Sender;
+    use lapce_rpc::buffer::SaveMode;
+
+    use super::*;
+    use crate::save::write_file;
+
+    struct Events(Sender<Vec<(WatchToken, Event)>>);
+
+    impl Notify for Events {
+        fn notify(&self, events: Vec<(WatchToken, Event)>) {
+            let _ = self.0.send(events);
+        }
+    }
+
+    #[test]
+    fn test_rewatch_after_atomic_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "old").unwrap();
+        let token = WatchToken(1);
+        let (tx, rx) = unbounded();
+        let mut watcher = FileWatcher::new();
+        watcher.notify(Events(tx));
+        watcher.watch(&path, false, token);
+
+        write_file(&path, b"saved", SaveMode::Atomic).unwrap();
+        watcher.rewatch(&path);
+        // the events of the save itself
+        while rx.recv_timeout(Duration::from_millis(200)).is_ok() {}
+
+        fs::write(&path, "edited").unwrap();
+        let path = path.canonicalize().unwrap();
+        let mut events =
+            std::iter::from_fn(|| rx.recv_timeout(Duration::from_secs(5)).ok());
+        let edited = events.any(|events| {
+            events
+                .iter()
+                .any(|(t, event)| *t == token && event.paths.contains(&path))
+        });
+        assert!(edited);
+    }
+}
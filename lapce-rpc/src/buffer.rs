@@ -1,3 +1,5 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
 use crate::counter::Counter;
@@ -12,6 +14,113 @@ impl BufferId {
     }
 }
 
+/// The encodings a file can be reopened or saved with, by their names in the
+/// Encoding Standard.
+pub const ENCODINGS: &[&str] = &[
+    "UTF-8",
+    "UTF-16LE",
+    "UTF-16BE",
+    "windows-1252",
+    "ISO-8859-2",
+    "ISO-8859-3",
+    "ISO-8859-4",
+    "ISO-8859-5",
+    "ISO-8859-6",
+    "ISO-8859-7",
+    "ISO-8859-8",
+    "ISO-8859-10",
+    "ISO-8859-13",
+    "ISO-8859-14",
+    "ISO-8859-15",
+    "ISO-8859-16",
+    "windows-1250",
+    "windows-1251",
+    "windows-1253",
+    "windows-1254",
+    "windows-1255",
+    "windows-1256",
+    "windows-1257",
+    "windows-1258",
+    "windows-874",
+    "KOI8-R",
+    "KOI8-U",
+    "IBM866",
+    "macintosh",
+    "GBK",
+    "gb18030",
+    "Big5",
+    "Shift_JIS",
+    "EUC-JP",
+    "ISO-2022-JP",
+    "EUC-KR",
+];
+
+/// The encoding of the bytes of a file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FileEncoding {
+    /// One of [`ENCODINGS`]
+    pub name: String,
+    /// Whether the file starts with a byte order mark
+    pub bom:  bool,
+}
+
+impl FileEncoding {
+    pub fn new(name: &str, bom: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            bom,
+        }
+    }
+
+    /// Every encoding, with UTF-8 both with and without a BOM. UTF-16 is
+    /// always written with one.
+    pub fn all() -> Vec<FileEncoding> {
+        let mut encodings = Vec::with_capacity(ENCODINGS.len() + 1);
+        for name in ENCODINGS {
+            if *name == "UTF-8" {
+                encodings.push(FileEncoding::new(name, false));
+            }
+            let bom = name.starts_with("UTF-");
+            encodings.push(FileEncoding::new(name, bom));
+        }
+        encodings
+    }
+}
+
+impl Default for FileEncoding {
+    fn default() -> Self {
+        Self::new("UTF-8", false)
+    }
+}
+
+impl fmt::Display for FileEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.bom && self.name == "UTF-8" {
+            write!(f, "UTF-8 with BOM")
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+/// How a file is written on save, from `save-mode` of the core settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SaveMode {
+    /// Write a temporary file next to the file and rename it over it, so that
+    /// a crash never leaves the file half written. New files, hard-linked
+    /// files and files in directories that can't be written to are written in
+    /// place.
+    #[default]
+    Atomic,
+    /// Truncate the file and write it
+    InPlace,
+}
+
+impl SaveMode {
+    pub const VARIANTS: &'static [&'static str] = &["atomic", "in-place"];
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewBufferResponse {
     pub content: String,
@@ -5,13 +5,15 @@ use std::{
         Arc,
         atomic::{AtomicU64, Ordering},
     },
+    time::Duration,
 };
 
-use cozy_floem::views::tree_with_panel::data::StyledText;
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use indexmap::IndexMap;
 use lsp_types::{
-    CancelParams, CompletionResponse, LogMessageParams, ProgressParams,
-    PublishDiagnosticsParams, ShowMessageParams, SignatureHelp,
+    CancelParams, CompletionResponse, LogMessageParams, MessageActionItem,
+    ProgressParams, PublishDiagnosticsParams, ShowDocumentParams, ShowMessageParams,
+    ShowMessageRequestParams, SignatureHelp, WorkspaceEdit,
 };
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
@@ -23,7 +25,7 @@ use crate::{
     },
     file::PathObject,
     plugin::{PluginId, VoltInfo, VoltMetadata},
-    proxy::ProxyStatus,
+    proxy::{ProxyStatus, SearchMatch},
     source_control::DiffInfo,
     terminal::TermId,
 };
@@ -41,6 +43,14 @@ pub enum FileChanged {
     Delete,
 }
 
+/// A line printed by a running cargo command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RustBuildOutput {
+    StdOut(String),
+    StdErr(String),
+}
+
 /// Proxy inform core(lapce)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -84,6 +94,8 @@ pub enum CoreNotification {
         title:   String,
         message: ShowMessageParams,
     },
+    /// A language server asks for the code lenses to be fetched again
+    CodeLensRefresh,
     LogMessage {
         message: LogMessageParams,
         target:  String,
@@ -111,6 +123,8 @@ pub enum CoreNotification {
         only_installing: bool,
     },
     DiffInfo {
+        /// The folder of the workspace the repository was found from
+        root: PathBuf,
         diff: DiffInfo,
     },
     TerminalUpdateContent {
@@ -138,6 +152,22 @@ pub enum CoreNotification {
         term_id:   TermId,
         exit_code: Option<i32>,
     },
+    RustBuildOutput {
+        rev:    u64,
+        output: RustBuildOutput,
+    },
+    RustBuildStopped {
+        rev:       u64,
+        exit_code: Option<i32>,
+    },
+    TestRunOutput {
+        run_id: u64,
+        output: RustBuildOutput,
+    },
+    TestRunStopped {
+        run_id:    u64,
+        exit_code: Option<i32>,
+    },
     DapRunInTerminal {
         config: RunDebugConfig,
     },
@@ -160,18 +190,75 @@ pub enum CoreNotification {
         path:        PathBuf,
         breakpoints: Vec<dap_types::Breakpoint>,
     },
+    DapCapabilities {
+        dap_id:       DapId,
+        capabilities: dap_types::DebuggerCapabilities,
+    },
+    /// A batch of matches found by a running global search, with the
+    /// running totals so far.
+    GlobalSearchMatches {
+        search_id:      u64,
+        matches:        IndexMap<PathBuf, Vec<SearchMatch>>,
+        files_searched: usize,
+        match_count:    usize,
+    },
 }
 
+/// Proxy asks core(lapce), for the requests of the language servers that
+/// need the editor or the user
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum CoreRequest {}
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "method", content = "params")]
+pub enum CoreRequest {
+    ApplyWorkspaceEdit {
+        edit:  WorkspaceEdit,
+        label: Option<String>,
+    },
+    ShowMessageRequest {
+        title:   String,
+        message: ShowMessageRequestParams,
+    },
+    ShowDocument {
+        params: ShowDocumentParams,
+    },
+}
+
+impl CoreRequest {
+    /// How long the proxy waits for the answer
+    fn timeout(&self) -> Duration {
+        match self {
+            CoreRequest::ShowMessageRequest { .. } => USER_REQUEST_TIMEOUT,
+            CoreRequest::ApplyWorkspaceEdit { .. }
+            | CoreRequest::ShowDocument { .. } => EDITOR_REQUEST_TIMEOUT,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "method", content = "params")]
-pub enum CoreResponse {}
+pub enum CoreResponse {
+    ApplyWorkspaceEditResponse {
+        applied:        bool,
+        /// Why the edit wasn't applied
+        failure_reason: Option<String>,
+    },
+    /// The action the user picked, none if the message was dismissed
+    ShowMessageRequestResponse {
+        action: Option<MessageActionItem>,
+    },
+    ShowDocumentResponse {
+        success: bool,
+    },
+}
 
 pub type CoreMessage = RpcMessage<CoreRequest, CoreNotification, CoreResponse>;
 
+/// How long a request the editor answers by itself is waited for
+const EDITOR_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a request the user answers is waited for
+const USER_REQUEST_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
 pub trait CoreHandler {
     fn handle_notification(&mut self, rpc: CoreNotification);
     fn handle_request(&mut self, id: RequestId, rpc: CoreRequest);
@@ -232,7 +319,11 @@ impl CoreRpcHandler {
         }
     }
 
+    /// Sends a request to core and waits for its response, at most for the
+    /// timeout of the request, after which the response is dropped if it
+    /// still comes.
     pub fn request(&self, request: CoreRequest) -> Result<CoreResponse, RpcError> {
+        let timeout = request.timeout();
         let (tx, rx) = crossbeam_channel::bounded(1);
         let id = self.id.fetch_add(1, Ordering::Relaxed);
         {
@@ -242,10 +333,14 @@ impl CoreRpcHandler {
         if let Err(err) = self.tx.send(CoreRpc::Request(id, request)) {
             log::error!("{:?}", err);
         }
-        rx.recv().unwrap_or_else(|_| {
+        rx.recv_timeout(timeout).unwrap_or_else(|err| {
+            self.pending.lock().remove(&id);
             Err(RpcError {
                 code:    0,
-                message: "io error".to_string(),
+                message: match err {
+                    RecvTimeoutError::Timeout => "timed out".to_string(),
+                    RecvTimeoutError::Disconnected => "io error".to_string(),
+                },
             })
         })
     }
@@ -267,8 +362,8 @@ impl CoreRpcHandler {
         self.notification(CoreNotification::WorkspaceFileChange);
     }
 
-    pub fn diff_info(&self, diff: DiffInfo) {
-        self.notification(CoreNotification::DiffInfo { diff });
+    pub fn diff_info(&self, root: PathBuf, diff: DiffInfo) {
+        self.notification(CoreNotification::DiffInfo { root, diff });
     }
 
     pub fn open_file_changed(&self, path: PathBuf, content: FileChanged) {
@@ -350,6 +445,33 @@ impl CoreRpcHandler {
         self.notification(CoreNotification::ShowMessage { title, message });
     }
 
+    pub fn show_message_request(
+        &self,
+        title: String,
+        message: ShowMessageRequestParams,
+    ) -> Result<CoreResponse, RpcError> {
+        self.request(CoreRequest::ShowMessageRequest { title, message })
+    }
+
+    pub fn apply_workspace_edit(
+        &self,
+        edit: WorkspaceEdit,
+        label: Option<String>,
+    ) -> Result<CoreResponse, RpcError> {
+        self.request(CoreRequest::ApplyWorkspaceEdit { edit, label })
+    }
+
+    pub fn show_document(
+        &self,
+        params: ShowDocumentParams,
+    ) -> Result<CoreResponse, RpcError> {
+        self.request(CoreRequest::ShowDocument { params })
+    }
+
+    pub fn code_lens_refresh(&self) {
+        self.notification(CoreNotification::CodeLensRefresh);
+    }
+
     pub fn show_status_message(&self, message: String) {
         self.notification(CoreNotification::ShowStatusMessage { message });
     }
@@ -406,10 +528,20 @@ impl CoreRpcHandler {
         self.notification(CoreNotification::TerminalSetTitle { term_id, title });
     }
 
-    pub fn update_rust_build_panel(&self, _rev: u64, _styled_text: StyledText) {
-        todo!()
-        // self.notification(CoreNotification::UpdateTerminal { term_id, content
-        // });
+    pub fn rust_build_output(&self, rev: u64, output: RustBuildOutput) {
+        self.notification(CoreNotification::RustBuildOutput { rev, output });
+    }
+
+    pub fn rust_build_stopped(&self, rev: u64, exit_code: Option<i32>) {
+        self.notification(CoreNotification::RustBuildStopped { rev, exit_code });
+    }
+
+    pub fn test_run_output(&self, run_id: u64, output: RustBuildOutput) {
+        self.notification(CoreNotification::TestRunOutput { run_id, output });
+    }
+
+    pub fn test_run_stopped(&self, run_id: u64, exit_code: Option<i32>) {
+        self.notification(CoreNotification::TestRunStopped { run_id, exit_code });
     }
 
     pub fn dap_stopped(
@@ -444,6 +576,32 @@ impl CoreRpcHandler {
         });
     }
 
+    pub fn dap_capabilities(
+        &self,
+        dap_id: DapId,
+        capabilities: dap_types::DebuggerCapabilities,
+    ) {
+        self.notification(CoreNotification::DapCapabilities {
+            dap_id,
+            capabilities,
+        });
+    }
+
+    pub fn global_search_matches(
+        &self,
+        search_id: u64,
+        matches: IndexMap<PathBuf, Vec<SearchMatch>>,
+        files_searched: usize,
+        match_count: usize,
+    ) {
+        self.notification(CoreNotification::GlobalSearchMatches {
+            search_id,
+            matches,
+            files_searched,
+            match_count,
+        });
+    }
+
     pub fn home_dir(&self, path: PathBuf) {
         self.notification(CoreNotification::HomeDir { path });
     }
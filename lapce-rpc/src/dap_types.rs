@@ -58,24 +58,48 @@ impl RunDebugProgram {
 #[serde(rename_all = "kebab-case")]
 pub struct RunDebugConfig {
     #[serde(rename = "type")]
-    pub ty:             Option<String>,
-    pub name:           String,
-    pub program:        String,
-    pub args:           Option<Vec<String>>,
-    pub cwd:            Option<String>,
-    pub env:            Option<HashMap<String, String>>,
-    pub prelaunch:      Option<RunDebugProgram>,
+    pub ty:                Option<String>,
+    pub name:              String,
+    /// Whether the adapter launches `program` or attaches to a running
+    /// process.
+    #[serde(default)]
+    pub request:           DebugRequest,
+    #[serde(default)]
+    pub program:           String,
+    pub args:              Option<Vec<String>>,
+    pub cwd:               Option<String>,
+    pub env:               Option<HashMap<String, String>>,
+    pub prelaunch:         Option<RunDebugProgram>,
     #[serde(skip)]
-    pub debug_command:  Option<Vec<String>>,
+    pub debug_command:     Option<Vec<String>>,
     #[serde(skip)]
-    pub dap_id:         DapId,
+    pub dap_id:            DapId,
+    #[serde(default)]
+    pub tracing_output:    bool,
     #[serde(default)]
-    pub tracing_output: bool,
+    pub config_source:     ConfigSource,
+    /// Exception filters to enable; `None` uses the adapter's defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exception_filters: Option<Vec<String>>,
+    /// Stop in `rust_panic` when the debuggee panics.
     #[serde(default)]
-    pub config_source:  ConfigSource,
+    pub break_on_panic:    bool,
+    /// Process to attach to; when missing the process picker is shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid:               Option<u32>,
 }
 
 impl RunDebugConfig {
+    pub fn is_attach(&self) -> bool {
+        self.request == DebugRequest::Attach
+    }
+
+    /// Whether the session runs under an LLDB based adapter, which is where
+    /// Rust programs are debugged.
+    pub fn is_lldb(&self) -> bool {
+        self.ty.as_deref().is_some_and(|ty| ty.contains("lldb"))
+    }
+
     pub fn update_by_workspace(&mut self, workspace: &str) {
         self.program = self.program.replace("${workspace}", workspace);
 
@@ -101,6 +125,14 @@ impl RunDebugConfig {
     }
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DebugRequest {
+    #[default]
+    Launch,
+    Attach,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
 pub enum ConfigSource {
     #[default]
@@ -353,6 +385,23 @@ pub struct DebuggerCapabilities {
     pub supported_checksum_algorithms:         Option<Vec<String>>,
 }
 
+impl DebuggerCapabilities {
+    pub fn exception_filters(&self) -> &[ExceptionBreakpointsFilter] {
+        self.exception_breakpoint_filters
+            .as_deref()
+            .unwrap_or_default()
+    }
+
+    /// The filters the adapter wants enabled when the user hasn't chosen any.
+    pub fn default_exception_filters(&self) -> Vec<String> {
+        self.exception_filters()
+            .iter()
+            .filter(|f| f.default.unwrap_or_default())
+            .map(|f| f.filter.clone())
+            .collect()
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Stopped {
@@ -566,6 +615,15 @@ impl Request for Launch {
     const COMMAND: &'static str = "launch";
 }
 
+pub enum Attach {}
+
+impl Request for Attach {
+    type Arguments = Value;
+    type Result = Value;
+
+    const COMMAND: &'static str = "attach";
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RunInTerminalResponse {
@@ -627,6 +685,72 @@ impl Request for SetBreakpoints {
     const COMMAND: &'static str = "setBreakpoints";
 }
 
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetExceptionBreakpointsArguments {
+    pub filters: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetExceptionBreakpointsResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakpoints: Option<Vec<Breakpoint>>,
+}
+
+#[derive(Debug)]
+pub enum SetExceptionBreakpoints {}
+
+impl Request for SetExceptionBreakpoints {
+    type Arguments = SetExceptionBreakpointsArguments;
+    type Result = Option<SetExceptionBreakpointsResponse>;
+
+    const COMMAND: &'static str = "setExceptionBreakpoints";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionBreakpoint {
+    pub name:          String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition:     Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+}
+
+impl FunctionBreakpoint {
+    /// The function every Rust panic goes through before unwinding, so
+    /// stopping there lands in the panicking frame.
+    pub fn rust_panic() -> Self {
+        Self {
+            name: "rust_panic".to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetFunctionBreakpointsArguments {
+    pub breakpoints: Vec<FunctionBreakpoint>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetFunctionBreakpointsResponse {
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Debug)]
+pub enum SetFunctionBreakpoints {}
+
+impl Request for SetFunctionBreakpoints {
+    type Arguments = SetFunctionBreakpointsArguments;
+    type Result = SetFunctionBreakpointsResponse;
+
+    const COMMAND: &'static str = "setFunctionBreakpoints";
+}
+
 #[derive(Debug)]
 pub enum ConfigurationDone {}
 
@@ -953,3 +1077,136 @@ impl Request for StepOut {
 
     const COMMAND: &'static str = "stepOut";
 }
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateArguments {
+    pub expression: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id:   Option<usize>,
+    /// `watch`, `repl`, `hover`, `clipboard` or `variables`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context:    Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format:     Option<ValueFormat>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponse {
+    pub result:              String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty:                  Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint:   Option<VariablePresentationHint>,
+    pub variables_reference: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables:     Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables:   Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference:    Option<String>,
+}
+
+impl EvaluateResponse {
+    /// Present the result as a variable named after the evaluated expression,
+    /// so that it can be expanded like any other variable.
+    pub fn into_variable(self, expression: String) -> Variable {
+        Variable {
+            name:                expression.clone(),
+            value:               self.result,
+            ty:                  self.ty,
+            presentation_hint:   self.presentation_hint,
+            evaluate_name:       Some(expression),
+            variables_reference: self.variables_reference,
+            named_variables:     self.named_variables,
+            indexed_variables:   self.indexed_variables,
+            memory_reference:    self.memory_reference,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Evaluate {}
+
+impl Request for Evaluate {
+    type Arguments = EvaluateArguments;
+    type Result = EvaluateResponse;
+
+    const COMMAND: &'static str = "evaluate";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembleArguments {
+    pub memory_reference:   String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset:             Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_offset: Option<i64>,
+    pub instruction_count:  usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_symbols:    Option<bool>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembledInstruction {
+    pub address:           String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_bytes: Option<String>,
+    pub instruction:       String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol:            Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location:          Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line:              Option<usize>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembleResponse {
+    pub instructions: Vec<DisassembledInstruction>,
+}
+
+#[derive(Debug)]
+pub enum Disassemble {}
+
+impl Request for Disassemble {
+    type Arguments = DisassembleArguments;
+    type Result = DisassembleResponse;
+
+    const COMMAND: &'static str = "disassemble";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMemoryArguments {
+    pub memory_reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset:           Option<i64>,
+    pub count:            usize,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMemoryResponse {
+    /// The address of the first byte of `data`.
+    pub address:          String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unreadable_bytes: Option<usize>,
+    /// The bytes read, encoded in base64.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data:             Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ReadMemory {}
+
+impl Request for ReadMemory {
+    type Arguments = ReadMemoryArguments;
+    type Result = ReadMemoryResponse;
+
+    const COMMAND: &'static str = "readMemory";
+}
@@ -320,6 +320,12 @@ impl FileNodeItem {
         children
     }
 
+    /// Whether this is the root of a multi-root workspace, which has an empty
+    /// path and the folders of the workspace as children.
+    pub fn is_virtual_root(&self) -> bool {
+        self.path.as_os_str().is_empty()
+    }
+
     /// Returns an iterator over the ancestors of `path`, starting with the
     /// first descendant of `prefix`.
     ///
@@ -347,7 +353,15 @@ impl FileNodeItem {
         &self,
         path: &'a Path,
     ) -> Option<impl Iterator<Item = &'a Path>> {
-        let take = if let Ok(suffix) = path.strip_prefix(&self.path) {
+        let take = if self.is_virtual_root() && path != self.path {
+            // the folder the path is in comes first
+            let folder = self
+                .children
+                .keys()
+                .filter(|folder| path.starts_with(folder))
+                .max_by_key(|folder| folder.components().count())?;
+            path.strip_prefix(folder).ok()?.components().count() + 1
+        } else if let Ok(suffix) = path.strip_prefix(&self.path) {
             suffix.components().count()
         } else {
             return None;
@@ -377,9 +391,7 @@ impl FileNodeItem {
         let parent = path.parent()?;
         let node = self.get_file_node_mut(parent)?;
         let node = node.children.remove(path)?;
-        for p in path.ancestors() {
-            self.update_node_count(p);
-        }
+        self.update_node_count_recursive(path);
 
         Some(node)
     }
@@ -399,9 +411,7 @@ impl FileNodeItem {
                 children_open_count: 0,
             },
         );
-        for p in path.ancestors() {
-            self.update_node_count(p);
-        }
+        self.update_node_count_recursive(path);
 
         Some(())
     }
@@ -419,15 +429,16 @@ impl FileNodeItem {
             node.children = children;
         }
 
-        for p in path.ancestors() {
-            self.update_node_count(p);
-        }
+        self.update_node_count_recursive(path);
     }
 
     pub fn update_node_count_recursive(&mut self, path: &Path) {
         for current_path in path.ancestors() {
             self.update_node_count(current_path);
         }
+        if self.is_virtual_root() {
+            self.update_node_count(Path::new(""));
+        }
     }
 
     pub fn update_node_count(&mut self, path: &Path) -> Option<()> {
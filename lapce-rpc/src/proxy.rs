@@ -12,12 +12,12 @@ use crossbeam_channel::{Receiver, Sender};
 use indexmap::IndexMap;
 use lapce_xi_rope::RopeDelta;
 use lsp_types::{
-    CallHierarchyIncomingCall, CallHierarchyItem, CodeAction, CodeActionResponse,
-    CodeLens, CompletionItem, Diagnostic, DocumentHighlight, DocumentSymbolResponse,
-    FoldingRange, GotoDefinitionResponse, Hover, InlayHint,
-    InlineCompletionResponse, InlineCompletionTriggerKind, Location, Position,
-    PrepareRenameResponse, SelectionRange, SymbolInformation, TextDocumentItem,
-    TextEdit, WorkspaceEdit,
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall,
+    CodeAction, CodeActionResponse, CodeLens, CompletionItem, Diagnostic,
+    DocumentHighlight, DocumentSymbolResponse, FoldingRange, GotoDefinitionResponse,
+    Hover, InlayHint, InlineCompletionResponse, InlineCompletionTriggerKind,
+    Location, Position, PrepareRenameResponse, Range, SelectionRange,
+    SymbolInformation, TextDocumentItem, TextEdit, TypeHierarchyItem, WorkspaceEdit,
     request::{GotoImplementationResponse, GotoTypeDefinitionResponse},
 };
 use parking_lot::Mutex;
@@ -26,12 +26,12 @@ use serde::{Deserialize, Serialize};
 use super::plugin::VoltID;
 use crate::{
     RequestId, RpcError, RpcMessage, RpcResult,
-    buffer::BufferId,
+    buffer::{BufferId, FileEncoding, SaveMode},
     dap_types::{self, DapId, RunDebugConfig, SourceBreakpoint, ThreadId},
     file::{FileNodeItem, PathObject},
     file_line::FileLine,
     plugin::{PluginId, VoltInfo, VoltMetadata},
-    source_control::FileDiff,
+    source_control::{FileDiff, GitBlame, GitCommitInfo, StageSelection},
     style::SemanticStyles,
     terminal::{TermId, TerminalProfile},
 };
@@ -58,6 +58,44 @@ pub struct SearchMatch {
     pub line_content: String,
 }
 
+/// The search matches of a file that should be replaced.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileReplace {
+    pub path:    PathBuf,
+    /// The 1-based line and the start in the line of each match, as in
+    /// `SearchMatch::line` and `SearchMatch::start`
+    pub matches: Vec<(usize, usize)>,
+}
+
+/// A step of a test explorer run, the build of the test binaries is
+/// followed by a run of each of them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TestCommand {
+    /// `cargo` with `arguments`
+    Build { arguments: Vec<String> },
+    /// A test binary, with the unstable libtest options allowed
+    Binary {
+        executable: PathBuf,
+        /// The directory of the package, where cargo runs its tests
+        cwd:        PathBuf,
+        arguments:  Vec<String>,
+    },
+}
+
+/// Limits which files a global search looks at and how much it returns.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Globs relative to the workspace, a file has to match one of them
+    /// when any are given.
+    pub include:       Vec<String>,
+    /// Globs relative to the workspace for files that are skipped.
+    pub exclude:       Vec<String>,
+    /// Files larger than this many bytes are skipped, 0 for no limit.
+    pub max_file_size: u64,
+    /// The search stops after this many matches, 0 for no limit.
+    pub max_matches:   usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "method", content = "params")]
@@ -73,11 +111,55 @@ pub enum ProxyRequest {
     BufferHead {
         path: PathBuf,
     },
+    /// Reads an open file again with another encoding, answered with a
+    /// `NewBufferResponse`. The buffer keeps its content until the editor
+    /// sends the update to the new one. It is read-only when the file isn't
+    /// valid in that encoding.
+    ReopenWithEncoding {
+        path:     PathBuf,
+        encoding: FileEncoding,
+    },
+    /// The content of the file in the git index, answered with a
+    /// `BufferHeadResponse`
+    GitIndexContent {
+        path: PathBuf,
+    },
+    /// Who last changed each line of the HEAD version of a file
+    GitBlame {
+        path: PathBuf,
+    },
+    /// Stages part or all of the changes of a file, taking the working copy
+    /// from its open buffer when there is one
+    GitStage {
+        path:      PathBuf,
+        selection: StageSelection,
+    },
+    GitUnstage {
+        path:      PathBuf,
+        selection: StageSelection,
+    },
+    /// A page of the commits reachable from HEAD, newest first, limited to
+    /// those touching `path` if given
+    GitLog {
+        path:  Option<PathBuf>,
+        skip:  usize,
+        limit: usize,
+    },
+    /// The content of a file at a revision, answered with a
+    /// `BufferHeadResponse`. Empty if the file doesn't exist there.
+    GitFileContent {
+        rev:  String,
+        path: PathBuf,
+    },
+    /// Matches are streamed back as `CoreNotification::GlobalSearchMatches`,
+    /// the response only arrives once the search is finished.
     GlobalSearch {
+        search_id:      u64,
         pattern:        String,
         case_sensitive: bool,
         whole_word:     bool,
         is_regex:       bool,
+        options:        SearchOptions,
     },
     CompletionResolve {
         plugin_id:       PluginId,
@@ -87,6 +169,12 @@ pub enum ProxyRequest {
         plugin_id:   PluginId,
         action_item: Box<CodeAction>,
     },
+    /// `workspace/executeCommand` to the server of a code action or lens
+    ExecuteCommand {
+        plugin_id: PluginId,
+        command:   String,
+        arguments: Vec<serde_json::Value>,
+    },
     GetHover {
         request_id: usize,
         path:       PathBuf,
@@ -128,6 +216,22 @@ pub enum ProxyRequest {
         path:                PathBuf,
         call_hierarchy_item: CallHierarchyItem,
     },
+    CallHierarchyOutgoing {
+        path:                PathBuf,
+        call_hierarchy_item: CallHierarchyItem,
+    },
+    ShowTypeHierarchy {
+        path:     PathBuf,
+        position: Position,
+    },
+    TypeHierarchySupertypes {
+        path:                PathBuf,
+        type_hierarchy_item: TypeHierarchyItem,
+    },
+    TypeHierarchySubtypes {
+        path:                PathBuf,
+        type_hierarchy_item: TypeHierarchyItem,
+    },
     GetTypeDefinition {
         request_id: usize,
         path:       PathBuf,
@@ -182,6 +286,16 @@ pub enum ProxyRequest {
     GetDocumentFormatting {
         path: PathBuf,
     },
+    GetDocumentRangeFormatting {
+        path:  PathBuf,
+        range: Range,
+    },
+    /// Formatting after typing `ch`, a trigger character of the server
+    GetOnTypeFormatting {
+        path:     PathBuf,
+        position: Position,
+        ch:       String,
+    },
     GetOpenFilesContent {},
     GetFiles {
         path: String,
@@ -194,6 +308,10 @@ pub enum ProxyRequest {
         path:           PathBuf,
         /// Whether to create the parent directories if they do not exist.
         create_parents: bool,
+        /// The encoding to save with from now on, if not the one of the
+        /// buffer
+        encoding:       Option<FileEncoding>,
+        save_mode:      SaveMode,
     },
     SaveBufferAs {
         buffer_id:      BufferId,
@@ -202,6 +320,25 @@ pub enum ProxyRequest {
         content:        String,
         /// Whether to create the parent directories if they do not exist.
         create_parents: bool,
+        /// The encoding of the document, which the new file is written with
+        encoding:       FileEncoding,
+        save_mode:      SaveMode,
+    },
+    /// The content of files that aren't open in the editor, as they are on
+    /// disk.
+    ReadFiles {
+        paths: Vec<PathBuf>,
+    },
+    /// Replace the global search matches in files that aren't open in the
+    /// editor.
+    ReplaceInFiles {
+        pattern:        String,
+        case_sensitive: bool,
+        whole_word:     bool,
+        is_regex:       bool,
+        replacement:    String,
+        files:          Vec<FileReplace>,
+        save_mode:      SaveMode,
     },
     CreateFile {
         path: PathBuf,
@@ -231,6 +368,25 @@ pub enum ProxyRequest {
         dap_id:   DapId,
         frame_id: usize,
     },
+    DapEvaluate {
+        dap_id:     DapId,
+        expression: String,
+        frame_id:   Option<usize>,
+        context:    Option<String>,
+    },
+    DapDisassemble {
+        dap_id:             DapId,
+        memory_reference:   String,
+        instruction_offset: i64,
+        instruction_count:  usize,
+    },
+    DapReadMemory {
+        dap_id:           DapId,
+        memory_reference: String,
+        offset:           i64,
+        count:            usize,
+    },
+    ListProcesses {},
     ReferencesResolve {
         items: Vec<Location>,
     },
@@ -251,6 +407,8 @@ pub enum ProxyRequest {
     },
     Initialize {
         workspace:             Option<PathBuf>,
+        /// Every root folder of the workspace, `workspace` being the first
+        folders:               Vec<PathBuf>,
         disabled_volts:        Vec<VoltID>,
         /// Paths to extra plugins that should be loaded
         extra_plugin_paths:    Vec<PathBuf>,
@@ -296,6 +454,9 @@ pub enum ProxyNotification {
     UpdatePluginConfigs {
         configs: HashMap<String, HashMap<String, serde_json::Value>>,
     },
+    CancelGlobalSearch {
+        search_id: u64,
+    },
     NewTerminal {
         term_id: TermId,
         raw_id:  u64,
@@ -355,6 +516,10 @@ pub enum ProxyNotification {
         dap_id:    DapId,
         thread_id: ThreadId,
     },
+    DapStepInstruction {
+        dap_id:    DapId,
+        thread_id: ThreadId,
+    },
     DapPause {
         dap_id:    DapId,
         thread_id: ThreadId,
@@ -374,11 +539,27 @@ pub enum ProxyNotification {
         path:        PathBuf,
         breakpoints: Vec<SourceBreakpoint>,
     },
+    DapSetExceptionBreakpoints {
+        dap_id:         DapId,
+        filters:        Vec<String>,
+        break_on_panic: Option<bool>,
+    },
     RustBuild {
         rev:       u64,
         command:   String,
         arguments: Option<Vec<String>>,
     },
+    RustBuildCancel {
+        rev: u64,
+    },
+    /// Runs a step of a test explorer run.
+    TestRun {
+        run_id:  u64,
+        command: TestCommand,
+    },
+    TestRunCancel {
+        run_id: u64,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -391,11 +572,27 @@ pub enum ProxyResponse {
     NewBufferResponse {
         content:   String,
         read_only: bool,
+        encoding:  FileEncoding,
     },
     BufferHeadResponse {
         version: String,
         content: String,
     },
+    GitBlameResponse {
+        blame: GitBlame,
+    },
+    GitLogResponse {
+        commits: Vec<GitCommitInfo>,
+    },
+    ReadFilesResponse {
+        /// The files that could be read, with their content
+        files: Vec<(PathBuf, String)>,
+    },
+    ReplaceInFilesResponse {
+        /// The files that were rewritten
+        replaced: Vec<PathBuf>,
+        errors:   Vec<String>,
+    },
     ReadDirResponse {
         items: Vec<FileNodeItem>,
     },
@@ -405,6 +602,9 @@ pub enum ProxyResponse {
     CodeActionResolveResponse {
         item: Box<CodeAction>,
     },
+    ExecuteCommandResponse {
+        result: serde_json::Value,
+    },
     HoverResponse {
         request_id: usize,
         hover:      Hover,
@@ -422,6 +622,16 @@ pub enum ProxyResponse {
     CallHierarchyIncomingResponse {
         items: Option<Vec<CallHierarchyIncomingCall>>,
     },
+    CallHierarchyOutgoingResponse {
+        items: Option<Vec<CallHierarchyOutgoingCall>>,
+    },
+    ShowTypeHierarchyResponse {
+        items: Option<Vec<TypeHierarchyItem>>,
+    },
+    /// The supertypes or the subtypes of an item
+    TypeHierarchyResponse {
+        items: Option<Vec<TypeHierarchyItem>>,
+    },
     GetTypeDefinition {
         request_id: usize,
         definition: GotoTypeDefinitionResponse,
@@ -484,7 +694,9 @@ pub enum ProxyResponse {
         items: Vec<TextDocumentItem>,
     },
     GlobalSearchResponse {
-        matches: IndexMap<PathBuf, Vec<SearchMatch>>,
+        files_searched: usize,
+        match_count:    usize,
+        limit_reached:  bool,
     },
     DapVariableResponse {
         varialbes: Vec<dap_types::Variable>,
@@ -492,6 +704,18 @@ pub enum ProxyResponse {
     DapGetScopesResponse {
         scopes: Vec<(dap_types::Scope, Vec<dap_types::Variable>)>,
     },
+    DapEvaluateResponse {
+        result: dap_types::EvaluateResponse,
+    },
+    DapDisassembleResponse {
+        instructions: Vec<dap_types::DisassembledInstruction>,
+    },
+    DapReadMemoryResponse {
+        result: dap_types::ReadMemoryResponse,
+    },
+    ListProcessesResponse {
+        processes: Vec<ProcessInfo>,
+    },
     CreatePathResponse {
         path: PathBuf,
     },
@@ -508,6 +732,13 @@ pub enum ProxyResponse {
     },
 }
 
+/// A local process that a debugger can attach to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid:     u32,
+    pub command: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileAndLine {
     pub file: PathBuf,
@@ -692,9 +923,11 @@ impl ProxyRpcHandler {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         &self,
         workspace: Option<PathBuf>,
+        folders: Vec<PathBuf>,
         disabled_volts: Vec<VoltID>,
         extra_plugin_paths: Vec<PathBuf>,
         plugin_configurations: HashMap<String, HashMap<String, serde_json::Value>>,
@@ -704,6 +937,7 @@ impl ProxyRpcHandler {
         self.request_async(
             ProxyRequest::Initialize {
                 workspace,
+                folders,
                 disabled_volts,
                 extra_plugin_paths,
                 plugin_configurations,
@@ -790,10 +1024,46 @@ impl ProxyRpcHandler {
         self.request_async(ProxyRequest::NewBuffer { buffer_id, path }, f);
     }
 
+    pub fn reopen_with_encoding(
+        &self,
+        path: PathBuf,
+        encoding: FileEncoding,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::ReopenWithEncoding { path, encoding }, f);
+    }
+
     pub fn get_buffer_head(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
         self.request_async(ProxyRequest::BufferHead { path }, f);
     }
 
+    pub fn git_index_content(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::GitIndexContent { path }, f);
+    }
+
+    pub fn git_blame(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::GitBlame { path }, f);
+    }
+
+    pub fn git_log(
+        &self,
+        path: Option<PathBuf>,
+        skip: usize,
+        limit: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GitLog { path, skip, limit }, f);
+    }
+
+    pub fn git_file_content(
+        &self,
+        rev: String,
+        path: PathBuf,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GitFileContent { rev, path }, f);
+    }
+
     pub fn create_file(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
         self.request_async(ProxyRequest::CreateFile { path }, f);
     }
@@ -846,6 +1116,7 @@ impl ProxyRpcHandler {
         self.request_async(ProxyRequest::TestCreateAtPath { path }, f);
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn save_buffer_as(
         &self,
         buffer_id: BufferId,
@@ -853,6 +1124,8 @@ impl ProxyRpcHandler {
         rev: u64,
         content: String,
         create_parents: bool,
+        encoding: FileEncoding,
+        save_mode: SaveMode,
         f: impl ProxyCallback + 'static,
     ) {
         self.request_async(
@@ -862,35 +1135,78 @@ impl ProxyRpcHandler {
                 rev,
                 content,
                 create_parents,
+                encoding,
+                save_mode,
             },
             f,
         );
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn global_search(
         &self,
+        search_id: u64,
         pattern: String,
         case_sensitive: bool,
         whole_word: bool,
         is_regex: bool,
+        options: SearchOptions,
         f: impl ProxyCallback + 'static,
     ) {
         self.request_async(
             ProxyRequest::GlobalSearch {
+                search_id,
                 pattern,
                 case_sensitive,
                 whole_word,
                 is_regex,
+                options,
             },
             f,
         );
     }
 
+    pub fn read_files(&self, paths: Vec<PathBuf>, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::ReadFiles { paths }, f);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn replace_in_files(
+        &self,
+        pattern: String,
+        case_sensitive: bool,
+        whole_word: bool,
+        is_regex: bool,
+        replacement: String,
+        files: Vec<FileReplace>,
+        save_mode: SaveMode,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::ReplaceInFiles {
+                pattern,
+                case_sensitive,
+                whole_word,
+                is_regex,
+                replacement,
+                files,
+                save_mode,
+            },
+            f,
+        );
+    }
+
+    pub fn cancel_global_search(&self, search_id: u64) {
+        self.notification(ProxyNotification::CancelGlobalSearch { search_id });
+    }
+
     pub fn save(
         &self,
         rev: u64,
         path: PathBuf,
         create_parents: bool,
+        encoding: Option<FileEncoding>,
+        save_mode: SaveMode,
         f: impl ProxyCallback + 'static,
     ) {
         self.request_async(
@@ -898,6 +1214,8 @@ impl ProxyRpcHandler {
                 rev,
                 path,
                 create_parents,
+                encoding,
+                save_mode,
             },
             f,
         );
@@ -950,6 +1268,23 @@ impl ProxyRpcHandler {
         );
     }
 
+    pub fn execute_command(
+        &self,
+        plugin_id: PluginId,
+        command: String,
+        arguments: Vec<serde_json::Value>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::ExecuteCommand {
+                plugin_id,
+                command,
+                arguments,
+            },
+            f,
+        );
+    }
+
     pub fn get_hover(
         &self,
         request_id: usize,
@@ -1017,6 +1352,60 @@ impl ProxyRpcHandler {
         );
     }
 
+    pub fn call_hierarchy_outgoing(
+        &self,
+        path: PathBuf,
+        call_hierarchy_item: CallHierarchyItem,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::CallHierarchyOutgoing {
+                path,
+                call_hierarchy_item,
+            },
+            f,
+        );
+    }
+
+    pub fn show_type_hierarchy(
+        &self,
+        path: PathBuf,
+        position: Position,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::ShowTypeHierarchy { path, position }, f);
+    }
+
+    pub fn type_hierarchy_supertypes(
+        &self,
+        path: PathBuf,
+        type_hierarchy_item: TypeHierarchyItem,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::TypeHierarchySupertypes {
+                path,
+                type_hierarchy_item,
+            },
+            f,
+        );
+    }
+
+    pub fn type_hierarchy_subtypes(
+        &self,
+        path: PathBuf,
+        type_hierarchy_item: TypeHierarchyItem,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::TypeHierarchySubtypes {
+                path,
+                type_hierarchy_item,
+            },
+            f,
+        );
+    }
+
     pub fn get_type_definition(
         &self,
         request_id: usize,
@@ -1106,6 +1495,31 @@ impl ProxyRpcHandler {
         self.request_async(ProxyRequest::GetDocumentFormatting { path }, f);
     }
 
+    pub fn get_document_range_formatting(
+        &self,
+        path: PathBuf,
+        range: Range,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetDocumentRangeFormatting { path, range },
+            f,
+        );
+    }
+
+    pub fn get_on_type_formatting(
+        &self,
+        path: PathBuf,
+        position: Position,
+        ch: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetOnTypeFormatting { path, position, ch },
+            f,
+        );
+    }
+
     pub fn get_semantic_tokens(
         &self,
         path: PathBuf,
@@ -1219,6 +1633,24 @@ impl ProxyRpcHandler {
         self.notification(ProxyNotification::GitDiscardWorkspaceChanges {});
     }
 
+    pub fn git_stage(
+        &self,
+        path: PathBuf,
+        selection: StageSelection,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GitStage { path, selection }, f);
+    }
+
+    pub fn git_unstage(
+        &self,
+        path: PathBuf,
+        selection: StageSelection,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GitUnstage { path, selection }, f);
+    }
+
     pub fn get_selection_range(
         &self,
         path: PathBuf,
@@ -1279,6 +1711,13 @@ impl ProxyRpcHandler {
         self.notification(ProxyNotification::DapStepOut { dap_id, thread_id })
     }
 
+    pub fn dap_step_instruction(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.notification(ProxyNotification::DapStepInstruction {
+            dap_id,
+            thread_id,
+        })
+    }
+
     pub fn dap_pause(&self, dap_id: DapId, thread_id: ThreadId) {
         self.notification(ProxyNotification::DapPause { dap_id, thread_id })
     }
@@ -1304,6 +1743,46 @@ impl ProxyRpcHandler {
         })
     }
 
+    /// `break_on_panic` is `None` when the adapter can't set function
+    /// breakpoints, in which case only the exception filters are sent.
+    pub fn dap_set_exception_breakpoints(
+        &self,
+        dap_id: DapId,
+        filters: Vec<String>,
+        break_on_panic: Option<bool>,
+    ) {
+        self.notification(ProxyNotification::DapSetExceptionBreakpoints {
+            dap_id,
+            filters,
+            break_on_panic,
+        })
+    }
+
+    pub fn rust_build(
+        &self,
+        rev: u64,
+        command: String,
+        arguments: Option<Vec<String>>,
+    ) {
+        self.notification(ProxyNotification::RustBuild {
+            rev,
+            command,
+            arguments,
+        })
+    }
+
+    pub fn rust_build_cancel(&self, rev: u64) {
+        self.notification(ProxyNotification::RustBuildCancel { rev })
+    }
+
+    pub fn test_run(&self, run_id: u64, command: TestCommand) {
+        self.notification(ProxyNotification::TestRun { run_id, command })
+    }
+
+    pub fn test_run_cancel(&self, run_id: u64) {
+        self.notification(ProxyNotification::TestRunCancel { run_id })
+    }
+
     pub fn dap_variable(
         &self,
         dap_id: DapId,
@@ -1321,6 +1800,67 @@ impl ProxyRpcHandler {
     ) {
         self.request_async(ProxyRequest::DapGetScopes { dap_id, frame_id }, f);
     }
+
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        context: Option<String>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapEvaluate {
+                dap_id,
+                expression,
+                frame_id,
+                context,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_disassemble(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapDisassemble {
+                dap_id,
+                memory_reference,
+                instruction_offset,
+                instruction_count,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_read_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapReadMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                count,
+            },
+            f,
+        );
+    }
+
+    pub fn list_processes(&self, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::ListProcesses {}, f);
+    }
 }
 
 impl Default for ProxyRpcHandler {
@@ -4,10 +4,38 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct DiffInfo {
-    pub head:     String,
-    pub branches: Vec<String>,
-    pub tags:     Vec<String>,
-    pub diffs:    Vec<FileDiff>,
+    pub head:        String,
+    /// The commit HEAD points at, empty before the first commit
+    #[serde(default)]
+    pub head_commit: String,
+    pub branches:    Vec<String>,
+    pub tags:        Vec<String>,
+    /// Every changed file, staged or not
+    pub diffs:       Vec<FileDiff>,
+    /// Files whose index entry differs from HEAD
+    #[serde(default)]
+    pub staged:      Vec<FileDiff>,
+    /// Files whose working copy differs from the index
+    #[serde(default)]
+    pub unstaged:    Vec<FileDiff>,
+}
+
+/// The part of a file's changes to stage or unstage. Lines are 0-based and
+/// belong to the newer side of the diff: the working copy when staging and
+/// the index when unstaging.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StageSelection {
+    File,
+    /// Every hunk touching a line in `start..=end`
+    Hunks {
+        start: usize,
+        end:   usize,
+    },
+    /// Only the changed lines in `start..=end`
+    Lines {
+        start: usize,
+        end:   usize,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -45,3 +73,45 @@ pub enum FileDiffKind {
     Deleted,
     Renamed,
 }
+
+/// A commit a blamed line was last changed in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlameCommit {
+    pub id:      String,
+    pub author:  String,
+    /// Seconds since the Unix epoch
+    pub time:    i64,
+    pub summary: String,
+}
+
+/// The blame of the HEAD version of a file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct GitBlame {
+    pub commits: Vec<BlameCommit>,
+    /// The index in `commits` for each line
+    pub lines:   Vec<usize>,
+}
+
+impl GitBlame {
+    pub fn commit_of_line(&self, line: usize) -> Option<&BlameCommit> {
+        self.lines.get(line).and_then(|i| self.commits.get(*i))
+    }
+}
+
+/// A commit in the history of the workspace or of a file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GitCommitInfo {
+    pub id:      String,
+    pub author:  String,
+    /// Seconds since the Unix epoch
+    pub time:    i64,
+    pub message: String,
+    /// The files changed against the first parent
+    pub files:   Vec<FileDiff>,
+}
+
+impl GitCommitInfo {
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
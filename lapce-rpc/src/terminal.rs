@@ -20,11 +20,15 @@ impl TermId {
 
 #[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize, Default)]
 pub struct TerminalProfile {
-    pub name:        String,
-    pub command:     Option<String>,
-    pub arguments:   Option<Vec<String>>,
-    pub workdir:     Option<url::Url>,
-    pub environment: Option<HashMap<String, String>>,
+    pub name:              String,
+    pub command:           Option<String>,
+    pub arguments:         Option<Vec<String>>,
+    pub workdir:           Option<url::Url>,
+    pub environment:       Option<HashMap<String, String>>,
+    /// Whether bash and zsh load the scripts which mark the prompts and
+    /// commands for the terminal
+    #[serde(default)]
+    pub shell_integration: bool,
 }
 
 impl TerminalProfile {}